@@ -0,0 +1,122 @@
+//! Allocations and time per `prepare()` for queries with 1 to 8 filters
+//! and with 64, per copy of such a query given a limit, as a request
+//! handler would, and per `prepare()` of a filter on a large IN list.
+//!
+//! `cargo bench --bench prepare`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use traits::{Dialect, SQLComp, SQLFilter, SQLable, SqlValue};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const RUNS: usize = 10_000;
+
+fn query(filters: usize) -> SQLable {
+    let mut q = SQLable::new("events e");
+    q.select(vec!["id".to_owned(), "createdAt".to_owned()])
+        .dialect(Dialect::Postgres);
+    q.filter_values(
+        (0..filters)
+            .map(|idx| SQLFilter {
+                column: format!("e.col{}", idx).into(),
+                filter: SqlValue::Int(idx as i64),
+                cmp: SQLComp::GEQ,
+            })
+            .collect(),
+    );
+    q
+}
+
+fn in_list(values: usize) -> SQLable {
+    let mut q = SQLable::new("events");
+    q.dialect(Dialect::Postgres);
+    q.filter_values(vec![SQLFilter {
+        column: "userId".into(),
+        filter: SqlValue::List((0..values as i64).map(SqlValue::Int).collect()),
+        cmp: SQLComp::EQ,
+    }]);
+    q
+}
+
+fn main() {
+    println!("filters  allocations/prepare  ns/prepare");
+    for filters in (1..=8).chain([64]) {
+        let q = query(filters);
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            std::hint::black_box(q.prepare());
+        }
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!(
+            "{:>7}  {:>19}  {:>10}",
+            filters,
+            allocations / RUNS,
+            elapsed.as_nanos() / RUNS as u128
+        );
+    }
+
+    println!("filters  allocations/copy");
+    for filters in 1..=8 {
+        let q = query(filters);
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        for _ in 0..RUNS {
+            let mut copy = q.clone();
+            std::hint::black_box(copy.limit(20));
+        }
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!("{:>7}  {:>16}", filters, allocations / RUNS);
+    }
+
+    println!(" values  allocations/prepare  us/prepare  allocations/params  us/params");
+    for values in [100, 1_000, 10_000] {
+        let q = in_list(values);
+        let runs = RUNS / values * 10;
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..runs {
+            std::hint::black_box(q.prepare());
+        }
+        let inline = start.elapsed();
+        let middle = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..runs {
+            std::hint::black_box(q.prepare_params().unwrap());
+        }
+        let params = start.elapsed();
+        let after = ALLOCATIONS.load(Ordering::Relaxed);
+        println!(
+            "{:>7}  {:>19}  {:>10}  {:>18}  {:>9}",
+            values,
+            (middle - before) / runs,
+            inline.as_micros() / runs as u128,
+            (after - middle) / runs,
+            params.as_micros() / runs as u128
+        );
+    }
+}
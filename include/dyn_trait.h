@@ -0,0 +1,63 @@
+/*
+ * C interface of the `traits` crate, built with
+ * `cargo rustc --lib --features ffi --crate-type cdylib` (or staticlib).
+ *
+ * Functions returning int return 0 on success and -1 on failure; call
+ * dt_last_error() for a description of the failure.
+ */
+#ifndef DYN_TRAIT_H
+#define DYN_TRAIT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DtQuery DtQuery;
+typedef struct DtStatement DtStatement;
+
+/* Parameter types, see dt_statement_param_type. */
+#define DT_NULL 0
+#define DT_INT 1
+#define DT_UINT 2
+#define DT_FLOAT 3
+#define DT_TEXT 4
+
+const char *dt_last_error(void);
+
+DtQuery *dt_query_new(const char *table);
+void dt_query_free(DtQuery *q);
+
+int dt_query_dialect(DtQuery *q, const char *name);
+int dt_query_select(DtQuery *q, const char *column);
+
+int dt_query_filter_int(DtQuery *q, const char *column, const char *op, int64_t value);
+int dt_query_filter_float(DtQuery *q, const char *column, const char *op, double value);
+int dt_query_filter_text(DtQuery *q, const char *column, const char *op, const char *value);
+int dt_query_filter_null(DtQuery *q, const char *column, const char *op);
+int dt_query_filter_int_list(DtQuery *q, const char *column, const char *op,
+                             const int64_t *values, size_t len);
+
+/* Values inlined as literals. Free the result with dt_string_free. */
+char *dt_query_render(const DtQuery *q);
+void dt_string_free(char *s);
+
+/* Placeholders and the values to bind to them, in order. Free the result
+ * with dt_statement_free; the strings it returns live as long as it. */
+DtStatement *dt_query_prepare(const DtQuery *q);
+void dt_statement_free(DtStatement *s);
+const char *dt_statement_sql(const DtStatement *s);
+size_t dt_statement_param_count(const DtStatement *s);
+int dt_statement_param_type(const DtStatement *s, size_t idx);
+int dt_statement_param_int(const DtStatement *s, size_t idx, int64_t *out);
+int dt_statement_param_uint(const DtStatement *s, size_t idx, uint64_t *out);
+int dt_statement_param_float(const DtStatement *s, size_t idx, double *out);
+const char *dt_statement_param_text(const DtStatement *s, size_t idx);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DYN_TRAIT_H */
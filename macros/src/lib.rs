@@ -0,0 +1,428 @@
+//! Procedural macros for the `traits` crate, re-exported there behind the
+//! `macros` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::Token;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, ExprLit, Fields, GenericArgument, Lit, LitStr,
+    Meta, PathArguments, Type,
+};
+
+/// Checks at compile time that the fragment is a syntactically valid SQL
+/// expression and expands to `traits::Raw::new(fragment)`.
+///
+/// ```
+/// # use traits::*;
+/// let f = raw_sql!("date_trunc('day', created_at) >= now() - interval '1 day'");
+/// assert_eq!(f.apply_filter(), "date_trunc('day', created_at) >= now() - interval '1 day'");
+/// ```
+#[proc_macro]
+pub fn raw_sql(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    let sql = lit.value();
+
+    if let Err(msg) = check_expr(&sql) {
+        return syn::Error::new(lit.span(), format!("invalid SQL fragment: {}", msg))
+            .to_compile_error()
+            .into();
+    }
+
+    quote!(::traits::Raw::new(#lit)).into()
+}
+
+fn check_expr(sql: &str) -> Result<(), String> {
+    let dialect = GenericDialect {};
+    let mut parser = Parser::new(&dialect)
+        .try_with_sql(sql)
+        .map_err(|e| e.to_string())?;
+    parser.parse_expr().map_err(|e| e.to_string())?;
+
+    match parser.peek_token().token {
+        Token::EOF => Ok(()),
+        t => Err(format!("unexpected {} after the expression", t)),
+    }
+}
+
+/// Implements `traits::SqlEnum` for an enum of unit variants, plus `ToSql`
+/// and `From<Self> for SqlValue` so values are written as their labels.
+///
+/// The type is named after the enum and each label after its variant,
+/// as written, unless renamed with `#[sql(name = "...")]` on the enum or
+/// `#[sql(rename = "...")]` on a variant.
+#[proc_macro_derive(SqlEnum, attributes(sql))]
+pub fn derive_sql_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match sql_enum(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn sql_enum(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => return Err(syn::Error::new_spanned(input, "SqlEnum needs an enum")),
+    };
+    let ident = &input.ident;
+    let type_name = SqlAttrs::parse(&input.attrs, &["name"], &[])?
+        .string("name")
+        .unwrap_or_else(|| ident.to_string());
+
+    let mut variants = Vec::new();
+    let mut labels = Vec::new();
+    for v in &data.variants {
+        if !matches!(v.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                v,
+                "SqlEnum variants cannot hold data",
+            ));
+        }
+        variants.push(&v.ident);
+        let attrs = SqlAttrs::parse(&v.attrs, &["rename"], &[])?;
+        labels.push(
+            attrs
+                .string("rename")
+                .unwrap_or_else(|| v.ident.to_string()),
+        );
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::traits::SqlEnum for #ident #ty_generics #where_clause {
+            const TYPE_NAME: &'static str = #type_name;
+            const VARIANTS: &'static [&'static str] = &[#(#labels),*];
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    #(#ident::#variants => #labels,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::traits::ToSql for #ident #ty_generics #where_clause {
+            fn write_sql(&self, out: &mut dyn ::std::fmt::Write) -> ::std::fmt::Result {
+                ::traits::ToSql::write_sql(&::traits::SqlEnum::as_str(self), out)
+            }
+
+            fn to_value(&self) -> Option<::traits::SqlValue> {
+                Some(::traits::SqlEnum::as_str(self).into())
+            }
+        }
+
+        impl #impl_generics From<#ident #ty_generics> for ::traits::SqlValue #where_clause {
+            fn from(v: #ident #ty_generics) -> Self {
+                ::traits::SqlEnum::as_str(&v).into()
+            }
+        }
+    })
+}
+
+/// Implements `traits::Table` for a struct with named fields, building a
+/// `TableDef` with a column per field. Doc comments become the table and
+/// column comments.
+///
+/// The table is named after the struct in snake case and each column after
+/// its field, unless renamed with `#[sql(name = "...")]` on the struct or
+/// `#[sql(rename = "...")]` on a field. `Option` fields are nullable.
+///
+/// Fields take constraints as `#[sql(primary_key)]`, `#[sql(unique)]`,
+/// `#[sql(default = "expr")]` and `#[sql(references = "table(column)")]`;
+/// several `primary_key` fields make a composite key.
+#[proc_macro_derive(Table, attributes(sql))]
+pub fn derive_table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match table(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn table(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(input, "Table needs named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "Table needs a struct")),
+    };
+    let ident = &input.ident;
+    let name = match SqlAttrs::parse(&input.attrs, &["name"], &[])?.string("name") {
+        Some(name) => quote!(#name),
+        None => {
+            let ident = ident.to_string();
+            quote!(&::traits::inflection::snake_case(#ident))
+        }
+    };
+    let table_comment = doc_comment(&input.attrs).map(|doc| quote!(.comment(#doc)));
+
+    let mut columns = Vec::new();
+    let mut primary_key = Vec::new();
+    for field in fields {
+        let attrs = SqlAttrs::parse(&field.attrs, FIELD_VALUES, FIELD_FLAGS)?;
+        let column = match attrs.string("rename") {
+            Some(rename) => rename,
+            None => field.ident.as_ref().unwrap().to_string(),
+        };
+        if attrs.flag("primary_key") {
+            primary_key.push(column.clone());
+        }
+        let (ty, not_null) = match option_inner(&field.ty) {
+            Some(inner) => (inner, None),
+            None => (&field.ty, Some(quote!(.not_null()))),
+        };
+        let unique = attrs.flag("unique").then(|| quote!(.unique()));
+        let default = attrs.string("default").map(|expr| quote!(.default(#expr)));
+        let references = match attrs.value("references") {
+            Some(lit) => {
+                let (table, referred) = references(lit)?;
+                Some(quote!(.references(#table, #referred)))
+            }
+            None => None,
+        };
+        let comment = doc_comment(&field.attrs).map(|doc| quote!(.comment(#doc)));
+        columns.push(quote! {
+            .column(
+                ::traits::ColumnDef::of::<#ty>(#column)
+                    #not_null #default #unique #references #comment
+            )
+        });
+    }
+    let primary_key = (!primary_key.is_empty()).then(|| quote!(.primary_key(&[#(#primary_key),*])));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::traits::Table for #ident #ty_generics #where_clause {
+            fn table_def() -> ::traits::TableDef {
+                ::traits::TableDef::new(#name) #table_comment #(#columns)* #primary_key
+            }
+        }
+    })
+}
+
+/// Implements `traits::FromRow` for a struct, reading each named field
+/// from the column named after it, or renamed with `#[sql(rename = "...")]`
+/// as in `#[derive(Table)]`, and each field of a tuple struct from the
+/// column at its position.
+#[proc_macro_derive(FromRow, attributes(sql))]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match from_row(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn from_row(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new_spanned(input, "FromRow needs a struct")),
+    };
+
+    let body = match fields {
+        Fields::Named(fields) => {
+            let mut reads = Vec::new();
+            for field in &fields.named {
+                let ident = field.ident.as_ref().unwrap();
+                let attrs = SqlAttrs::parse(&field.attrs, FIELD_VALUES, FIELD_FLAGS)?;
+                let column = match attrs.string("rename") {
+                    Some(rename) => rename,
+                    None => ident.to_string(),
+                };
+                reads.push(quote!(#ident: row.get(#column)?));
+            }
+            quote!(Self { #(#reads),* })
+        }
+        Fields::Unnamed(fields) => {
+            let reads = (0..fields.unnamed.len()).map(|idx| quote!(row.get_at(#idx)?));
+            quote!(Self(#(#reads),*))
+        }
+        Fields::Unit => quote!(Self),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::traits::FromRow for #ident #ty_generics #where_clause {
+            fn from_row(row: &dyn ::traits::Row) -> Result<Self, ::traits::SQLError> {
+                Ok(#body)
+            }
+        }
+    })
+}
+
+/// Implements `traits::ToRow` for a struct with named fields, naming each
+/// column after its field or `#[sql(rename = "...")]`, as `FromRow` reads
+/// them. Fields implement `traits::ToSql`; `to_row` fails on those whose
+/// `ToSql::to_value` is `None`.
+///
+/// ```
+/// # use traits::*;
+/// #[derive(ToRow)]
+/// struct User {
+///     #[sql(rename = "age")]
+///     years: i64,
+///     name: String,
+/// }
+///
+/// let adults = SQLFilter { column: "age".into(), filter: 18, cmp: SQLComp::GEQ };
+/// assert!(adults.matches(&User { years: 30, name: "ann".to_owned() }).unwrap());
+/// ```
+#[proc_macro_derive(ToRow, attributes(sql))]
+pub fn derive_to_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match to_row(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn to_row(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(input, "ToRow needs named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "ToRow needs a struct")),
+    };
+
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let attrs = SqlAttrs::parse(&field.attrs, FIELD_VALUES, FIELD_FLAGS)?;
+        let column = match attrs.string("rename") {
+            Some(rename) => rename,
+            None => ident.to_string(),
+        };
+        columns.push(quote!(#column.to_owned()));
+        let missing = format!("the field {} has no value to evaluate, only SQL", ident);
+        values.push(quote! {
+            ::traits::ToSql::to_value(&self.#ident)
+                .ok_or_else(|| ::traits::SQLError::Unsupported(#missing.to_owned()))?
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::traits::ToRow for #ident #ty_generics #where_clause {
+            fn to_row(&self) -> Result<::traits::Record, ::traits::SQLError> {
+                Ok(::traits::Record {
+                    columns: vec![#(#columns),*],
+                    values: vec![#(#values),*],
+                })
+            }
+        }
+    })
+}
+
+/// The table and column of `#[sql(references = "table(column)")]`.
+fn references(lit: &LitStr) -> syn::Result<(String, String)> {
+    let value = lit.value();
+    let parsed = value
+        .strip_suffix(')')
+        .and_then(|rest| rest.split_once('('))
+        .map(|(table, column)| (table.trim(), column.trim()))
+        .filter(|(table, column)| !table.is_empty() && !column.is_empty());
+    match parsed {
+        Some((table, column)) => Ok((table.to_owned(), column.to_owned())),
+        None => Err(syn::Error::new_spanned(
+            lit,
+            "expected references = \"table(column)\"",
+        )),
+    }
+}
+
+/// `T` if `ty` is written `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+            match args.args.first()? {
+                GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The `///` lines on an item, trimmed and joined into one line; a blank
+/// line starts a new one.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("doc")) {
+        if let Meta::NameValue(meta) = &attr.meta {
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) = &meta.value
+            {
+                lines.push(s.value().trim().to_owned());
+            }
+        }
+    }
+
+    let doc = lines
+        .split(|line| line.is_empty())
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.join(" "))
+        .collect::<Vec<String>>()
+        .join("\n");
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// The field options of `#[derive(Table)]`, also accepted by
+/// `#[derive(FromRow)]` so that a struct can derive both.
+const FIELD_VALUES: &[&str] = &["rename", "default", "references"];
+const FIELD_FLAGS: &[&str] = &["primary_key", "unique"];
+
+/// The `#[sql(...)]` options on an item: `key = "value"` for the keys in
+/// `values`, a bare `key` for those in `flags`.
+struct SqlAttrs(Vec<(String, Option<LitStr>)>);
+
+impl SqlAttrs {
+    fn parse(attrs: &[syn::Attribute], values: &[&str], flags: &[&str]) -> syn::Result<Self> {
+        let mut result = Vec::new();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("sql")) {
+            attr.parse_nested_meta(|meta| {
+                let key = match meta.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => return Err(meta.error("unknown sql attribute")),
+                };
+                if values.contains(&key.as_str()) {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    result.push((key, Some(value)));
+                } else if flags.contains(&key.as_str()) {
+                    result.push((key, None));
+                } else {
+                    return Err(meta.error("unknown sql attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(SqlAttrs(result))
+    }
+
+    /// The value of `key = "value"`, if given.
+    fn value(&self, key: &str) -> Option<&LitStr> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.as_ref())
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.value(key).map(LitStr::value)
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+}
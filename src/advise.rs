@@ -0,0 +1,180 @@
+use crate::validate::Scope;
+use crate::{Expr, Filter, SQLComp, SQLError, SQLable, Schema, SqlValue};
+
+/// Advice on how the database can find the rows of a query, from the
+/// indexes of its table, see `SQLable::advise`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Advice {
+    /// The index that serves the query best: the leading columns of it the
+    /// filters narrow on, and whether it returns rows in the query's order.
+    Index {
+        name: String,
+        columns: Vec<String>,
+        sorted: bool,
+    },
+    /// No index serves all the filters and the order; one over these
+    /// columns would.
+    Suggest(Vec<String>),
+}
+
+impl std::fmt::Display for Advice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Advice::Index {
+                name,
+                columns,
+                sorted,
+            } => {
+                write!(f, "index {} narrows on ({})", name, columns.join(","))?;
+                match sorted {
+                    true => write!(f, " and gives the order"),
+                    false => Ok(()),
+                }
+            }
+            Advice::Suggest(columns) => {
+                write!(f, "consider an index on ({})", columns.join(","))
+            }
+        }
+    }
+}
+
+impl SQLable {
+    /// Checks the query's filters and order against the indexes of its
+    /// table in `schema`: its primary key, unique constraints and
+    /// `TableDef::index`es. An index serves columns compared for equality,
+    /// `IN` and `IS NULL` included, then one compared with `<`, `>`, `<=`
+    /// or `>=`, then the order, if the query sorts on columns in one
+    /// direction.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let schema = Schema::new().table(
+    ///     TableDef::new("orders")
+    ///         .column(ColumnDef::of::<i64>("id"))
+    ///         .column(ColumnDef::of::<i64>("customerId"))
+    ///         .column(ColumnDef::of::<String>("status"))
+    ///         .column(ColumnDef::of::<String>("placedAt"))
+    ///         .primary_key(&["id"])
+    ///         .index("orders_customer", &["customerId", "placedAt"]),
+    /// );
+    ///
+    /// let mut q = SQLable::new("orders");
+    /// q.filter(vec![Box::new(SQLFilter { column: "customerId".into(), filter: 7, cmp: SQLComp::EQ })])
+    ///     .order_by("placedAt", Order::Desc);
+    /// assert_eq!(
+    ///     q.advise(&schema).unwrap(),
+    ///     vec![Advice::Index {
+    ///         name: "orders_customer".to_owned(),
+    ///         columns: vec!["customer_id".to_owned()],
+    ///         sorted: true,
+    ///     }]
+    /// );
+    ///
+    /// q.filter(vec![Box::new(SQLFilter { column: "status".into(), filter: "open", cmp: SQLComp::EQ })]);
+    /// assert_eq!(
+    ///     q.advise(&schema).unwrap(),
+    ///     vec![Advice::Suggest(vec!["status".to_owned(), "placed_at".to_owned()])]
+    /// );
+    /// ```
+    ///
+    /// The advice is a first look, not the database's plan: it knows
+    /// nothing of the table's statistics, and leaves out raw SQL and
+    /// conditions under `OR`. A query without filters or order gets none.
+    pub fn advise(&self, schema: &Schema) -> Result<Vec<Advice>, SQLError> {
+        let scope = Scope::new(self, schema)?;
+        let name = |c: &str| scope.name(c);
+
+        let mut equal: Vec<String> = Vec::new();
+        let mut range: Vec<String> = Vec::new();
+        for predicate in self.filter.iter().filter_map(|f| f.predicate()) {
+            for p in predicate.normalize(&name)?.conjuncts() {
+                let (column, into) = match p.as_compare() {
+                    Some((c, SQLComp::EQ, _)) => (c, &mut equal),
+                    Some((c, SQLComp::GT | SQLComp::LT | SQLComp::GEQ | SQLComp::LEQ, v))
+                        if !matches!(v, SqlValue::List(_)) =>
+                    {
+                        (c, &mut range)
+                    }
+                    _ => continue,
+                };
+                if !into.iter().any(|c| c == column) {
+                    into.push(column.to_owned());
+                }
+            }
+        }
+        range.retain(|c| !equal.contains(c));
+        let order = self.index_order(&scope, &equal)?;
+        let ordered = order.as_ref().is_some_and(|o| !o.is_empty());
+        if equal.is_empty() && range.is_empty() && !ordered {
+            return Ok(Vec::new());
+        }
+
+        let mut best: Option<(String, Vec<String>, bool)> = None;
+        for (index, columns) in scope.indexes() {
+            let eq = columns.iter().take_while(|c| equal.contains(c)).count();
+            let rest = &columns[eq..];
+            let mut used = columns[..eq].to_vec();
+            used.extend(rest.first().filter(|c| range.contains(c)).cloned());
+            let sorted = ordered && order.as_ref().is_some_and(|o| rest.starts_with(o));
+            if used.is_empty() && !sorted {
+                continue;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(_, u, s)| (used.len(), sorted) > (u.len(), *s))
+            {
+                best = Some((index, used, sorted));
+            }
+        }
+
+        let narrowed = equal.len() + usize::from(!range.is_empty());
+        let covered = best
+            .as_ref()
+            .is_some_and(|(_, used, sorted)| used.len() == narrowed && (*sorted || !ordered));
+
+        let mut result = Vec::new();
+        if let Some((name, columns, sorted)) = best {
+            result.push(Advice::Index {
+                name,
+                columns,
+                sorted,
+            });
+        }
+        if !covered {
+            // the order, if the range filter leads it or there is none;
+            // otherwise the range filter, leaving the sort to the database
+            let mut columns = equal;
+            match order.filter(|o| !o.is_empty()) {
+                Some(o) if range.is_empty() || range.contains(&o[0]) => columns.extend(o),
+                _ => columns.extend(range.into_iter().take(1)),
+            }
+            result.push(Advice::Suggest(columns));
+        }
+        Ok(result)
+    }
+
+    /// The columns an index must continue with to give the query's
+    /// order, leaving out those the filters fix. `None` if no index gives
+    /// it: the query sorts on an expression, in both directions, or with
+    /// NULLs placed.
+    fn index_order(
+        &self,
+        scope: &Scope,
+        equal: &[String],
+    ) -> Result<Option<Vec<String>>, SQLError> {
+        let mut columns = Vec::new();
+        let first = self.order_by.first().map(|(_, order, _)| *order);
+        for (key, order, nulls) in self.order_by.iter() {
+            match key {
+                Expr::Column(c) if Some(*order) == first && nulls.is_none() => {
+                    let c = scope.name(c)?;
+                    if !equal.contains(&c) {
+                        columns.push(c);
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(columns))
+    }
+}
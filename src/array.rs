@@ -0,0 +1,124 @@
+use crate::{Dialect, Renderer, SQLComp, SQLError, SqlValue, ToSql};
+
+/// A Postgres array: `ARRAY[1,2,3]`, or the whole array bound as a single
+/// parameter. Compared with the array operators of `SQLComp`, or with `=`
+/// against an array column:
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("posts");
+/// q.dialect(Dialect::Postgres).filter(vec![
+///     Box::new(SQLFilter { column: "tags".into(), filter: Array(vec!["rust", "sql"]), cmp: SQLComp::OVERLAPS }),
+///     Box::new(SQLFilter { column: "flags".into(), filter: Array(vec![1, 2]), cmp: SQLComp::CONTAINS }),
+/// ]);
+/// assert_eq!(
+///     q.prepare(),
+///     "SELECT\n  *\nFROM posts\nWHERE\n  (tags && ARRAY['rust','sql'])\n  AND (flags @> ARRAY[1,2])\n"
+/// );
+/// assert_eq!(
+///     q.prepare_params().unwrap().0,
+///     "SELECT\n  *\nFROM posts\nWHERE\n  (tags && $1)\n  AND (flags @> $2)\n"
+/// );
+///
+/// q.dialect(Dialect::MySql);
+/// assert!(q.try_prepare().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Array<T: ToSql>(pub Vec<T>);
+
+impl<T: ToSql> ToSql for Array<T> {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return out.write_str("'{}'");
+        }
+        out.write_str("ARRAY[")?;
+        for (idx, v) in self.0.iter().enumerate() {
+            if idx > 0 {
+                out.write_char(',')?;
+            }
+            v.write_sql(out)?;
+        }
+        out.write_char(']')
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        self.0.to_value()
+    }
+
+    fn wraps_value(&self) -> bool {
+        true
+    }
+
+    fn is_array(&self) -> bool {
+        true
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        require_postgres("array literals", r)?;
+        match self.to_value() {
+            Some(list) if r.is_parameterized() => Ok(r.bind(list)),
+            _ if self.0.is_empty() => Ok("'{}'".to_owned()),
+            _ => {
+                let items = self
+                    .0
+                    .iter()
+                    .map(|v| v.render(r))
+                    .collect::<Result<Vec<String>, SQLError>>()?;
+                Ok(format!("ARRAY[{}]", items.join(",")))
+            }
+        }
+    }
+}
+
+impl SQLComp {
+    /// Whether the operator compares Postgres arrays: `&&`, `@>` or `<@`.
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            SQLComp::OVERLAPS | SQLComp::CONTAINS | SQLComp::CONTAINEDBY
+        )
+    }
+}
+
+fn require_postgres(what: &str, r: &Renderer) -> Result<(), SQLError> {
+    match r.dialect {
+        Dialect::Postgres => Ok(()),
+        dialect => Err(SQLError::Unsupported(format!(
+            "{} in {:?}, arrays are Postgres-only",
+            what, dialect
+        ))),
+    }
+}
+
+/// `value` as an array, the right side of an array operator: a list as
+/// `ARRAY[...]` or a single parameter, anything else as it renders, e.g.
+/// a subquery.
+pub(crate) fn array_operand<T: ToSql + ?Sized>(
+    value: &T,
+    cmp: &SQLComp,
+    r: &mut Renderer,
+) -> Result<String, SQLError> {
+    require_postgres(cmp.symbol(), r)?;
+    match value.to_value() {
+        Some(list @ SqlValue::List(_)) if r.is_parameterized() => Ok(r.bind(list)),
+        Some(SqlValue::List(items)) => Ok(array_sql(&items)),
+        _ => value.render(r),
+    }
+}
+
+/// `ARRAY[...]` of `items`, written inline. An untyped `'{}'` takes the
+/// type of the array it is compared with, unlike `ARRAY[]`.
+pub(crate) fn array_sql(items: &[SqlValue]) -> String {
+    if items.is_empty() {
+        return "'{}'".to_owned();
+    }
+    let mut result = String::from("ARRAY[");
+    for (idx, v) in items.iter().enumerate() {
+        if idx > 0 {
+            result.push(',');
+        }
+        let _ = v.write_sql(&mut result);
+    }
+    result.push(']');
+    result
+}
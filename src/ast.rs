@@ -0,0 +1,421 @@
+use crate::size::StatementSize;
+use crate::{
+    conditions, order_keys, window::window_clause, Dialect, Expr, Filter, Join, Nulls, Order,
+    Renderer, SQLError, SQLable, SqlValue, TableName, ToSql, Window,
+};
+
+/// The `DISTINCT` modifier of a `Select`.
+#[derive(Clone, Debug)]
+pub enum Distinct {
+    /// `SELECT DISTINCT`
+    Rows,
+    /// `SELECT DISTINCT ON (...)`, Postgres only.
+    On(Vec<Expr>),
+}
+
+/// Where a `Select` reads from.
+#[derive(Clone, Debug)]
+pub enum Source {
+    Table(TableName),
+    /// `FROM (SELECT * FROM a WHERE ... UNION ALL SELECT * FROM b WHERE ...) AS alias`.
+    /// The select's filters on the union's own columns are applied inside
+    /// each branch, so that partitions can be pruned on either side, rather
+    /// than in `WHERE`: all of them when nothing is joined, else those
+    /// comparing columns qualified with `alias`.
+    UnionAll {
+        tables: Vec<TableName>,
+        alias: String,
+    },
+}
+
+/// A `SELECT` statement as a tree, built by `SQLable::ast`.
+///
+/// Nothing is rendered until `render`, so the tree can be inspected or
+/// changed first, see `QueryVisitor`, and rendered for several dialects.
+/// Filters render themselves when the tree is rendered; those of the query
+/// are borrowed from it.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("users");
+/// q.select(vec!["id".to_owned(), "name".to_owned()]);
+/// let mut ast = q.ast();
+/// ast.order_by.push((col("name"), Order::Asc, None));
+/// for dialect in [Dialect::Postgres, Dialect::MsSql] {
+///     let mut r = Renderer::parameterized();
+///     r.dialect = dialect;
+///     let sql = ast.render(&mut r).unwrap();
+///     let params = r.into_params();
+/// }
+/// ```
+pub struct Select<'a> {
+    pub distinct: Option<Distinct>,
+    /// The select list; `*` when empty.
+    pub projection: Vec<Expr>,
+    pub from: Source,
+    pub joins: Vec<Join>,
+    pub filter: Vec<Box<dyn Filter + 'a>>,
+    pub group_by: Vec<Expr>,
+    pub having: Vec<Box<dyn Filter + 'a>>,
+    pub windows: Vec<(String, Window)>,
+    pub order_by: Vec<(Expr, Order, Option<Nulls>)>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl SQLable {
+    /// The query as a `Select` tree. Rendering the tree with the query's
+    /// renderer gives the same SQL as `prepare`.
+    pub fn ast(&self) -> Select<'_> {
+        let distinct = match (&self.distinct_on[..], self.distinct) {
+            ([], false) => None,
+            ([], true) => Some(Distinct::Rows),
+            (cols, _) => Some(Distinct::On(cols.iter().map(|c| Expr::column(c)).collect())),
+        };
+
+        let from = match &self.archive {
+            _ if !self.partitions.is_empty() => {
+                let alias = self.table.qualifier().to_owned();
+                let mut tables = self.partitions.to_vec();
+                tables
+                    .iter_mut()
+                    .for_each(|t| t.alias = Some(alias.clone()));
+                match tables.len() {
+                    1 => Source::Table(tables.remove(0)),
+                    _ => Source::UnionAll { tables, alias },
+                }
+            }
+            Some(archive) if self.include_archive => {
+                // the archive takes the live table's qualifier so that
+                // qualified columns resolve in either branch
+                let alias = self.table.qualifier().to_owned();
+                let mut archive = archive.clone();
+                archive.alias = Some(alias.clone());
+                let tables = vec![(*self.table).clone(), archive];
+                Source::UnionAll { tables, alias }
+            }
+            _ => Source::Table((*self.table).clone()),
+        };
+
+        let expanded = self
+            .explicit_columns
+            .as_ref()
+            .and_then(|schema| schema.get(&self.table).ok())
+            .and_then(|def| self.expanded(def));
+        let cols = expanded.as_ref().or(self.cols.as_ref().as_ref());
+
+        let mut projection: Vec<Expr> = cols
+            .into_iter()
+            .flatten()
+            .map(|c| Expr::column(c))
+            .collect();
+        match self.auto_alias {
+            true => projection.extend(self.aliased_exprs(&projection)),
+            false => projection.extend(self.exprs.iter().cloned()),
+        }
+
+        Select {
+            distinct,
+            projection,
+            from,
+            joins: self.joins.to_vec(),
+            filter: self.filter.iter().map(|f| borrow(f)).collect(),
+            group_by: self.group_by.iter().map(|c| Expr::column(c)).collect(),
+            having: self.having.iter().map(|f| borrow(f.as_ref())).collect(),
+            windows: self.windows.to_vec(),
+            order_by: self.order_by.to_vec(),
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+}
+
+impl Select<'_> {
+    pub fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let mut size = StatementSize::new(r);
+        // most statements fit, saving the reallocations of a growing buffer
+        let mut output = String::with_capacity(256);
+        let mut push = |name, sql: String, r: &Renderer| {
+            size.clause(name, &sql, r);
+            output.push_str(&sql);
+        };
+
+        let select = format!(
+            "SELECT{}\n  {}\n",
+            self.render_distinct(r)?,
+            self.render_projection(r)?
+        );
+        push("SELECT", select, r);
+        let (pushed, kept) = self.pushed_filters();
+        push("FROM", self.render_from(&pushed, r)?, r);
+        for join in &self.joins {
+            push("JOIN", join.render(r), r);
+        }
+        push("WHERE", conditions("WHERE", &each_filter(&kept, r)?), r);
+
+        if !self.group_by.is_empty() {
+            let group_by = format!("GROUP BY\n  {}\n", exprs(&self.group_by, r)?);
+            push("GROUP BY", group_by, r);
+        }
+        push(
+            "HAVING",
+            conditions("HAVING", &filters(&self.having, r)?),
+            r,
+        );
+        push("WINDOW", window_clause(&self.windows, r)?, r);
+
+        let keys = order_keys(&self.order_by, r)?;
+        if !keys.is_empty() {
+            push("ORDER BY", format!("ORDER BY\n  {}\n", keys.join(",")), r);
+        }
+        push("LIMIT", self.render_limit(keys.is_empty(), r), r);
+
+        size.check(r)?;
+        Ok(output)
+    }
+
+    fn render_limit(&self, unordered: bool, r: &Renderer) -> String {
+        if self.limit.is_none() && self.offset.is_none() {
+            return String::new();
+        }
+        if r.dialect == Dialect::MsSql {
+            // OFFSET ... FETCH is part of ORDER BY
+            let mut result = String::new();
+            if unordered {
+                result.push_str("ORDER BY\n  (SELECT NULL)\n");
+            }
+            result.push_str(&format!("OFFSET {} ROWS\n", self.offset.unwrap_or(0)));
+            if let Some(limit) = self.limit {
+                result.push_str(&format!("FETCH NEXT {} ROWS ONLY\n", limit));
+            }
+            return result;
+        }
+
+        // MySQL and SQLite only take OFFSET after a LIMIT
+        let limit = match (self.limit, r.dialect) {
+            (Some(limit), _) => Some(limit.to_string()),
+            (None, Dialect::MySql) => Some(u64::MAX.to_string()),
+            (None, Dialect::Sqlite) => Some("-1".to_owned()),
+            (None, _) => None,
+        };
+        let mut result = String::new();
+        if let Some(limit) = limit {
+            result.push_str(&format!("LIMIT {}\n", limit));
+        }
+        if let Some(offset) = self.offset {
+            result.push_str(&format!("OFFSET {}\n", offset));
+        }
+        result
+    }
+
+    pub(crate) fn render_projection(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        if self.projection.is_empty() {
+            return Ok("*".to_owned());
+        }
+        exprs(&self.projection, r)
+    }
+
+    fn render_distinct(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        match &self.distinct {
+            None => Ok(String::new()),
+            Some(Distinct::Rows) => Ok(" DISTINCT".to_owned()),
+            Some(Distinct::On(_)) if r.dialect != Dialect::Postgres => Err(SQLError::Unsupported(
+                format!("DISTINCT ON in {:?}", r.dialect),
+            )),
+            Some(Distinct::On(cols)) => Ok(format!(" DISTINCT ON ({})", exprs(cols, r)?)),
+        }
+    }
+
+    /// The filters applied inside each branch of a `UNION ALL`, and those
+    /// left to `WHERE`, see `Source::UnionAll`.
+    fn pushed_filters(&self) -> (Vec<&dyn Filter>, Vec<&dyn Filter>) {
+        let filters = self.filter.iter().map(|f| f.as_ref() as &dyn Filter);
+        let alias = match &self.from {
+            Source::Table(_) => return (Vec::new(), filters.collect()),
+            Source::UnionAll { .. } if self.joins.is_empty() => {
+                return (filters.collect(), Vec::new())
+            }
+            Source::UnionAll { alias, .. } => alias,
+        };
+        // a filter only SQL describes may read a joined table
+        filters.partition(|f| {
+            f.predicate().is_some_and(|p| {
+                p.comparisons().iter().all(|(column, _, _)| {
+                    column
+                        .rsplit_once('.')
+                        .is_some_and(|(qualifier, _)| qualifier == alias)
+                })
+            })
+        })
+    }
+
+    fn render_from(&self, pushed: &[&dyn Filter], r: &mut Renderer) -> Result<String, SQLError> {
+        let (tables, alias) = match &self.from {
+            Source::Table(table) => return Ok(format!("FROM {}\n", table.render(r))),
+            Source::UnionAll { tables, alias } => (tables, alias),
+        };
+
+        let mut result = String::from("FROM (\n");
+        for (idx, table) in tables.iter().enumerate() {
+            if idx > 0 {
+                result.push_str("  UNION ALL\n");
+            }
+            result.push_str(&format!("  SELECT * FROM {}\n", table.render(r)));
+            let f = each_filter(pushed, r)?;
+            if !f.is_empty() {
+                result.push_str(&format!("  WHERE ({})\n", f.join(") AND (")));
+            }
+        }
+        result.push_str(&format!(") AS {}\n", r.ident(alias)));
+
+        Ok(result)
+    }
+}
+
+impl SQLable {
+    /// The query's expressions, those without an alias named after what
+    /// they compute, apart from each other and from the `columns`.
+    fn aliased_exprs(&self, columns: &[Expr]) -> Vec<Expr> {
+        let mut taken: Vec<String> = columns
+            .iter()
+            .chain(self.exprs.iter())
+            .filter_map(|e| match e {
+                Expr::Column(c) => Some(self.naming.apply(c.rsplit('.').next().unwrap_or(c))),
+                Expr::Alias(_, alias) => Some(alias.clone()),
+                _ => None,
+            })
+            .map(|name| name.to_lowercase())
+            .collect();
+
+        let mut result = Vec::with_capacity(self.exprs.len());
+        for e in self.exprs.iter() {
+            if matches!(e, Expr::Column(_) | Expr::Alias(..)) {
+                result.push(e.clone());
+                continue;
+            }
+            let base = e.default_alias();
+            let mut alias = base.clone();
+            let mut n = 1;
+            while taken.contains(&alias) {
+                n += 1;
+                alias = format!("{}_{}", base, n);
+            }
+            taken.push(alias.clone());
+            result.push(e.clone().alias(&alias));
+        }
+        result
+    }
+}
+
+fn borrow(f: &dyn Filter) -> Box<dyn Filter + '_> {
+    Box::new(f)
+}
+
+/// Renders each filter's condition.
+pub(crate) fn filters(
+    filters: &[Box<dyn Filter + '_>],
+    r: &mut Renderer,
+) -> Result<Vec<String>, SQLError> {
+    filters.iter().map(|f| f.try_apply_filter(r)).collect()
+}
+
+fn each_filter(filters: &[&dyn Filter], r: &mut Renderer) -> Result<Vec<String>, SQLError> {
+    filters.iter().map(|f| f.try_apply_filter(r)).collect()
+}
+
+fn exprs(exprs: &[Expr], r: &mut Renderer) -> Result<String, SQLError> {
+    let result = exprs
+        .iter()
+        .map(|e| e.render(r))
+        .collect::<Result<Vec<String>, SQLError>>()?;
+    Ok(result.join(","))
+}
+
+/// Rewrites a `Select` before it is rendered, for policies applied to every
+/// query in one place: `SQLable::prepare_with` runs it over the query's tree.
+///
+/// `visit_select` sees the whole statement first, then `visit_table` each
+/// table it reads, `visit_join` each join before its table, and
+/// `visit_expr` each expression of the select list, `DISTINCT ON`,
+/// `GROUP BY` and `ORDER BY`. Subqueries used as filter values and window
+/// definitions are not visited.
+///
+/// ```
+/// # use traits::*;
+/// struct Tenant(i64);
+///
+/// impl QueryVisitor for Tenant {
+///     fn visit_select(&mut self, select: &mut Select<'_>) {
+///         select.filter.push(Box::new(SQLFilter {
+///             column: "tenantId".into(),
+///             filter: self.0,
+///             cmp: SQLComp::EQ,
+///         }));
+///     }
+///
+///     fn visit_table(&mut self, table: &mut TableName) {
+///         table.name = format!("{}_{}", table.name, self.0 % 4);
+///     }
+/// }
+///
+/// let q = SQLable::new("orders");
+/// let (sql, params) = q.prepare_with(&mut Tenant(42)).unwrap();
+/// ```
+pub trait QueryVisitor {
+    fn visit_select(&mut self, _select: &mut Select<'_>) {}
+
+    fn visit_table(&mut self, _table: &mut TableName) {}
+
+    /// Sees a join with its `ON` columns.
+    fn visit_join(&mut self, _join: &mut Join) {}
+
+    fn visit_expr(&mut self, _expr: &mut Expr) {}
+}
+
+impl Select<'_> {
+    /// Runs `visitor` over the tree, see `QueryVisitor`.
+    pub fn accept(&mut self, visitor: &mut dyn QueryVisitor) {
+        visitor.visit_select(self);
+
+        match &mut self.from {
+            Source::Table(table) => visitor.visit_table(table),
+            Source::UnionAll { tables, .. } => {
+                tables.iter_mut().for_each(|t| visitor.visit_table(t))
+            }
+        }
+        for join in &mut self.joins {
+            visitor.visit_join(join);
+            visitor.visit_table(&mut join.table);
+        }
+
+        let distinct_on = match &mut self.distinct {
+            Some(Distinct::On(cols)) => &mut cols[..],
+            _ => &mut [],
+        };
+        let exprs = distinct_on
+            .iter_mut()
+            .chain(&mut self.projection)
+            .chain(&mut self.group_by)
+            .chain(self.order_by.iter_mut().map(|(key, _, _)| key));
+        for expr in exprs {
+            visitor.visit_expr(expr);
+        }
+    }
+}
+
+impl SQLable {
+    /// Like `prepare_params`, with `visitor` rewriting the query first. The
+    /// query is checked as `prepare_params` checks it.
+    pub fn prepare_with(
+        &self,
+        visitor: &mut dyn QueryVisitor,
+    ) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let mut ast = self.ast();
+        ast.accept(visitor);
+
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        let sql = self.render_select(&ast, &mut r)?;
+        Ok((sql, r.into_params()))
+    }
+}
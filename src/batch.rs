@@ -0,0 +1,110 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::{Condition, SQLComp, SQLFilter, SQLable, ToSql};
+
+/// Coalesces point lookups (`WHERE id = ?`) into `WHERE id IN (...)` queries.
+///
+/// Callers `push` the keys they would have looked up one by one, run the
+/// `queries()` once the window is over, and hand the rows to `fan_out`,
+/// which returns each caller's rows in push order.
+///
+/// ```
+/// # use traits::*;
+/// let mut batch = LookupBatch::new(|| SQLable::new("users"), "id");
+/// let alice = batch.push(1);
+/// let bob = batch.push(2);
+/// let again = batch.push(1);
+/// assert_eq!(batch.queries().len(), 1);
+///
+/// let rows = vec![(1, "alice"), (2, "bob")]; // as returned by the database
+/// let results = batch.fan_out(rows, |row| row.0);
+/// assert_eq!(results[alice], vec![(1, "alice")]);
+/// assert_eq!(results[again], vec![(1, "alice")]);
+/// # let _ = bob;
+/// ```
+pub struct LookupBatch<K, F> {
+    query: F,
+    column: String,
+    keys: Vec<K>,
+    max_batch: usize,
+}
+
+impl<K, F> LookupBatch<K, F>
+where
+    K: ToSql + Clone + Eq + Hash + 'static,
+    F: Fn() -> SQLable,
+{
+    /// `query` builds the lookup without the key filter; `column` is the key.
+    pub fn new(query: F, column: &str) -> Self {
+        LookupBatch {
+            query,
+            column: column.to_owned(),
+            keys: Vec::new(),
+            max_batch: 1000,
+        }
+    }
+
+    /// Sets how many keys go into one IN list at most, 1000 by default.
+    /// Longer lists are split over several queries.
+    pub fn max_batch(mut self, n: usize) -> Self {
+        self.max_batch = n.max(1);
+        self
+    }
+
+    /// Queues a lookup of `key` and returns its index into `fan_out`'s result.
+    pub fn push(&mut self, key: K) -> usize {
+        self.keys.push(key);
+        self.keys.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The queries fetching every queued key, each key once.
+    pub fn queries(&self) -> Vec<SQLable> {
+        let mut seen = HashSet::new();
+        let unique: Vec<K> = self
+            .keys
+            .iter()
+            .filter(|k| seen.insert(*k))
+            .cloned()
+            .collect();
+
+        unique
+            .chunks(self.max_batch)
+            .map(|chunk| {
+                let mut query = (self.query)();
+                query.filter.push(Condition::Shared(Arc::new(SQLFilter {
+                    column: self.column.clone().into(),
+                    filter: chunk.to_vec(),
+                    cmp: SQLComp::EQ,
+                })));
+                query
+            })
+            .collect()
+    }
+
+    /// Distributes the rows of all `queries()` to the queued lookups: the
+    /// `n`-th element holds the rows whose `key` equals the `n`-th pushed key.
+    pub fn fan_out<R: Clone>(
+        &self,
+        rows: impl IntoIterator<Item = R>,
+        key: impl Fn(&R) -> K,
+    ) -> Vec<Vec<R>> {
+        let mut callers: HashMap<&K, Vec<usize>> = HashMap::new();
+        for (idx, k) in self.keys.iter().enumerate() {
+            callers.entry(k).or_default().push(idx);
+        }
+
+        let mut results = vec![Vec::new(); self.keys.len()];
+        for row in rows {
+            for &idx in callers.get(&key(&row)).into_iter().flatten() {
+                results[idx].push(row.clone());
+            }
+        }
+        results
+    }
+}
@@ -0,0 +1,154 @@
+//! The project manifest lists the definition files making up the project's
+//! query catalog:
+//!
+//! ```yaml
+//! # dyn-trait.yaml
+//! dialect: postgres
+//! catalogs:
+//!   - queries/users.yaml
+//!   - queries/reports.json
+//! ```
+//!
+//! Paths are relative to the manifest. Query names must be unique across
+//! all files.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, Subcommand};
+use serde::Deserialize;
+use traits::{Dialect, QueryCatalog, SqlValue};
+
+use crate::{dialect_parser, parse_param, print_query, read_defs, Cli};
+
+pub const MANIFEST: &str = "dyn-trait.yaml";
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[arg(short, long, default_value = MANIFEST, global = true)]
+    manifest: PathBuf,
+    #[command(subcommand)]
+    command: Option<CatalogCommand>,
+}
+
+#[derive(Subcommand)]
+enum CatalogCommand {
+    /// List the registered queries (the default)
+    List,
+    /// Render one registered query
+    Render {
+        name: String,
+        /// Overrides the manifest's dialect
+        #[arg(short, long, value_parser = dialect_parser())]
+        dialect: Option<Dialect>,
+        /// Value of a `param` filter, as NAME=VALUE
+        #[arg(short = 'p', long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+        values: Vec<(String, SqlValue)>,
+        /// Print placeholders and the bound values instead of inlining them
+        #[arg(long)]
+        bind: bool,
+    },
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    dialect: Option<String>,
+    catalogs: Vec<PathBuf>,
+}
+
+struct Project {
+    dialect: Dialect,
+    catalog: QueryCatalog,
+    /// The file each query was read from.
+    sources: BTreeMap<String, PathBuf>,
+}
+
+fn load(manifest: &Path) -> Result<Project, String> {
+    let text =
+        std::fs::read_to_string(manifest).map_err(|e| format!("{}: {}", manifest.display(), e))?;
+    let m: Manifest =
+        serde_yaml::from_str(&text).map_err(|e| format!("{}: {}", manifest.display(), e))?;
+    let dialect = match m.dialect {
+        Some(d) => d.parse().map_err(|e: traits::SQLError| e.to_string())?,
+        None => Dialect::default(),
+    };
+
+    let base = manifest.parent().unwrap_or(Path::new(""));
+    let mut project = Project {
+        dialect,
+        catalog: QueryCatalog::new(),
+        sources: BTreeMap::new(),
+    };
+    for file in m.catalogs {
+        let path = base.join(file);
+        let defs = read_defs(&path)?;
+        for name in defs.names() {
+            project.sources.insert(name.to_owned(), path.clone());
+        }
+        project
+            .catalog
+            .merge(defs)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    Ok(project)
+}
+
+pub fn run(args: Args) -> Result<bool, String> {
+    let project = load(&args.manifest)?;
+    match args.command.unwrap_or(CatalogCommand::List) {
+        CatalogCommand::List => {
+            for (name, def) in project.catalog.iter() {
+                let params = def.params();
+                let params = if params.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", params.join(", "))
+                };
+                println!(
+                    "{}{}\t{}\t{}",
+                    name,
+                    params,
+                    def.table,
+                    project.sources[name].display()
+                );
+            }
+        }
+        CatalogCommand::Render {
+            name,
+            dialect,
+            values,
+            bind,
+        } => {
+            let params: BTreeMap<String, SqlValue> = values.into_iter().collect();
+            let mut query = project
+                .catalog
+                .build(&name, &params)
+                .map_err(|e| e.to_string())?;
+            query.dialect(dialect.unwrap_or(project.dialect));
+            print_query(&name, &query, bind)?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Writes the completion script to stdout. When the manifest exists its
+/// query names complete `catalog render`.
+pub fn completions(shell: clap_complete::Shell, manifest: &Path) -> Result<(), String> {
+    let mut cmd = Cli::command();
+    if manifest.exists() {
+        let project = load(manifest)?;
+        let names: Vec<String> = project.catalog.names().map(str::to_owned).collect();
+        cmd = cmd.mut_subcommand("catalog", |c| {
+            c.mut_subcommand("render", |r| {
+                r.mut_arg("name", |a| a.value_parser(PossibleValuesParser::new(names)))
+            })
+        });
+    }
+
+    clap_complete::generate(shell, &mut cmd, "dyn-trait", &mut std::io::stdout());
+    Ok(())
+}
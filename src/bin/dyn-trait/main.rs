@@ -0,0 +1,253 @@
+//! Renders, lints and fingerprints query definitions stored as YAML or JSON,
+//! and compares saved query plans.
+//!
+//! A definition file maps query names to `QueryDef`s:
+//!
+//! ```yaml
+//! active_users:
+//!   table: users
+//!   columns: [userId, name]
+//!   filters:
+//!     - { column: status, op: "=", value: active }
+//!     - { column: country, op: "=", param: country }
+//! ```
+//!
+//! Values of `param` filters are given on the command line with
+//! `--param country=fr`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand};
+use traits::{Dialect, QueryCatalog, QueryDef, QueryPlans, SQLable, SqlValue};
+
+mod catalog;
+mod repl;
+
+const DIALECTS: [&str; 5] = ["generic", "postgres", "mysql", "sqlite", "mssql"];
+
+fn dialect_parser() -> impl TypedValueParser<Value = Dialect> {
+    PossibleValuesParser::new(DIALECTS).map(|s| s.parse::<Dialect>().unwrap())
+}
+
+/// Parses `NAME=VALUE`, with VALUE read as YAML: `18`, `fr`, `[a, b]`, `null`.
+fn parse_param(s: &str) -> Result<(String, SqlValue), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got {}", s))?;
+    let value =
+        serde_yaml::from_str(value).map_err(|e| format!("bad value for {}: {}", name, e))?;
+    Ok((name.to_owned(), value))
+}
+
+#[derive(Parser)]
+#[command(
+    name = "dyn-trait",
+    about = "Render and check stored query definitions"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the SQL of each query
+    Render {
+        #[command(flatten)]
+        input: Input,
+        /// Print placeholders and the bound values instead of inlining them
+        #[arg(long)]
+        bind: bool,
+    },
+    /// Report likely mistakes; exits with status 1 if there are any
+    Lint {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Print the shape fingerprint of each query
+    Fingerprint {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Build a query interactively, starting empty or from a definition
+    Repl {
+        /// Definition file to start from
+        file: Option<PathBuf>,
+        /// Query of the file to start from, required if it holds several
+        #[arg(short, long, requires = "file")]
+        query: Option<String>,
+        #[arg(short, long, default_value = "generic", value_parser = dialect_parser())]
+        dialect: Dialect,
+        /// Table to start from when no file is given
+        #[arg(short, long, default_value = "tbl", conflicts_with = "file")]
+        table: String,
+    },
+    /// Compare plans saved from `QueryPlans`; exits with status 1 if any
+    /// plan changed
+    PlanDiff {
+        /// YAML or JSON file of the reference plans
+        before: PathBuf,
+        /// YAML or JSON file of the plans to check
+        after: PathBuf,
+    },
+    /// List and render the queries registered in the project manifest
+    Catalog(catalog::Args),
+    /// Print a shell completion script, including the catalog's query names
+    Completions {
+        shell: clap_complete::Shell,
+        #[arg(short, long, default_value = catalog::MANIFEST)]
+        manifest: PathBuf,
+    },
+}
+
+#[derive(clap::Args)]
+struct Input {
+    /// YAML or JSON definition file, `-` for stdin
+    file: PathBuf,
+    #[arg(short, long, default_value = "generic", value_parser = dialect_parser())]
+    dialect: Dialect,
+    /// Only process the named query
+    #[arg(short, long)]
+    query: Option<String>,
+    /// Value of a `param` filter, as NAME=VALUE
+    #[arg(short = 'p', long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+    values: Vec<(String, SqlValue)>,
+}
+
+impl Input {
+    fn load(&self) -> Result<Vec<(String, SQLable)>, String> {
+        let catalog = read_defs(&self.file)?;
+        if let Some(name) = &self.query {
+            if catalog.get(name).is_none() {
+                return Err(format!("no query named {}", name));
+            }
+        }
+
+        let params: BTreeMap<String, SqlValue> = self.values.iter().cloned().collect();
+        catalog
+            .names()
+            .filter(|name| self.query.as_deref().is_none_or(|q| q == *name))
+            .map(|name| {
+                let mut query = catalog
+                    .build(name, &params)
+                    .map_err(|e| format!("{}: {}", name, e))?;
+                query.dialect(self.dialect);
+                Ok((name.to_owned(), query))
+            })
+            .collect()
+    }
+}
+
+fn read_defs(path: &Path) -> Result<QueryCatalog, String> {
+    let text = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?
+    };
+
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+    if is_json {
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    } else {
+        // YAML also accepts JSON, which covers stdin
+        serde_yaml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+fn read_plans(path: &Path) -> Result<QueryPlans, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_yaml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn pick_def(catalog: QueryCatalog, name: Option<String>) -> Result<QueryDef, String> {
+    let name = match name {
+        Some(name) => name,
+        None if catalog.len() == 1 => catalog.names().next().unwrap().to_owned(),
+        None => return Err("the file holds several queries, pick one with --query".to_owned()),
+    };
+    catalog
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("no query named {}", name))
+}
+
+fn print_query(name: &str, query: &SQLable, bind: bool) -> Result<(), String> {
+    println!("-- {}", name);
+    if bind {
+        let (sql, values) = query.prepare_params().map_err(|e| e.to_string())?;
+        println!("{}-- params: {:?}\n", sql, values);
+    } else {
+        println!("{}", query.try_prepare().map_err(|e| e.to_string())?);
+    }
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<bool, String> {
+    match cli.command {
+        Command::Render { input, bind } => {
+            for (name, query) in input.load()? {
+                print_query(&name, &query, bind)?;
+            }
+            Ok(true)
+        }
+        Command::Lint { input } => {
+            let mut clean = true;
+            for (name, query) in input.load()? {
+                for lint in query.lint() {
+                    println!("{}: warning: {}", name, lint);
+                    clean = false;
+                }
+            }
+            Ok(clean)
+        }
+        Command::Fingerprint { input } => {
+            for (name, query) in input.load()? {
+                let fp = query.fingerprint().map_err(|e| e.to_string())?;
+                println!("{:016x}  {}", fp, name);
+            }
+            Ok(true)
+        }
+        Command::Repl {
+            file,
+            query,
+            dialect,
+            table,
+        } => {
+            let def = match file {
+                Some(file) => pick_def(read_defs(&file)?, query)?,
+                None => QueryDef {
+                    table,
+                    ..Default::default()
+                },
+            };
+            repl::run(def, dialect)?;
+            Ok(true)
+        }
+        Command::PlanDiff { before, after } => {
+            let changes = read_plans(&before)?.diff(&read_plans(&after)?);
+            for change in &changes {
+                print!("{}", change);
+            }
+            Ok(changes.is_empty())
+        }
+        Command::Catalog(args) => catalog::run(args),
+        Command::Completions { shell, manifest } => {
+            catalog::completions(shell, &manifest)?;
+            Ok(true)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("dyn-trait: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
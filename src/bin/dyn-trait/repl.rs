@@ -0,0 +1,241 @@
+//! Interactive prototyping: build a query one command at a time and see the
+//! rendered SQL after each change.
+
+use std::io::{BufRead, IsTerminal, Write};
+
+use traits::{is_reserved, Dialect, FilterDef, QueryDef, SQLComp, SqlValue};
+
+const HELP: &str = "\
+commands:
+  table NAME                 select from NAME
+  select COL [COL ...]       replace the select list (no columns selects *)
+  filter COL OP VALUE        add a filter, VALUE is YAML: 18, 'a', [a, b], null
+  drop N                     remove the N-th filter
+  dialect NAME               render for generic, postgres, mysql, sqlite or mssql
+  params                     toggle placeholders and bound values
+  lint                       report likely mistakes
+  show                       print the definition as YAML
+  help                       print this message
+  quit                       leave";
+
+struct Session {
+    def: QueryDef,
+    dialect: Dialect,
+    params: bool,
+    color: bool,
+}
+
+pub fn run(def: QueryDef, dialect: Dialect) -> Result<(), String> {
+    let mut session = Session {
+        def,
+        dialect,
+        params: false,
+        color: std::io::stdout().is_terminal(),
+    };
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        println!("{}\n", HELP);
+    }
+    session.render();
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            std::io::stdout().flush().map_err(|e| e.to_string())?;
+        }
+        let line = match lines.next() {
+            Some(line) => line.map_err(|e| e.to_string())?,
+            None => return Ok(()),
+        };
+
+        match session.command(line.trim()) {
+            Ok(Step::Quit) => return Ok(()),
+            Ok(Step::Render) => session.render(),
+            Ok(Step::Continue) => {}
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+enum Step {
+    Render,
+    Continue,
+    Quit,
+}
+
+impl Session {
+    fn command(&mut self, line: &str) -> Result<Step, String> {
+        let (cmd, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        match cmd {
+            "" => Ok(Step::Continue),
+            "table" if !rest.is_empty() => {
+                self.def.table = rest.to_owned();
+                Ok(Step::Render)
+            }
+            "select" => {
+                self.def.columns = rest.split_whitespace().map(str::to_owned).collect();
+                Ok(Step::Render)
+            }
+            "filter" => {
+                self.def.filters.push(parse_filter(rest)?);
+                Ok(Step::Render)
+            }
+            "drop" => {
+                let n: usize = rest.parse().map_err(|_| "usage: drop N".to_owned())?;
+                if n == 0 || n > self.def.filters.len() {
+                    return Err(format!("there is no filter {}", n));
+                }
+                self.def.filters.remove(n - 1);
+                Ok(Step::Render)
+            }
+            "dialect" => {
+                self.dialect = rest.parse().map_err(|e: traits::SQLError| e.to_string())?;
+                Ok(Step::Render)
+            }
+            "params" => {
+                self.params = !self.params;
+                Ok(Step::Render)
+            }
+            "lint" => {
+                let query = self.def.to_query().map_err(|e| e.to_string())?;
+                for lint in query.lint() {
+                    println!("warning: {}", lint);
+                }
+                Ok(Step::Continue)
+            }
+            "show" => {
+                print!(
+                    "{}",
+                    serde_yaml::to_string(&self.def).map_err(|e| e.to_string())?
+                );
+                Ok(Step::Continue)
+            }
+            "help" => {
+                println!("{}", HELP);
+                Ok(Step::Continue)
+            }
+            "quit" | "exit" => Ok(Step::Quit),
+            _ => Err(format!("unknown command {:?}, try help", line)),
+        }
+    }
+
+    fn render(&self) {
+        let rendered = self.def.to_query().and_then(|mut query| {
+            query.dialect(self.dialect);
+            if self.params {
+                query
+                    .prepare_params()
+                    .map(|(sql, values)| format!("{}-- params: {:?}\n", sql, values))
+            } else {
+                query.try_prepare()
+            }
+        });
+
+        match rendered {
+            Ok(sql) if self.color => println!("{}", highlight(&sql)),
+            Ok(sql) => println!("{}", sql),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+/// Parses `COL OP VALUE`, where OP may span several words (`is distinct from`).
+fn parse_filter(s: &str) -> Result<FilterDef, String> {
+    let usage = || "usage: filter COL OP VALUE".to_owned();
+    let (column, rest) = s.split_once(' ').ok_or_else(usage)?;
+    let words: Vec<&str> = rest.split_whitespace().collect();
+
+    for n in (1..=words.len().min(4)).rev() {
+        let op = words[..n].join(" ");
+        if op.parse::<SQLComp>().is_err() {
+            continue;
+        }
+        let value = words[n..].join(" ");
+        let value: SqlValue = if value.is_empty() {
+            SqlValue::Null
+        } else {
+            serde_yaml::from_str(&value).map_err(|e| format!("bad value: {}", e))?
+        };
+        return Ok(FilterDef {
+            column: column.to_owned(),
+            op,
+            value,
+            param: None,
+            origin: None,
+        });
+    }
+
+    Err(usage())
+}
+
+const KEYWORD: &str = "\x1b[1;34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[36m";
+const PARAM: &str = "\x1b[35m";
+const COMMENT: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Colours keywords, literals and placeholders with ANSI escapes.
+fn highlight(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len() * 2);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let color = if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            Some(COMMENT)
+        } else if c == '\'' {
+            i += 1;
+            while i < chars.len() {
+                // '' is an escaped quote inside the literal
+                if chars[i] == '\'' && chars.get(i + 1) != Some(&'\'') {
+                    break;
+                }
+                i += if chars[i] == '\'' { 2 } else { 1 };
+            }
+            i += 1;
+            Some(STRING)
+        } else if c == '?' || ((c == '$' || c == '@') && i + 1 < chars.len()) {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric()) {
+                i += 1;
+            }
+            Some(PARAM)
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            Some(NUMBER)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let keyword = word.chars().all(|c| !c.is_lowercase()) && is_reserved(&word);
+            keyword.then_some(KEYWORD)
+        } else {
+            i += 1;
+            None
+        };
+
+        let token: String = chars[start..i.min(chars.len())].iter().collect();
+        match color {
+            Some(color) => {
+                out.push_str(color);
+                out.push_str(&token);
+                out.push_str(RESET);
+            }
+            None => out.push_str(&token),
+        }
+    }
+
+    out
+}
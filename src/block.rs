@@ -0,0 +1,200 @@
+use crate::{Call, Dialect, ExecError, Execute, Insert, SQLError, SQLable};
+
+/// Statements run as one unit on the server, with procedural glue written
+/// around them, e.g. an operational fixup: a Postgres `DO $$ ... $$`
+/// block, or a SQL Server batch.
+///
+/// ```
+/// # use traits::*;
+/// let mut stale = SQLable::new("sessions");
+/// stale.dialect(Dialect::Postgres).limit(1);
+/// let mut cleanup = Call::procedure("purge_sessions");
+/// cleanup.dialect(Dialect::Postgres).arg(30);
+///
+/// let mut block = Block::new(Dialect::Postgres);
+/// block
+///     .query(&stale)
+///     .raw("IF NOT FOUND THEN\n  RETURN;\nEND IF")
+///     .call(&cleanup)
+///     .raw("RAISE NOTICE 'purged'");
+/// assert_eq!(
+///     block.render().unwrap(),
+///     "DO $$\nBEGIN\n  PERFORM\n    *\n  FROM sessions\n  LIMIT 1;\n  IF NOT FOUND THEN\n    RETURN;\n  END IF;\n  CALL purge_sessions(30);\n  RAISE NOTICE 'purged';\nEND\n$$\n"
+/// );
+/// ```
+///
+/// On SQL Server the statements follow each other, and `batch` separates
+/// batches with `GO`:
+///
+/// ```
+/// # use traits::*;
+/// let mut block = Block::new(Dialect::MsSql);
+/// block
+///     .raw("CREATE SCHEMA audit")
+///     .batch()
+///     .raw("CREATE VIEW audit.recent AS SELECT 1 AS x");
+/// assert_eq!(
+///     block.render().unwrap(),
+///     "CREATE SCHEMA audit;\nGO\nCREATE VIEW audit.recent AS SELECT 1 AS x;\nGO\n"
+/// );
+/// ```
+///
+/// Statements are rendered with their values inlined, with their own
+/// dialect and options. Other dialects have no such blocks.
+pub struct Block<'a> {
+    dialect: Dialect,
+    steps: Vec<Step<'a>>,
+}
+
+enum Step<'a> {
+    Query(&'a SQLable),
+    Insert(&'a Insert),
+    Call(&'a Call),
+    Raw(String),
+    Batch,
+}
+
+impl<'a> Block<'a> {
+    pub fn new(dialect: Dialect) -> Self {
+        Block {
+            dialect,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a query, whose rows are discarded: `PERFORM` in a Postgres
+    /// block, which sets `FOUND`.
+    pub fn query(&mut self, query: &'a SQLable) -> &mut Self {
+        self.steps.push(Step::Query(query));
+        self
+    }
+
+    pub fn insert(&mut self, insert: &'a Insert) -> &mut Self {
+        self.steps.push(Step::Insert(insert));
+        self
+    }
+
+    /// Adds a call. A Postgres block cannot read OUT parameters, so
+    /// procedures with some fail to render there; a function's value is
+    /// discarded.
+    pub fn call(&mut self, call: &'a Call) -> &mut Self {
+        self.steps.push(Step::Call(call));
+        self
+    }
+
+    /// Adds a statement as written, e.g. `IF ... END IF` or `DECLARE`.
+    /// A trailing `;` is dropped.
+    pub fn raw(&mut self, sql: impl Into<String>) -> &mut Self {
+        self.steps.push(Step::Raw(sql.into()));
+        self
+    }
+
+    /// Ends a batch and starts another: the next statements go into a
+    /// block of their own on Postgres, after `GO` on SQL Server, for
+    /// statements that must come first in their batch such as
+    /// `CREATE VIEW`.
+    pub fn batch(&mut self) -> &mut Self {
+        self.steps.push(Step::Batch);
+        self
+    }
+
+    /// The whole script: each batch as a block on Postgres, each followed
+    /// by `GO` on SQL Server.
+    pub fn render(&self) -> Result<String, SQLError> {
+        let batches = self.batches()?;
+        let end = match self.dialect {
+            Dialect::MsSql => "GO\n",
+            _ => "",
+        };
+        Ok(batches.into_iter().map(|b| b + end).collect())
+    }
+
+    /// The batches, each sent to the server as one statement by
+    /// `execute`. Empty batches are left out.
+    pub fn batches(&self) -> Result<Vec<String>, SQLError> {
+        if !matches!(self.dialect, Dialect::Postgres | Dialect::MsSql) {
+            return Err(SQLError::Unsupported(format!(
+                "code blocks in {:?}",
+                self.dialect
+            )));
+        }
+        let mut batches = Vec::new();
+        let mut statements = Vec::new();
+        for step in self.steps.iter().chain([&Step::Batch]) {
+            match step {
+                Step::Batch if statements.is_empty() => {}
+                Step::Batch => batches.push(self.wrap(std::mem::take(&mut statements))),
+                step => statements.push(self.inline(step)?),
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Runs every batch on `conn`, in order, stopping at the first that
+    /// fails.
+    pub fn execute<C: Execute + ?Sized>(&self, conn: &C) -> Result<(), ExecError<C::Error>> {
+        for batch in self.batches()? {
+            conn.execute(&batch, &[])?;
+        }
+        Ok(())
+    }
+
+    /// The statements of a batch as the dialect runs them together.
+    fn wrap(&self, statements: Vec<String>) -> String {
+        let body: String = statements.iter().map(|s| format!("{};\n", s)).collect();
+        if self.dialect == Dialect::MsSql {
+            return body;
+        }
+        let body: String = body.lines().map(|l| format!("  {}\n", l)).collect();
+        let body = format!("BEGIN\n{}END\n", body);
+        let tag = dollar_tag(&body);
+        format!("DO {}\n{}{}\n", tag, body, tag)
+    }
+
+    /// The statement of a step with values inlined, without its trailing
+    /// newline or `;`.
+    fn inline(&self, step: &Step) -> Result<String, SQLError> {
+        let postgres = self.dialect == Dialect::Postgres;
+        let sql = match step {
+            Step::Query(q) if postgres => perform(&q.try_prepare()?),
+            Step::Query(q) => q.try_prepare()?,
+            Step::Insert(i) => i.try_prepare()?,
+            // a function's value is discarded like a query's
+            Step::Call(c) if postgres && c.try_prepare()?.starts_with("SELECT") => {
+                perform(&c.try_prepare()?)
+            }
+            Step::Call(c) if postgres && !c.outputs().is_empty() => {
+                return Err(SQLError::Unsupported(
+                    "OUT parameters in a DO block".to_owned(),
+                ))
+            }
+            Step::Call(c) => c.try_prepare()?,
+            Step::Raw(sql) => sql.clone(),
+            Step::Batch => String::new(),
+        };
+        Ok(sql.trim_end().trim_end_matches(';').to_owned())
+    }
+}
+
+/// A query as a PL/pgSQL statement, which has no bare `SELECT`.
+fn perform(sql: &str) -> String {
+    match sql.strip_prefix("SELECT") {
+        Some(rest) => format!("PERFORM{}", rest),
+        None => format!("PERFORM * FROM (\n{}) AS q", sql),
+    }
+}
+
+/// A dollar quote that does not occur in `body`: `$$`, else `$do$`,
+/// `$do1$`, ...
+fn dollar_tag(body: &str) -> String {
+    if !body.contains("$$") {
+        return "$$".to_owned();
+    }
+    (0..)
+        .map(|n| match n {
+            0 => "$do$".to_owned(),
+            n => format!("$do{}$", n),
+        })
+        .find(|tag| !body.contains(tag.as_str()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,189 @@
+use crate::observer::RepeatDetector;
+use crate::{fingerprint, Insert, Lint, Observer, SQLError, SQLable, SqlValue, Touched};
+
+/// Counts the statements rendered for one logical operation (say, one HTTP
+/// request) and the approximate size of their parameters, and refuses to
+/// render more than the configured caps. A loop that issues one query per
+/// row fails loudly instead of quietly sending thousands of statements.
+///
+/// ```
+/// # use traits::*;
+/// let mut budget = QueryBudget::new().max_statements(2);
+/// let q = SQLable::new("users");
+/// assert!(budget.prepare(&q).is_ok());
+/// assert!(budget.prepare(&q).is_ok());
+/// assert!(budget.prepare(&q).is_err());
+/// ```
+///
+/// With `warn_repeats` it also flags query shapes that run too often, the
+/// usual sign of an N+1 loop. Warnings are kept in `warnings()` and passed
+/// to the observer, if any:
+///
+/// ```
+/// # use traits::*;
+/// let mut budget = QueryBudget::new().warn_repeats(2);
+/// for id in 1..=3 {
+///     let mut q = SQLable::new("users");
+///     q.filter(vec![Box::new(SQLFilter { column: "id".into(), filter: id, cmp: SQLComp::EQ })]);
+///     budget.prepare(&q).unwrap();
+/// }
+/// assert!(matches!(budget.warnings(), [Lint::RepeatedQuery(_, 3)]));
+/// ```
+#[derive(Default)]
+pub struct QueryBudget {
+    max_statements: Option<usize>,
+    max_param_bytes: Option<usize>,
+    statements: usize,
+    param_bytes: usize,
+    repeats: Option<RepeatDetector>,
+    warnings: Vec<Lint>,
+    observer: Option<Box<dyn Observer>>,
+}
+
+impl QueryBudget {
+    /// A budget without caps, which only counts.
+    pub fn new() -> Self {
+        QueryBudget::default()
+    }
+
+    /// Caps the number of statements.
+    pub fn max_statements(mut self, n: usize) -> Self {
+        self.max_statements = Some(n);
+        self
+    }
+
+    /// Caps the estimated total size of the bound parameters, in bytes.
+    pub fn max_param_bytes(mut self, n: usize) -> Self {
+        self.max_param_bytes = Some(n);
+        self
+    }
+
+    /// Raises a warning when a query with the same fingerprint is
+    /// rendered more than `n` times.
+    pub fn warn_repeats(mut self, n: usize) -> Self {
+        self.repeats = Some(RepeatDetector::new(n));
+        self
+    }
+
+    /// Sets the observer notified of statements and warnings.
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// The warnings raised so far.
+    pub fn warnings(&self) -> &[Lint] {
+        &self.warnings
+    }
+
+    /// The number of statements rendered so far.
+    pub fn statements(&self) -> usize {
+        self.statements
+    }
+
+    /// The estimated size of the parameters bound so far, in bytes.
+    pub fn param_bytes(&self) -> usize {
+        self.param_bytes
+    }
+
+    /// Renders `query` with parameters and charges it to the budget. A
+    /// statement that would exceed a cap is not rendered and not counted.
+    pub fn prepare(&mut self, query: &SQLable) -> Result<(String, Vec<SqlValue>), SQLError> {
+        self.check_statements()?;
+        let (sql, params) = query.prepare_params()?;
+        self.charge(sql, params, None)
+    }
+
+    /// Like `prepare`, for an insert. The observer is also told which rows
+    /// it writes, see `Insert::touched`.
+    pub fn prepare_insert(&mut self, insert: &Insert) -> Result<(String, Vec<SqlValue>), SQLError> {
+        self.check_statements()?;
+        let (sql, params) = insert.prepare_params()?;
+        let touched = self.observer.is_some().then(|| insert.touched());
+        self.charge(sql, params, touched)
+    }
+
+    fn check_statements(&self) -> Result<(), SQLError> {
+        if self
+            .max_statements
+            .is_some_and(|max| self.statements >= max)
+        {
+            return Err(SQLError::BudgetExceeded(format!(
+                "more than {} statements",
+                self.statements
+            )));
+        }
+        Ok(())
+    }
+
+    /// Counts a rendered statement unless it exceeds the parameter cap, and
+    /// reports it.
+    fn charge(
+        &mut self,
+        sql: String,
+        params: Vec<SqlValue>,
+        touched: Option<Touched>,
+    ) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let bytes = params.iter().map(estimated_size).sum::<usize>();
+        if let Some(max) = self.max_param_bytes {
+            if self.param_bytes + bytes > max {
+                return Err(SQLError::BudgetExceeded(format!(
+                    "more than {} bytes of parameters",
+                    max
+                )));
+            }
+        }
+
+        self.statements += 1;
+        self.param_bytes += bytes;
+
+        let warning = self
+            .repeats
+            .as_mut()
+            .and_then(|r| r.record(fingerprint(&sql)));
+        if let Some(observer) = self.observer.as_mut() {
+            observer.statement(&sql, &params);
+            if let Some(touched) = &touched {
+                observer.touched(touched);
+            }
+            if let Some(lint) = &warning {
+                observer.warning(lint);
+            }
+        }
+        self.warnings.extend(warning);
+        Ok((sql, params))
+    }
+
+    /// Starts the next operation with the same caps and observer.
+    pub fn reset(&mut self) {
+        self.statements = 0;
+        self.param_bytes = 0;
+        self.warnings.clear();
+        if let Some(repeats) = self.repeats.as_mut() {
+            repeats.reset();
+        }
+    }
+}
+
+/// Roughly what the value costs on the wire.
+pub(crate) fn estimated_size(value: &SqlValue) -> usize {
+    match value {
+        SqlValue::Null => 0,
+        SqlValue::Int(_) | SqlValue::UInt(_) | SqlValue::Float(_) => 8,
+        SqlValue::Text(s) => s.len(),
+        SqlValue::List(v) => v.iter().map(estimated_size).sum(),
+    }
+}
+
+impl std::fmt::Debug for QueryBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryBudget")
+            .field("max_statements", &self.max_statements)
+            .field("max_param_bytes", &self.max_param_bytes)
+            .field("statements", &self.statements)
+            .field("param_bytes", &self.param_bytes)
+            .field("repeats", &self.repeats)
+            .field("warnings", &self.warnings)
+            .finish_non_exhaustive()
+    }
+}
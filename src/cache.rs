@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::{SQLError, SQLable, SqlValue};
+
+/// Keeps the SQL of the most recently used query shapes, so that hot
+/// paths render a query once and then only collect its values. A shape is
+/// a query built with `param`s, named by the caller, and the shape of the
+/// values it runs with: which are NULL, and how long the lists are, as
+/// those change the SQL.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use traits::*;
+/// let mut q = SQLable::new("users");
+/// q.dialect(Dialect::Postgres).filter(vec![Box::new(SQLFilter {
+///     column: "age".into(),
+///     filter: param("min_age"),
+///     cmp: SQLComp::GEQ,
+/// })]);
+///
+/// let mut cache = StatementCache::new(16);
+/// let mut values = BTreeMap::new();
+/// for age in [18, 21] {
+///     values.insert("min_age".to_owned(), SqlValue::Int(age));
+///     let (sql, params) = cache.prepare("adults", &q, &values).unwrap();
+///     assert_eq!(&*sql, "SELECT\n  *\nFROM users\nWHERE\n  (age >= $1)\n");
+///     assert_eq!(params, vec![SqlValue::Int(age)]);
+/// }
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// ```
+///
+/// The name stands for the query: a name must not be used for queries
+/// that differ other than in their `param` values. Values bound other
+/// than through a `param` are kept as first rendered. Queries are rendered
+/// without `SQLable::reuse_params`, which depends on the values.
+#[derive(Debug)]
+pub struct StatementCache {
+    capacity: usize,
+    entries: HashMap<Key, Entry>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    name: String,
+    values: Vec<(String, Shape)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Shape {
+    Null,
+    Scalar,
+    List(usize),
+}
+
+#[derive(Debug)]
+struct Entry {
+    sql: Arc<str>,
+    slots: Vec<Slot>,
+    used: u64,
+}
+
+/// Where a bound value comes from, recorded while rendering.
+#[derive(Clone, Debug)]
+enum Slot {
+    Fixed(SqlValue),
+    /// The value of a `param`, or the element of it if a list.
+    Param(String, usize),
+}
+
+/// The slots of a render in progress, see `Renderer::bind`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Slots {
+    slots: Vec<Slot>,
+    param: Option<(String, usize)>,
+}
+
+impl Slots {
+    pub(crate) fn record(&mut self, value: &SqlValue) {
+        match self.param.as_mut() {
+            Some((name, idx)) => {
+                self.slots.push(Slot::Param(name.clone(), *idx));
+                *idx += 1;
+            }
+            None => self.slots.push(Slot::Fixed(value.clone())),
+        }
+    }
+
+    /// Attributes the values bound until `leave` to the `param` `name`.
+    pub(crate) fn enter(&mut self, name: &str) {
+        self.param = Some((name.to_owned(), 0));
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.param = None;
+    }
+}
+
+impl StatementCache {
+    /// A cache of at most `capacity` shapes, evicting the least recently
+    /// used.
+    pub fn new(capacity: usize) -> Self {
+        StatementCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Like `SQLable::prepare_params_with`, rendering `query` only the
+    /// first time its shape, named `name`, is seen.
+    pub fn prepare(
+        &mut self,
+        name: &str,
+        query: &SQLable,
+        values: &BTreeMap<String, SqlValue>,
+    ) -> Result<(Arc<str>, Vec<SqlValue>), SQLError> {
+        let key = Key {
+            name: name.to_owned(),
+            values: values.iter().map(|(k, v)| (k.clone(), shape(v))).collect(),
+        };
+        self.clock += 1;
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            entry.used = self.clock;
+            let params = entry
+                .slots
+                .iter()
+                .map(|slot| resolve(slot, values))
+                .collect();
+            return Ok((entry.sql.clone(), params));
+        }
+
+        self.misses += 1;
+        let mut r = query.renderer();
+        r.params = Some(Vec::new());
+        r.reuse_params = false;
+        r.values = values.clone();
+        r.slots = Some(Slots::default());
+        let sql: Arc<str> = query.render_query(&mut r)?.into();
+        let slots = r.slots.take().unwrap_or_default().slots;
+
+        if self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        if self.capacity > 0 {
+            self.entries.insert(
+                key,
+                Entry {
+                    sql: sql.clone(),
+                    slots,
+                    used: self.clock,
+                },
+            );
+        }
+        Ok((sql, r.into_params()))
+    }
+
+    /// The number of queries served without rendering.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of queries rendered.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Forgets every shape.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+fn shape(value: &SqlValue) -> Shape {
+    match value {
+        SqlValue::Null => Shape::Null,
+        SqlValue::List(v) => Shape::List(v.len()),
+        _ => Shape::Scalar,
+    }
+}
+
+fn resolve(slot: &Slot, values: &BTreeMap<String, SqlValue>) -> SqlValue {
+    match slot {
+        Slot::Fixed(value) => value.clone(),
+        Slot::Param(name, idx) => match values.get(name) {
+            Some(SqlValue::List(v)) => v[*idx].clone(),
+            Some(v) => v.clone(),
+            // the shape, part of the key, has every name
+            None => SqlValue::Null,
+        },
+    }
+}
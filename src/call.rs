@@ -0,0 +1,380 @@
+use std::future::Future;
+
+use crate::{
+    unrendered, CastType, Dialect, ExecError, Execute, Expr, Quoting, Record, Renderer, SQLError,
+    SqlType, SqlValue, ToSql,
+};
+
+/// An argument of a `Call`.
+#[derive(Clone, Debug)]
+pub enum CallArg {
+    /// A value or expression passed in; values are bound as parameters.
+    In(Expr),
+    /// An OUT parameter, read back under `name`.
+    Out { name: String, ty: CastType },
+    /// An INOUT parameter: `value` passed in, the result read back under
+    /// `name`.
+    InOut {
+        name: String,
+        ty: CastType,
+        value: SqlValue,
+    },
+}
+
+/// A statement and the values to bind to it.
+type Statement = (String, Vec<SqlValue>);
+
+/// A call of a stored procedure, `CALL transfer($1, $2)`, or of a
+/// function, `SELECT next_invoice()`. OUT and INOUT parameters are read
+/// back by `execute`, which each dialect needs its own statements for:
+///
+/// ```
+/// # use traits::*;
+/// let mut call = Call::procedure("transfer");
+/// call.arg(1).arg(2).arg(100).out::<i64>("balance");
+///
+/// call.dialect(Dialect::Postgres);
+/// assert_eq!(call.prepare(), "CALL transfer(1, 2, 100, CAST(NULL AS BIGINT))\n");
+///
+/// call.dialect(Dialect::MySql);
+/// assert_eq!(
+///     call.statements().unwrap(),
+///     vec![
+///         ("CALL transfer(?, ?, ?, @balance)\n".to_owned(), vec![1.into(), 2.into(), 100.into()]),
+///         ("SELECT @balance AS balance\n".to_owned(), vec![]),
+///     ]
+/// );
+///
+/// call.dialect(Dialect::MsSql);
+/// assert_eq!(
+///     call.prepare(),
+///     "DECLARE @balance BIGINT;\nEXEC transfer 1, 2, 100, @balance OUTPUT;\nSELECT @balance AS balance\n"
+/// );
+/// ```
+///
+/// Names of OUT parameters must be bare identifiers, since MySQL and SQL
+/// Server hold them in variables.
+#[derive(Clone, Debug)]
+pub struct Call {
+    name: String,
+    args: Vec<CallArg>,
+    function: bool,
+    dialect: Dialect,
+    quoting: Quoting,
+}
+
+impl Call {
+    /// A call of the stored procedure `name`, schema-qualified if need be.
+    pub fn procedure(name: &str) -> Self {
+        Call {
+            name: name.to_owned(),
+            args: Vec::new(),
+            function: false,
+            dialect: Dialect::default(),
+            quoting: Quoting::default(),
+        }
+    }
+
+    /// A call of the function `name`, whose value `execute` reads back
+    /// under its unqualified name.
+    pub fn function(name: &str) -> Self {
+        Call {
+            function: true,
+            ..Call::procedure(name)
+        }
+    }
+
+    /// Adds an argument, bound as a parameter when rendering with
+    /// parameters.
+    pub fn arg(&mut self, value: impl Into<SqlValue>) -> &mut Self {
+        self.args.push(CallArg::In(Expr::Value(value.into())));
+        self
+    }
+
+    /// Adds an argument computed in the database, e.g. `func("now", [])`.
+    pub fn arg_expr(&mut self, expr: Expr) -> &mut Self {
+        self.args.push(CallArg::In(expr));
+        self
+    }
+
+    /// Adds an OUT parameter of the SQL type of `T`, read back as `name`.
+    pub fn out<T: SqlType>(&mut self, name: &str) -> &mut Self {
+        self.args.push(CallArg::Out {
+            name: name.to_owned(),
+            ty: CastType::Of(T::column_type),
+        });
+        self
+    }
+
+    /// Adds an INOUT parameter of the SQL type of `T`, passing `value` and
+    /// reading the result back as `name`.
+    pub fn inout<T: SqlType>(&mut self, name: &str, value: impl Into<SqlValue>) -> &mut Self {
+        self.args.push(CallArg::InOut {
+            name: name.to_owned(),
+            ty: CastType::Of(T::column_type),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn quoting(&mut self, quoting: Quoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// The names `execute` reads back: the OUT and INOUT parameters in
+    /// order, or the function's name.
+    pub fn outputs(&self) -> Vec<String> {
+        if self.function {
+            let name = self.name.rsplit('.').next().unwrap_or(&self.name);
+            return vec![name.to_owned()];
+        }
+        self.args
+            .iter()
+            .filter_map(|a| match a {
+                CallArg::In(_) => None,
+                CallArg::Out { name, .. } | CallArg::InOut { name, .. } => Some(name.clone()),
+            })
+            .collect()
+    }
+
+    /// Renders the statements with the values inlined, each but the last
+    /// ending with `;`. If the call cannot be rendered, see `try_prepare`,
+    /// the SQL is the error between angle brackets, which the database
+    /// refuses to run.
+    pub fn prepare(&self) -> String {
+        match self.try_prepare() {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e) + "\n",
+        }
+    }
+
+    /// Renders the statements with the values inlined, failing on OUT
+    /// parameters of a function or with an invalid name, and on
+    /// procedures in SQLite, which has none.
+    pub fn try_prepare(&self) -> Result<String, SQLError> {
+        let statements: Vec<String> = self
+            .render(false)?
+            .into_iter()
+            .map(|(sql, _)| sql.trim_end().to_owned())
+            .collect();
+        Ok(statements.join(";\n") + "\n")
+    }
+
+    /// The statements with placeholders, and the values to bind to each.
+    /// The last one reads the outputs when there are any.
+    pub fn statements(&self) -> Result<Vec<Statement>, SQLError> {
+        self.render(true)
+    }
+
+    fn render(&self, parameterized: bool) -> Result<Vec<Statement>, SQLError> {
+        let outputs = self.outputs();
+        if let Some(name) = outputs
+            .iter()
+            .find(|n| !n.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            return Err(SQLError::InvalidName(name.clone()));
+        }
+        if self.function && self.args.iter().any(|a| !matches!(a, CallArg::In(_))) {
+            return Err(SQLError::Unsupported(format!(
+                "OUT parameters of the function {}",
+                self.name
+            )));
+        }
+
+        let mut statements = Vec::new();
+        let mut r = self.renderer(parameterized);
+        let name = r.dialect.ident(&self.name, r.quoting);
+        if self.function {
+            let args = self.render_args(&mut r, true, |_, _| String::new())?;
+            statements.push((format!("SELECT {}({})\n", name, args), r.into_params()));
+            return Ok(statements);
+        }
+
+        match self.dialect {
+            Dialect::Sqlite => {
+                return Err(SQLError::Unsupported(
+                    "stored procedures in Sqlite".to_owned(),
+                ))
+            }
+            Dialect::Generic | Dialect::Postgres => {
+                // OUT parameters take a NULL of their type, the call
+                // returns their values as a row
+                let dialect = r.dialect;
+                let args = self.render_args(&mut r, true, |_, ty| {
+                    format!("CAST(NULL AS {})", ty.name(dialect))
+                })?;
+                statements.push((format!("CALL {}({})\n", name, args), r.into_params()));
+            }
+            Dialect::MySql => {
+                for arg in &self.args {
+                    if let CallArg::InOut { name, value, .. } = arg {
+                        let mut r = self.renderer(parameterized);
+                        let value = value.render(&mut r)?;
+                        statements.push((format!("SET @{} = {}\n", name, value), r.into_params()));
+                    }
+                }
+                let args = self.render_args(&mut r, false, |name, _| format!("@{}", name))?;
+                statements.push((format!("CALL {}({})\n", name, args), r.into_params()));
+                if !outputs.is_empty() {
+                    let select: Vec<String> =
+                        outputs.iter().map(|o| format!("@{} AS {}", o, o)).collect();
+                    statements.push((format!("SELECT {}\n", select.join(", ")), Vec::new()));
+                }
+            }
+            Dialect::MsSql => {
+                let mut sql = String::new();
+                for arg in &self.args {
+                    match arg {
+                        CallArg::Out { name, ty } => {
+                            sql.push_str(&format!("DECLARE @{} {};\n", name, ty.name(r.dialect)))
+                        }
+                        CallArg::InOut { name, ty, value } => sql.push_str(&format!(
+                            "DECLARE @{} {} = {};\n",
+                            name,
+                            ty.name(r.dialect),
+                            value.render(&mut r)?
+                        )),
+                        CallArg::In(_) => {}
+                    }
+                }
+                let args =
+                    self.render_args(&mut r, false, |name, _| format!("@{} OUTPUT", name))?;
+                let space = if args.is_empty() { "" } else { " " };
+                sql.push_str(&format!("EXEC {}{}{}", name, space, args));
+                if !outputs.is_empty() {
+                    let select: Vec<String> =
+                        outputs.iter().map(|o| format!("@{} AS {}", o, o)).collect();
+                    sql.push_str(&format!(";\nSELECT {}", select.join(", ")));
+                }
+                statements.push((sql + "\n", r.into_params()));
+            }
+        }
+        Ok(statements)
+    }
+
+    fn renderer(&self, parameterized: bool) -> Renderer {
+        let mut r = Renderer {
+            dialect: self.dialect,
+            quoting: self.quoting,
+            ..Default::default()
+        };
+        if parameterized {
+            r.params = Some(Vec::new());
+        }
+        r
+    }
+
+    /// The arguments, separated by commas: IN arguments rendered, OUT
+    /// parameters written by `param` from their name and type, and INOUT
+    /// ones as their value if `pass_inout`, by `param` otherwise.
+    fn render_args(
+        &self,
+        r: &mut Renderer,
+        pass_inout: bool,
+        param: impl Fn(&str, &CastType) -> String,
+    ) -> Result<String, SQLError> {
+        let mut args = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            match arg {
+                CallArg::In(expr) => args.push(expr.render(r)?),
+                CallArg::InOut { value, .. } if pass_inout => args.push(value.render(r)?),
+                CallArg::Out { name, ty } | CallArg::InOut { name, ty, .. } => {
+                    args.push(param(name, ty))
+                }
+            }
+        }
+        Ok(args.join(", "))
+    }
+
+    /// Runs the call on `conn`, returning its outputs, see `outputs`: the
+    /// values of the OUT and INOUT parameters, or the value of the
+    /// function, in a row of their own. Without outputs the row is
+    /// empty.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// # struct Db;
+    /// # impl Execute for Db {
+    /// #     type Error = ();
+    /// #     fn query(
+    /// #         &self,
+    /// #         _: &str,
+    /// #         _: &[SqlValue],
+    /// #         each: &mut dyn FnMut(&dyn Row) -> Result<(), SQLError>,
+    /// #     ) -> Result<(), ExecError<()>> {
+    /// #         each(&Record { columns: vec!["total_out".to_owned()], values: vec![42.into()] })?;
+    /// #         Ok(())
+    /// #     }
+    /// #     fn execute(&self, _: &str, _: &[SqlValue]) -> Result<u64, ExecError<()>> {
+    /// #         Ok(0)
+    /// #     }
+    /// # }
+    /// let mut call = Call::procedure("close_month");
+    /// call.dialect(Dialect::Postgres).arg(7).out::<i64>("total");
+    /// let outputs = call.execute(&Db).unwrap();
+    /// assert_eq!((&outputs as &dyn Row).get::<i64>("total").unwrap(), 42);
+    /// ```
+    pub fn execute<C: Execute + ?Sized>(&self, conn: &C) -> Result<Record, ExecError<C::Error>> {
+        let mut statements = self.statements()?;
+        let outputs = self.outputs();
+        let last = match outputs.is_empty() {
+            true => None,
+            false => statements.pop(),
+        };
+        for (sql, params) in &statements {
+            conn.execute(sql, params)?;
+        }
+        let mut record = Record::default();
+        if let Some((sql, params)) = last {
+            conn.query(&sql, &params, &mut |row| {
+                record = read_outputs(row, &outputs)?;
+                Ok(())
+            })?;
+        }
+        Ok(record)
+    }
+
+    /// Like `execute`, on an asynchronous connection.
+    pub fn execute_async<'c, C: crate::AsyncExecute + ?Sized>(
+        &self,
+        conn: &'c C,
+    ) -> impl Future<Output = Result<Record, ExecError<C::Error>>> + 'c {
+        let prepared = self.statements();
+        let outputs = self.outputs();
+        async move {
+            let mut statements = prepared?;
+            let last = match outputs.is_empty() {
+                true => None,
+                false => statements.pop(),
+            };
+            for (sql, params) in &statements {
+                conn.execute(sql, params).await?;
+            }
+            let mut record = Record::default();
+            if let Some((sql, params)) = last {
+                if let Some(row) = conn.query(&sql, &params).await?.first() {
+                    record = read_outputs(row, &outputs)?;
+                }
+            }
+            Ok(record)
+        }
+    }
+}
+
+/// The outputs of a call from the row that returns them, by position,
+/// since a Postgres procedure names them after its parameters.
+fn read_outputs(row: &dyn crate::Row, outputs: &[String]) -> Result<Record, SQLError> {
+    let values = (0..outputs.len())
+        .map(|idx| row.value(idx))
+        .collect::<Result<Vec<SqlValue>, SQLError>>()?;
+    Ok(Record {
+        columns: outputs.to_vec(),
+        values,
+    })
+}
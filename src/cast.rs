@@ -0,0 +1,96 @@
+use crate::{Dialect, Expr};
+
+/// How `CAST` expressions are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CastSyntax {
+    /// `CAST(x AS DATE)`, understood everywhere.
+    #[default]
+    Standard,
+    /// `x::date` on Postgres, `CAST` elsewhere.
+    Shorthand,
+}
+
+/// The SQL type a Rust type is cast to, see `Expr::cast`.
+pub trait SqlType {
+    fn type_name(dialect: Dialect) -> &'static str;
+
+    /// The type of a column holding `Self`, see `ColumnDef::of`; the cast
+    /// type unless the dialect casts to a narrower set of types.
+    fn column_type(dialect: Dialect) -> &'static str {
+        Self::type_name(dialect)
+    }
+}
+
+/// The target of a cast: a type name as written, or the name of a Rust
+/// type's SQL counterpart in the dialect being rendered.
+#[derive(Clone, Copy, Debug)]
+pub enum CastType {
+    Named(&'static str),
+    Of(fn(Dialect) -> &'static str),
+}
+
+impl CastType {
+    pub fn name(&self, dialect: Dialect) -> &'static str {
+        match self {
+            CastType::Named(name) => name,
+            CastType::Of(f) => f(dialect),
+        }
+    }
+}
+
+/// `CAST(expr AS ty)`, with `ty` written as given: `cast("createdAt", "DATE")`.
+pub fn cast(expr: impl Into<Expr>, ty: &'static str) -> Expr {
+    Expr::Cast(Box::new(expr.into()), CastType::Named(ty))
+}
+
+impl Expr {
+    /// Casts to the SQL type of `T` in the rendered dialect:
+    /// `lit("42").cast::<i64>()`.
+    pub fn cast<T: SqlType>(self) -> Expr {
+        Expr::Cast(Box::new(self), CastType::Of(T::type_name))
+    }
+}
+
+/// A timestamp, for the columns the crate defines itself whichever date
+/// crate is enabled, e.g. the `applied_at` of `Migrations`.
+pub(crate) struct Timestamp;
+
+// MySQL only casts to a handful of types, hence SIGNED and CHAR, so its
+// column type is given separately after the `/`.
+macro_rules! sql_type {
+    ($($t:ty),* => $generic:literal, $pg:literal, $mysql:literal / $mysql_column:literal, $sqlite:literal, $mssql:literal) => {
+        $(
+            impl SqlType for $t {
+                fn type_name(dialect: Dialect) -> &'static str {
+                    match dialect {
+                        Dialect::Generic => $generic,
+                        Dialect::Postgres => $pg,
+                        Dialect::MySql => $mysql,
+                        Dialect::Sqlite => $sqlite,
+                        Dialect::MsSql => $mssql,
+                    }
+                }
+
+                fn column_type(dialect: Dialect) -> &'static str {
+                    match dialect {
+                        Dialect::MySql => $mysql_column,
+                        _ => Self::type_name(dialect),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+sql_type!(i64, isize => "BIGINT", "BIGINT", "SIGNED" / "BIGINT", "INTEGER", "BIGINT");
+sql_type!(i32, i16, i8 => "INTEGER", "INTEGER", "SIGNED" / "INT", "INTEGER", "INT");
+sql_type!(u64, u32, u16, u8, usize => "BIGINT", "BIGINT", "UNSIGNED" / "BIGINT UNSIGNED", "INTEGER", "BIGINT");
+sql_type!(f64 => "DOUBLE PRECISION", "DOUBLE PRECISION", "DOUBLE" / "DOUBLE", "REAL", "FLOAT");
+sql_type!(f32 => "REAL", "REAL", "FLOAT" / "FLOAT", "REAL", "REAL");
+sql_type!(String, &str => "VARCHAR", "TEXT", "CHAR" / "TEXT", "TEXT", "NVARCHAR(MAX)");
+sql_type!(bool => "BOOLEAN", "BOOLEAN", "SIGNED" / "BOOLEAN", "INTEGER", "BIT");
+sql_type!(Timestamp => "TIMESTAMP", "TIMESTAMP", "DATETIME" / "DATETIME", "TEXT", "DATETIME2");
+#[cfg(feature = "chrono")]
+sql_type!(chrono::NaiveDate => "DATE", "DATE", "DATE" / "DATE", "TEXT", "DATE");
+#[cfg(feature = "chrono")]
+sql_type!(chrono::NaiveDateTime => "TIMESTAMP", "TIMESTAMP", "DATETIME" / "DATETIME", "TEXT", "DATETIME2");
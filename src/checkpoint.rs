@@ -0,0 +1,37 @@
+use crate::SQLable;
+
+/// A saved state of a query, taken with `SQLable::checkpoint`.
+#[derive(Clone)]
+pub struct Checkpoint(SQLable);
+
+impl SQLable {
+    /// Saves the query as it is, to go back to it later with `restore`,
+    /// e.g. to undo filters tried out in an interactive tool:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// let before = q.checkpoint();
+    /// q.filter_values(vec![SQLFilter {
+    ///     column: "age".into(),
+    ///     filter: SqlValue::Int(18),
+    ///     cmp: SQLComp::GEQ,
+    /// }]);
+    /// q.restore(&before);
+    /// assert_eq!(q.prepare(), SQLable::new("users").prepare());
+    /// ```
+    ///
+    /// Filters are shared between the query and its checkpoints rather than
+    /// copied, so a checkpoint costs about as much as the query's names and
+    /// values.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.clone())
+    }
+
+    /// Puts the query back in the state saved by `checkpoint`, which can
+    /// be restored again later.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> &mut Self {
+        *self = checkpoint.0.clone();
+        self
+    }
+}
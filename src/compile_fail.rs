@@ -0,0 +1,96 @@
+//! Misuses of the derives and `raw_sql!` that must not compile, checked as
+//! `compile_fail` doctests.
+
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(SqlEnum)]
+/// struct Status;
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(SqlEnum)]
+/// enum Status {
+///     Pending(u32),
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(SqlEnum)]
+/// #[sql(table = "status")]
+/// enum Status {
+///     Pending,
+/// }
+/// ```
+pub struct SqlEnumErrors;
+
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(Table)]
+/// struct Account(i64);
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(Table)]
+/// struct Account {
+///     #[sql(primary)]
+///     id: i64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(Table)]
+/// struct Order {
+///     #[sql(references = "accounts.id")]
+///     account_id: i64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(Table)]
+/// struct Order {
+///     #[sql(rename = 1)]
+///     account_id: i64,
+/// }
+/// ```
+pub struct TableErrors;
+
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(FromRow)]
+/// enum User {
+///     Anonymous,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(FromRow)]
+/// struct User {
+///     #[sql(column = "user_id")]
+///     id: i64,
+/// }
+/// ```
+pub struct FromRowErrors;
+
+/// ```compile_fail
+/// use traits::*;
+/// #[derive(ToRow)]
+/// struct User(i64);
+/// ```
+pub struct ToRowErrors;
+
+/// ```compile_fail
+/// use traits::*;
+/// let f = raw_sql!("created_at >= (1");
+/// ```
+///
+/// ```compile_fail
+/// use traits::*;
+/// let f = raw_sql!("a = 1; DROP TABLE users");
+/// ```
+pub struct RawSqlErrors;
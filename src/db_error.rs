@@ -0,0 +1,131 @@
+use crate::Dialect;
+
+/// What a database error means, independent of the vendor reporting it.
+///
+/// Drivers report failures as SQLSTATE strings (`23505`) or vendor error
+/// numbers (MySQL `1062`, SQL Server `2627`); this maps both onto one enum so
+/// callers can match on `UniqueViolation` instead of a code per backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DbErrorKind {
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    /// Some other integrity constraint failed, or the backend does not say
+    /// which kind (SQL Server reports foreign key and check failures alike).
+    IntegrityViolation,
+    /// The transaction could not be serialized with a concurrent one.
+    SerializationFailure,
+    Deadlock,
+    /// A lock could not be acquired in time (SQLite `SQLITE_BUSY`).
+    LockTimeout,
+    /// The statement was cancelled or hit a statement timeout.
+    QueryCanceled,
+    UndefinedTable,
+    UndefinedColumn,
+    SyntaxError,
+    ConnectionFailure,
+    /// Anything not listed above.
+    Other,
+}
+
+impl DbErrorKind {
+    /// Classifies a five character SQLSTATE.
+    pub fn from_sqlstate(state: &str) -> Self {
+        match state {
+            "23505" => DbErrorKind::UniqueViolation,
+            "23503" => DbErrorKind::ForeignKeyViolation,
+            "23502" => DbErrorKind::NotNullViolation,
+            "23514" => DbErrorKind::CheckViolation,
+            "40001" => DbErrorKind::SerializationFailure,
+            "40P01" => DbErrorKind::Deadlock,
+            "55P03" => DbErrorKind::LockTimeout,
+            "57014" => DbErrorKind::QueryCanceled,
+            "42P01" | "42S02" => DbErrorKind::UndefinedTable,
+            "42703" | "42S22" => DbErrorKind::UndefinedColumn,
+            "42601" => DbErrorKind::SyntaxError,
+            s if s.starts_with("23") => DbErrorKind::IntegrityViolation,
+            s if s.starts_with("08") => DbErrorKind::ConnectionFailure,
+            _ => DbErrorKind::Other,
+        }
+    }
+
+    /// Classifies a vendor error number: the MySQL error number, the SQLite
+    /// extended result code or the SQL Server error number. Postgres only
+    /// reports SQLSTATEs, use `from_sqlstate` for it.
+    pub fn from_code(dialect: Dialect, code: i64) -> Self {
+        match dialect {
+            Dialect::MySql => match code {
+                1062 | 1586 => DbErrorKind::UniqueViolation,
+                1216 | 1217 | 1451 | 1452 => DbErrorKind::ForeignKeyViolation,
+                1048 | 1364 => DbErrorKind::NotNullViolation,
+                3819 => DbErrorKind::CheckViolation,
+                1213 => DbErrorKind::Deadlock,
+                1205 => DbErrorKind::LockTimeout,
+                1317 | 3024 => DbErrorKind::QueryCanceled,
+                1146 => DbErrorKind::UndefinedTable,
+                1054 => DbErrorKind::UndefinedColumn,
+                1064 => DbErrorKind::SyntaxError,
+                2002 | 2003 | 2006 | 2013 => DbErrorKind::ConnectionFailure,
+                _ => DbErrorKind::Other,
+            },
+            Dialect::Sqlite => match code {
+                1555 | 2067 => DbErrorKind::UniqueViolation,
+                787 => DbErrorKind::ForeignKeyViolation,
+                1299 => DbErrorKind::NotNullViolation,
+                275 => DbErrorKind::CheckViolation,
+                // primary result code SQLITE_CONSTRAINT and its other extensions
+                c if c & 0xff == 19 => DbErrorKind::IntegrityViolation,
+                c if c & 0xff == 5 || c & 0xff == 6 => DbErrorKind::LockTimeout,
+                9 => DbErrorKind::QueryCanceled,
+                _ => DbErrorKind::Other,
+            },
+            Dialect::MsSql => match code {
+                2601 | 2627 => DbErrorKind::UniqueViolation,
+                515 => DbErrorKind::NotNullViolation,
+                547 => DbErrorKind::IntegrityViolation,
+                3960 => DbErrorKind::SerializationFailure,
+                1205 => DbErrorKind::Deadlock,
+                1222 => DbErrorKind::LockTimeout,
+                208 => DbErrorKind::UndefinedTable,
+                207 => DbErrorKind::UndefinedColumn,
+                102 => DbErrorKind::SyntaxError,
+                _ => DbErrorKind::Other,
+            },
+            Dialect::Generic | Dialect::Postgres => DbErrorKind::Other,
+        }
+    }
+
+    /// Whether running the same transaction again may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            DbErrorKind::SerializationFailure
+                | DbErrorKind::Deadlock
+                | DbErrorKind::LockTimeout
+                | DbErrorKind::ConnectionFailure
+        )
+    }
+}
+
+impl std::fmt::Display for DbErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            DbErrorKind::UniqueViolation => "unique constraint violated",
+            DbErrorKind::ForeignKeyViolation => "foreign key constraint violated",
+            DbErrorKind::NotNullViolation => "not null constraint violated",
+            DbErrorKind::CheckViolation => "check constraint violated",
+            DbErrorKind::IntegrityViolation => "integrity constraint violated",
+            DbErrorKind::SerializationFailure => "could not serialize transaction",
+            DbErrorKind::Deadlock => "deadlock detected",
+            DbErrorKind::LockTimeout => "lock not available",
+            DbErrorKind::QueryCanceled => "statement canceled",
+            DbErrorKind::UndefinedTable => "table does not exist",
+            DbErrorKind::UndefinedColumn => "column does not exist",
+            DbErrorKind::SyntaxError => "syntax error",
+            DbErrorKind::ConnectionFailure => "connection failure",
+            DbErrorKind::Other => "database error",
+        };
+        f.write_str(text)
+    }
+}
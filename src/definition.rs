@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Dialect, Expr, Filter, Labeled, Nulls, Order, SQLComp, SQLError, SQLFilter, SQLable, SqlValue,
+};
+
+/// A query described as data, e.g. in a YAML or JSON file:
+///
+/// ```yaml
+/// table: users
+/// alias: u
+/// columns: [userId, name]
+/// filters:
+///   - { column: age, op: ">=", value: 18 }
+///   - { column: status, op: "=", value: [active, invited] }
+///   - { column: country, op: "=", param: country }
+/// order_by:
+///   - { column: name }
+///   - { column: age, order: desc }
+/// limit: 20
+/// dialect: postgres
+/// ```
+///
+/// `SQLable` serializes through its `QueryDef`, see `SQLable::to_def`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryDef {
+    /// The table, optionally qualified and aliased: `analytics.events e`.
+    pub table: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<FilterDef>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub distinct: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<OrderDef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<Dialect>,
+}
+
+/// An `ORDER BY` key of a `QueryDef`, ascending unless `order: desc`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrderDef {
+    pub column: String,
+    #[serde(default)]
+    pub order: Order,
+    /// `first` or `last`, otherwise the database's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls: Option<Nulls>,
+}
+
+/// A single filter of a `QueryDef`; `op` is anything `SQLComp` parses.
+///
+/// The value is either given inline, or named by `param` and supplied when
+/// the query is built with `QueryDef::to_query_with`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterDef {
+    pub column: String,
+    pub op: String,
+    #[serde(default = "null")]
+    pub value: SqlValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub param: Option<String>,
+    /// Where the filter comes from, see `Labeled`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+fn null() -> SqlValue {
+    SqlValue::Null
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+impl QueryDef {
+    /// Builds the query, failing on unknown operators and on filters that
+    /// take their value from a parameter.
+    pub fn to_query(&self) -> Result<SQLable, SQLError> {
+        self.to_query_with(&BTreeMap::new())
+    }
+
+    /// The names of the parameters the filters refer to.
+    pub fn params(&self) -> Vec<&str> {
+        self.filters
+            .iter()
+            .filter_map(|f| f.param.as_deref())
+            .collect()
+    }
+
+    /// Builds the query, taking the values of `param` filters from `params`.
+    pub fn to_query_with(&self, params: &BTreeMap<String, SqlValue>) -> Result<SQLable, SQLError> {
+        let mut query = SQLable::new(&self.table);
+        if let Some(schema) = &self.schema {
+            query.schema(schema);
+        }
+        if let Some(alias) = &self.alias {
+            query.alias(alias);
+        }
+        if !self.columns.is_empty() {
+            query.select(self.columns.clone());
+        }
+
+        let mut filters: Vec<Box<dyn Filter>> = Vec::with_capacity(self.filters.len());
+        for f in &self.filters {
+            let cmp: SQLComp = f.op.parse()?;
+            let value = match &f.param {
+                Some(name) => params
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| SQLError::MissingParam(name.clone()))?,
+                None => f.value.clone(),
+            };
+            let filter = Box::new(SQLFilter {
+                column: f.column.clone().into(),
+                filter: value,
+                cmp,
+            });
+            match &f.origin {
+                Some(origin) => filters.push(Box::new(Labeled(origin.clone(), filter))),
+                None => filters.push(filter),
+            }
+        }
+        if !filters.is_empty() {
+            query.filter(filters);
+        }
+
+        if self.distinct {
+            query.distinct();
+        }
+        query.group_by(self.group_by.clone());
+        for key in &self.order_by {
+            match key.nulls {
+                Some(nulls) => query.order_by_nulls(key.column.as_str(), key.order, nulls),
+                None => query.order_by(key.column.as_str(), key.order),
+            };
+        }
+        if let Some(limit) = self.limit {
+            query.limit(limit);
+        }
+        if let Some(offset) = self.offset {
+            query.offset(offset);
+        }
+        if let Some(dialect) = self.dialect {
+            query.dialect(dialect);
+        }
+
+        Ok(query)
+    }
+}
+
+impl SQLable {
+    /// The query as data, failing on anything a `QueryDef` cannot describe:
+    /// select expressions, `DISTINCT ON`, `HAVING`, windows, an archive
+    /// table, partitions or joins, ordering by expressions, and filters other than `SQLFilter`s
+    /// over plain values. The naming, quoting, empty-list and cast options
+    /// are not kept.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users u");
+    /// q.filter(vec![Box::new(SQLFilter {
+    ///     column: "age".into(),
+    ///     filter: 18,
+    ///     cmp: SQLComp::GEQ,
+    /// })]);
+    /// let def = q.to_def().unwrap();
+    /// assert_eq!(def.to_query().unwrap().prepare(), q.prepare());
+    /// ```
+    pub fn to_def(&self) -> Result<QueryDef, SQLError> {
+        let unsupported =
+            |what: &str| Err(SQLError::Unsupported(format!("{} in a QueryDef", what)));
+        if !self.exprs.is_empty() {
+            return unsupported("select expressions");
+        }
+        if !self.distinct_on.is_empty() {
+            return unsupported("DISTINCT ON");
+        }
+        if !self.having.is_empty() {
+            return unsupported("HAVING");
+        }
+        if !self.windows.is_empty() {
+            return unsupported("named windows");
+        }
+        if self.archive.is_some() {
+            return unsupported("an archive table");
+        }
+        if !self.partitions.is_empty() {
+            return unsupported("partitions");
+        }
+        if !self.joins.is_empty() {
+            return unsupported("joins");
+        }
+
+        let mut filters = Vec::new();
+        for f in self.filter.iter() {
+            match f.to_def() {
+                Some(def) => filters.push(def),
+                None => return unsupported("a filter other than a SQLFilter over a value"),
+            }
+        }
+
+        let mut order_by = Vec::with_capacity(self.order_by.len());
+        for (key, order, nulls) in &self.order_by {
+            match key {
+                Expr::Column(column) => order_by.push(OrderDef {
+                    column: column.clone(),
+                    order: *order,
+                    nulls: *nulls,
+                }),
+                _ => return unsupported("ordering by an expression"),
+            }
+        }
+
+        Ok(QueryDef {
+            table: self.table.name.clone(),
+            schema: self.table.schema.clone(),
+            alias: self.table.alias.clone(),
+            columns: (*self.cols).clone().unwrap_or_default(),
+            filters,
+            distinct: self.distinct,
+            group_by: self.group_by.to_vec(),
+            order_by,
+            limit: self.limit,
+            offset: self.offset,
+            dialect: (self.dialect != Dialect::Generic).then_some(self.dialect),
+        })
+    }
+}
+
+/// Serialized as its `QueryDef`, failing for queries `to_def` rejects.
+impl Serialize for SQLable {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_def()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Deserialized from a `QueryDef` without parameters.
+impl<'de> Deserialize<'de> for SQLable {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        QueryDef::deserialize(deserializer)?
+            .to_query()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Named query definitions, e.g. all the stored queries of a project.
+/// Serialized as a map from name to `QueryDef`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct QueryCatalog {
+    queries: BTreeMap<String, QueryDef>,
+}
+
+impl QueryCatalog {
+    pub fn new() -> Self {
+        QueryCatalog::default()
+    }
+
+    /// Adds a query, failing if the name is already taken.
+    pub fn register(&mut self, name: &str, def: QueryDef) -> Result<(), SQLError> {
+        if self.queries.contains_key(name) {
+            return Err(SQLError::DuplicateQuery(name.to_owned()));
+        }
+        self.queries.insert(name.to_owned(), def);
+        Ok(())
+    }
+
+    /// Adds all queries of `other`, failing on the first duplicate name.
+    pub fn merge(&mut self, other: QueryCatalog) -> Result<(), SQLError> {
+        for (name, def) in other.queries {
+            self.register(&name, def)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&QueryDef> {
+        self.queries.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// The query names, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.queries.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &QueryDef)> {
+        self.queries.iter().map(|(name, def)| (name.as_str(), def))
+    }
+
+    /// Builds the named query with the given parameter values.
+    pub fn build(
+        &self,
+        name: &str,
+        params: &BTreeMap<String, SqlValue>,
+    ) -> Result<SQLable, SQLError> {
+        self.get(name)
+            .ok_or_else(|| SQLError::UnknownQuery(name.to_owned()))?
+            .to_query_with(params)
+    }
+}
@@ -0,0 +1,338 @@
+use std::borrow::Cow;
+
+use crate::{fingerprint, SQLError};
+
+/// The database flavour a query is rendered for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Dialect {
+    /// Standard SQL with `?` placeholders.
+    #[default]
+    Generic,
+    /// PostgreSQL, with `$1, $2, ...` placeholders.
+    Postgres,
+    MySql,
+    Sqlite,
+    /// Microsoft SQL Server.
+    MsSql,
+}
+
+/// When identifiers (table and column names) are quoted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quoting {
+    /// Quote names that are reserved words, contain characters that are not
+    /// allowed in a bare identifier, or whose case the dialect would fold.
+    #[default]
+    Auto,
+    /// Quote every name.
+    Always,
+    /// Emit names as given.
+    Never,
+}
+
+/// What rendering does with a name longer than the dialect takes, see
+/// `Dialect::max_name_len`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LongNames {
+    /// Write names as given, for the database to truncate or refuse.
+    #[default]
+    Keep,
+    /// Cut names to fit, see `Dialect::fit_name`.
+    Truncate,
+    /// Refuse to render the query, see `SQLable::check_names`.
+    Error,
+}
+
+/// Words that cannot be used as bare identifiers in at least one of the
+/// supported dialects. Sorted, compared uppercase.
+#[rustfmt::skip]
+const RESERVED: &[&str] = &[
+    "ADD", "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "CHECK",
+    "COLLATE", "COLUMN", "CONSTRAINT", "CREATE", "CROSS", "CURRENT_DATE", "CURRENT_TIME",
+    "CURRENT_TIMESTAMP", "CURRENT_USER", "DATABASE", "DEFAULT", "DELETE", "DESC", "DISTINCT",
+    "DROP", "ELSE", "END", "EXCEPT", "EXISTS", "FALSE", "FETCH", "FOR", "FOREIGN", "FROM", "FULL",
+    "GRANT", "GROUP", "HAVING", "IN", "INDEX", "INNER", "INSERT", "INTERSECT", "INTO", "IS",
+    "JOIN", "KEY", "LEFT", "LIKE", "LIMIT", "NATURAL", "NOT", "NULL", "OFFSET", "ON", "OR",
+    "ORDER", "OUTER", "PRIMARY", "REFERENCES", "RIGHT", "ROW", "ROWS", "SELECT", "SET", "SOME",
+    "TABLE", "THEN", "TO", "TOP", "TRUE", "UNION", "UNIQUE", "UPDATE", "USER", "USING", "VALUES",
+    "WHEN", "WHERE", "WINDOW", "WITH",
+];
+
+/// Whether `word` is reserved in at least one supported dialect.
+pub fn is_reserved(word: &str) -> bool {
+    RESERVED
+        .binary_search(&word.to_uppercase().as_str())
+        .is_ok()
+}
+
+/// The words `is_reserved` recognizes, uppercase and sorted. Names among
+/// them are quoted in every dialect under `Quoting::Auto`.
+pub fn reserved_words() -> &'static [&'static str] {
+    RESERVED
+}
+
+/// The functions the builder writes, by dialect: the helpers of `expr` and
+/// `window`, and those behind dialect-specific rendering. Sorted,
+/// compared uppercase.
+#[rustfmt::skip]
+const GENERIC_FUNCTIONS: &[&str] = &[
+    "AVG", "COALESCE", "COUNT", "DENSE_RANK", "LAG", "LEAD", "LOWER", "MAX", "MIN", "NULLIF",
+    "RANK", "ROW_NUMBER", "SUM", "TRIM", "UPPER",
+];
+
+#[rustfmt::skip]
+const POSTGRES_FUNCTIONS: &[&str] = &[
+    "AVG", "COALESCE", "COUNT", "DENSE_RANK", "LAG", "LEAD", "LOWER", "MAX", "MIN", "NULLIF",
+    "PLAINTO_TSQUERY", "RANK", "ROW_NUMBER", "SUM", "TO_TSVECTOR", "TRIM", "UPPER",
+];
+
+#[rustfmt::skip]
+const MYSQL_FUNCTIONS: &[&str] = &[
+    "AVG", "COALESCE", "CONCAT", "COUNT", "DATABASE", "DENSE_RANK", "JSON_CONTAINS",
+    "JSON_CONTAINS_PATH", "JSON_EXTRACT", "JSON_UNQUOTE", "LAG", "LEAD", "LOWER", "MAX", "MIN",
+    "NULLIF", "RANK", "ROW_NUMBER", "SUM", "TRIM", "UNHEX", "UPPER",
+];
+
+#[rustfmt::skip]
+const SQLITE_FUNCTIONS: &[&str] = &[
+    "AVG", "COALESCE", "COUNT", "DATETIME", "DENSE_RANK", "JSON_EXTRACT", "JSON_TYPE", "LAG",
+    "LEAD", "LOWER", "MAX", "MIN", "NULLIF", "RANK", "ROW_NUMBER", "SUM", "TRIM", "UPPER",
+];
+
+#[rustfmt::skip]
+const MSSQL_FUNCTIONS: &[&str] = &[
+    "AVG", "COALESCE", "CONCAT", "COUNT", "DATEADD", "DENSE_RANK", "FREETEXT", "JSON_PATH_EXISTS",
+    "JSON_QUERY", "JSON_VALUE", "LAG", "LEAD", "LOWER", "MAX", "MIN", "NULLIF", "OBJECT_ID",
+    "RANK", "ROW_NUMBER", "SUM", "TRIM", "UPPER",
+];
+
+fn is_bare_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_ok = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    starts_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+impl Dialect {
+    /// Every dialect, for tools that list them.
+    pub const ALL: [Dialect; 5] = [
+        Dialect::Generic,
+        Dialect::Postgres,
+        Dialect::MySql,
+        Dialect::Sqlite,
+        Dialect::MsSql,
+    ];
+
+    /// The functions the builder writes for the dialect, uppercase and
+    /// sorted, e.g. `CONCAT` on MySQL where others have `||`. Window
+    /// functions are listed even where the server version lacks them, see
+    /// `Feature::WindowFunctions`.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// assert!(Dialect::MySql.has_function("concat"));
+    /// assert!(!Dialect::Postgres.has_function("CONCAT"));
+    /// assert!(Dialect::ALL.iter().all(|d| d.has_function("COUNT")));
+    /// assert!(reserved_words().contains(&"SELECT"));
+    /// ```
+    pub fn functions(&self) -> &'static [&'static str] {
+        match self {
+            Dialect::Generic => GENERIC_FUNCTIONS,
+            Dialect::Postgres => POSTGRES_FUNCTIONS,
+            Dialect::MySql => MYSQL_FUNCTIONS,
+            Dialect::Sqlite => SQLITE_FUNCTIONS,
+            Dialect::MsSql => MSSQL_FUNCTIONS,
+        }
+    }
+
+    /// Whether `functions` lists `name`, in any case.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.functions()
+            .binary_search(&name.to_uppercase().as_str())
+            .is_ok()
+    }
+
+    /// Quotes a single identifier, escaping the quote character by doubling it.
+    pub fn quote_ident(&self, name: &str) -> String {
+        let mut result = String::with_capacity(name.len() + 2);
+        self.push_quoted(&mut result, name);
+        result
+    }
+
+    fn push_quoted(&self, out: &mut String, name: &str) {
+        let (open, close) = match self {
+            Dialect::MySql => ('`', '`'),
+            Dialect::MsSql => ('[', ']'),
+            _ => ('"', '"'),
+        };
+        out.push(open);
+        for ch in name.chars() {
+            if ch == close {
+                out.push(close);
+            }
+            out.push(ch);
+        }
+        out.push(close);
+    }
+
+    /// Renders a possibly schema-qualified name (`schema.table`) under the
+    /// quoting policy, each dotted part on its own. `*` is never quoted.
+    pub fn ident(&self, name: &str, quoting: Quoting) -> String {
+        let mut result = String::with_capacity(name.len() + 2);
+        for (idx, part) in name.split('.').enumerate() {
+            if idx > 0 {
+                result.push('.');
+            }
+            self.push_ident_part(&mut result, part, quoting);
+        }
+        result
+    }
+
+    /// Renders a single identifier under the quoting policy; unlike `ident`
+    /// a `.` is part of the name.
+    pub fn ident_part(&self, part: &str, quoting: Quoting) -> String {
+        let mut result = String::with_capacity(part.len() + 2);
+        self.push_ident_part(&mut result, part, quoting);
+        result
+    }
+
+    /// Whether `ident` would quote some part of `name`, i.e. not return it
+    /// as it is.
+    pub(crate) fn needs_quotes(&self, name: &str, quoting: Quoting) -> bool {
+        name.split('.').any(|part| self.quotes_part(part, quoting))
+    }
+
+    fn quotes_part(&self, part: &str, quoting: Quoting) -> bool {
+        match quoting {
+            Quoting::Never => false,
+            Quoting::Always => part != "*",
+            Quoting::Auto => {
+                part != "*"
+                    && (!is_bare_identifier(part) || is_reserved(part) || self.folds_case(part))
+            }
+        }
+    }
+
+    fn push_ident_part(&self, out: &mut String, part: &str, quoting: Quoting) {
+        if self.quotes_part(part, quoting) {
+            self.push_quoted(out, part);
+        } else {
+            out.push_str(part);
+        }
+    }
+
+    /// Whether an unquoted `part` would be looked up under a different name:
+    /// Postgres folds bare identifiers to lowercase, so `userId` needs quotes.
+    fn folds_case(&self, part: &str) -> bool {
+        *self == Dialect::Postgres && part.chars().any(char::is_uppercase)
+    }
+
+    /// The longest name the database takes: in bytes on Postgres, in
+    /// characters elsewhere. `None` for SQLite, which has no limit, and
+    /// `Dialect::Generic`.
+    pub fn max_name_len(&self) -> Option<usize> {
+        match self {
+            // NAMEDATALEN - 1
+            Dialect::Postgres => Some(63),
+            Dialect::MySql => Some(64),
+            Dialect::MsSql => Some(128),
+            Dialect::Sqlite | Dialect::Generic => None,
+        }
+    }
+
+    /// The length of `name` as `max_name_len` counts it.
+    fn name_len(&self, name: &str) -> usize {
+        match self {
+            Dialect::Postgres => name.len(),
+            _ => name.chars().count(),
+        }
+    }
+
+    /// Fails if the database refuses `name` as a single identifier, even
+    /// quoted: empty, longer than `max_name_len`, or holding a NUL; on
+    /// MySQL also ending with a space or holding a character outside the
+    /// Basic Multilingual Plane.
+    pub fn check_name(&self, name: &str) -> Result<(), SQLError> {
+        let too_long = self
+            .max_name_len()
+            .is_some_and(|max| self.name_len(name) > max);
+        let refused = |c: char| match self {
+            Dialect::MySql => c == '\0' || c > '\u{FFFF}',
+            _ => c == '\0',
+        };
+        if name.is_empty()
+            || too_long
+            || name.chars().any(refused)
+            || (*self == Dialect::MySql && name.ends_with(' '))
+        {
+            return Err(SQLError::InvalidName(name.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// `name` cut to `max_name_len` if longer, ending with `_` and a hash
+    /// of the whole name, so that long names sharing a prefix stay apart
+    /// and a name is always cut the same way:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let name = format!("total_{}", "revenue_".repeat(10));
+    /// let fit = Dialect::Postgres.fit_name(&name);
+    /// assert_eq!(fit.len(), 63);
+    /// assert_eq!(&fit[..6], "total_");
+    /// assert_eq!(Dialect::Postgres.fit_name("total"), "total");
+    /// ```
+    pub fn fit_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        let max = match self.max_name_len() {
+            Some(max) if self.name_len(name) > max => max,
+            _ => return Cow::Borrowed(name),
+        };
+        let suffix = format!("_{:08x}", fingerprint(name) as u32);
+        let budget = max - suffix.len();
+        let mut len = 0;
+        let end = name
+            .char_indices()
+            .find(|(_, c)| {
+                len += self.name_len(c.encode_utf8(&mut [0; 4]));
+                len > budget
+            })
+            .map_or(name.len(), |(idx, _)| idx);
+        Cow::Owned(format!("{}{}", &name[..end], suffix))
+    }
+
+    /// The placeholder for the `n`-th (1-based) bound parameter.
+    pub fn placeholder(&self, n: usize) -> String {
+        let mut result = String::new();
+        self.push_placeholder(&mut result, n);
+        result
+    }
+
+    pub(crate) fn push_placeholder(&self, out: &mut String, n: usize) {
+        use std::fmt::Write;
+        let _ = match self {
+            Dialect::Postgres => write!(out, "${}", n),
+            Dialect::MsSql => write!(out, "@P{}", n),
+            _ => out.write_char('?'),
+        };
+    }
+}
+
+impl std::str::FromStr for Dialect {
+    type Err = SQLError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "generic" | "ansi" => Ok(Dialect::Generic),
+            "postgres" | "postgresql" | "pg" => Ok(Dialect::Postgres),
+            "mysql" | "mariadb" => Ok(Dialect::MySql),
+            "sqlite" => Ok(Dialect::Sqlite),
+            "mssql" | "sqlserver" | "tsql" => Ok(Dialect::MsSql),
+            _ => Err(SQLError::UnknownDialect(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use crate::{ColumnDef, Dialect, Renderer, SQLError, TableDef, TableName};
+
+/// A single difference between a table's definition and the table in the
+/// database, see `TableDiff`.
+#[derive(Clone, Debug)]
+pub enum SchemaChange {
+    /// The table is missing from the database.
+    CreateTable,
+    /// The table is in the database but not defined.
+    DropTable,
+    AddColumn(ColumnDef),
+    /// A column only in the database, named as in the database.
+    DropColumn(ColumnDef),
+    /// A column whose type or nullability differs, from its state in the
+    /// database to its definition.
+    AlterColumn {
+        from: Box<ColumnDef>,
+        to: Box<ColumnDef>,
+    },
+    /// A named table check missing from the database, with its expression.
+    AddCheck(String, String),
+    DropCheck(String, String),
+}
+
+/// The changes to a single table. `table` is the definition, or the table
+/// as found in the database when it is dropped.
+#[derive(Clone, Debug)]
+pub struct TableDiff {
+    pub table: TableDef,
+    pub changes: Vec<SchemaChange>,
+}
+
+/// The changes reconciling a database with table definitions, as DDL with
+/// `statements` or as a report through `Display`.
+///
+/// The state of the database is given as `TableDef`s too, typically built
+/// from its catalog. Tables are matched by name, and by schema when both
+/// sides have one; columns by their database name, after each side's
+/// naming strategy. Only column types, nullability and named table checks
+/// are compared: types as written, ignoring case and spacing, and checks by
+/// name alone since databases reformat their expressions. Column checks,
+/// generated expressions and comments are not compared.
+#[derive(Clone, Debug)]
+pub struct SchemaDiff {
+    pub dialect: Dialect,
+    pub tables: Vec<TableDiff>,
+}
+
+impl TableDef {
+    /// The changes turning `actual`, this table as it is in the database,
+    /// into this definition.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let actual = TableDef::new("users")
+    ///     .column(ColumnDef::new("id", "bigint").not_null())
+    ///     .column(ColumnDef::new("nickname", "text"));
+    /// let users = TableDef::new("users")
+    ///     .column(ColumnDef::of::<i64>("id").not_null())
+    ///     .column(ColumnDef::of::<String>("email").not_null());
+    /// let diff = users.diff(&actual, Dialect::Postgres);
+    /// assert_eq!(
+    ///     diff.statements().unwrap(),
+    ///     vec![
+    ///         "ALTER TABLE users DROP COLUMN nickname\n",
+    ///         "ALTER TABLE users ADD COLUMN email TEXT NOT NULL\n",
+    ///     ]
+    /// );
+    /// ```
+    pub fn diff(&self, actual: &TableDef, dialect: Dialect) -> SchemaDiff {
+        let changes = self.changes(actual, dialect);
+        let tables = if changes.is_empty() {
+            Vec::new()
+        } else {
+            vec![TableDiff {
+                table: self.clone(),
+                changes,
+            }]
+        };
+        SchemaDiff { dialect, tables }
+    }
+
+    fn changes(&self, actual: &TableDef, dialect: Dialect) -> Vec<SchemaChange> {
+        let mut drops = Vec::new();
+        let mut alters = Vec::new();
+        let mut adds = Vec::new();
+
+        for (name, expr) in &actual.checks {
+            if !self.checks.iter().any(|(n, _)| n == name) {
+                drops.push(SchemaChange::DropCheck(name.clone(), expr.clone()));
+            }
+        }
+        for column in &actual.columns {
+            let name = actual.column_name(column);
+            if !self.columns.iter().any(|c| self.column_name(c) == name) {
+                drops.push(SchemaChange::DropColumn(ColumnDef {
+                    name,
+                    ..column.clone()
+                }));
+            }
+        }
+
+        for column in &self.columns {
+            let name = self.column_name(column);
+            match actual
+                .columns
+                .iter()
+                .find(|c| actual.column_name(c) == name)
+            {
+                Some(current) => {
+                    let same_type = normalize_type(&current.type_name(dialect))
+                        == normalize_type(&column.type_name(dialect));
+                    if !same_type || current.nullable != column.nullable {
+                        alters.push(SchemaChange::AlterColumn {
+                            from: Box::new(current.clone()),
+                            to: Box::new(column.clone()),
+                        });
+                    }
+                }
+                None => adds.push(SchemaChange::AddColumn(column.clone())),
+            }
+        }
+        for (name, expr) in &self.checks {
+            if !actual.checks.iter().any(|(n, _)| n == name) {
+                adds.push(SchemaChange::AddCheck(name.clone(), expr.clone()));
+            }
+        }
+
+        drops.extend(alters);
+        drops.extend(adds);
+        drops
+    }
+
+    fn same_table(&self, other: &TableDef) -> bool {
+        let schemas_match = match (&self.table.schema, &other.table.schema) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        schemas_match && self.table.name == other.table.name
+    }
+}
+
+/// The changes turning the `actual` tables of a database into the
+/// `desired` ones: missing tables are created, tables that are not defined
+/// are dropped, and the others are diffed with `TableDef::diff`.
+pub fn diff_schema(desired: &[TableDef], actual: &[TableDef], dialect: Dialect) -> SchemaDiff {
+    let mut tables = Vec::new();
+    for def in desired {
+        let changes = match actual.iter().find(|t| def.same_table(t)) {
+            Some(current) => def.changes(current, dialect),
+            None => vec![SchemaChange::CreateTable],
+        };
+        if !changes.is_empty() {
+            tables.push(TableDiff {
+                table: def.clone(),
+                changes,
+            });
+        }
+    }
+    for current in actual {
+        if !desired.iter().any(|t| t.same_table(current)) {
+            tables.push(TableDiff {
+                table: current.clone(),
+                changes: vec![SchemaChange::DropTable],
+            });
+        }
+    }
+    SchemaDiff { dialect, tables }
+}
+
+impl SchemaDiff {
+    /// Whether the database already matches the definitions.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The statements applying the changes, in order. Fails on SQLite for
+    /// changes it cannot make in place, which need the table rebuilt.
+    ///
+    /// New `NOT NULL` columns fail on tables that already have rows, and
+    /// the enum types of new columns are not created, see
+    /// `TableDef::create_types`.
+    pub fn statements(&self) -> Result<Vec<String>, SQLError> {
+        let mut result = Vec::new();
+        for diff in &self.tables {
+            for change in &diff.changes {
+                result.extend(diff.statements(change, self.dialect)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// The changes undoing this diff, in reverse order, for a
+    /// down-migration. The structure is restored, but not the data of
+    /// dropped tables and columns, see `irreversible`.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let actual = TableDef::new("users").column(ColumnDef::new("nickname", "TEXT"));
+    /// let users = TableDef::new("users").column(ColumnDef::new("email", "TEXT"));
+    /// let up = users.diff(&actual, Dialect::Postgres);
+    /// let down = up.rollback().statements().unwrap();
+    /// assert_eq!(
+    ///     down,
+    ///     vec![
+    ///         "ALTER TABLE users DROP COLUMN email\n",
+    ///         "ALTER TABLE users ADD COLUMN nickname TEXT\n",
+    ///     ]
+    /// );
+    /// assert_eq!(up.irreversible().to_string(), "- column users.nickname\n");
+    /// ```
+    pub fn rollback(&self) -> SchemaDiff {
+        let tables = self
+            .tables
+            .iter()
+            .rev()
+            .map(|diff| TableDiff {
+                table: diff.table.clone(),
+                changes: diff
+                    .changes
+                    .iter()
+                    .rev()
+                    .map(SchemaChange::reverse)
+                    .collect(),
+            })
+            .collect();
+        SchemaDiff {
+            dialect: self.dialect,
+            tables,
+        }
+    }
+
+    /// The changes whose rollback cannot restore what they lose: dropped
+    /// tables and columns, and columns whose type changed.
+    pub fn irreversible(&self) -> SchemaDiff {
+        let tables = self
+            .tables
+            .iter()
+            .filter_map(|diff| {
+                let changes: Vec<SchemaChange> = diff
+                    .changes
+                    .iter()
+                    .filter(|c| c.loses_data(self.dialect))
+                    .cloned()
+                    .collect();
+                (!changes.is_empty()).then(|| TableDiff {
+                    table: diff.table.clone(),
+                    changes,
+                })
+            })
+            .collect();
+        SchemaDiff {
+            dialect: self.dialect,
+            tables,
+        }
+    }
+}
+
+impl SchemaChange {
+    /// The change undoing this one.
+    pub fn reverse(&self) -> SchemaChange {
+        match self {
+            SchemaChange::CreateTable => SchemaChange::DropTable,
+            SchemaChange::DropTable => SchemaChange::CreateTable,
+            SchemaChange::AddColumn(column) => SchemaChange::DropColumn(column.clone()),
+            SchemaChange::DropColumn(column) => SchemaChange::AddColumn(column.clone()),
+            SchemaChange::AlterColumn { from, to } => SchemaChange::AlterColumn {
+                from: to.clone(),
+                to: from.clone(),
+            },
+            SchemaChange::AddCheck(name, expr) => {
+                SchemaChange::DropCheck(name.clone(), expr.clone())
+            }
+            SchemaChange::DropCheck(name, expr) => {
+                SchemaChange::AddCheck(name.clone(), expr.clone())
+            }
+        }
+    }
+
+    /// Whether applying the change discards data that `reverse` cannot
+    /// bring back. Changing a type may truncate or round values, so any
+    /// type change counts.
+    pub fn loses_data(&self, dialect: Dialect) -> bool {
+        match self {
+            SchemaChange::DropTable | SchemaChange::DropColumn(_) => true,
+            SchemaChange::AlterColumn { from, to } => {
+                normalize_type(&from.type_name(dialect)) != normalize_type(&to.type_name(dialect))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl TableDiff {
+    fn statements(&self, change: &SchemaChange, dialect: Dialect) -> Result<Vec<String>, SQLError> {
+        let r = self.table.renderer(dialect);
+        let table = self.table.table.render(&r);
+        let alter = |action: String| format!("ALTER TABLE {} {}\n", table, action);
+
+        let result = match change {
+            SchemaChange::CreateTable => return self.table.create_statements(dialect),
+            SchemaChange::DropTable => vec![format!("DROP TABLE {}\n", table)],
+            SchemaChange::AddColumn(column) => {
+                let keyword = match dialect {
+                    Dialect::MsSql => "ADD",
+                    _ => "ADD COLUMN",
+                };
+                vec![alter(format!("{} {}", keyword, column.render(&r)?))]
+            }
+            SchemaChange::DropColumn(ColumnDef { name, .. }) => {
+                vec![alter(format!("DROP COLUMN {}", r.ident(name)))]
+            }
+            SchemaChange::AlterColumn { from, to } => {
+                alter_column(from, to, &r)?.into_iter().map(alter).collect()
+            }
+            SchemaChange::AddCheck(name, expr) => {
+                unsupported_in_sqlite(dialect, "adding a check")?;
+                vec![alter(format!(
+                    "ADD CONSTRAINT {} CHECK ({})",
+                    r.ident(name),
+                    expr
+                ))]
+            }
+            SchemaChange::DropCheck(name, _) => {
+                unsupported_in_sqlite(dialect, "dropping a check")?;
+                let keyword = match dialect {
+                    Dialect::MySql => "CHECK",
+                    _ => "CONSTRAINT",
+                };
+                vec![alter(format!("DROP {} {}", keyword, r.ident(name)))]
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// The `ALTER TABLE` actions changing `from` into `to`.
+fn alter_column(from: &ColumnDef, to: &ColumnDef, r: &Renderer) -> Result<Vec<String>, SQLError> {
+    unsupported_in_sqlite(r.dialect, "changing a column")?;
+    let name = r.column(&to.name);
+    let ty = match &to.enumeration {
+        Some(e) if r.dialect == Dialect::Postgres => r.ident(e.name),
+        _ => to.type_name(r.dialect),
+    };
+
+    let result = match r.dialect {
+        // both restate the whole column
+        Dialect::MySql => vec![format!("MODIFY COLUMN {}", to.render(r)?)],
+        Dialect::MsSql => {
+            let null = if to.nullable { "NULL" } else { "NOT NULL" };
+            vec![format!("ALTER COLUMN {} {} {}", name, ty, null)]
+        }
+        _ => {
+            let mut result = Vec::new();
+            if normalize_type(&from.type_name(r.dialect))
+                != normalize_type(&to.type_name(r.dialect))
+            {
+                let keyword = match r.dialect {
+                    Dialect::Postgres => "TYPE",
+                    _ => "SET DATA TYPE",
+                };
+                result.push(format!("ALTER COLUMN {} {} {}", name, keyword, ty));
+            }
+            if from.nullable != to.nullable {
+                let action = if to.nullable { "DROP" } else { "SET" };
+                result.push(format!("ALTER COLUMN {} {} NOT NULL", name, action));
+            }
+            result
+        }
+    };
+    Ok(result)
+}
+
+fn unsupported_in_sqlite(dialect: Dialect, what: &str) -> Result<(), SQLError> {
+    if dialect == Dialect::Sqlite {
+        return Err(SQLError::Unsupported(format!(
+            "{} in Sqlite, the table has to be rebuilt",
+            what
+        )));
+    }
+    Ok(())
+}
+
+fn normalize_type(ty: &str) -> String {
+    ty.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+fn describe(column: &ColumnDef, dialect: Dialect) -> String {
+    let null = if column.nullable { "NULL" } else { "NOT NULL" };
+    format!("{} {}", column.type_name(dialect), null)
+}
+
+/// One line per change: `+` for additions, `-` for removals and `~` for
+/// changed columns.
+impl std::fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diff in &self.tables {
+            let table = qualified(&diff.table.table);
+            for change in &diff.changes {
+                match change {
+                    SchemaChange::CreateTable => writeln!(f, "+ table {}", table)?,
+                    SchemaChange::DropTable => writeln!(f, "- table {}", table)?,
+                    SchemaChange::AddColumn(column) => writeln!(
+                        f,
+                        "+ column {}.{} {}",
+                        table,
+                        diff.table.column_name(column),
+                        describe(column, self.dialect)
+                    )?,
+                    SchemaChange::DropColumn(ColumnDef { name, .. }) => {
+                        writeln!(f, "- column {}.{}", table, name)?
+                    }
+                    SchemaChange::AlterColumn { from, to } => writeln!(
+                        f,
+                        "~ column {}.{} {} -> {}",
+                        table,
+                        diff.table.column_name(to),
+                        describe(from, self.dialect),
+                        describe(to, self.dialect)
+                    )?,
+                    SchemaChange::AddCheck(name, expr) => {
+                        writeln!(f, "+ check {} on {}: {}", name, table, expr)?
+                    }
+                    SchemaChange::DropCheck(name, _) => {
+                        writeln!(f, "- check {} on {}", name, table)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn qualified(table: &TableName) -> String {
+    match &table.schema {
+        Some(schema) => format!("{}.{}", schema, table.name),
+        None => table.name.clone(),
+    }
+}
@@ -0,0 +1,194 @@
+use crate::validate::{expr_columns, Scope};
+use crate::{
+    Expr, Filter, Join, Nulls, Order, Renderer, SQLComp, SQLError, SQLable, Schema, SqlValue,
+    TableName, ToSql,
+};
+
+impl SQLable {
+    /// Whether the two queries return the same rows, in the same order
+    /// where that is defined, whatever the tables of `schema` hold. Meant
+    /// to check a refactor of a query, e.g. from raw fragments to filters:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let schema = Schema::new().table(
+    ///     TableDef::new("users")
+    ///         .column(ColumnDef::of::<i64>("id").not_null())
+    ///         .column(ColumnDef::of::<String>("status").not_null())
+    ///         .primary_key(&["id"]),
+    /// );
+    ///
+    /// let mut before = SQLable::new("users");
+    /// before
+    ///     .filter(vec![Box::new(SQLFilter { column: "status".into(), filter: vec!["b", "a"], cmp: SQLComp::EQ })])
+    ///     .order_by("id", Order::Asc)
+    ///     .order_by("status", Order::Asc);
+    /// let mut after = SQLable::new("public.users u");
+    /// after
+    ///     .distinct()
+    ///     .filter(vec![Box::new(Not(Box::new(SQLFilter { column: "u.status".into(), filter: vec!["a", "b"], cmp: SQLComp::NEQ })))])
+    ///     .order_by("u.id", Order::Asc);
+    ///
+    /// assert!(before.equivalent(&after, &schema).unwrap());
+    /// ```
+    ///
+    /// The check is conservative: `false` means that no equivalence was
+    /// found, not that the queries differ. Filters match when their
+    /// predicates are the same up to the order of `AND` / `OR` operands and
+    /// list values, negations, and `IS NOT NULL` on `NOT NULL` columns;
+    /// filters without a predicate, such as `Raw` SQL, must render the
+    /// same. The schema's keys make `DISTINCT` redundant over a selected
+    /// key, and sort keys after a key irrelevant. Fails if either query
+    /// does not `validate`.
+    pub fn equivalent(&self, other: &SQLable, schema: &Schema) -> Result<bool, SQLError> {
+        self.validate(schema)?;
+        other.validate(schema)?;
+        Ok(Shape::of(self, schema)? == Shape::of(other, schema)?)
+    }
+}
+
+/// What decides the result of a query, in a canonical form.
+#[derive(Debug, PartialEq)]
+struct Shape {
+    table: TableName,
+    archive: Option<TableName>,
+    partitions: Vec<TableName>,
+    joins: Vec<Join>,
+    distinct: bool,
+    distinct_on: Vec<String>,
+    select: Vec<String>,
+    filter: Vec<String>,
+    group_by: Vec<String>,
+    having: Vec<String>,
+    windows: Vec<String>,
+    order_by: Vec<(String, Order, Option<Nulls>)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Shape {
+    fn of(q: &SQLable, schema: &Schema) -> Result<Self, SQLError> {
+        let scope = Scope::new(q, schema)?;
+        let mut r = Renderer {
+            naming: q.naming,
+            empty_list: q.empty_list,
+            ..Renderer::default()
+        };
+        let name = |c: &str| scope.name(c);
+        let all = || scope.def.columns.iter().map(|c| scope.db_name(c));
+
+        let mut select = Vec::new();
+        match q.cols.as_deref() {
+            None if q.exprs.is_empty() => select.extend(all()),
+            None => {}
+            Some(cols) => {
+                for c in cols {
+                    match name(c)?.as_str() {
+                        "*" => select.extend(all()),
+                        c => select.push(c.to_owned()),
+                    }
+                }
+            }
+        }
+        let columns = select.clone();
+        for e in q.exprs.iter() {
+            select.push(expr(e, &scope, &mut r)?);
+        }
+
+        let mut filter = Vec::new();
+        for f in q.filter.iter() {
+            match f.predicate() {
+                Some(p) => filter.extend(
+                    p.normalize(&name)?
+                        .conjuncts()
+                        .into_iter()
+                        .filter(|p| !is_not_null(p.as_compare(), &scope))
+                        .map(|p| p.key()),
+                ),
+                None => filter.push(f.try_apply_filter(&mut r)?),
+            }
+        }
+        filter.sort();
+        filter.dedup();
+
+        let mut group_by = q
+            .group_by
+            .iter()
+            .map(|c| name(c))
+            .collect::<Result<Vec<String>, SQLError>>()?;
+        group_by.sort();
+        group_by.dedup();
+        let mut having = q
+            .having
+            .iter()
+            .map(|f| f.try_apply_filter(&mut r))
+            .collect::<Result<Vec<String>, SQLError>>()?;
+        having.sort();
+        having.dedup();
+
+        // a single table's rows are told apart by any of its keys; the
+        // union with an archive or partitions, or a join, may repeat them
+        let single = q.archive.is_none() && q.partitions.is_empty() && q.joins.is_empty();
+        let keys = scope.unique_keys();
+        let covers =
+            |cols: &[String]| single && keys.iter().any(|k| k.iter().all(|c| cols.contains(c)));
+
+        let mut order_by = Vec::new();
+        let mut sorted = Vec::new();
+        for (key, order, nulls) in q.order_by.iter() {
+            let key = expr(key, &scope, &mut r)?;
+            sorted.push(key.clone());
+            order_by.push((key, *order, *nulls));
+            if group_by.is_empty() && covers(&sorted) {
+                break;
+            }
+        }
+
+        Ok(Shape {
+            table: scope.def.table.clone(),
+            archive: q.archive.clone().filter(|_| q.include_archive),
+            partitions: q.partitions.to_vec(),
+            joins: q.joins.to_vec(),
+            distinct: q.distinct && !(group_by.is_empty() && covers(&columns)),
+            distinct_on: q
+                .distinct_on
+                .iter()
+                .map(|c| name(c))
+                .collect::<Result<Vec<String>, SQLError>>()?,
+            select,
+            filter,
+            group_by,
+            having,
+            windows: q.windows.iter().map(|w| format!("{:?}", w)).collect(),
+            order_by,
+            limit: q.limit,
+            offset: q.offset,
+        })
+    }
+}
+
+/// A column by its database name, anything else rendered.
+fn expr(e: &Expr, scope: &Scope, r: &mut Renderer) -> Result<String, SQLError> {
+    match e {
+        Expr::Column(c) => scope.name(c),
+        e => {
+            let mut columns = Vec::new();
+            expr_columns(e, &mut columns);
+            columns.iter().try_for_each(|c| scope.name(c).map(|_| ()))?;
+            e.render(r)
+        }
+    }
+}
+
+/// Whether the comparison is `IS NOT NULL` on a `NOT NULL` column, which
+/// every row meets.
+fn is_not_null(compare: Option<(&str, SQLComp, &SqlValue)>, scope: &Scope) -> bool {
+    match compare {
+        Some((column, SQLComp::NEQ, SqlValue::Null)) => scope
+            .def
+            .columns
+            .iter()
+            .any(|c| !c.nullable && scope.db_name(c) == column),
+        _ => false,
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{Dialect, SQLError, SQLable, SqlValue, TableName};
+
+impl SQLable {
+    /// A fast estimate of the number of rows in the query's table, read
+    /// from the statistics the database keeps instead of counting, as a
+    /// single `estimate` column. Meant for pagination UIs over tables too
+    /// large for `COUNT(*)`:
+    ///
+    /// - Postgres: `pg_class.reltuples`, -1 until the table was first
+    ///   vacuumed or analyzed;
+    /// - MySQL: `information_schema.tables.table_rows`, within tens of
+    ///   percent for InnoDB;
+    /// - SQL Server: the row counts of `sys.partitions`;
+    /// - SQLite and generic SQL keep no such statistics, so the rows are
+    ///   counted exactly.
+    ///
+    /// The estimate is for the whole table: filters, the archive table and
+    /// everything else about the query are ignored.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("analytics.events e");
+    /// q.dialect(Dialect::Postgres);
+    /// let (sql, params) = q.estimate_count().unwrap();
+    /// assert_eq!(params, vec![SqlValue::Text("analytics.events".to_owned())]);
+    /// ```
+    pub fn estimate_count(&self) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        let table = TableName {
+            alias: None,
+            ..(*self.table).clone()
+        };
+
+        let sql = match r.dialect {
+            Dialect::Postgres => {
+                // regclass resolves the name as SQL would, quotes included
+                let name = r.bind(SqlValue::Text(table.render(&r)));
+                format!(
+                    "SELECT CAST(reltuples AS BIGINT) AS estimate\nFROM pg_class\nWHERE oid = CAST({} AS regclass)\n",
+                    name
+                )
+            }
+            Dialect::MySql => {
+                let schema = match &table.schema {
+                    Some(schema) => r.bind(SqlValue::Text(schema.clone())),
+                    None => "DATABASE()".to_owned(),
+                };
+                let name = r.bind(SqlValue::Text(table.name.clone()));
+                format!(
+                    "SELECT table_rows AS estimate\nFROM information_schema.tables\nWHERE table_schema = {}\n  AND table_name = {}\n",
+                    schema, name
+                )
+            }
+            Dialect::MsSql => {
+                let name = r.bind(SqlValue::Text(table.render(&r)));
+                // index 0 is the heap, 1 the clustered index: one of them
+                // holds every row exactly once
+                format!(
+                    "SELECT SUM(rows) AS estimate\nFROM sys.partitions\nWHERE object_id = OBJECT_ID({})\n  AND index_id IN (0, 1)\n",
+                    name
+                )
+            }
+            Dialect::Sqlite | Dialect::Generic => {
+                format!("SELECT COUNT(*) AS estimate\nFROM {}\n", table.render(&r))
+            }
+        };
+        Ok((sql, r.into_params()))
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use crate::{Insert, NamingStrategy, SQLError, SQLable, SqlValue};
+
+/// A row of a result set, as handed out by an `Execute` backend.
+pub trait Row {
+    /// The number of columns.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The 0-based index of the column named `name`, as the database
+    /// names it.
+    fn index_of(&self, name: &str) -> Option<usize>;
+
+    /// The value of the column at `idx`.
+    fn value(&self, idx: usize) -> Result<SqlValue, SQLError>;
+}
+
+impl dyn Row + '_ {
+    /// The value of the column at `idx`, read as a `T`.
+    pub fn get_at<T: FromValue>(&self, idx: usize) -> Result<T, SQLError> {
+        if idx >= self.len() {
+            return Err(SQLError::Decode(format!("no column {}", idx)));
+        }
+        T::from_value(self.value(idx)?)
+    }
+
+    /// The value of the column named `name`, read as a `T`.
+    pub fn get<T: FromValue>(&self, name: &str) -> Result<T, SQLError> {
+        let idx = self
+            .index_of(name)
+            .ok_or_else(|| SQLError::Decode(format!("no column {}", name)))?;
+        self.get_at(idx)
+    }
+}
+
+/// A row held in memory, e.g. buffered by a backend or built in tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    pub columns: Vec<String>,
+    pub values: Vec<SqlValue>,
+}
+
+impl Row for Record {
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    fn value(&self, idx: usize) -> Result<SqlValue, SQLError> {
+        self.values
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| SQLError::Decode(format!("no column {}", idx)))
+    }
+}
+
+/// A Rust value read from a single column.
+pub trait FromValue: Sized {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError>;
+}
+
+fn mismatch<T>(value: &SqlValue, ty: &str) -> Result<T, SQLError> {
+    Err(SQLError::Decode(format!(
+        "cannot read {:?} as {}",
+        value, ty
+    )))
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+                    let converted = match &value {
+                        SqlValue::Int(v) => <$t>::try_from(*v).ok(),
+                        SqlValue::UInt(v) => <$t>::try_from(*v).ok(),
+                        _ => None,
+                    };
+                    converted.map_or_else(|| mismatch(&value, stringify!($t)), Ok)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i64, i32, i16, i8, u64, u32, u16, u8, usize, isize);
+
+impl FromValue for f64 {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        match value {
+            SqlValue::Float(v) => Ok(v),
+            SqlValue::Int(v) => Ok(v as f64),
+            SqlValue::UInt(v) => Ok(v as f64),
+            _ => mismatch(&value, "f64"),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        match value {
+            SqlValue::Text(v) => Ok(v),
+            _ => mismatch(&value, "String"),
+        }
+    }
+}
+
+/// Databases without a boolean type store it as an integer.
+impl FromValue for bool {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        match value {
+            SqlValue::Int(v) => Ok(v != 0),
+            SqlValue::UInt(v) => Ok(v != 0),
+            _ => mismatch(&value, "bool"),
+        }
+    }
+}
+
+impl FromValue for SqlValue {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        Ok(value)
+    }
+}
+
+/// NULL reads as `None`; any other value must fit `T`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        match value {
+            SqlValue::Null => Ok(None),
+            v => T::from_value(v).map(Some),
+        }
+    }
+}
+
+/// A Rust value read from a whole row, usually by column name:
+///
+/// ```
+/// # use traits::*;
+/// struct User {
+///     id: i64,
+///     name: Option<String>,
+/// }
+///
+/// impl FromRow for User {
+///     fn from_row(row: &dyn Row) -> Result<Self, SQLError> {
+///         Ok(User {
+///             id: row.get("id")?,
+///             name: row.get("name")?,
+///         })
+///     }
+/// }
+/// ```
+///
+/// Rows fetched through a query look columns up by the name the query's
+/// naming strategy gives them, as in its select list, so with the default
+/// `SnakeCase` a struct's `userId` reads the `user_id` column. With the
+/// `macros` feature, `#[derive(FromRow)]` writes the impl above, fields
+/// renamed with `#[sql(rename = "...")]` as for `#[derive(Table)]`.
+///
+/// Tuples read the columns in order.
+pub trait FromRow: Sized {
+    fn from_row(row: &dyn Row) -> Result<Self, SQLError>;
+}
+
+/// A Rust value as a row, the counterpart of `FromRow`, e.g. to evaluate
+/// filters on it with `Filter::matches`. With the `macros` feature,
+/// `#[derive(ToRow)]` names each column after its field, or renamed with
+/// `#[sql(rename = "...")]` as for `#[derive(FromRow)]`, and reads each
+/// field with `ToSql::to_value`. It fails on a field whose type has no
+/// value, only SQL, such as `Raw`, rather than reading it as NULL.
+pub trait ToRow {
+    fn to_row(&self) -> Result<Record, SQLError>;
+}
+
+impl ToRow for Record {
+    fn to_row(&self) -> Result<Record, SQLError> {
+        Ok(self.clone())
+    }
+}
+
+macro_rules! tuple_from_row {
+    ($($t:ident: $idx:tt),*) => {
+        impl<$($t: FromValue),*> FromRow for ($($t,)*) {
+            fn from_row(row: &dyn Row) -> Result<Self, SQLError> {
+                Ok(($(row.get_at::<$t>($idx)?,)*))
+            }
+        }
+    };
+}
+
+tuple_from_row!(A: 0);
+tuple_from_row!(A: 0, B: 1);
+tuple_from_row!(A: 0, B: 1, C: 2);
+tuple_from_row!(A: 0, B: 1, C: 2, D: 3);
+tuple_from_row!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_from_row!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+
+/// A row whose columns are looked up through a naming strategy, falling
+/// back to the name as given, e.g. for an expression's alias.
+pub(crate) struct NamedRow<'a> {
+    pub(crate) row: &'a dyn Row,
+    pub(crate) naming: NamingStrategy,
+}
+
+impl Row for NamedRow<'_> {
+    fn len(&self) -> usize {
+        self.row.len()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.row
+            .index_of(&self.naming.apply(name))
+            .or_else(|| self.row.index_of(name))
+    }
+
+    fn value(&self, idx: usize) -> Result<SqlValue, SQLError> {
+        self.row.value(idx)
+    }
+}
+
+/// A connection that runs rendered statements, implemented by database
+/// backends so that queries run through `SQLable::fetch`.
+pub trait Execute {
+    type Error;
+
+    /// Runs `sql` with `params` bound to its placeholders in order, calling
+    /// `each` on every row of the result.
+    fn query(
+        &self,
+        sql: &str,
+        params: &[SqlValue],
+        each: &mut dyn FnMut(&dyn Row) -> Result<(), SQLError>,
+    ) -> Result<(), ExecError<Self::Error>>;
+
+    /// Runs a statement that returns no rows, returning the number of rows
+    /// it changed.
+    fn execute(&self, sql: &str, params: &[SqlValue]) -> Result<u64, ExecError<Self::Error>>;
+}
+
+/// The asynchronous counterpart of `Execute`, for drivers such as
+/// `tokio-postgres`: queries run through `SQLable::fetch_async`.
+pub trait AsyncExecute {
+    type Error;
+    /// The driver's row, read through `Row`.
+    type Row: Row;
+
+    /// Runs `sql` with `params` bound to its placeholders in order.
+    fn query(
+        &self,
+        sql: &str,
+        params: &[SqlValue],
+    ) -> impl Future<Output = Result<Vec<Self::Row>, ExecError<Self::Error>>> + Send;
+
+    /// Runs a statement that returns no rows, returning the number of rows
+    /// it changed.
+    fn execute(
+        &self,
+        sql: &str,
+        params: &[SqlValue],
+    ) -> impl Future<Output = Result<u64, ExecError<Self::Error>>> + Send;
+}
+
+/// Why running a query failed.
+#[derive(Debug)]
+pub enum ExecError<E> {
+    /// The query could not be rendered, or a row not read; the message of
+    /// `SQLError::Decode` says which column.
+    Sql(SQLError),
+    /// The backend failed.
+    Database(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ExecError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecError::Sql(e) => write!(f, "{}", e),
+            ExecError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for ExecError<E> {}
+
+impl<E> From<SQLError> for ExecError<E> {
+    fn from(e: SQLError) -> Self {
+        ExecError::Sql(e)
+    }
+}
+
+impl SQLable {
+    /// Runs the query on `conn` with bound parameters, rendered for the
+    /// query's dialect, and reads every row as a `T`, looking columns up
+    /// through the query's naming strategy.
+    pub fn fetch<T: FromRow, C: Execute + ?Sized>(
+        &self,
+        conn: &C,
+    ) -> Result<Vec<T>, ExecError<C::Error>> {
+        self.fetch_with(conn, &BTreeMap::new())
+    }
+
+    /// Like `fetch`, taking the values of the query's `param`s from
+    /// `values`.
+    pub fn fetch_with<T: FromRow, C: Execute + ?Sized>(
+        &self,
+        conn: &C,
+        values: &BTreeMap<String, SqlValue>,
+    ) -> Result<Vec<T>, ExecError<C::Error>> {
+        let (sql, params) = self.prepare_params_with(values)?;
+        let mut result = Vec::new();
+        let naming = self.naming;
+        conn.query(&sql, &params, &mut |row| {
+            result.push(T::from_row(&NamedRow { row, naming })?);
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Like `fetch`, on an asynchronous connection. The query is rendered
+    /// before the returned future first runs, so the future does not borrow
+    /// the query.
+    pub fn fetch_async<'c, T: FromRow, C: AsyncExecute + ?Sized>(
+        &self,
+        conn: &'c C,
+    ) -> impl Future<Output = Result<Vec<T>, ExecError<C::Error>>> + 'c {
+        let prepared = self.prepare_params();
+        let naming = self.naming;
+        async move {
+            let (sql, params) = prepared?;
+            let rows = conn.query(&sql, &params).await?;
+            rows.iter()
+                .map(|row| T::from_row(&NamedRow { row, naming }).map_err(ExecError::Sql))
+                .collect()
+        }
+    }
+}
+
+impl Insert {
+    /// Runs the insert on `conn` with bound parameters, returning the
+    /// number of rows inserted.
+    pub fn execute<C: Execute + ?Sized>(&self, conn: &C) -> Result<u64, ExecError<C::Error>> {
+        let (sql, params) = self.prepare_params()?;
+        conn.execute(&sql, &params)
+    }
+
+    /// Like `execute`, on an asynchronous connection.
+    pub fn execute_async<'c, C: AsyncExecute + ?Sized>(
+        &self,
+        conn: &'c C,
+    ) -> impl Future<Output = Result<u64, ExecError<C::Error>>> + 'c {
+        let prepared = self.prepare_params();
+        async move {
+            let (sql, params) = prepared?;
+            conn.execute(&sql, &params).await
+        }
+    }
+}
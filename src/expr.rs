@@ -0,0 +1,503 @@
+use std::sync::Arc;
+
+use crate::{
+    compare_condition, empty_list_condition, snake_case, unrendered, CastSyntax, CastType, Dialect,
+    EmptyList, Filter, JsonKey, Renderer, SQLComp, SQLError, SqlValue, ToSql, Window,
+};
+
+/// A computed value in the select list or on the left side of a filter,
+/// e.g. `COUNT(*)` or `SUM(amount) AS total`.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("orders");
+/// q.select_expr(count_star().alias("n"))
+///     .group_by(vec!["customerId".to_owned()])
+///     .having(vec![Box::new(sum("amount").is(SQLComp::GT, 100))]);
+/// ```
+#[derive(Clone, Debug)]
+pub enum Expr {
+    /// A column, converted by the naming strategy like any other column.
+    /// `*` is rendered as is.
+    Column(String),
+    /// A value, bound as a parameter when rendering with parameters.
+    Value(SqlValue),
+    /// A function call: `name(args)` or `name(DISTINCT args)`.
+    Function {
+        name: String,
+        distinct: bool,
+        args: Vec<Expr>,
+    },
+    /// `CAST(expr AS type)`, see `cast` and `Expr::cast`.
+    Cast(Box<Expr>, CastType),
+    /// A window function call: `expr OVER (...)`, see `Expr::over`.
+    Over(Box<Expr>, Window),
+    /// `CASE WHEN ... END`, see `case`.
+    Case(Case),
+    /// `left op right`, built with the arithmetic operators on `Expr`.
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+    /// `-expr`
+    Neg(Box<Expr>),
+    /// String concatenation: `a || b` or `CONCAT(a, b)`, see `Expr::concat`.
+    Concat(Vec<Expr>),
+    /// `expr AS alias`. The alias is quoted but not renamed.
+    Alias(Box<Expr>, String),
+    /// The value at `path` of a JSON document, as text if `text`, see
+    /// `Expr::json`.
+    Json {
+        expr: Box<Expr>,
+        path: Vec<JsonKey>,
+        text: bool,
+    },
+}
+
+/// An arithmetic operator of `Expr::Binary`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BinOp::Add | BinOp::Sub => 3,
+            BinOp::Mul | BinOp::Div | BinOp::Rem => 4,
+        }
+    }
+}
+
+impl Expr {
+    pub fn column(name: &str) -> Self {
+        Expr::Column(name.to_owned())
+    }
+
+    /// Names the expression in the select list.
+    pub fn alias(self, alias: &str) -> Self {
+        Expr::Alias(Box::new(self), alias.to_owned())
+    }
+
+    /// A name made from what the expression computes, e.g. `sum_amount`
+    /// for `SUM(amount)`, see `SQLable::auto_alias`.
+    pub fn default_alias(&self) -> String {
+        let mut words = Vec::new();
+        self.alias_words(&mut words);
+        let name: String = words
+            .join("_")
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect::<String>()
+            .to_lowercase();
+        match name.chars().next() {
+            None => "expr".to_owned(),
+            Some(c) if c.is_ascii_digit() => format!("expr_{}", name),
+            Some(_) => name,
+        }
+    }
+
+    fn alias_words(&self, out: &mut Vec<String>) {
+        match self {
+            Expr::Column(c) if c == "*" => {}
+            Expr::Column(c) => out.push(snake_case(c.rsplit('.').next().unwrap_or(c))),
+            Expr::Value(_) => out.push("value".to_owned()),
+            Expr::Function { name, args, .. } => {
+                out.push(name.clone());
+                args.iter().for_each(|e| e.alias_words(out));
+            }
+            Expr::Case(_) => out.push("case".to_owned()),
+            Expr::Concat(parts) => {
+                out.push("concat".to_owned());
+                parts.iter().for_each(|e| e.alias_words(out));
+            }
+            Expr::Binary(lhs, _, rhs) => {
+                lhs.alias_words(out);
+                rhs.alias_words(out);
+            }
+            Expr::Cast(e, _) | Expr::Neg(e) | Expr::Over(e, _) => e.alias_words(out),
+            Expr::Alias(_, alias) => out.push(alias.clone()),
+            Expr::Json { expr, path, .. } => {
+                expr.alias_words(out);
+                for key in path {
+                    match key {
+                        JsonKey::Field(f) => out.push(snake_case(f)),
+                        JsonKey::Index(i) => out.push(i.to_string()),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Concatenates `other` to the string expression:
+    /// `col("first").concat(lit(" ")).concat(col("last"))`. Rendered as
+    /// `||`, or `CONCAT(...)` on MySQL and SQL Server, where a NULL argument
+    /// counts as an empty string on SQL Server but makes the result NULL on
+    /// the others.
+    pub fn concat(self, other: impl Into<Expr>) -> Self {
+        let mut parts = match self {
+            Expr::Concat(parts) => parts,
+            first => vec![first],
+        };
+        parts.push(other.into());
+        Expr::Concat(parts)
+    }
+
+    /// How tightly the rendered expression binds, for parenthesising it
+    /// when it is the operand of another one.
+    fn precedence(&self, r: &Renderer) -> u8 {
+        match self {
+            Expr::Binary(_, op, _) => op.precedence(),
+            Expr::Neg(_) => 5,
+            Expr::Concat(_) if !concat_function(r.dialect) => 2,
+            // -> and ->> bind looser than arithmetic, like ||
+            Expr::Json { .. } if matches!(r.dialect, Dialect::Generic | Dialect::Postgres) => 2,
+            Expr::Alias(..) => 0,
+            _ => 6,
+        }
+    }
+
+    /// Renders the expression as an operand that needs at least `min`
+    /// precedence, in parentheses if it binds less tightly.
+    fn operand(&self, min: u8, r: &mut Renderer) -> Result<String, SQLError> {
+        let sql = self.render(r)?;
+        if self.precedence(r) < min {
+            return Ok(format!("({})", sql));
+        }
+        Ok(sql)
+    }
+
+    /// A filter comparing the expression against `value`.
+    pub fn is<T: ToSql>(self, cmp: SQLComp, value: T) -> ExprFilter<T> {
+        ExprFilter {
+            expr: self,
+            filter: value,
+            cmp,
+        }
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(name: &str) -> Self {
+        Expr::column(name)
+    }
+}
+
+impl From<String> for Expr {
+    fn from(name: String) -> Self {
+        Expr::Column(name)
+    }
+}
+
+/// An expression used as a filter value, e.g. comparing two columns.
+impl ToSql for Expr {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        match self.render(&mut Renderer::default()) {
+            Ok(sql) => out.write_str(&sql),
+            Err(e) => out.write_str(&unrendered(&e)),
+        }
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        match self {
+            Expr::Column(name) => Ok(r.column(name)),
+            Expr::Value(v) => v.render(r),
+            Expr::Function {
+                name,
+                distinct,
+                args,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|a| a.render(r))
+                    .collect::<Result<Vec<String>, SQLError>>()?;
+                let distinct = if *distinct { "DISTINCT " } else { "" };
+                Ok(format!("{}({}{})", name, distinct, args.join(", ")))
+            }
+            Expr::Cast(expr, ty) => {
+                let ty = ty.name(r.dialect);
+                if r.cast == CastSyntax::Shorthand && r.dialect == Dialect::Postgres {
+                    return Ok(format!("{}::{}", expr.operand(6, r)?, ty.to_lowercase()));
+                }
+                Ok(format!("CAST({} AS {})", expr.render(r)?, ty))
+            }
+            Expr::Case(case) => case.render(r),
+            Expr::Over(expr, window) => {
+                Ok(format!("{} {}", expr.render(r)?, window.render_over(r)?))
+            }
+            Expr::Binary(left, op, right) => {
+                // - and / are not associative: a - (b - c) keeps its parentheses
+                let right_min = match op {
+                    BinOp::Add | BinOp::Mul => op.precedence(),
+                    _ => op.precedence() + 1,
+                };
+                Ok(format!(
+                    "{} {} {}",
+                    left.operand(op.precedence(), r)?,
+                    op.symbol(),
+                    right.operand(right_min, r)?
+                ))
+            }
+            Expr::Neg(expr) => {
+                let operand = expr.operand(6, r)?;
+                // --x would start a comment
+                if operand.starts_with('-') {
+                    return Ok(format!("-({})", operand));
+                }
+                Ok(format!("-{}", operand))
+            }
+            Expr::Concat(parts) => {
+                if concat_function(r.dialect) {
+                    let parts = parts
+                        .iter()
+                        .map(|p| p.render(r))
+                        .collect::<Result<Vec<String>, SQLError>>()?;
+                    return Ok(format!("CONCAT({})", parts.join(", ")));
+                }
+                // SQLite binds || tighter than arithmetic, Postgres looser
+                let parts = parts
+                    .iter()
+                    .map(|p| p.operand(5, r))
+                    .collect::<Result<Vec<String>, SQLError>>()?;
+                Ok(parts.join(" || "))
+            }
+            Expr::Alias(expr, alias) => Ok(format!("{} AS {}", expr.render(r)?, r.ident(alias))),
+            Expr::Json { expr, path, text } => Expr::render_json(expr, path, *text, r),
+        }
+    }
+}
+
+/// MySQL reads `||` as a logical OR, SQL Server has no such operator.
+fn concat_function(dialect: Dialect) -> bool {
+    dialect.has_function("CONCAT")
+}
+
+macro_rules! binary_op {
+    ($($trait:ident, $method:ident => $op:ident;)*) => {
+        $(
+            impl<T: Into<Expr>> std::ops::$trait<T> for Expr {
+                type Output = Expr;
+
+                fn $method(self, rhs: T) -> Expr {
+                    Expr::Binary(Box::new(self), BinOp::$op, Box::new(rhs.into()))
+                }
+            }
+        )*
+    };
+}
+
+binary_op! {
+    Add, add => Add;
+    Sub, sub => Sub;
+    Mul, mul => Mul;
+    Div, div => Div;
+    Rem, rem => Rem;
+}
+
+impl std::ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Neg(Box::new(self))
+    }
+}
+
+/// A column: `col("price") * col("qty")`.
+pub fn col(name: &str) -> Expr {
+    Expr::column(name)
+}
+
+/// A value used in an expression, e.g. the fallback of `coalesce`.
+pub fn lit(v: impl Into<SqlValue>) -> Expr {
+    Expr::Value(v.into())
+}
+
+/// A call of the function `name`, rendered as given:
+/// `func("COALESCE", [Expr::column("nick"), Expr::column("name")])`.
+pub fn func(name: &str, args: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::Function {
+        name: name.to_owned(),
+        distinct: false,
+        args: args.into_iter().collect(),
+    }
+}
+
+/// `LOWER(expr)`
+pub fn lower(expr: impl Into<Expr>) -> Expr {
+    func("LOWER", [expr.into()])
+}
+
+/// `UPPER(expr)`
+pub fn upper(expr: impl Into<Expr>) -> Expr {
+    func("UPPER", [expr.into()])
+}
+
+/// `TRIM(expr)`
+pub fn trim(expr: impl Into<Expr>) -> Expr {
+    func("TRIM", [expr.into()])
+}
+
+/// `COALESCE(args)`, the first argument that is not NULL.
+pub fn coalesce(args: impl IntoIterator<Item = Expr>) -> Expr {
+    func("COALESCE", args)
+}
+
+/// `NULLIF(a, b)`, NULL if `a` equals `b` and `a` otherwise.
+pub fn nullif(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    func("NULLIF", [a.into(), b.into()])
+}
+
+fn aggregate(name: &str, distinct: bool, arg: Expr) -> Expr {
+    Expr::Function {
+        name: name.to_owned(),
+        distinct,
+        args: vec![arg],
+    }
+}
+
+/// `COUNT(*)`
+pub fn count_star() -> Expr {
+    aggregate("COUNT", false, Expr::column("*"))
+}
+
+/// `COUNT(expr)`, which skips NULLs.
+pub fn count(expr: impl Into<Expr>) -> Expr {
+    aggregate("COUNT", false, expr.into())
+}
+
+/// `COUNT(DISTINCT expr)`
+pub fn count_distinct(expr: impl Into<Expr>) -> Expr {
+    aggregate("COUNT", true, expr.into())
+}
+
+/// `SUM(expr)`
+pub fn sum(expr: impl Into<Expr>) -> Expr {
+    aggregate("SUM", false, expr.into())
+}
+
+/// `AVG(expr)`
+pub fn avg(expr: impl Into<Expr>) -> Expr {
+    aggregate("AVG", false, expr.into())
+}
+
+/// `MIN(expr)`
+pub fn min(expr: impl Into<Expr>) -> Expr {
+    aggregate("MIN", false, expr.into())
+}
+
+/// `MAX(expr)`
+pub fn max(expr: impl Into<Expr>) -> Expr {
+    aggregate("MAX", false, expr.into())
+}
+
+/// Starts a `CASE WHEN cond THEN value ... ELSE value END` expression.
+///
+/// ```
+/// # use traits::*;
+/// let tier = case()
+///     .when(SQLFilter { column: "spent".into(), filter: 1000, cmp: SQLComp::GEQ }, "gold")
+///     .when(SQLFilter { column: "spent".into(), filter: 100, cmp: SQLComp::GEQ }, "silver")
+///     .otherwise("bronze")
+///     .end()
+///     .alias("tier");
+/// ```
+pub fn case() -> Case {
+    Case {
+        whens: Vec::new(),
+        otherwise: None,
+    }
+}
+
+/// The branches of a `CASE` expression, tried in order.
+#[derive(Clone)]
+pub struct Case {
+    whens: Vec<(Arc<dyn Filter>, Arc<dyn ToSql>)>,
+    otherwise: Option<Arc<dyn ToSql>>,
+}
+
+impl Case {
+    /// Adds a branch: the result is `then` if `cond` holds and no earlier
+    /// branch did.
+    pub fn when(mut self, cond: impl Filter + 'static, then: impl ToSql + 'static) -> Self {
+        self.whens.push((Arc::new(cond), Arc::new(then)));
+        self
+    }
+
+    /// The result if no branch holds; NULL if not set.
+    pub fn otherwise(mut self, value: impl ToSql + 'static) -> Self {
+        self.otherwise = Some(Arc::new(value));
+        self
+    }
+
+    pub fn end(self) -> Expr {
+        Expr::Case(self)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        if self.whens.is_empty() {
+            return Err(SQLError::Unsupported("CASE without WHEN".to_owned()));
+        }
+
+        let mut result = String::from("CASE");
+        for (cond, then) in &self.whens {
+            let cond = cond.try_apply_filter(r)?;
+            result.push_str(&format!(" WHEN {} THEN {}", cond, then.render(r)?));
+        }
+        if let Some(otherwise) = &self.otherwise {
+            result.push_str(&format!(" ELSE {}", otherwise.render(r)?));
+        }
+        result.push_str(" END");
+
+        Ok(result)
+    }
+}
+
+impl std::fmt::Debug for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.render(&mut Renderer::default()) {
+            Ok(sql) => f.write_str(&sql),
+            Err(e) => write!(f, "<{}>", e),
+        }
+    }
+}
+
+/// Like `SQLFilter`, with an expression in place of the column, e.g.
+/// `LOWER(email) = 'a@b.c'` or, in `HAVING`, `COUNT(*) > 5`.
+pub struct ExprFilter<T: ToSql> {
+    pub expr: Expr,
+    pub filter: T,
+    pub cmp: SQLComp,
+}
+
+impl<T: ToSql> Filter for ExprFilter<T> {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        // before rendering the expression, whose values would stay bound
+        if self.filter.is_empty_list() && !self.cmp.is_array() {
+            if r.empty_list == EmptyList::Error {
+                return Err(SQLError::EmptyList(self.expr.to_sql()));
+            }
+            return Ok(empty_list_condition(&self.cmp).to_owned());
+        }
+
+        let lhs = self.expr.render(r)?;
+        compare_condition(&lhs, &self.filter, &self.cmp, r)
+    }
+}
@@ -0,0 +1,459 @@
+//! C ABI for embedding the generator in non-Rust hosts, see
+//! `include/dyn_trait.h`.
+//!
+//! Every function returning `int` returns `0` on success and `-1` on failure,
+//! in which case `dt_last_error()` describes what went wrong. Strings returned
+//! by `dt_query_render` are owned by the caller and must be released with
+//! `dt_string_free`; statements returned by `dt_query_prepare` with
+//! `dt_statement_free`.
+//!
+//! The library is built with `cargo rustc --lib --features ffi --crate-type
+//! cdylib` (or `staticlib`).
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::sync::Arc;
+
+use crate::{SQLComp, SQLError, SQLFilter, SQLable, SqlValue};
+
+pub struct DtQuery {
+    inner: SQLable,
+}
+
+/// The SQL of a query with placeholders, and the values to bind to them.
+pub struct DtStatement {
+    sql: CString,
+    params: Vec<SqlValue>,
+    /// The text parameters as C strings, `None` for the others.
+    texts: Vec<Option<CString>>,
+}
+
+pub const DT_NULL: c_int = 0;
+pub const DT_INT: c_int = 1;
+pub const DT_UINT: c_int = 2;
+pub const DT_FLOAT: c_int = 3;
+pub const DT_TEXT: c_int = 4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(msg: String) {
+    let msg = CString::new(msg).unwrap_or_else(|_| c"invalid error message".to_owned());
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+fn status(result: Result<(), String>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_error(e);
+            -1
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+unsafe fn query_arg<'a>(q: *mut DtQuery) -> Result<&'a mut DtQuery, String> {
+    q.as_mut().ok_or_else(|| "query is NULL".to_owned())
+}
+
+/// The parameter `idx` of `s`.
+unsafe fn param_arg<'a>(s: *const DtStatement, idx: usize) -> Result<&'a SqlValue, String> {
+    let s = s.as_ref().ok_or_else(|| "statement is NULL".to_owned())?;
+    s.params
+        .get(idx)
+        .ok_or_else(|| format!("no parameter {}, the statement has {}", idx, s.params.len()))
+}
+
+/// Writes the parameter `idx` of `s` to `out` if `read` accepts it.
+unsafe fn read_param<T>(
+    s: *const DtStatement,
+    idx: usize,
+    out: *mut T,
+    kind: &str,
+    read: impl FnOnce(&SqlValue) -> Option<T>,
+) -> c_int {
+    status((|| {
+        let value =
+            read(param_arg(s, idx)?).ok_or_else(|| format!("parameter {} is not {}", idx, kind))?;
+        let out = out.as_mut().ok_or_else(|| "out is NULL".to_owned())?;
+        *out = value;
+        Ok(())
+    })())
+}
+
+unsafe fn add_filter(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+    value: SqlValue,
+) -> c_int {
+    status((|| {
+        let q = query_arg(q)?;
+        let column = str_arg(column, "column")?;
+        let cmp: SQLComp = str_arg(op, "op")?
+            .parse()
+            .map_err(|e: SQLError| e.to_string())?;
+        q.inner.filter_values(vec![SQLFilter {
+            column: column.to_owned().into(),
+            filter: value,
+            cmp,
+        }]);
+        Ok(())
+    })())
+}
+
+/// The message of the last failed call on this thread, or NULL. The pointer
+/// stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn dt_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |s| s.as_ptr()))
+}
+
+/// Creates a query selecting from `table`, `[schema.]table [[AS] alias]`;
+/// NULL on invalid input.
+///
+/// # Safety
+///
+/// `table` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_new(table: *const c_char) -> *mut DtQuery {
+    let query = str_arg(table, "table")
+        .and_then(|table| SQLable::try_new(table).map_err(|e| e.to_string()));
+    match query {
+        Ok(inner) => Box::into_raw(Box::new(DtQuery { inner })),
+        Err(e) => {
+            set_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `q` must be NULL or a pointer returned by `dt_query_new` that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_free(q: *mut DtQuery) {
+    if !q.is_null() {
+        drop(Box::from_raw(q));
+    }
+}
+
+/// Sets the dialect by name (`"postgres"`, `"mysql"`, `"sqlite"`, ...).
+///
+/// # Safety
+///
+/// `q` must be a live query and `name` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_dialect(q: *mut DtQuery, name: *const c_char) -> c_int {
+    status((|| {
+        let q = query_arg(q)?;
+        let dialect = str_arg(name, "name")?
+            .parse()
+            .map_err(|e: SQLError| e.to_string())?;
+        q.inner.dialect(dialect);
+        Ok(())
+    })())
+}
+
+/// Appends a column to the select list.
+///
+/// # Safety
+///
+/// `q` must be a live query and `column` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_select(q: *mut DtQuery, column: *const c_char) -> c_int {
+    status((|| {
+        let q = query_arg(q)?;
+        let column = str_arg(column, "column")?;
+        Arc::make_mut(&mut q.inner.cols)
+            .get_or_insert_with(Vec::new)
+            .push(column.to_owned());
+        Ok(())
+    })())
+}
+
+/// # Safety
+///
+/// `q` must be a live query, `column` and `op` valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_filter_int(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+    value: i64,
+) -> c_int {
+    add_filter(q, column, op, SqlValue::Int(value))
+}
+
+/// # Safety
+///
+/// `q` must be a live query, `column` and `op` valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_filter_float(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+    value: f64,
+) -> c_int {
+    add_filter(q, column, op, SqlValue::Float(value))
+}
+
+/// # Safety
+///
+/// `q` must be a live query, `column`, `op` and `value` valid NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_filter_text(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    match str_arg(value, "value") {
+        Ok(value) => add_filter(q, column, op, SqlValue::Text(value.to_owned())),
+        Err(e) => status(Err(e)),
+    }
+}
+
+/// Compares `column` against NULL, e.g. `op = "="` renders `IS NULL`.
+///
+/// # Safety
+///
+/// `q` must be a live query, `column` and `op` valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_filter_null(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+) -> c_int {
+    add_filter(q, column, op, SqlValue::Null)
+}
+
+/// Compares `column` against a list, e.g. `op = "="` renders an IN list.
+///
+/// # Safety
+///
+/// `q` must be a live query, `column` and `op` valid NUL-terminated strings
+/// and `values` must point to `len` integers (or be NULL when `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_filter_int_list(
+    q: *mut DtQuery,
+    column: *const c_char,
+    op: *const c_char,
+    values: *const i64,
+    len: usize,
+) -> c_int {
+    let values = if len == 0 {
+        &[]
+    } else if values.is_null() {
+        return status(Err("values is NULL".to_owned()));
+    } else {
+        std::slice::from_raw_parts(values, len)
+    };
+    let list = values.iter().map(|v| SqlValue::Int(*v)).collect();
+    add_filter(q, column, op, SqlValue::List(list))
+}
+
+/// Renders the query with values inlined, as quoted literals; NULL on
+/// failure. The result must be released with `dt_string_free`. Prefer
+/// `dt_query_prepare`, which leaves the values to the driver.
+///
+/// # Safety
+///
+/// `q` must be a live query.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_render(q: *const DtQuery) -> *mut c_char {
+    let sql = q
+        .as_ref()
+        .ok_or_else(|| "query is NULL".to_owned())
+        .and_then(|q| q.inner.try_prepare().map_err(|e| e.to_string()))
+        .and_then(|sql| CString::new(sql).map_err(|e| e.to_string()));
+
+    match sql {
+        Ok(sql) => sql.into_raw(),
+        Err(e) => {
+            set_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `s` must be NULL or a string returned by `dt_query_render` that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dt_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Renders the query with the dialect's placeholders in place of the
+/// values; NULL on failure. The result must be released with
+/// `dt_statement_free`.
+///
+/// # Safety
+///
+/// `q` must be a live query.
+#[no_mangle]
+pub unsafe extern "C" fn dt_query_prepare(q: *const DtQuery) -> *mut DtStatement {
+    let statement = (|| {
+        let q = q.as_ref().ok_or_else(|| "query is NULL".to_owned())?;
+        let (sql, params) = q.inner.prepare_params().map_err(|e| e.to_string())?;
+        if params.iter().any(|p| matches!(p, SqlValue::List(_))) {
+            return Err("array parameters are not supported over the C ABI".to_owned());
+        }
+        let texts = params
+            .iter()
+            .map(|p| match p {
+                SqlValue::Text(text) => CString::new(text.as_str()).map(Some),
+                _ => Ok(None),
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        let sql = CString::new(sql).map_err(|e| e.to_string())?;
+        Ok(DtStatement { sql, params, texts })
+    })();
+
+    match statement {
+        Ok(statement) => Box::into_raw(Box::new(statement)),
+        Err(e) => {
+            set_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `s` must be NULL or a pointer returned by `dt_query_prepare` that has
+/// not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_free(s: *mut DtStatement) {
+    if !s.is_null() {
+        drop(Box::from_raw(s));
+    }
+}
+
+/// The SQL of the statement, valid until it is freed; NULL if `s` is NULL.
+///
+/// # Safety
+///
+/// `s` must be NULL or a live statement.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_sql(s: *const DtStatement) -> *const c_char {
+    s.as_ref().map_or(std::ptr::null(), |s| s.sql.as_ptr())
+}
+
+/// The number of values to bind, in placeholder order.
+///
+/// # Safety
+///
+/// `s` must be NULL or a live statement.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_count(s: *const DtStatement) -> usize {
+    s.as_ref().map_or(0, |s| s.params.len())
+}
+
+/// The type of the parameter `idx`, one of the `DT_*` constants; `-1` if
+/// there is no such parameter.
+///
+/// # Safety
+///
+/// `s` must be NULL or a live statement.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_type(s: *const DtStatement, idx: usize) -> c_int {
+    match param_arg(s, idx) {
+        Ok(SqlValue::Null) => DT_NULL,
+        Ok(SqlValue::Int(_)) => DT_INT,
+        Ok(SqlValue::UInt(_)) => DT_UINT,
+        Ok(SqlValue::Float(_)) => DT_FLOAT,
+        Ok(SqlValue::Text(_)) => DT_TEXT,
+        // refused by `dt_query_prepare`
+        Ok(SqlValue::List(_)) => -1,
+        Err(e) => status(Err(e)),
+    }
+}
+
+/// # Safety
+///
+/// `s` must be NULL or a live statement, `out` must point to an `int64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_int(
+    s: *const DtStatement,
+    idx: usize,
+    out: *mut i64,
+) -> c_int {
+    read_param(s, idx, out, "an integer", |v| match v {
+        SqlValue::Int(v) => Some(*v),
+        _ => None,
+    })
+}
+
+/// # Safety
+///
+/// `s` must be NULL or a live statement, `out` must point to a `uint64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_uint(
+    s: *const DtStatement,
+    idx: usize,
+    out: *mut u64,
+) -> c_int {
+    read_param(s, idx, out, "an unsigned integer", |v| match v {
+        SqlValue::UInt(v) => Some(*v),
+        _ => None,
+    })
+}
+
+/// # Safety
+///
+/// `s` must be NULL or a live statement, `out` must point to a `double`.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_float(
+    s: *const DtStatement,
+    idx: usize,
+    out: *mut f64,
+) -> c_int {
+    read_param(s, idx, out, "a float", |v| match v {
+        SqlValue::Float(v) => Some(*v),
+        _ => None,
+    })
+}
+
+/// The text of the parameter `idx`, valid until the statement is freed;
+/// NULL if it is not text.
+///
+/// # Safety
+///
+/// `s` must be NULL or a live statement.
+#[no_mangle]
+pub unsafe extern "C" fn dt_statement_param_text(
+    s: *const DtStatement,
+    idx: usize,
+) -> *const c_char {
+    let text = param_arg(s, idx).and_then(|_| {
+        let s = &*s;
+        s.texts[idx]
+            .as_ref()
+            .ok_or_else(|| format!("parameter {} is not text", idx))
+    });
+    match text {
+        Ok(text) => text.as_ptr(),
+        Err(e) => {
+            set_error(e);
+            std::ptr::null()
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::{Display, LowerExp};
+
+use crate::{unrendered, Numeric, Renderer, SQLError, SqlValue, ToSql};
+
+/// How floats are written as inline literals, see `SQLable::float_format`.
+/// Bound values are passed to the driver as they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FloatFormat {
+    /// Digits after the decimal point, rounding; otherwise the fewest that
+    /// read back as the same value.
+    pub precision: Option<usize>,
+    /// Writes `1.5e300` instead of every digit of the number.
+    pub scientific: bool,
+    pub non_finite: NonFinite,
+}
+
+/// What rendering does with a NaN or infinite float, which SQL has no
+/// literal for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Refuse to render the query with `SQLError::NonFinite`. `ToSql::to_sql`,
+    /// having no renderer, writes the error between angle brackets.
+    #[default]
+    Error,
+    /// Write `NULL` instead.
+    Null,
+    /// Write `'NaN'`, `'Infinity'` or `'-Infinity'`, which Postgres casts
+    /// to a float.
+    Quoted,
+}
+
+/// `f32` and `f64`, written with their own precision.
+pub(crate) trait Float: Copy + Display + LowerExp {
+    fn is_finite(self) -> bool;
+    fn is_nan(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+}
+
+macro_rules! float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+
+                fn is_sign_negative(self) -> bool {
+                    <$t>::is_sign_negative(self)
+                }
+            }
+
+            impl ToSql for $t {
+                fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                    match self.is_finite() {
+                        true => write!(out, "{}", self),
+                        false => out.write_str(&unrendered(&SQLError::NonFinite(self.to_string()))),
+                    }
+                }
+
+                fn to_value(&self) -> Option<SqlValue> {
+                    Some(Numeric::to_value(self))
+                }
+
+                fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+                    match r.is_parameterized() {
+                        true => Ok(r.bind(Numeric::to_value(self))),
+                        false => r.float(*self),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float!(f64, f32);
+
+fn quoted<F: Float>(v: F) -> &'static str {
+    match (v.is_nan(), v.is_sign_negative()) {
+        (true, _) => "'NaN'",
+        (false, false) => "'Infinity'",
+        (false, true) => "'-Infinity'",
+    }
+}
+
+impl FloatFormat {
+    /// `v` as a literal, if it is finite.
+    pub(crate) fn write<F: Float>(&self, v: F) -> Option<String> {
+        if !v.is_finite() {
+            return None;
+        }
+        Some(match (self.precision, self.scientific) {
+            (None, false) => format!("{}", v),
+            (None, true) => format!("{:e}", v),
+            (Some(p), false) => format!("{:.*}", p, v),
+            (Some(p), true) => format!("{:.*e}", p, v),
+        })
+    }
+}
+
+impl Renderer {
+    /// `v` as an inline literal, written as `floats` says.
+    pub(crate) fn float<F: Float>(&self, v: F) -> Result<String, SQLError> {
+        if let Some(sql) = self.floats.write(v) {
+            return Ok(sql);
+        }
+        match self.floats.non_finite {
+            NonFinite::Error => Err(SQLError::NonFinite(v.to_string())),
+            NonFinite::Null => Ok("NULL".to_owned()),
+            NonFinite::Quoted => Ok(quoted(v).to_owned()),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::validate::{type_named, value_type, Scope};
+use crate::{BinOp, Dialect, Expr, SQLError, SQLable, Schema, SqlValue, ValueType};
+
+/// The signature of a SQL function the database has beyond those the
+/// builder writes, e.g. a user-defined one, added to a `Schema` with
+/// `Schema::function`. `SQLable::validate` then checks its calls, and
+/// `SQLable::expr_type` knows what they return:
+///
+/// ```
+/// # use traits::*;
+/// let schema = Schema::new()
+///     .table(
+///         TableDef::new("products")
+///             .column(ColumnDef::of::<String>("name"))
+///             .column(ColumnDef::of::<i64>("price")),
+///     )
+///     .function(
+///         FunctionDef::new("similarity", ValueType::Number)
+///             .arg(ValueType::Text)
+///             .arg(ValueType::Text)
+///             .dialect(Dialect::Postgres),
+///     );
+///
+/// let mut q = SQLable::new("products");
+/// q.dialect(Dialect::Postgres)
+///     .select_expr(func("similarity", [col("name"), lit("chair")]).alias("score"));
+/// assert!(q.validate(&schema).is_ok());
+/// assert_eq!(q.expr_type(&schema, &func("similarity", [col("name"), lit("x")])).unwrap(), ValueType::Number);
+///
+/// let mut q = SQLable::new("products");
+/// q.dialect(Dialect::Postgres).select_expr(func("similarity", [col("price"), lit("chair")]));
+/// assert!(matches!(q.validate(&schema), Err(SQLError::InvalidCall(_))));
+///
+/// q.dialect(Dialect::MySql);
+/// assert!(matches!(q.validate(&schema), Err(SQLError::Unsupported(_))));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDef {
+    pub name: String,
+    pub args: Vec<ValueType>,
+    /// The last argument repeats: it is given once or more.
+    pub variadic: bool,
+    pub returns: ValueType,
+    /// The dialects that have the function, every one if empty.
+    pub dialects: Vec<Dialect>,
+}
+
+impl FunctionDef {
+    pub fn new(name: &str, returns: ValueType) -> Self {
+        FunctionDef {
+            name: name.to_owned(),
+            args: Vec::new(),
+            variadic: false,
+            returns,
+            dialects: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, ty: ValueType) -> Self {
+        self.args.push(ty);
+        self
+    }
+
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        self
+    }
+
+    /// Adds `dialect` to those that have the function.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialects.push(dialect);
+        self
+    }
+
+    /// Whether `dialect` has the function. The generic dialect has them
+    /// all.
+    pub fn available(&self, dialect: Dialect) -> bool {
+        dialect == Dialect::Generic || self.dialects.is_empty() || self.dialects.contains(&dialect)
+    }
+
+    /// Fails unless a call in `dialect` with arguments of types `args`
+    /// matches the signature. Arguments of unknown type match any.
+    fn check(&self, dialect: Dialect, args: &[ValueType]) -> Result<(), SQLError> {
+        if !self.available(dialect) {
+            return Err(SQLError::Unsupported(format!(
+                "{} in {:?}",
+                self.name, dialect
+            )));
+        }
+        let count_ok = match self.variadic {
+            true => args.len() >= self.args.len(),
+            false => args.len() == self.args.len(),
+        };
+        if !count_ok {
+            let at_least = if self.variadic { "at least " } else { "" };
+            return Err(SQLError::InvalidCall(format!(
+                "{} takes {}{} arguments, got {}",
+                self.name,
+                at_least,
+                self.args.len(),
+                args.len()
+            )));
+        }
+        let declared = self.args.iter().chain(std::iter::repeat(
+            self.args.last().unwrap_or(&ValueType::Any),
+        ));
+        for (idx, (ty, declared)) in args.iter().zip(declared).enumerate() {
+            if !fits(*ty, *declared) {
+                return Err(SQLError::InvalidCall(format!(
+                    "argument {} of {}: expected {:?}, got {:?}",
+                    idx + 1,
+                    self.name,
+                    declared,
+                    ty
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a value of type `ty` can be given where `declared` is.
+fn fits(ty: ValueType, declared: ValueType) -> bool {
+    match (ty, declared) {
+        (ValueType::Any, _) | (_, ValueType::Any) => true,
+        (ValueType::Integer, ValueType::Number) => true,
+        (ty, declared) => ty == declared,
+    }
+}
+
+/// What a function the builder writes returns, from its arguments.
+fn builtin_type(name: &str, args: &[ValueType]) -> ValueType {
+    let first = args.first().copied().unwrap_or(ValueType::Any);
+    match name.to_uppercase().as_str() {
+        "COUNT" | "ROW_NUMBER" | "RANK" | "DENSE_RANK" => ValueType::Integer,
+        "AVG" => ValueType::Number,
+        "SUM" if first == ValueType::Integer => ValueType::Integer,
+        "SUM" => ValueType::Number,
+        "MIN" | "MAX" | "COALESCE" | "NULLIF" | "LAG" | "LEAD" => first,
+        "LOWER" | "UPPER" | "TRIM" | "CONCAT" | "JSON_UNQUOTE" | "JSON_VALUE" => ValueType::Text,
+        _ => ValueType::Any,
+    }
+}
+
+impl SQLable {
+    /// The kind of values `expr` yields in this query, its columns read
+    /// from `schema`; `ValueType::Any` where unknown. Calls to functions
+    /// of the schema return what their `FunctionDef` says.
+    pub fn expr_type(&self, schema: &Schema, expr: &Expr) -> Result<ValueType, SQLError> {
+        let scope = Scope::new(self, schema)?;
+        self.type_in(&scope, schema, expr)
+    }
+
+    fn type_in(&self, scope: &Scope, schema: &Schema, expr: &Expr) -> Result<ValueType, SQLError> {
+        let ty = match expr {
+            Expr::Column(c) if c == "*" => ValueType::Any,
+            Expr::Column(c) => value_type(scope.column(c)?),
+            Expr::Value(SqlValue::Int(_) | SqlValue::UInt(_)) => ValueType::Integer,
+            Expr::Value(SqlValue::Float(_)) => ValueType::Number,
+            Expr::Value(SqlValue::Text(_)) => ValueType::Text,
+            Expr::Value(_) | Expr::Case(_) => ValueType::Any,
+            Expr::Function { name, args, .. } => {
+                let args = args
+                    .iter()
+                    .map(|a| self.type_in(scope, schema, a))
+                    .collect::<Result<Vec<ValueType>, SQLError>>()?;
+                match schema.function_def(name) {
+                    Some(def) => {
+                        def.check(self.dialect, &args)?;
+                        def.returns
+                    }
+                    None => builtin_type(name, &args),
+                }
+            }
+            Expr::Cast(_, ty) => type_named(ty.name(self.dialect)),
+            Expr::Over(e, _) | Expr::Neg(e) | Expr::Alias(e, _) => {
+                self.type_in(scope, schema, e)?
+            }
+            Expr::Binary(lhs, op, rhs) => {
+                let lhs = self.type_in(scope, schema, lhs)?;
+                let rhs = self.type_in(scope, schema, rhs)?;
+                // MySQL divides integers into decimals
+                let divides = *op == BinOp::Div && self.dialect == Dialect::MySql;
+                match (lhs, rhs) {
+                    (ValueType::Integer, ValueType::Integer) if !divides => ValueType::Integer,
+                    (
+                        ValueType::Integer | ValueType::Number,
+                        ValueType::Integer | ValueType::Number,
+                    ) => ValueType::Number,
+                    _ => ValueType::Any,
+                }
+            }
+            Expr::Concat(_) | Expr::Json { text: true, .. } => ValueType::Text,
+            Expr::Json { .. } => ValueType::Any,
+        };
+        Ok(ty)
+    }
+
+    /// Fails on the first call in `expr` to a function of `schema` that
+    /// does not match its `FunctionDef`.
+    pub(crate) fn check_calls(
+        &self,
+        scope: &Scope,
+        schema: &Schema,
+        expr: &Expr,
+    ) -> Result<(), SQLError> {
+        match expr {
+            // typing a call checks it and its arguments
+            Expr::Function { .. } => self.type_in(scope, schema, expr).map(|_| ()),
+            Expr::Concat(args) => args
+                .iter()
+                .try_for_each(|a| self.check_calls(scope, schema, a)),
+            Expr::Binary(lhs, _, rhs) => {
+                self.check_calls(scope, schema, lhs)?;
+                self.check_calls(scope, schema, rhs)
+            }
+            Expr::Cast(e, _)
+            | Expr::Neg(e)
+            | Expr::Alias(e, _)
+            | Expr::Over(e, _)
+            | Expr::Json { expr: e, .. } => self.check_calls(scope, schema, e),
+            Expr::Column(_) | Expr::Value(_) | Expr::Case(_) => Ok(()),
+        }
+    }
+}
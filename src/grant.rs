@@ -0,0 +1,255 @@
+use crate::{Dialect, NamingStrategy, Quoting, Renderer, SQLError, TableName};
+
+/// A privilege on a table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Privilege {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    /// Postgres only.
+    Truncate,
+    References,
+    /// Every table privilege: `ALL PRIVILEGES`, spelled out on SQL Server
+    /// where `ALL` is deprecated.
+    All,
+}
+
+impl Privilege {
+    fn keyword(&self) -> &'static str {
+        match self {
+            Privilege::Select => "SELECT",
+            Privilege::Insert => "INSERT",
+            Privilege::Update => "UPDATE",
+            Privilege::Delete => "DELETE",
+            Privilege::Truncate => "TRUNCATE",
+            Privilege::References => "REFERENCES",
+            Privilege::All => "ALL PRIVILEGES",
+        }
+    }
+
+    /// Whether the privilege can be limited to some columns.
+    fn takes_columns(&self) -> bool {
+        matches!(
+            self,
+            Privilege::Select | Privilege::Insert | Privilege::Update | Privilege::References
+        )
+    }
+}
+
+/// A `GRANT` of table privileges to roles, or with `Grant::revoke` the
+/// matching `REVOKE`.
+///
+/// ```
+/// # use traits::*;
+/// let mut grant = Grant::new(&[Privilege::Select, Privilege::Insert]);
+/// grant.on("billing.invoices").to("reporting").dialect(Dialect::Postgres);
+/// assert_eq!(
+///     grant.statements().unwrap(),
+///     vec!["GRANT SELECT, INSERT ON billing.invoices TO reporting\n"]
+/// );
+/// ```
+///
+/// MySQL and SQL Server grant on one table per statement, so there is one
+/// statement per table everywhere. SQLite has no privileges.
+pub struct Grant {
+    revoke: bool,
+    privileges: Vec<Privilege>,
+    columns: Vec<String>,
+    tables: Vec<TableName>,
+    roles: Vec<String>,
+    grant_option: bool,
+    dialect: Dialect,
+    naming: NamingStrategy,
+    quoting: Quoting,
+}
+
+impl Grant {
+    pub fn new(privileges: &[Privilege]) -> Self {
+        Grant {
+            revoke: false,
+            privileges: privileges.to_vec(),
+            columns: Vec::new(),
+            tables: Vec::new(),
+            roles: Vec::new(),
+            grant_option: false,
+            dialect: Dialect::default(),
+            naming: NamingStrategy::default(),
+            quoting: Quoting::default(),
+        }
+    }
+
+    /// Takes the privileges back: `REVOKE ... FROM role`.
+    pub fn revoke(privileges: &[Privilege]) -> Self {
+        Grant {
+            revoke: true,
+            ..Grant::new(privileges)
+        }
+    }
+
+    /// Adds a table, which may be schema-qualified.
+    pub fn on(&mut self, tbl: &str) -> &mut Self {
+        self.tables.push(TableName::parse(tbl));
+        self
+    }
+
+    /// Adds a role (or user) receiving or losing the privileges.
+    pub fn to(&mut self, role: &str) -> &mut Self {
+        self.roles.push(role.to_owned());
+        self
+    }
+
+    /// Limits the privileges to these columns: `SELECT (a, b)`.
+    pub fn columns(&mut self, cols: Vec<String>) -> &mut Self {
+        self.columns = cols;
+        self
+    }
+
+    /// Lets the roles grant the privileges on: `WITH GRANT OPTION`. On a
+    /// revoke, takes back only that right: `REVOKE GRANT OPTION FOR`.
+    pub fn with_grant_option(&mut self) -> &mut Self {
+        self.grant_option = true;
+        self
+    }
+
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn naming(&mut self, naming: NamingStrategy) -> &mut Self {
+        self.naming = naming;
+        self
+    }
+
+    pub fn quoting(&mut self, quoting: Quoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// One statement per table.
+    pub fn statements(&self) -> Result<Vec<String>, SQLError> {
+        let r = Renderer {
+            dialect: self.dialect,
+            naming: self.naming,
+            quoting: self.quoting,
+            ..Default::default()
+        };
+        if self.dialect == Dialect::Sqlite {
+            return Err(SQLError::Unsupported("privileges in Sqlite".to_owned()));
+        }
+        if self.privileges.is_empty() || self.roles.is_empty() {
+            return Err(SQLError::Unsupported(
+                "a grant without privileges or roles".to_owned(),
+            ));
+        }
+
+        let privileges = self.render_privileges(&r)?;
+        let roles = self
+            .roles
+            .iter()
+            .map(|role| r.ident(role))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut result = Vec::with_capacity(self.tables.len());
+        for table in &self.tables {
+            let table = table.render(&r);
+            let statement = match (self.revoke, self.grant_option) {
+                (false, false) => format!("GRANT {} ON {} TO {}\n", privileges, table, roles),
+                (false, true) => format!(
+                    "GRANT {} ON {} TO {} WITH GRANT OPTION\n",
+                    privileges, table, roles
+                ),
+                (true, false) => format!("REVOKE {} ON {} FROM {}\n", privileges, table, roles),
+                (true, true) if self.dialect == Dialect::MySql => {
+                    return Err(SQLError::Unsupported(
+                        "revoking only the grant option in MySql".to_owned(),
+                    ))
+                }
+                // SQL Server insists on CASCADE once the option was used
+                (true, true) if self.dialect == Dialect::MsSql => format!(
+                    "REVOKE GRANT OPTION FOR {} ON {} FROM {} CASCADE\n",
+                    privileges, table, roles
+                ),
+                (true, true) => format!(
+                    "REVOKE GRANT OPTION FOR {} ON {} FROM {}\n",
+                    privileges, table, roles
+                ),
+            };
+            result.push(statement);
+        }
+        Ok(result)
+    }
+
+    fn render_privileges(&self, r: &Renderer) -> Result<String, SQLError> {
+        let mut privileges = Vec::new();
+        for privilege in &self.privileges {
+            match (privilege, r.dialect) {
+                (Privilege::Truncate, Dialect::MySql | Dialect::MsSql) => {
+                    return Err(SQLError::Unsupported(format!(
+                        "the TRUNCATE privilege in {:?}",
+                        r.dialect
+                    )))
+                }
+                (Privilege::All, Dialect::MsSql) => privileges.extend([
+                    Privilege::Select,
+                    Privilege::Insert,
+                    Privilege::Update,
+                    Privilege::Delete,
+                    Privilege::References,
+                ]),
+                (p, _) => privileges.push(*p),
+            }
+        }
+
+        let columns = if self.columns.is_empty() {
+            String::new()
+        } else {
+            let cols = self.columns.iter().map(|c| r.column(c));
+            format!(" ({})", cols.collect::<Vec<String>>().join(", "))
+        };
+        let mut result = Vec::with_capacity(privileges.len());
+        for privilege in privileges {
+            if columns.is_empty() {
+                result.push(privilege.keyword().to_owned());
+            } else if privilege.takes_columns() {
+                result.push(format!("{}{}", privilege.keyword(), columns));
+            } else {
+                return Err(SQLError::Unsupported(format!(
+                    "{} on columns",
+                    privilege.keyword()
+                )));
+            }
+        }
+        Ok(result.join(", "))
+    }
+}
+
+/// Makes `member` a member of `role`, inheriting its privileges:
+/// `GRANT role TO member`, `ALTER ROLE ... ADD MEMBER` on SQL Server.
+pub fn grant_role(role: &str, member: &str, dialect: Dialect) -> Result<String, SQLError> {
+    role_membership(role, member, dialect, false)
+}
+
+/// Undoes `grant_role`.
+pub fn revoke_role(role: &str, member: &str, dialect: Dialect) -> Result<String, SQLError> {
+    role_membership(role, member, dialect, true)
+}
+
+fn role_membership(
+    role: &str,
+    member: &str,
+    dialect: Dialect,
+    revoke: bool,
+) -> Result<String, SQLError> {
+    let role = dialect.ident(role, Quoting::Auto);
+    let member = dialect.ident(member, Quoting::Auto);
+    match (dialect, revoke) {
+        (Dialect::Sqlite, _) => Err(SQLError::Unsupported("roles in Sqlite".to_owned())),
+        (Dialect::MsSql, false) => Ok(format!("ALTER ROLE {} ADD MEMBER {}\n", role, member)),
+        (Dialect::MsSql, true) => Ok(format!("ALTER ROLE {} DROP MEMBER {}\n", role, member)),
+        (_, false) => Ok(format!("GRANT {} TO {}\n", role, member)),
+        (_, true) => Ok(format!("REVOKE {} FROM {}\n", role, member)),
+    }
+}
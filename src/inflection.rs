@@ -0,0 +1,115 @@
+//! Case conversion for identifiers.
+//!
+//! A name is first split into words, then the words are joined in the target
+//! case. Word boundaries are:
+//!
+//! - separators: `_`, `-`, whitespace and any other non-alphanumeric character,
+//! - a lowercase letter or digit followed by an uppercase letter (`userId`, `v2Beta`),
+//! - the last letter of an acronym before a capitalised word (`HTTPServer`).
+//!
+//! Digits stay with the word they follow, so `address2` is one word.
+//! Letters without case (most non-Latin scripts) never start a new word.
+
+/// Splits `s` into its words, keeping their original case.
+///
+/// `words("HTTPServerID")` is `["HTTP", "Server", "ID"]`.
+pub fn words(s: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, &(pos, ch)) in chars.iter().enumerate() {
+        if !ch.is_alphanumeric() {
+            if let Some(begin) = start.take() {
+                words.push(&s[begin..pos]);
+            }
+            continue;
+        }
+
+        let boundary = start.is_some() && case_boundary(&chars, i);
+        match start {
+            Some(begin) if boundary => {
+                words.push(&s[begin..pos]);
+                start = Some(pos);
+            }
+            Some(_) => {}
+            None => start = Some(pos),
+        }
+    }
+
+    if let Some(begin) = start {
+        words.push(&s[begin..]);
+    }
+    words
+}
+
+/// Whether an uppercase letter at `i` starts a new word: after a lowercase
+/// letter or a digit, or as the capital of a word after an acronym.
+fn case_boundary(chars: &[(usize, char)], i: usize) -> bool {
+    let ch = chars[i].1;
+    match i.checked_sub(1).map(|p| chars[p].1) {
+        Some(prev) if ch.is_uppercase() => {
+            let next_lower = chars.get(i + 1).is_some_and(|&(_, c)| c.is_lowercase());
+            prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower)
+        }
+        _ => false,
+    }
+}
+
+/// `HTTPServerID` becomes `http_server_id`.
+///
+/// Only case boundaries are changed: every other character is kept, so
+/// `type_`, `a__b`, `_rowid` and `amount$` stay as they are.
+pub fn snake_case(s: &str) -> String {
+    if is_snake_case(s) {
+        return s.to_owned();
+    }
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let mut result = String::with_capacity(s.len() + 4);
+    for (i, &(_, ch)) in chars.iter().enumerate() {
+        if case_boundary(&chars, i) {
+            result.push('_');
+        }
+        result.extend(ch.to_lowercase());
+    }
+    result
+}
+
+/// Whether `snake_case` leaves `s` as it is, checked without splitting it.
+pub(crate) fn is_snake_case(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii() && !b.is_ascii_uppercase())
+}
+
+/// `http_server_id` becomes `httpServerId`.
+pub fn camel_case(s: &str) -> String {
+    let mut result = leading_underscores(s).to_owned();
+    for (i, word) in words(s).into_iter().enumerate() {
+        if i == 0 {
+            result.extend(word.chars().flat_map(char::to_lowercase));
+        } else {
+            push_capitalized(&mut result, word);
+        }
+    }
+    result
+}
+
+/// `http_server_id` becomes `HttpServerId`.
+pub fn pascal_case(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for word in words(s) {
+        push_capitalized(&mut result, word);
+    }
+    result
+}
+
+fn push_capitalized(out: &mut String, word: &str) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+        out.extend(first.to_uppercase());
+        out.extend(chars.flat_map(char::to_lowercase));
+    }
+}
+
+fn leading_underscores(s: &str) -> &str {
+    &s[..s.len() - s.trim_start_matches('_').len()]
+}
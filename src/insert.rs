@@ -0,0 +1,318 @@
+use crate::size::StatementSize;
+use crate::{
+    unrendered, Dialect, Expr, FloatFormat, KeyRange, NamingStrategy, Quoting, Renderer, SQLError,
+    SqlValue, TableDef, TableName, ToSql, Touched, Uuid, UuidFormat,
+};
+
+/// One value of an inserted row.
+#[derive(Clone, Debug)]
+pub enum InsertValue {
+    /// Bound as a parameter when rendering with parameters.
+    Value(SqlValue),
+    /// Rendered in place, e.g. `func("now", [])`; only the values inside it
+    /// are bound.
+    Expr(Expr),
+    /// The column's default: `DEFAULT`.
+    Default,
+    /// A UUID, written as the insert's `UuidFormat` says.
+    Uuid(Uuid),
+}
+
+impl<T: Into<SqlValue>> From<T> for InsertValue {
+    fn from(v: T) -> Self {
+        InsertValue::Value(v.into())
+    }
+}
+
+impl From<Uuid> for InsertValue {
+    fn from(id: Uuid) -> Self {
+        InsertValue::Uuid(id)
+    }
+}
+
+impl From<Expr> for InsertValue {
+    fn from(e: Expr) -> Self {
+        InsertValue::Expr(e)
+    }
+}
+
+/// An `INSERT INTO table (columns) VALUES (...), ...` statement.
+///
+/// ```
+/// # use traits::*;
+/// let mut insert = Insert::new("users");
+/// insert
+///     .columns(vec!["id".to_owned(), "name".to_owned(), "createdAt".to_owned()])
+///     .row(vec![InsertValue::Default, "alice".into(), func("now", []).into()]);
+/// let (sql, params) = insert.prepare_params().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Insert {
+    table: TableName,
+    columns: Vec<String>,
+    rows: Vec<Vec<InsertValue>>,
+    generated: Vec<String>,
+    key: Vec<String>,
+    dialect: Dialect,
+    naming: NamingStrategy,
+    quoting: Quoting,
+    max_statement_bytes: Option<usize>,
+    floats: FloatFormat,
+    uuids: UuidFormat,
+}
+
+impl Insert {
+    pub fn new(tbl: &str) -> Self {
+        Insert::from_table(TableName::parse(tbl))
+    }
+
+    /// Creates an insert into `table` as given, without parsing.
+    pub fn from_table(table: TableName) -> Self {
+        Insert {
+            table,
+            columns: Vec::new(),
+            rows: Vec::new(),
+            generated: Vec::new(),
+            key: Vec::new(),
+            dialect: Dialect::default(),
+            naming: NamingStrategy::default(),
+            quoting: Quoting::default(),
+            max_statement_bytes: None,
+            floats: FloatFormat::default(),
+            uuids: UuidFormat::default(),
+        }
+    }
+
+    /// Sets the columns every row gives a value for.
+    pub fn columns(&mut self, cols: Vec<String>) -> &mut Self {
+        self.columns = cols;
+        self
+    }
+
+    /// Adds a row, one value per column.
+    pub fn row(&mut self, values: Vec<InsertValue>) -> &mut Self {
+        self.rows.push(values);
+        self
+    }
+
+    /// Leaves out the columns `def` declares as generated, together with
+    /// their values, since the database refuses writes to them, and takes
+    /// its primary key as the `key`.
+    pub fn table_def(&mut self, def: &TableDef) -> &mut Self {
+        self.generated = def
+            .columns
+            .iter()
+            .filter(|c| c.is_generated())
+            .map(|c| c.name.clone())
+            .collect();
+        self.key = def.primary_key.clone();
+        self
+    }
+
+    /// Sets the columns identifying a row, for `touched`.
+    pub fn key(&mut self, cols: Vec<String>) -> &mut Self {
+        self.key = cols;
+        self
+    }
+
+    /// The rows the insert writes: the keys of its rows when the key
+    /// columns are given a value in every row, any row otherwise, e.g.
+    /// when the database computes the key.
+    pub fn touched(&self) -> Touched {
+        let table = self.table.clone();
+        let positions = self
+            .key
+            .iter()
+            .map(|k| {
+                let k = self.naming.apply(k);
+                self.columns.iter().position(|c| self.naming.apply(c) == k)
+            })
+            .collect::<Option<Vec<usize>>>();
+        let positions = match positions {
+            Some(positions) if !positions.is_empty() => positions,
+            _ => {
+                return Touched {
+                    table,
+                    rows: KeyRange::All,
+                }
+            }
+        };
+
+        let values = self
+            .rows
+            .iter()
+            .map(|row| {
+                positions
+                    .iter()
+                    .map(|idx| match row.get(*idx) {
+                        Some(InsertValue::Value(v)) => Some(v.clone()),
+                        Some(InsertValue::Uuid(id)) => id.to_value(),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<SqlValue>>>()
+            })
+            .collect::<Option<Vec<Vec<SqlValue>>>>();
+        let rows = match values {
+            Some(values) => KeyRange::Keys {
+                columns: self.key.iter().map(|k| self.naming.apply(k)).collect(),
+                values,
+            },
+            None => KeyRange::All,
+        };
+        Touched { table, rows }
+    }
+
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn naming(&mut self, naming: NamingStrategy) -> &mut Self {
+        self.naming = naming;
+        self
+    }
+
+    pub fn quoting(&mut self, quoting: Quoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Caps the size of the statement as `SQLable::max_statement_bytes`
+    /// does; the `VALUES` of a large batch are what usually exceeds it.
+    pub fn max_statement_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_statement_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets how floats are written inline, see `SQLable::float_format`.
+    pub fn float_format(&mut self, format: FloatFormat) -> &mut Self {
+        self.floats = format;
+        self
+    }
+
+    /// Sets how `Uuid` values are written, see `SQLable::uuid_format`.
+    pub fn uuid_format(&mut self, format: UuidFormat) -> &mut Self {
+        self.uuids = format;
+        self
+    }
+
+    /// Renders the statement with the values inlined. If it cannot be
+    /// rendered, see `try_prepare`, the SQL is the error between angle
+    /// brackets, which the database refuses to run.
+    pub fn prepare(&self) -> String {
+        match self.try_prepare() {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e) + "\n",
+        }
+    }
+
+    /// Renders the statement with the values inlined, failing if a row
+    /// does not match the columns.
+    pub fn try_prepare(&self) -> Result<String, SQLError> {
+        self.render(&mut self.renderer())
+    }
+
+    /// Renders the statement with placeholders, returning the values to bind.
+    pub fn prepare_params(&self) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        let sql = self.render(&mut r)?;
+        Ok((sql, r.into_params()))
+    }
+
+    fn renderer(&self) -> Renderer {
+        Renderer {
+            dialect: self.dialect,
+            naming: self.naming,
+            quoting: self.quoting,
+            max_statement_bytes: self.max_statement_bytes,
+            floats: self.floats,
+            uuids: self.uuids,
+            ..Default::default()
+        }
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let table = self.table.render(r);
+        if self.columns.is_empty() {
+            return defaults(table, self.rows.len(), r);
+        }
+        if self.rows.is_empty() {
+            return Err(SQLError::Unsupported("INSERT without rows".to_owned()));
+        }
+
+        let keep = self
+            .columns
+            .iter()
+            .map(|c| {
+                let c = self.naming.apply(c);
+                !self.generated.iter().any(|g| self.naming.apply(g) == c)
+            })
+            .collect::<Vec<bool>>();
+        let cols = self
+            .columns
+            .iter()
+            .zip(&keep)
+            .filter(|(_, keep)| **keep)
+            .map(|(c, _)| r.column(c))
+            .collect::<Vec<String>>();
+        let mut rows = self.rows.iter().enumerate();
+        if let Some((idx, row)) = rows.find(|(_, row)| row.len() != self.columns.len()) {
+            return Err(SQLError::Unsupported(format!(
+                "row {} has {} values for {} columns",
+                idx + 1,
+                row.len(),
+                self.columns.len()
+            )));
+        }
+        // every column generated, or a single row of defaults
+        let defaulted = |row: &Vec<InsertValue>| {
+            row.iter()
+                .zip(&keep)
+                .all(|(v, keep)| !keep || matches!(v, InsertValue::Default))
+        };
+        if cols.is_empty() || (self.rows.len() == 1 && defaulted(&self.rows[0])) {
+            return defaults(table, self.rows.len(), r);
+        }
+        let mut size = StatementSize::new(r);
+        let mut rows = Vec::with_capacity(self.rows.len());
+        for row in &self.rows {
+            let values = row
+                .iter()
+                .zip(&keep)
+                .filter(|(_, keep)| **keep)
+                .map(|(v, _)| match v {
+                    InsertValue::Value(v) => v.render(r),
+                    InsertValue::Expr(e) => e.render(r),
+                    InsertValue::Uuid(id) => id.render(r),
+                    InsertValue::Default if r.dialect == Dialect::Sqlite => {
+                        Err(SQLError::Unsupported(
+                            "DEFAULT in VALUES on SQLite, leave the column out".to_owned(),
+                        ))
+                    }
+                    InsertValue::Default => Ok("DEFAULT".to_owned()),
+                })
+                .collect::<Result<Vec<String>, SQLError>>()?;
+            rows.push(format!("  ({})", values.join(",")));
+        }
+        let values = format!("VALUES\n{}\n", rows.join(",\n"));
+        size.clause("VALUES", &values, r);
+
+        let insert = format!("INSERT INTO {} ({})\n", table, cols.join(","));
+        size.clause("INSERT", &insert, r);
+        size.check(r)?;
+        Ok(insert + &values)
+    }
+}
+
+/// An insert of `rows` rows of defaults only; SQL Server rejects an empty
+/// `VALUES ()`.
+fn defaults(table: String, rows: usize, r: &Renderer) -> Result<String, SQLError> {
+    match (r.dialect, rows) {
+        (Dialect::MySql, 0 | 1) => Ok(format!("INSERT INTO {} () VALUES ()\n", table)),
+        (_, 0 | 1) => Ok(format!("INSERT INTO {} DEFAULT VALUES\n", table)),
+        _ => Err(SQLError::Unsupported(
+            "several rows without columns".to_owned(),
+        )),
+    }
+}
@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use crate::{unrendered, Dialect, Expr, Filter, Renderer, SQLError, ToSql};
+
+/// A span of time, to the microsecond, written as an interval literal:
+/// `INTERVAL '90 seconds'`, or `INTERVAL 90 SECOND` on MySQL. SQLite and
+/// SQL Server have no interval literals, though `Expr::newer_than` and
+/// `Expr::older_than` compare with one in every dialect.
+///
+/// `std::time::Duration` and, with the `chrono` feature,
+/// `chrono::Duration` write the same literals:
+///
+/// ```
+/// # use traits::*;
+/// use std::time::Duration;
+///
+/// let mut q = SQLable::new("jobs");
+/// q.filter(vec![Box::new(SQLFilter { column: "timeout".into(), filter: Duration::from_millis(1500), cmp: SQLComp::GT })]);
+/// assert_eq!(q.prepare(), "SELECT\n  *\nFROM jobs\nWHERE\n  (timeout > INTERVAL '1500000 microseconds')\n");
+///
+/// q.filter(vec![Box::new(SQLFilter { column: "timeout".into(), filter: Duration::from_secs(90), cmp: SQLComp::GT })]);
+/// q.dialect(Dialect::MySql);
+/// assert_eq!(q.prepare(), "SELECT\n  *\nFROM jobs\nWHERE\n  (timeout > INTERVAL 90 SECOND)\n");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval {
+    micros: i128,
+}
+
+impl Interval {
+    pub fn from_micros(micros: i128) -> Self {
+        Interval { micros }
+    }
+
+    pub fn micros(&self) -> i128 {
+        self.micros
+    }
+
+    /// The amount and unit the interval is written in: seconds if whole,
+    /// microseconds otherwise.
+    fn amount(&self) -> (i128, &'static str) {
+        match self.micros % 1_000_000 {
+            0 => (self.micros / 1_000_000, "second"),
+            _ => (self.micros, "microsecond"),
+        }
+    }
+
+    fn render_for(&self, dialect: Dialect) -> Result<String, SQLError> {
+        let (n, unit) = self.amount();
+        match dialect {
+            Dialect::Generic | Dialect::Postgres => Ok(format!("INTERVAL '{} {}s'", n, unit)),
+            Dialect::MySql => Ok(format!("INTERVAL {} {}", n, unit.to_uppercase())),
+            Dialect::Sqlite | Dialect::MsSql => Err(SQLError::Unsupported(format!(
+                "interval literals in {:?}",
+                dialect
+            ))),
+        }
+    }
+}
+
+impl From<Duration> for Interval {
+    fn from(d: Duration) -> Self {
+        Interval::from_micros(d.as_micros() as i128)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::Duration> for Interval {
+    fn from(d: chrono::Duration) -> Self {
+        let micros = match d.num_microseconds() {
+            Some(micros) => micros as i128,
+            // beyond i64 microseconds, whole milliseconds are exact enough
+            None => d.num_milliseconds() as i128 * 1000,
+        };
+        Interval::from_micros(micros)
+    }
+}
+
+impl ToSql for Interval {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        let (n, unit) = self.amount();
+        write!(out, "INTERVAL '{} {}s'", n, unit)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.render_for(r.dialect)
+    }
+}
+
+impl ToSql for Duration {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        Interval::from(*self).write_sql(out)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Interval::from(*self).render(r)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToSql for chrono::Duration {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        Interval::from(*self).write_sql(out)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Interval::from(*self).render(r)
+    }
+}
+
+impl Expr {
+    /// A filter keeping rows whose timestamp is less than `age` old:
+    /// `created_at > CURRENT_TIMESTAMP - INTERVAL '3600 seconds'`.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut q = SQLable::new("events");
+    /// q.filter(vec![Box::new(col("createdAt").newer_than(Duration::from_secs(3600)))]);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM events\nWHERE\n  (created_at > CURRENT_TIMESTAMP - INTERVAL '3600 seconds')\n"
+    /// );
+    ///
+    /// q.dialect(Dialect::Sqlite);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM events\nWHERE\n  (created_at > datetime('now', '-3600 seconds'))\n"
+    /// );
+    /// ```
+    pub fn newer_than(self, age: impl Into<Interval>) -> AgeFilter {
+        AgeFilter {
+            expr: self,
+            age: age.into(),
+            newer: true,
+        }
+    }
+
+    /// A filter keeping rows whose timestamp is more than `age` old, the
+    /// converse of `newer_than`.
+    pub fn older_than(self, age: impl Into<Interval>) -> AgeFilter {
+        AgeFilter {
+            expr: self,
+            age: age.into(),
+            newer: false,
+        }
+    }
+}
+
+/// Compares a timestamp with the current time less an interval, see
+/// `Expr::newer_than`.
+pub struct AgeFilter {
+    pub expr: Expr,
+    pub age: Interval,
+    /// Keeps the timestamps after the cutoff, otherwise those before.
+    pub newer: bool,
+}
+
+impl AgeFilter {
+    /// The current time less the age, in the dialect rendered.
+    fn cutoff(&self, dialect: Dialect) -> Result<String, SQLError> {
+        let (n, unit) = self.age.amount();
+        match dialect {
+            Dialect::Generic | Dialect::Postgres | Dialect::MySql => Ok(format!(
+                "CURRENT_TIMESTAMP - {}",
+                self.age.render_for(dialect)?
+            )),
+            Dialect::Sqlite => {
+                let sign = if n < 0 { "+" } else { "-" };
+                let n = n.abs();
+                let seconds = match unit {
+                    "second" => n.to_string(),
+                    _ => format!("{}.{:06}", n / 1_000_000, n % 1_000_000),
+                };
+                Ok(format!("datetime('now', '{}{} seconds')", sign, seconds))
+            }
+            Dialect::MsSql => {
+                // DATEADD takes an int, too small for microseconds past half
+                // an hour; SYSDATETIME is a datetime2, which keeps them
+                let micros = self.age.micros();
+                let seconds = i32::try_from(micros / 1_000_000).map_err(|_| {
+                    SQLError::Unsupported(format!("an interval of {} {}s in MsSql", n, unit))
+                })?;
+                let cutoff = format!("DATEADD(second, {}, SYSDATETIME())", -(seconds as i64));
+                Ok(match micros % 1_000_000 {
+                    0 => cutoff,
+                    fraction => format!("DATEADD(microsecond, {}, {})", -fraction, cutoff),
+                })
+            }
+        }
+    }
+}
+
+impl Filter for AgeFilter {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let op = if self.newer { ">" } else { "<" };
+        Ok(format!(
+            "{} {} {}",
+            self.expr.render(r)?,
+            op,
+            self.cutoff(r.dialect)?
+        ))
+    }
+}
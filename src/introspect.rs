@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+use crate::{ColumnDef, Dialect, ExecError, Execute, NamingStrategy, Schema, TableDef, TableName};
+
+impl Schema {
+    /// Reads the tables and columns of the database `conn` is connected
+    /// to, so that queries are validated against the real database:
+    ///
+    /// ```no_run
+    /// # use traits::*;
+    /// # fn run<C: Execute>(conn: &C) -> Result<(), ExecError<C::Error>> {
+    /// let schema = Schema::introspect(conn, Dialect::Postgres)?;
+    /// let mut q = SQLable::new("users");
+    /// q.expand_star(&schema)?;
+    /// q.validate(&schema)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Columns are read from `information_schema.columns`, leaving out the
+    /// system schemas, and on SQLite from `sqlite_master` and
+    /// `pragma_table_info`. MySQL reads the current database only, and
+    /// names its tables without a schema, as does SQLite.
+    ///
+    /// The columns have the types the database reports, such as
+    /// `character varying` on Postgres, and keep their names as given.
+    pub fn introspect<C: Execute + ?Sized>(
+        conn: &C,
+        dialect: Dialect,
+    ) -> Result<Schema, ExecError<C::Error>> {
+        let mut tables: Vec<TableDef> = Vec::new();
+        conn.query(&columns_query(dialect), &[], &mut |row| {
+            let table = TableName {
+                schema: row.get_at::<Option<String>>(0)?,
+                name: row.get_at(1)?,
+                alias: None,
+                raw: false,
+            };
+            let mut column = ColumnDef::new(&row.get_at::<String>(2)?, intern(row.get_at(3)?));
+            column.nullable = row.get_at(4)?;
+
+            match tables.last_mut() {
+                Some(def) if def.table == table => def.columns.push(column),
+                _ => tables.push(
+                    TableDef::from_table(table)
+                        .naming(NamingStrategy::AsIs)
+                        .column(column),
+                ),
+            }
+            Ok(())
+        })?;
+
+        Ok(tables.into_iter().fold(Schema::new(), Schema::table))
+    }
+}
+
+/// Schema, table, column name, type and nullability of every column,
+/// ordered by table and then position.
+fn columns_query(dialect: Dialect) -> String {
+    let (schema, filter) = match dialect {
+        Dialect::Sqlite => {
+            return "SELECT NULL, m.name, p.name, p.type, p.\"notnull\" = 0\nFROM sqlite_master AS m\nJOIN pragma_table_info(m.name) AS p\nWHERE m.type = 'table'\n  AND m.name NOT LIKE 'sqlite_%'\nORDER BY\n  m.name,\n  p.cid\n".to_owned();
+        }
+        Dialect::MySql => ("NULL", "table_schema = DATABASE()"),
+        Dialect::MsSql => (
+            "table_schema",
+            "table_schema NOT IN ('INFORMATION_SCHEMA', 'sys')",
+        ),
+        Dialect::Postgres | Dialect::Generic => (
+            "table_schema",
+            "table_schema NOT IN ('pg_catalog', 'information_schema')",
+        ),
+    };
+    format!(
+        "SELECT {}, table_name, column_name, data_type, CASE WHEN is_nullable = 'YES' THEN 1 ELSE 0 END\nFROM information_schema.columns\nWHERE {}\nORDER BY\n  table_schema,\n  table_name,\n  ordinal_position\n",
+        schema, filter
+    )
+}
+
+/// `ty` as a `&'static str`, as `ColumnDef` keeps types. A database has
+/// few distinct type names, each kept once for the life of the program.
+fn intern(ty: String) -> &'static str {
+    static TYPES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut types = TYPES
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match types.get(ty.as_str()) {
+        Some(ty) => ty,
+        None => {
+            let ty: &'static str = Box::leak(ty.into_boxed_str());
+            types.insert(ty);
+            ty
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{SqlValue, TableName};
+
+/// The rows of a table a write may change, for invalidating the cached
+/// reads of those rows only rather than of the whole table. Passed to
+/// `Observer::touched`.
+///
+/// ```
+/// # use traits::*;
+/// let users = TableDef::new("users")
+///     .column(ColumnDef::of::<i64>("id").not_null())
+///     .column(ColumnDef::of::<String>("name"))
+///     .primary_key(&["id"]);
+/// let mut insert = users.insert();
+/// insert.row(vec![7.into(), "alice".into()]);
+///
+/// let touched = insert.touched();
+/// assert_eq!(touched.table.name, "users");
+/// assert_eq!(
+///     touched.rows,
+///     KeyRange::Keys {
+///         columns: vec!["id".to_owned()],
+///         values: vec![vec![SqlValue::Int(7)]],
+///     }
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Touched {
+    pub table: TableName,
+    pub rows: KeyRange,
+}
+
+/// Which rows of a table, by key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyRange {
+    /// The rows whose key columns, named as in the database, hold one of
+    /// these tuples of values.
+    Keys {
+        columns: Vec<String>,
+        values: Vec<Vec<SqlValue>>,
+    },
+    /// Any row: the key is unknown, or computed by the database.
+    All,
+}
+
+impl KeyRange {
+    /// Whether the range holds the row with `key`, given in the order of
+    /// the key columns.
+    pub fn contains(&self, key: &[SqlValue]) -> bool {
+        match self {
+            KeyRange::Keys { values, .. } => values.iter().any(|v| v == key),
+            KeyRange::All => true,
+        }
+    }
+}
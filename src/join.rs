@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+
+use crate::{Renderer, SQLError, SQLable, Schema, TableDef, TableName};
+
+/// A `JOIN` of a query: `JOIN table ON a = b AND ...`, each pair of
+/// columns qualified, see `SQLable::join`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Join {
+    pub table: TableName,
+    pub on: Vec<(String, String)>,
+}
+
+impl Join {
+    pub(crate) fn render(&self, r: &Renderer) -> String {
+        let on: Vec<String> = self
+            .on
+            .iter()
+            .map(|(a, b)| format!("{} = {}", r.column(a), r.column(b)))
+            .collect();
+        format!("JOIN {} ON {}\n", self.table.render(r), on.join(" AND "))
+    }
+}
+
+impl SQLable {
+    /// Joins `table` on pairs of equal columns, qualified as the query
+    /// qualifies them:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("orders o");
+    /// q.join("customers c", &[("o.customerId", "c.id")]);
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM orders AS o\nJOIN customers AS c ON o.customer_id = c.id\n");
+    /// ```
+    pub fn join(&mut self, table: &str, on: &[(&str, &str)]) -> &mut Self {
+        self.joins.push(Join {
+            table: TableName::parse(table),
+            on: on
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect(),
+        });
+        self
+    }
+
+    /// Joins the tables that lead from `from`, which the query reads, to
+    /// `to` over the foreign keys of `schema`, following the fewest keys
+    /// either way. Tables the query already reads are not joined again.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let schema = Schema::new()
+    ///     .table(TableDef::new("regions").column(ColumnDef::of::<i64>("id")))
+    ///     .table(
+    ///         TableDef::new("customers")
+    ///             .column(ColumnDef::of::<i64>("id"))
+    ///             .column(ColumnDef::of::<i64>("regionId"))
+    ///             .foreign_key(ForeignKey::new("customers_region", &["regionId"], "regions", &["id"])),
+    ///     )
+    ///     .table(
+    ///         TableDef::new("orders")
+    ///             .column(ColumnDef::of::<i64>("id"))
+    ///             .column(ColumnDef::of::<i64>("customerId"))
+    ///             .foreign_key(ForeignKey::new("orders_customer", &["customerId"], "customers", &["id"])),
+    ///     );
+    ///
+    /// let mut q = SQLable::new("orders o");
+    /// q.join_path(&schema, "orders", "regions").unwrap();
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM orders AS o\nJOIN customers ON o.customer_id = customers.id\nJOIN regions ON customers.region_id = regions.id\n"
+    /// );
+    /// ```
+    ///
+    /// Fails if either table is missing from `schema`, if the query does
+    /// not read `from`, and with `SQLError::NoJoinPath` if no foreign keys
+    /// lead from one to the other.
+    pub fn join_path(
+        &mut self,
+        schema: &Schema,
+        from: &str,
+        to: &str,
+    ) -> Result<&mut Self, SQLError> {
+        let tables = schema.tables();
+        let index = |name: &TableName| -> Result<usize, SQLError> {
+            let def = schema.get(name)?;
+            Ok(tables
+                .iter()
+                .position(|t| std::ptr::eq(t, def))
+                .unwrap_or(0))
+        };
+        let start = index(&TableName::parse(from))?;
+        let end = index(&TableName::parse(to))?;
+
+        // the tables the query reads, by index, with their qualifiers
+        let mut read: Vec<(usize, String)> = Vec::new();
+        let read_tables = std::iter::once(&*self.table).chain(self.joins.iter().map(|j| &j.table));
+        for table in read_tables {
+            if let Ok(idx) = index(table) {
+                read.push((idx, table.qualifier().to_owned()));
+            }
+        }
+        if !read.iter().any(|(idx, _)| *idx == start) {
+            return Err(SQLError::Unsupported(format!(
+                "joining from {}, which the query does not read",
+                from
+            )));
+        }
+
+        let path = shortest_path(schema, start, end).ok_or_else(|| SQLError::NoJoinPath {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        })?;
+        for (a, b, pairs) in path {
+            if read.iter().any(|(idx, _)| *idx == b) {
+                continue;
+            }
+            let qualifier = |idx: usize| {
+                read.iter()
+                    .find(|(i, _)| *i == idx)
+                    .map(|(_, q)| q.clone())
+                    .unwrap_or_else(|| tables[idx].table.qualifier().to_owned())
+            };
+            let (qa, qb) = (qualifier(a), qualifier(b));
+            self.joins.push(Join {
+                table: tables[b].table.clone(),
+                on: pairs
+                    .iter()
+                    .map(|(ca, cb)| (format!("{}.{}", qa, ca), format!("{}.{}", qb, cb)))
+                    .collect(),
+            });
+            read.push((b, qb));
+        }
+        Ok(self)
+    }
+}
+
+/// A step of a join path: from one table of a schema to another, by
+/// index, with the columns of the first equal to those of the second.
+type Step = (usize, usize, Vec<(String, String)>);
+
+/// The steps from table `start` to table `end` of `schema`, over the
+/// fewest foreign keys.
+fn shortest_path(schema: &Schema, start: usize, end: usize) -> Option<Vec<Step>> {
+    let tables = schema.tables();
+    let mut came_from: Vec<Option<Step>> = vec![None; tables.len()];
+    let mut queue = VecDeque::from([start]);
+    let mut seen = vec![false; tables.len()];
+    seen[start] = true;
+    while let Some(at) = queue.pop_front() {
+        if at == end {
+            break;
+        }
+        for (next, pairs) in neighbours(schema, at) {
+            if !seen[next] {
+                seen[next] = true;
+                came_from[next] = Some((at, next, pairs));
+                queue.push_back(next);
+            }
+        }
+    }
+    if !seen[end] {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let mut at = end;
+    while let Some(step) = came_from[at].take() {
+        at = step.0;
+        path.push(step);
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// The tables a foreign key links to table `at` of `schema`, either way,
+/// with the columns of `at` and those they equal.
+fn neighbours(schema: &Schema, at: usize) -> Vec<(usize, Vec<(String, String)>)> {
+    let tables = schema.tables();
+    let index = |def: &TableDef| tables.iter().position(|t| std::ptr::eq(t, def));
+    let mut result = Vec::new();
+    for (idx, def) in tables.iter().enumerate() {
+        for fk in &def.all_foreign_keys() {
+            let Some(parent) = schema.get(&fk.table).ok().and_then(index) else {
+                continue;
+            };
+            let pairs = fk
+                .columns
+                .iter()
+                .cloned()
+                .zip(fk.references.iter().cloned());
+            if idx == at {
+                result.push((parent, pairs.collect()));
+            } else if parent == at {
+                result.push((idx, pairs.map(|(c, r)| (r, c)).collect()));
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,253 @@
+use crate::{unrendered, Dialect, Expr, Filter, Renderer, SQLError, SqlValue, ToSql};
+
+/// A step into a JSON document: the field of an object or the element of
+/// an array, see `Expr::json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonKey {
+    Field(String),
+    Index(usize),
+}
+
+impl From<&str> for JsonKey {
+    fn from(field: &str) -> Self {
+        JsonKey::Field(field.to_owned())
+    }
+}
+
+impl From<String> for JsonKey {
+    fn from(field: String) -> Self {
+        JsonKey::Field(field)
+    }
+}
+
+impl From<usize> for JsonKey {
+    fn from(index: usize) -> Self {
+        JsonKey::Index(index)
+    }
+}
+
+/// The JSON path of `keys`, `$.a[0]`, a field that is no identifier in
+/// double quotes, as a string literal of the dialect.
+fn json_path(keys: &[JsonKey], r: &Renderer) -> String {
+    let mut path = String::from("$");
+    for key in keys {
+        match key {
+            JsonKey::Index(i) => path.push_str(&format!("[{}]", i)),
+            JsonKey::Field(f)
+                if !f.is_empty()
+                    && !f.starts_with(|c: char| c.is_ascii_digit())
+                    && f.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                path.push_str(&format!(".{}", f))
+            }
+            JsonKey::Field(f) => path.push_str(&format!(
+                ".\"{}\"",
+                f.replace('\\', "\\\\").replace('"', "\\\"")
+            )),
+        }
+    }
+    r.literal(&path)
+}
+
+impl Expr {
+    /// The JSON value at `key` of the JSON document: `data->'key'` on
+    /// Postgres, `JSON_EXTRACT(data, '$.key')` on MySQL and SQLite,
+    /// `JSON_QUERY(data, '$.key')` on SQL Server. Calls chain into nested
+    /// documents.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("orders");
+    /// q.filter(vec![Box::new(col("data").json("customer").json_text("status").is(SQLComp::EQ, "active"))]);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM orders\nWHERE\n  (data->'customer'->>'status' = 'active')\n"
+    /// );
+    ///
+    /// q.dialect(Dialect::MySql);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM orders\nWHERE\n  (JSON_UNQUOTE(JSON_EXTRACT(data, '$.customer.status')) = 'active')\n"
+    /// );
+    /// ```
+    pub fn json(self, key: impl Into<JsonKey>) -> Self {
+        self.json_step(key.into(), false)
+    }
+
+    /// The value at `key` of the JSON document as text, a string without
+    /// its quotes: `data->>'key'` on Postgres,
+    /// `JSON_UNQUOTE(JSON_EXTRACT(data, '$.key'))` on MySQL,
+    /// `JSON_EXTRACT(data, '$.key')` on SQLite and
+    /// `JSON_VALUE(data, '$.key')` on SQL Server.
+    pub fn json_text(self, key: impl Into<JsonKey>) -> Self {
+        self.json_step(key.into(), true)
+    }
+
+    fn json_step(self, key: JsonKey, text: bool) -> Self {
+        match self {
+            Expr::Json {
+                expr,
+                mut path,
+                text: false,
+            } => {
+                path.push(key);
+                Expr::Json { expr, path, text }
+            }
+            expr => Expr::Json {
+                expr: Box::new(expr),
+                path: vec![key],
+                text,
+            },
+        }
+    }
+
+    /// A filter keeping rows whose JSON document contains `json`:
+    /// `data @> '{"a":1}'` on Postgres, `JSON_CONTAINS(data, '{"a":1}')` on
+    /// MySQL. Unsupported elsewhere.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("events");
+    /// q.dialect(Dialect::Postgres).filter(vec![
+    ///     Box::new(col("payload").json_contains(r#"{"kind":"click"}"#)),
+    ///     Box::new(col("payload").has_key("user")),
+    /// ]);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM events\nWHERE\n  (payload @> '{\"kind\":\"click\"}')\n  AND (payload ? 'user')\n"
+    /// );
+    /// ```
+    pub fn json_contains(self, json: &str) -> JsonFilter {
+        JsonFilter::Contains(self, json.to_owned())
+    }
+
+    /// A filter keeping rows whose JSON object has the field `key`:
+    /// `data ? 'key'` on Postgres, `JSON_CONTAINS_PATH(data, 'one',
+    /// '$.key')` on MySQL, `JSON_TYPE(data, '$.key') IS NOT NULL` on SQLite
+    /// and `JSON_PATH_EXISTS(data, '$.key') = 1` on SQL Server. Unsupported
+    /// in the generic dialect, whose placeholders are `?` too.
+    pub fn has_key(self, key: &str) -> JsonFilter {
+        JsonFilter::HasKey(self, key.to_owned())
+    }
+
+    pub(crate) fn render_json(
+        expr: &Expr,
+        path: &[JsonKey],
+        text: bool,
+        r: &mut Renderer,
+    ) -> Result<String, SQLError> {
+        match r.dialect {
+            Dialect::Generic | Dialect::Postgres => {
+                let mut sql = expr.json_operand(r)?;
+                for (i, key) in path.iter().enumerate() {
+                    let op = if text && i == path.len() - 1 {
+                        "->>"
+                    } else {
+                        "->"
+                    };
+                    match key {
+                        JsonKey::Field(f) => sql.push_str(&format!("{}{}", op, r.literal(f))),
+                        JsonKey::Index(n) => sql.push_str(&format!("{}{}", op, n)),
+                    }
+                }
+                Ok(sql)
+            }
+            Dialect::MySql => {
+                let sql = format!("JSON_EXTRACT({}, {})", expr.render(r)?, json_path(path, r));
+                match text {
+                    true => Ok(format!("JSON_UNQUOTE({})", sql)),
+                    false => Ok(sql),
+                }
+            }
+            Dialect::Sqlite => Ok(format!(
+                "JSON_EXTRACT({}, {})",
+                expr.render(r)?,
+                json_path(path, r)
+            )),
+            Dialect::MsSql => {
+                let function = if text { "JSON_VALUE" } else { "JSON_QUERY" };
+                Ok(format!(
+                    "{}({}, {})",
+                    function,
+                    expr.render(r)?,
+                    json_path(path, r)
+                ))
+            }
+        }
+    }
+
+    /// Renders the expression as the left operand of a Postgres JSON
+    /// operator, which binds looser than arithmetic.
+    fn json_operand(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let sql = self.render(r)?;
+        match self {
+            Expr::Column(_) | Expr::Value(_) | Expr::Function { .. } | Expr::Case(_) => Ok(sql),
+            _ => Ok(format!("({})", sql)),
+        }
+    }
+}
+
+/// A filter on a JSON document, see `Expr::json_contains` and
+/// `Expr::has_key`.
+#[derive(Clone, Debug)]
+pub enum JsonFilter {
+    Contains(Expr, String),
+    HasKey(Expr, String),
+}
+
+impl Filter for JsonFilter {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        match self {
+            JsonFilter::Contains(expr, json) => {
+                let (lhs, function) = match r.dialect {
+                    Dialect::Generic | Dialect::Postgres => (expr.json_operand(r)?, false),
+                    Dialect::MySql => (expr.render(r)?, true),
+                    dialect => {
+                        return Err(SQLError::Unsupported(format!(
+                            "JSON containment in {:?}",
+                            dialect
+                        )))
+                    }
+                };
+                let json = SqlValue::Text(json.clone()).render(r)?;
+                match function {
+                    true => Ok(format!("JSON_CONTAINS({}, {})", lhs, json)),
+                    false => Ok(format!("{} @> {}", lhs, json)),
+                }
+            }
+            JsonFilter::HasKey(expr, key) => {
+                let path = json_path(&[JsonKey::Field(key.clone())], r);
+                match r.dialect {
+                    Dialect::Postgres => {
+                        Ok(format!("{} ? {}", expr.json_operand(r)?, r.literal(key)))
+                    }
+                    Dialect::MySql => Ok(format!(
+                        "JSON_CONTAINS_PATH({}, 'one', {})",
+                        expr.render(r)?,
+                        path
+                    )),
+                    Dialect::Sqlite => Ok(format!(
+                        "JSON_TYPE({}, {}) IS NOT NULL",
+                        expr.render(r)?,
+                        path
+                    )),
+                    Dialect::MsSql => Ok(format!(
+                        "JSON_PATH_EXISTS({}, {}) = 1",
+                        expr.render(r)?,
+                        path
+                    )),
+                    Dialect::Generic => Err(SQLError::Unsupported(
+                        "JSON key tests in Generic, use Postgres".to_owned(),
+                    )),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,2398 @@
+mod advise;
+mod array;
+mod ast;
+mod batch;
+mod block;
+mod budget;
+mod cache;
+mod call;
+mod cast;
+mod checkpoint;
+#[cfg(all(doctest, feature = "macros"))]
+mod compile_fail;
+mod db_error;
+#[cfg(feature = "serde")]
+mod definition;
+mod dialect;
+mod diff;
+mod equivalence;
+mod estimate;
+mod exec;
+mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod float;
+mod function;
+mod grant;
+pub mod inflection;
+mod insert;
+mod interval;
+mod introspect;
+mod invalidation;
+mod join;
+mod json;
+mod lint;
+mod logic;
+mod maintenance;
+mod matcher;
+mod migrations;
+mod naming;
+mod net;
+mod observer;
+mod paginate;
+mod param;
+#[cfg(feature = "chrono")]
+mod partition;
+mod parts;
+mod plan;
+mod polymorphic;
+mod query_string;
+mod raw;
+mod relation;
+mod retry;
+#[cfg(feature = "serde")]
+mod rule;
+mod schema;
+mod script;
+mod server;
+mod shared;
+mod size;
+mod snapshot;
+#[cfg(feature = "serde")]
+mod spec;
+mod table;
+mod text_search;
+mod uuid;
+mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod window;
+
+pub use advise::Advice;
+pub use array::Array;
+use array::{array_operand, array_sql};
+pub use ast::{Distinct, QueryVisitor, Select, Source};
+pub use batch::LookupBatch;
+pub use block::Block;
+pub use budget::QueryBudget;
+use cache::Slots;
+pub use cache::StatementCache;
+pub use call::{Call, CallArg};
+pub use cast::{cast, CastSyntax, CastType, SqlType};
+pub use checkpoint::Checkpoint;
+pub use db_error::DbErrorKind;
+#[cfg(feature = "serde")]
+pub use definition::{FilterDef, OrderDef, QueryCatalog, QueryDef};
+pub use dialect::{is_reserved, reserved_words, Dialect, LongNames, Quoting};
+pub use diff::{diff_schema, SchemaChange, SchemaDiff, TableDiff};
+pub use exec::{AsyncExecute, ExecError, Execute, FromRow, FromValue, Record, Row, ToRow};
+pub use expr::{
+    avg, case, coalesce, col, count, count_distinct, count_star, func, lit, lower, max, min,
+    nullif, sum, trim, upper, BinOp, Case, Expr, ExprFilter,
+};
+pub use float::{FloatFormat, NonFinite};
+pub use function::FunctionDef;
+pub use grant::{grant_role, revoke_role, Grant, Privilege};
+use inflection::snake_case;
+pub use insert::{Insert, InsertValue};
+pub use interval::{AgeFilter, Interval};
+pub use invalidation::{KeyRange, Touched};
+pub use join::Join;
+pub use json::{JsonFilter, JsonKey};
+pub use lint::Lint;
+pub use logic::{And, Labeled, Not, Or};
+pub use maintenance::Maintenance;
+pub use matcher::Predicate;
+pub use migrations::{Migration, Migrations};
+pub use naming::NamingStrategy;
+pub use observer::Observer;
+pub use paginate::{CursorSigner, Links, Paginator};
+pub use param::{param, Param};
+#[cfg(feature = "chrono")]
+pub use partition::{Bucket, Partitioning};
+pub use parts::SqlPart;
+pub use plan::{PlanChange, QueryPlans};
+pub use polymorphic::Polymorphic;
+pub use query_string::QueryParams;
+pub use raw::{raw, Raw};
+pub use relation::{Relation, WithChildren};
+pub use retry::{RetryError, RetryPolicy};
+#[cfg(feature = "serde")]
+pub use rule::{Field, Rule, RuleSet};
+pub use schema::{ColumnDef, EnumType, ForeignKey, OnDelete, SqlEnum, Storage, Table, TableDef};
+pub use script::Script;
+pub use server::{Feature, Server};
+use shared::SharedVec;
+pub use snapshot::Snapshot;
+#[cfg(feature = "serde")]
+pub use spec::FilterSpec;
+pub use table::TableName;
+pub use text_search::TextMatch;
+#[cfg(feature = "macros")]
+pub use traits_macros::{raw_sql, FromRow, SqlEnum, Table, ToRow};
+pub use uuid::{Uuid, UuidFormat};
+pub use validate::Schema;
+pub use window::{dense_rank, lag, lead, rank, row_number, Window};
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub trait Numeric {
+    fn to_value(&self) -> SqlValue;
+}
+
+macro_rules! numeric {
+    ($variant:ident: $($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                fn to_value(&self) -> SqlValue {
+                    SqlValue::$variant(*self as _)
+                }
+            }
+
+            impl From<$t> for SqlValue {
+                fn from(v: $t) -> Self {
+                    SqlValue::$variant(v as _)
+                }
+            }
+        )*
+    };
+}
+
+numeric!(Float: f64, f32);
+numeric!(Int: i64, i32, i16, i8, isize);
+numeric!(UInt: u64, u32, u16, u8, usize);
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                    write!(out, "{}", self)
+                }
+
+                fn to_value(&self) -> Option<SqlValue> {
+                    Some(Numeric::to_value(self))
+                }
+            }
+        )*
+    };
+}
+
+integer!(i64, i32, i16, i8, isize, u64, u32, u16, u8, usize);
+
+fn quote(x: &str) -> String {
+    let mut sql = String::with_capacity(x.len() + 2);
+    // writing to a String does not fail
+    let _ = write_quoted(x, &mut sql);
+    sql
+}
+
+/// What the infallible renderers (`prepare`, `to_sql`, `apply_filter`) write in place
+/// of what fails to render: the error between angle brackets, as `Debug`
+/// shows it, which no database parses, so that running the statement
+/// fails with the message instead of the caller panicking. The fallible
+/// renderers return the error itself.
+pub(crate) fn unrendered(e: &SQLError) -> String {
+    let message: String = e
+        .to_string()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || " .,:()_".contains(c) {
+            true => c,
+            false => ' ',
+        })
+        .collect();
+    format!("<{}>", message)
+}
+
+/// Writes `x` as a string literal, doubling the quotes in it.
+fn write_quoted(x: &str, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+    out.write_char('\'')?;
+    for (idx, part) in x.split('\'').enumerate() {
+        if idx > 0 {
+            out.write_str("''")?;
+        }
+        out.write_str(part)?;
+    }
+    out.write_char('\'')
+}
+
+#[derive(Debug)]
+pub enum SQLError {
+    /// A filter compared a column against an empty collection while the
+    /// query was configured with `EmptyList::Error`.
+    EmptyList(String),
+    /// A comparison operator given as text could not be parsed.
+    UnknownOperator(String),
+    /// A dialect name given as text could not be parsed.
+    UnknownDialect(String),
+    /// The construct cannot be expressed in the dialect being rendered.
+    Unsupported(String),
+    /// A query definition refers to a parameter that was not supplied.
+    MissingParam(String),
+    /// A query name is registered twice in a catalog.
+    DuplicateQuery(String),
+    /// A catalog has no query of that name.
+    UnknownQuery(String),
+    /// Rendering the statement would exceed a `QueryBudget` cap.
+    BudgetExceeded(String),
+    /// A raw fragment has a different number of placeholders than values.
+    ParamCount { placeholders: usize, params: usize },
+    /// A filter built from data refers to an unknown column or compares it
+    /// with a value of the wrong type.
+    InvalidFilter(String),
+    /// A value read from the database does not fit the Rust type asked for,
+    /// or the column does not exist.
+    Decode(String),
+    /// Two migrations have the same version.
+    DuplicateMigration(u64),
+    /// The database has a migration applied that is not defined.
+    UnknownMigration(u64),
+    /// A query reads from a table missing from the `Schema` it is
+    /// validated against.
+    UnknownTable(String),
+    /// A query refers to a column its table does not have.
+    UnknownColumn(String),
+    /// A name could refer to more than one table.
+    Ambiguous(String),
+    /// The rendered statement, with its bound values, is larger than the
+    /// server takes; `clause` weighs the most.
+    StatementTooLarge {
+        clause: String,
+        bytes: usize,
+        limit: usize,
+    },
+    /// A pagination cursor is malformed, forged, or from another query.
+    InvalidCursor(String),
+    /// A table name given as text is not `[schema.]table [[AS] alias]`, or
+    /// a name is one the dialect refuses, see `Dialect::check_name`.
+    InvalidName(String),
+    /// No foreign keys lead from one table to the other, see
+    /// `SQLable::join_path`.
+    NoJoinPath { from: String, to: String },
+    /// A float to write inline is NaN or infinite, under
+    /// `NonFinite::Error`.
+    NonFinite(String),
+    /// A call to a function of a `Schema` does not match its
+    /// `FunctionDef`.
+    InvalidCall(String),
+}
+
+impl std::fmt::Display for SQLError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SQLError::EmptyList(col) => write!(f, "empty list used as filter for column {}", col),
+            SQLError::UnknownOperator(op) => write!(f, "unknown comparison operator {}", quote(op)),
+            SQLError::UnknownDialect(d) => write!(f, "unknown SQL dialect {}", quote(d)),
+            SQLError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            SQLError::MissingParam(name) => write!(f, "no value for parameter {}", name),
+            SQLError::DuplicateQuery(name) => write!(f, "query {} is defined twice", name),
+            SQLError::UnknownQuery(name) => write!(f, "no query named {}", name),
+            SQLError::BudgetExceeded(what) => write!(f, "query budget exceeded: {}", what),
+            SQLError::ParamCount {
+                placeholders,
+                params,
+            } => write!(
+                f,
+                "fragment has {} placeholders but {} values",
+                placeholders, params
+            ),
+            SQLError::InvalidFilter(what) => write!(f, "invalid filter: {}", what),
+            SQLError::Decode(what) => write!(f, "cannot decode row: {}", what),
+            SQLError::DuplicateMigration(version) => {
+                write!(f, "migration {} is defined twice", version)
+            }
+            SQLError::UnknownMigration(version) => {
+                write!(f, "migration {} is applied but not defined", version)
+            }
+            SQLError::UnknownTable(table) => write!(f, "unknown table {}", table),
+            SQLError::UnknownColumn(column) => write!(f, "unknown column {}", column),
+            SQLError::Ambiguous(what) => write!(f, "ambiguous reference to {}", what),
+            SQLError::StatementTooLarge {
+                clause,
+                bytes,
+                limit,
+            } => write!(
+                f,
+                "statement of {} bytes exceeds the limit of {} bytes, mostly in {}",
+                bytes, limit, clause
+            ),
+            SQLError::InvalidCursor(what) => write!(f, "invalid cursor: {}", what),
+            SQLError::InvalidName(name) => write!(f, "invalid name {}", quote(name)),
+            SQLError::NoJoinPath { from, to } => {
+                write!(f, "no foreign keys lead from {} to {}", from, to)
+            }
+            SQLError::NonFinite(v) => write!(f, "{} has no SQL literal", v),
+            SQLError::InvalidCall(what) => write!(f, "invalid call: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SQLError {}
+
+/// How a filter against an empty collection (e.g. `vec![]`) is rendered.
+/// Writing `col IN ()` is not valid SQL, so it has to become something else.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyList {
+    /// Replace the condition with a constant: `1=0` for EQ / IN (nothing
+    /// matches) and `1=1` for NEQ / NOT IN (everything matches). Any other
+    /// comparison against nothing is false.
+    #[default]
+    Constant,
+    /// Refuse to render the query: `try_prepare()` returns `SQLError::EmptyList`.
+    Error,
+}
+
+/// The direction of an `ORDER BY` key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Where an `ORDER BY` key puts NULLs, see `SQLable::order_by_nulls`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+fn empty_list_condition(cmp: &SQLComp) -> &'static str {
+    match cmp {
+        SQLComp::NEQ | SQLComp::DISTINCT => "1=1",
+        _ => "1=0",
+    }
+}
+
+/// A value that can be sent to the database as a bound parameter.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum SqlValue {
+    Null,
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Text(String),
+    List(Vec<SqlValue>),
+}
+
+impl From<&str> for SqlValue {
+    fn from(v: &str) -> Self {
+        SqlValue::Text(v.to_owned())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(v: String) -> Self {
+        SqlValue::Text(v)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(SqlValue::Null, Into::into)
+    }
+}
+
+/// The values a column may be compared with, for filters built from
+/// untrusted input, see `QueryParams` and `FilterSpec`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ValueType {
+    Integer,
+    /// Integers or floats.
+    Number,
+    Text,
+    /// Any scalar value.
+    Any,
+}
+
+impl ValueType {
+    /// Whether `value` is a scalar of this type; NULL is not.
+    pub fn accepts(&self, value: &SqlValue) -> bool {
+        match (self, value) {
+            (_, SqlValue::List(_) | SqlValue::Null) => false,
+            (ValueType::Any, _) => true,
+            (ValueType::Integer, SqlValue::Int(_) | SqlValue::UInt(_)) => true,
+            (ValueType::Number, SqlValue::Int(_) | SqlValue::UInt(_) | SqlValue::Float(_)) => true,
+            (ValueType::Text, SqlValue::Text(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Reads a value of this type from text, e.g. a query-string value.
+    /// `Any` reads numbers as numbers and anything else as text.
+    pub fn parse(&self, s: &str) -> Option<SqlValue> {
+        let number = || {
+            s.parse::<i64>()
+                .map(SqlValue::Int)
+                .or_else(|_| s.parse::<u64>().map(SqlValue::UInt))
+                .or_else(|_| s.parse::<f64>().map(SqlValue::Float))
+                .ok()
+                .filter(|v| !matches!(v, SqlValue::Float(f) if !f.is_finite()))
+        };
+        match self {
+            ValueType::Integer => number().filter(|v| self.accepts(v)),
+            ValueType::Number => number(),
+            ValueType::Text => Some(SqlValue::Text(s.to_owned())),
+            ValueType::Any => Some(number().unwrap_or_else(|| SqlValue::Text(s.to_owned()))),
+        }
+    }
+}
+
+/// Rendering state threaded through `Filter::try_apply_filter`.
+///
+/// When parameterized, values are replaced by the dialect's placeholders and
+/// collected in order; otherwise they are inlined as SQL literals.
+#[derive(Clone, Debug, Default)]
+pub struct Renderer {
+    pub dialect: Dialect,
+    pub naming: NamingStrategy,
+    pub quoting: Quoting,
+    pub empty_list: EmptyList,
+    pub cast: CastSyntax,
+    /// The server rendered for, if known: constructs it lacks fail to
+    /// render, see `Feature`.
+    pub server: Option<Server>,
+    /// Binding a value already bound reuses its placeholder, in dialects
+    /// with numbered placeholders, see `SQLable::reuse_params`.
+    pub reuse_params: bool,
+    /// The values of the `param`s rendered.
+    pub values: BTreeMap<String, SqlValue>,
+    /// The largest statement to render, in bytes, in place of the limit
+    /// of the server or dialect, see `SQLable::max_statement_bytes`.
+    pub max_statement_bytes: Option<usize>,
+    pub long_names: LongNames,
+    pub floats: FloatFormat,
+    pub uuids: UuidFormat,
+    params: Option<Vec<SqlValue>>,
+    /// Binds mark the SQL with `parts::MARK` instead of a placeholder.
+    marks: bool,
+    /// Where the bound values come from, for `StatementCache`.
+    slots: Option<Slots>,
+}
+
+impl Renderer {
+    pub fn parameterized() -> Self {
+        Renderer {
+            params: Some(Vec::new()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_parameterized(&self) -> bool {
+        self.params.is_some()
+    }
+
+    /// Fails if the server rendered for lacks `feature`. Without a server,
+    /// or one of another dialect, every feature is assumed.
+    pub(crate) fn require(&self, feature: Feature) -> Result<(), SQLError> {
+        match &self.server {
+            Some(server) if server.dialect == self.dialect => server.require(feature),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the server rendered for has `feature`, assumed as for
+    /// `require`.
+    pub(crate) fn supports(&self, feature: Feature) -> bool {
+        self.require(feature).is_ok()
+    }
+
+    /// `x` as a string literal of the dialect: MySQL also reads backslash
+    /// escapes in strings, so its backslashes are doubled too.
+    pub(crate) fn literal(&self, x: &str) -> String {
+        match self.dialect {
+            Dialect::MySql if x.contains('\\') => quote(&x.replace('\\', "\\\\")),
+            _ => quote(x),
+        }
+    }
+
+    /// Binds a value and returns its placeholder. On an inline renderer the
+    /// value is rendered as a literal instead.
+    pub fn bind(&mut self, value: SqlValue) -> String {
+        let mut result = String::new();
+        self.push_bound(&mut result, value);
+        result
+    }
+
+    /// Like `bind`, appending to `out`.
+    fn push_bound(&mut self, out: &mut String, value: SqlValue) {
+        match self.params.as_mut() {
+            Some(params) => {
+                let numbered = matches!(self.dialect, Dialect::Postgres | Dialect::MsSql);
+                if self.reuse_params && numbered && !self.marks {
+                    if let Some(idx) = params.iter().position(|p| *p == value) {
+                        return self.dialect.push_placeholder(out, idx + 1);
+                    }
+                }
+                if let Some(slots) = self.slots.as_mut() {
+                    slots.record(&value);
+                }
+                params.push(value);
+                if self.marks {
+                    return out.push_str(parts::MARK);
+                }
+                self.dialect.push_placeholder(out, params.len())
+            }
+            None => match &value {
+                SqlValue::Text(x) => out.push_str(&self.literal(x)),
+                _ => {
+                    let _ = value.write_sql(out);
+                }
+            },
+        }
+    }
+
+    /// Renders a table or column name for the dialect and quoting policy.
+    pub fn ident(&self, name: &str) -> String {
+        self.dialect.ident(&self.fit(name), self.quoting)
+    }
+
+    /// Renders a column name: converted by the naming strategy, then quoted.
+    pub fn column(&self, name: &str) -> String {
+        let name = self.naming.apply(name);
+        let name = match self.fit(&name) {
+            Cow::Owned(fit) => fit,
+            Cow::Borrowed(_) => name,
+        };
+        if self.dialect.needs_quotes(&name, self.quoting) {
+            return self.ident(&name);
+        }
+        name
+    }
+
+    /// `part`, cut to the dialect's limit under `LongNames::Truncate`.
+    pub(crate) fn fit_part<'a>(&self, part: &'a str) -> Cow<'a, str> {
+        match self.long_names {
+            LongNames::Truncate => self.dialect.fit_name(part),
+            _ => Cow::Borrowed(part),
+        }
+    }
+
+    /// `name`, with each dotted part cut to the dialect's limit under
+    /// `LongNames::Truncate`.
+    pub(crate) fn fit<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        let long = |part: &str| matches!(self.dialect.fit_name(part), Cow::Owned(_));
+        if self.long_names != LongNames::Truncate || !name.split('.').any(long) {
+            return Cow::Borrowed(name);
+        }
+        let parts: Vec<Cow<str>> = name.split('.').map(|p| self.dialect.fit_name(p)).collect();
+        Cow::Owned(parts.join("."))
+    }
+
+    /// The values bound so far, in placeholder order.
+    pub fn into_params(self) -> Vec<SqlValue> {
+        self.params.unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SQLComp {
+    EQ,
+    NEQ,
+    GT,
+    LT,
+    GEQ,
+    LEQ,
+    /// NULL-safe inequality: `IS DISTINCT FROM`.
+    DISTINCT,
+    /// NULL-safe equality: `IS NOT DISTINCT FROM`.
+    NOTDISTINCT,
+    /// Postgres array overlap: `&&`, see `Array`.
+    OVERLAPS,
+    /// Postgres array containment: `@>`.
+    CONTAINS,
+    /// Postgres array containment the other way: `<@`.
+    CONTAINEDBY,
+}
+
+impl SQLComp {
+    /// The SQL spelling of the operator, which `from_str` parses back.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SQLComp::EQ => "=",
+            SQLComp::NEQ => "<>",
+            SQLComp::GT => ">",
+            SQLComp::LT => "<",
+            SQLComp::GEQ => ">=",
+            SQLComp::LEQ => "<=",
+            SQLComp::DISTINCT => "is distinct from",
+            SQLComp::NOTDISTINCT => "is not distinct from",
+            SQLComp::OVERLAPS => "&&",
+            SQLComp::CONTAINS => "@>",
+            SQLComp::CONTAINEDBY => "<@",
+        }
+    }
+}
+
+impl std::str::FromStr for SQLComp {
+    type Err = SQLError;
+
+    /// Parses either the SQL symbol (`=`, `<>`, `>=`, ...) or the variant
+    /// name (`eq`, `neq`, `geq`, ...), ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "=" | "==" | "eq" => Ok(SQLComp::EQ),
+            "<>" | "!=" | "neq" => Ok(SQLComp::NEQ),
+            ">" | "gt" => Ok(SQLComp::GT),
+            "<" | "lt" => Ok(SQLComp::LT),
+            ">=" | "geq" => Ok(SQLComp::GEQ),
+            "<=" | "leq" => Ok(SQLComp::LEQ),
+            "is distinct from" | "distinct" => Ok(SQLComp::DISTINCT),
+            "is not distinct from" | "notdistinct" | "<=>" => Ok(SQLComp::NOTDISTINCT),
+            "&&" | "overlaps" => Ok(SQLComp::OVERLAPS),
+            "@>" | "contains" => Ok(SQLComp::CONTAINS),
+            "<@" | "containedby" => Ok(SQLComp::CONTAINEDBY),
+            _ => Err(SQLError::UnknownOperator(s.to_owned())),
+        }
+    }
+}
+
+/// A value a filter compares a column with. Values are `Send` and `Sync`
+/// so that queries can be rendered on another thread than the one that
+/// built them. `&T`, `Box<T>` and `Arc<T>` are values when `T` is, so
+/// values owned elsewhere need no copy, and `Box<dyn ToSql>` serves for
+/// filters whose values are only typed at runtime:
+///
+/// ```
+/// # use traits::*;
+/// let values: Vec<(&str, Box<dyn ToSql>)> = vec![("id", Box::new(42)), ("name", Box::new("bob"))];
+/// let filters: Vec<Box<dyn Filter>> = values
+///     .into_iter()
+///     .map(|(column, filter)| Box::new(SQLFilter { column: column.into(), filter, cmp: SQLComp::EQ }) as Box<dyn Filter>)
+///     .collect();
+/// let mut q = SQLable::new("users");
+/// q.filter(filters);
+///
+/// let sql = std::thread::spawn(move || q.prepare()).join().unwrap();
+/// assert_eq!(sql, "SELECT\n  *\nFROM users\nWHERE\n  (id = 42)\n  AND (name = 'bob')\n");
+///
+/// let banned = std::sync::Arc::new(vec![3, 5, 8]);
+/// let filter = SQLFilter { column: "id".into(), filter: banned.clone(), cmp: SQLComp::NEQ };
+/// assert_eq!(filter.apply_filter(), "id NOT IN (3,5,8)");
+/// ```
+pub trait ToSql: Send + Sync {
+    /// The value as an inline SQL literal, collected from `write_sql`.
+    fn to_sql(&self) -> String {
+        let mut sql = String::new();
+        // writing to a String does not fail
+        let _ = self.write_sql(&mut sql);
+        sql
+    }
+
+    /// Writes the value as an inline SQL literal, without building a
+    /// `String` per value.
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result;
+
+    /// The value to bind in parameterized mode. `None` keeps the value
+    /// inlined as `to_sql()` even when rendering with parameters.
+    fn to_value(&self) -> Option<SqlValue> {
+        None
+    }
+
+    /// Renders the value, binding it as a parameter if the renderer asks for it.
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        match self.to_value() {
+            Some(v) if r.is_parameterized() => Ok(r.bind(v)),
+            _ => Ok(self.to_sql()),
+        }
+    }
+
+    /// Whether the value renders as a parenthesised `SELECT`.
+    fn is_subquery(&self) -> bool {
+        false
+    }
+
+    /// Whether the value is a collection with no elements.
+    fn is_empty_list(&self) -> bool {
+        false
+    }
+
+    /// Whether `render` writes more than the value it binds, e.g. a cast,
+    /// so that a list renders each of its elements in turn.
+    fn wraps_value(&self) -> bool {
+        false
+    }
+
+    /// Whether the value is an array, whose list `to_value` is compared
+    /// whole with `=` rather than as `IN`.
+    fn is_array(&self) -> bool {
+        false
+    }
+
+    fn op_eq(&self) -> &'static str {
+        "="
+    }
+    fn op_neq(&self) -> &'static str {
+        "<>"
+    }
+
+    fn op_gt(&self) -> &'static str {
+        ">"
+    }
+
+    fn op_lt(&self) -> &'static str {
+        "<"
+    }
+
+    fn op_geq(&self) -> &'static str {
+        ">="
+    }
+
+    fn op_leq(&self) -> &'static str {
+        "<="
+    }
+
+    fn op_distinct(&self) -> &'static str {
+        "IS DISTINCT FROM"
+    }
+
+    fn op_not_distinct(&self) -> &'static str {
+        "IS NOT DISTINCT FROM"
+    }
+
+    fn op(&self, cmp: &SQLComp) -> &'static str {
+        match cmp {
+            SQLComp::EQ => self.op_eq(),
+            SQLComp::NEQ => self.op_neq(),
+            SQLComp::GT => self.op_gt(),
+            SQLComp::LT => self.op_lt(),
+            SQLComp::GEQ => self.op_geq(),
+            SQLComp::LEQ => self.op_leq(),
+            SQLComp::DISTINCT => self.op_distinct(),
+            SQLComp::NOTDISTINCT => self.op_not_distinct(),
+            SQLComp::OVERLAPS | SQLComp::CONTAINS | SQLComp::CONTAINEDBY => cmp.symbol(),
+        }
+    }
+
+    fn compare(&self, cmp: &SQLComp) -> String {
+        let op = self.op(cmp);
+        let mut result = String::with_capacity(op.len() + 16);
+        result.push_str(op);
+        result.push(' ');
+        match self.to_value() {
+            Some(SqlValue::List(items)) if cmp.is_array() => result.push_str(&array_sql(&items)),
+            _ => {
+                let _ = self.write_sql(&mut result);
+            }
+        }
+        result
+    }
+
+    /// Like `compare`, but renders the operator and value for `r`'s dialect.
+    fn render_compare(&self, cmp: &SQLComp, r: &mut Renderer) -> Result<String, SQLError> {
+        if matches!(cmp, SQLComp::DISTINCT | SQLComp::NOTDISTINCT) {
+            r.require(Feature::IsDistinctFrom)?;
+        }
+        if cmp.is_array() {
+            let value = array_operand(self, cmp, r)?;
+            return Ok(format!("{} {}", cmp.symbol(), value));
+        }
+        let op = match (cmp, r.dialect) {
+            // SQLite's IS / IS NOT are NULL-safe and predate IS DISTINCT FROM
+            (SQLComp::DISTINCT, Dialect::Sqlite) => "IS NOT",
+            (SQLComp::NOTDISTINCT, Dialect::Sqlite) => "IS",
+            // MySQL only has the NULL-safe equality, the filter negates it
+            (SQLComp::DISTINCT | SQLComp::NOTDISTINCT, Dialect::MySql) => "<=>",
+            _ => self.op(cmp),
+        };
+
+        let value = self.render(r)?;
+        let mut result = String::with_capacity(op.len() + 1 + value.len());
+        result.push_str(op);
+        result.push(' ');
+        result.push_str(&value);
+        Ok(result)
+    }
+}
+
+impl ToSql for &str {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write_quoted(self, out)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(r.bind(SqlValue::Text(self.to_string())))
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(SqlValue::Text(self.to_string()))
+    }
+}
+
+impl ToSql for String {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write_quoted(self, out)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(r.bind(SqlValue::Text(self.clone())))
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(SqlValue::Text(self.clone()))
+    }
+}
+
+/*
+==============================================================
+  Ideally we would want to implement this but Rust complains
+--------------------------------------------------------------
+
+impl<T: AsRef<str>> ToSql for T {
+    fn to_sql(&self) -> String {
+        quote(self.as_ref())
+    }
+}
+
+==============================================================
+*/
+
+#[cfg(feature = "chrono")]
+impl<T: chrono::TimeZone> ToSql for chrono::Date<T>
+where
+    T::Offset: std::fmt::Display + Send + Sync,
+{
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "'{}'", self.format("%Y-%m-%d"))
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(SqlValue::Text(self.format("%Y-%m-%d").to_string()))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<T: chrono::TimeZone> ToSql for chrono::DateTime<T>
+where
+    T::Offset: std::fmt::Display + Send + Sync,
+{
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "'{}'", self.format("%Y-%m-%d"))
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(SqlValue::Text(self.format("%Y-%m-%d").to_string()))
+    }
+}
+
+impl<T: ToSql> ToSql for Vec<T> {
+    fn is_empty_list(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn op_eq(&self) -> &'static str {
+        if self.len() > 1 {
+            return "IN";
+        }
+
+        "="
+    }
+
+    fn op_neq(&self) -> &'static str {
+        if self.len() > 1 {
+            return "NOT IN";
+        }
+
+        "<>"
+    }
+
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        if let [v] = &self[..] {
+            return v.write_sql(out);
+        }
+
+        out.write_char('(')?;
+        for (idx, v) in self.iter().enumerate() {
+            if idx > 0 {
+                out.write_char(',')?;
+            }
+            v.write_sql(out)?;
+        }
+        out.write_char(')')
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        self.iter()
+            .map(|x| x.to_value())
+            .collect::<Option<Vec<SqlValue>>>()
+            .map(SqlValue::List)
+    }
+
+    fn wraps_value(&self) -> bool {
+        self.iter().any(|v| v.wraps_value())
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        if let [v] = &self[..] {
+            return v.render(r);
+        }
+        // values render as they bind, written straight into the list
+        if !self.wraps_value() {
+            if let Some(SqlValue::List(values)) = self.to_value() {
+                return render_list(&values, r);
+            }
+        }
+
+        // placeholders and literals are short, `$123,` at most for the former
+        let mut result = String::with_capacity(2 + self.len() * 5);
+        result.push('(');
+        for (idx, v) in self.iter().enumerate() {
+            if idx > 0 {
+                result.push(',');
+            }
+            result.push_str(&v.render(r)?);
+        }
+        result.push(')');
+        Ok(result)
+    }
+}
+
+/*
+=======================================================================
+  Ideally we would like to abstract the case for Vec to any iterable,
+  but Rust complains again. Something like the code below
+-----------------------------------------------------------------------
+
+impl<T> ToSql for T where
+    T: IntoIterator,
+    T::Item: ToSql + std::fmt::Display
+{
+    fn op_eq(&self) -> &'static str {
+        return "IN";
+    }
+
+    fn op_neq(&self) -> &'static str {
+        return "NOT IN";
+    }
+
+    fn to_sql(&self) -> String {
+        let mut v = Vec::new();
+        for val in &self.into_iter() {
+            v.push(val.to_string());
+        }
+        format!("({})", v.join(","))
+    }
+}
+
+*/
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn is_empty_list(&self) -> bool {
+        self.as_ref().is_some_and(|v| v.is_empty_list())
+    }
+
+    fn op_eq(&self) -> &'static str {
+        if self.is_none() {
+            return "IS";
+        }
+
+        "="
+    }
+
+    fn op_neq(&self) -> &'static str {
+        if self.is_none() {
+            return "IS NOT";
+        }
+
+        "<>"
+    }
+
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        match self {
+            Some(v) => v.write_sql(out),
+            None => out.write_str("NULL"),
+        }
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        self.as_ref().map_or(Some(SqlValue::Null), |v| v.to_value())
+    }
+
+    fn wraps_value(&self) -> bool {
+        self.as_ref().is_some_and(|v| v.wraps_value())
+    }
+
+    fn is_array(&self) -> bool {
+        self.as_ref().is_some_and(|v| v.is_array())
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        // NULL stays inline so that `IS NULL` / `IS NOT NULL` keep working
+        self.as_ref().map_or(Ok("NULL".to_owned()), |v| v.render(r))
+    }
+}
+
+macro_rules! forward_to_sql {
+    ($($t:ty),*) => {
+        $(
+            impl<T: ToSql + ?Sized> ToSql for $t {
+                fn to_sql(&self) -> String {
+                    (**self).to_sql()
+                }
+
+                fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                    (**self).write_sql(out)
+                }
+
+                fn to_value(&self) -> Option<SqlValue> {
+                    (**self).to_value()
+                }
+
+                fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+                    (**self).render(r)
+                }
+
+                fn is_subquery(&self) -> bool {
+                    (**self).is_subquery()
+                }
+
+                fn is_empty_list(&self) -> bool {
+                    (**self).is_empty_list()
+                }
+
+                fn wraps_value(&self) -> bool {
+                    (**self).wraps_value()
+                }
+
+                fn is_array(&self) -> bool {
+                    (**self).is_array()
+                }
+
+                fn op_eq(&self) -> &'static str {
+                    (**self).op_eq()
+                }
+
+                fn op_neq(&self) -> &'static str {
+                    (**self).op_neq()
+                }
+
+                fn op_gt(&self) -> &'static str {
+                    (**self).op_gt()
+                }
+
+                fn op_lt(&self) -> &'static str {
+                    (**self).op_lt()
+                }
+
+                fn op_geq(&self) -> &'static str {
+                    (**self).op_geq()
+                }
+
+                fn op_leq(&self) -> &'static str {
+                    (**self).op_leq()
+                }
+
+                fn op_distinct(&self) -> &'static str {
+                    (**self).op_distinct()
+                }
+
+                fn op_not_distinct(&self) -> &'static str {
+                    (**self).op_not_distinct()
+                }
+
+                fn compare(&self, cmp: &SQLComp) -> String {
+                    (**self).compare(cmp)
+                }
+
+                fn render_compare(&self, cmp: &SQLComp, r: &mut Renderer) -> Result<String, SQLError> {
+                    (**self).render_compare(cmp, r)
+                }
+            }
+        )*
+    };
+}
+
+forward_to_sql!(&T, Box<T>, Arc<T>);
+
+/// `SqlValue` mirrors the behaviour of the typed impls: `Null` behaves like
+/// `None` and `List` like a `Vec`, so a `SQLFilter<SqlValue>` can hold values
+/// whose type is only known at runtime.
+impl ToSql for SqlValue {
+    fn is_empty_list(&self) -> bool {
+        matches!(self, SqlValue::List(v) if v.is_empty())
+    }
+
+    fn op_eq(&self) -> &'static str {
+        match self {
+            SqlValue::Null => "IS",
+            SqlValue::List(v) if v.len() > 1 => "IN",
+            _ => "=",
+        }
+    }
+
+    fn op_neq(&self) -> &'static str {
+        match self {
+            SqlValue::Null => "IS NOT",
+            SqlValue::List(v) if v.len() > 1 => "NOT IN",
+            _ => "<>",
+        }
+    }
+
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        match self {
+            SqlValue::Null => out.write_str("NULL"),
+            SqlValue::Int(v) => v.write_sql(out),
+            SqlValue::UInt(v) => v.write_sql(out),
+            SqlValue::Float(v) => v.write_sql(out),
+            SqlValue::Text(v) => v.write_sql(out),
+            SqlValue::List(v) => v.write_sql(out),
+        }
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(self.clone())
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        match self {
+            SqlValue::Null => Ok("NULL".to_owned()),
+            SqlValue::List(v) => match &v[..] {
+                [v] => v.render(r),
+                _ => render_list(v, r),
+            },
+            SqlValue::Float(v) if !r.is_parameterized() => r.float(*v),
+            _ => Ok(r.bind(self.clone())),
+        }
+    }
+}
+
+/// Renders `(a,b,...)`, binding each value but NULL into one buffer.
+fn render_list(values: &[SqlValue], r: &mut Renderer) -> Result<String, SQLError> {
+    let mut result = String::with_capacity(2 + values.len() * 5);
+    result.push('(');
+    for (idx, v) in values.iter().enumerate() {
+        if idx > 0 {
+            result.push(',');
+        }
+        match v {
+            SqlValue::Null => result.push_str("NULL"),
+            SqlValue::List(_) => result.push_str(&v.render(r)?),
+            v if r.is_parameterized() => r.push_bound(&mut result, v.clone()),
+            SqlValue::Float(v) => result.push_str(&r.float(*v)?),
+            v => {
+                let _ = v.write_sql(&mut result);
+            }
+        }
+    }
+    result.push(')');
+    Ok(result)
+}
+
+/// Compares a column against any element of an array or subquery, e.g.
+/// `col = ANY($1)` with the whole array bound as a single parameter.
+///
+/// Arrays are Postgres-only; every dialect accepts a subquery:
+///
+/// ```
+/// # use traits::*;
+/// let mut sub = SQLable::new("admins");
+/// sub.select(vec!["userId".to_owned()]);
+/// let f = SQLFilter { column: "id".into(), filter: Any(sub), cmp: SQLComp::EQ };
+/// ```
+pub struct Any<T: ToSql>(pub T);
+
+/// Compares a column against every element of an array or subquery, e.g.
+/// `col > ALL (SELECT ...)`. See `Any`.
+pub struct All<T: ToSql>(pub T);
+
+fn quantified_sql(v: &dyn ToSql) -> String {
+    if v.is_subquery() {
+        return v.to_sql();
+    }
+    let mut result = String::from("(ARRAY[");
+    let _ = match v.to_value() {
+        Some(SqlValue::List(items)) => items.iter().enumerate().try_for_each(|(idx, x)| {
+            if idx > 0 {
+                result.push(',');
+            }
+            x.write_sql(&mut result)
+        }),
+        _ => v.write_sql(&mut result),
+    };
+    result.push_str("])");
+    result
+}
+
+fn quantified_render(v: &dyn ToSql, r: &mut Renderer) -> Result<String, SQLError> {
+    if v.is_subquery() {
+        return v.render(r);
+    }
+    if r.dialect != Dialect::Postgres {
+        return Err(SQLError::Unsupported(format!(
+            "ANY / ALL over an array in {:?}, use a subquery or an IN list",
+            r.dialect
+        )));
+    }
+
+    match v.to_value() {
+        Some(list @ SqlValue::List(_)) if r.is_parameterized() => Ok(format!("({})", r.bind(list))),
+        // an untyped '{}' literal is coerced to the column's array type,
+        // unlike ARRAY[] which needs an explicit cast
+        Some(SqlValue::List(items)) if items.is_empty() => Ok("('{}')".to_owned()),
+        Some(SqlValue::List(_)) => Ok(quantified_sql(v)),
+        _ => Err(SQLError::Unsupported(
+            "ANY / ALL needs a list or a subquery".to_owned(),
+        )),
+    }
+}
+
+impl<T: ToSql> ToSql for Any<T> {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "ANY {}", quantified_sql(&self.0))
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(format!("ANY {}", quantified_render(&self.0, r)?))
+    }
+}
+
+impl<T: ToSql> ToSql for All<T> {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "ALL {}", quantified_sql(&self.0))
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(format!("ALL {}", quantified_render(&self.0, r)?))
+    }
+}
+
+/// A comparison of a column with a value. The column is a `Cow` so that
+/// names known at compile time are not copied: `column: "age".into()`.
+#[derive(Clone, Debug)]
+pub struct SQLFilter<T: ToSql> {
+    pub column: Cow<'static, str>,
+    pub filter: T,
+    pub cmp: SQLComp,
+}
+
+/// A comparison with a value whose type is only known at runtime, so that
+/// comparisons with values of different types fit in one `Vec`, see
+/// `SQLable::filter_dyn`.
+pub type DynFilter = SQLFilter<Box<dyn ToSql>>;
+
+impl<T: ToSql + 'static> SQLFilter<T> {
+    /// The comparison with its value boxed, see `DynFilter`.
+    pub fn boxed(self) -> DynFilter {
+        SQLFilter {
+            column: self.column,
+            filter: Box::new(self.filter),
+            cmp: self.cmp,
+        }
+    }
+}
+
+pub trait Filter: Send + Sync {
+    fn apply_filter(&self) -> String;
+
+    /// Like `apply_filter`, but honours the options of the query being
+    /// rendered and may refuse to produce a condition.
+    fn try_apply_filter(&self, _r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(self.apply_filter())
+    }
+
+    /// Warnings reported by `SQLable::lint()` for this filter.
+    fn lint(&self) -> Vec<Lint> {
+        vec![]
+    }
+
+    /// The filter evaluated in process, see `SQLable::matcher`. `None` if
+    /// only the database can evaluate it.
+    fn predicate(&self) -> Option<Predicate> {
+        None
+    }
+
+    /// Whether `value` meets the filter, evaluated in process with the
+    /// predicate the SQL expresses, e.g. in tests or to narrow rows already
+    /// fetched:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let adults = SQLFilter { column: "age".into(), filter: 18, cmp: SQLComp::GEQ };
+    /// let user = Record {
+    ///     columns: vec!["name".to_owned(), "age".to_owned()],
+    ///     values: vec!["alice".into(), 30.into()],
+    /// };
+    /// assert!(adults.matches(&user).unwrap());
+    /// ```
+    ///
+    /// Columns are looked up with the default naming strategy, and empty
+    /// lists follow `EmptyList::Constant`. Fails as `SQLable::matcher` does
+    /// if only the database can evaluate the filter.
+    fn matches(&self, value: &dyn ToRow) -> Result<bool, SQLError> {
+        match self.predicate() {
+            Some(predicate) => predicate.matches(value),
+            None => Err(matcher::unsupported(self, &mut Renderer::default())),
+        }
+    }
+
+    /// Where the filter comes from, see `Labeled`.
+    fn origin(&self) -> Option<&str> {
+        None
+    }
+
+    /// The filter as data, for serializing the query it belongs to. `None`
+    /// if it cannot be described by a `FilterDef`.
+    #[cfg(feature = "serde")]
+    fn to_def(&self) -> Option<FilterDef> {
+        None
+    }
+}
+
+impl<T: Filter + ?Sized> Filter for &T {
+    fn apply_filter(&self) -> String {
+        (**self).apply_filter()
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        (**self).try_apply_filter(r)
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        (**self).lint()
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        (**self).predicate()
+    }
+
+    fn origin(&self) -> Option<&str> {
+        (**self).origin()
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_def(&self) -> Option<FilterDef> {
+        (**self).to_def()
+    }
+}
+
+impl<T: ToSql> Filter for SQLFilter<T> {
+    fn apply_filter(&self) -> String {
+        if self.filter.is_empty_list() && !self.cmp.is_array() {
+            return empty_list_condition(&self.cmp).to_owned();
+        }
+
+        let column = Renderer::default().column(&self.column);
+        format!("{} {}", column, &self.filter.compare(&self.cmp))
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        if self.filter.is_empty_list() && !self.cmp.is_array() {
+            if r.empty_list == EmptyList::Error {
+                return Err(SQLError::EmptyList(self.column.to_string()));
+            }
+            return Ok(empty_list_condition(&self.cmp).to_owned());
+        }
+
+        compare_condition(&r.column(&self.column), &self.filter, &self.cmp, r)
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        self.lints()
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        let value = match self.filter.to_value()? {
+            // `IN` the array alone is `=` the array
+            array if self.filter.is_array() && matches!(self.cmp, SQLComp::EQ | SQLComp::NEQ) => {
+                SqlValue::List(vec![array])
+            }
+            value => value,
+        };
+        Some(Predicate::compare(&self.column, self.cmp, value))
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_def(&self) -> Option<FilterDef> {
+        Some(FilterDef {
+            column: self.column.to_string(),
+            op: self.cmp.symbol().to_owned(),
+            value: self.filter.to_value()?,
+            param: None,
+            origin: None,
+        })
+    }
+}
+
+/// A filter held by a query. Comparisons with a `SqlValue` are kept inline,
+/// any other filter behind a pointer shared by the query's copies.
+#[derive(Clone)]
+pub(crate) enum Condition {
+    Value(SQLFilter<SqlValue>),
+    Shared(Arc<dyn Filter>),
+}
+
+impl Condition {
+    fn get(&self) -> &dyn Filter {
+        match self {
+            Condition::Value(f) => f,
+            Condition::Shared(f) => f.as_ref(),
+        }
+    }
+}
+
+/// The condition as it renders, see `SQLable`'s `Debug`.
+impl std::fmt::Debug for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FilterSql(self.get()).fmt(f)
+    }
+}
+
+/// A filter shown as the SQL it renders to without options.
+struct FilterSql<'a>(&'a dyn Filter);
+
+impl std::fmt::Debug for FilterSql<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => f.write_str(&sql)?,
+            Err(e) => write!(f, "<{}>", e)?,
+        }
+        match self.0.origin() {
+            Some(origin) => write!(f, " (from {})", origin),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<Box<dyn Filter>> for Condition {
+    fn from(f: Box<dyn Filter>) -> Self {
+        Condition::Shared(f.into())
+    }
+}
+
+impl Filter for Condition {
+    fn apply_filter(&self) -> String {
+        self.get().apply_filter()
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.get().try_apply_filter(r)
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        self.get().lint()
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        self.get().predicate()
+    }
+
+    fn origin(&self) -> Option<&str> {
+        self.get().origin()
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_def(&self) -> Option<FilterDef> {
+        self.get().to_def()
+    }
+}
+
+/// Renders `lhs <op> value` for `r`'s dialect.
+pub(crate) fn compare_condition<T: ToSql + ?Sized>(
+    lhs: &str,
+    value: &T,
+    cmp: &SQLComp,
+    r: &mut Renderer,
+) -> Result<String, SQLError> {
+    let rhs = value.render_compare(cmp, r)?;
+    let negate = matches!(cmp, SQLComp::DISTINCT) && r.dialect == Dialect::MySql;
+    let mut cond = String::with_capacity(lhs.len() + rhs.len() + 7);
+    if negate {
+        cond.push_str("NOT (");
+    }
+    cond.push_str(lhs);
+    cond.push(' ');
+    cond.push_str(&rhs);
+    if negate {
+        cond.push(')');
+    }
+    Ok(cond)
+}
+
+/// A `SELECT` query, built step by step and rendered with `prepare`.
+///
+/// Copies of a query share its table, select list, filters and other
+/// lists until one of them changes its own, so a base query built once can
+/// be shared between threads and copied per request for the cost of the
+/// parts each request changes:
+///
+/// ```
+/// # use traits::*;
+/// use std::sync::Arc;
+///
+/// let mut base = SQLable::new("orders");
+/// base.select(vec!["id".to_owned(), "total".to_owned()])
+///     .order_by("createdAt", Order::Desc);
+/// let base = Arc::new(base);
+///
+/// let handlers: Vec<_> = (0..4)
+///     .map(|customer| {
+///         let base = Arc::clone(&base);
+///         std::thread::spawn(move || {
+///             // copies the filters only, the rest stays shared
+///             let mut q = SQLable::clone(&base);
+///             q.filter_values(vec![SQLFilter {
+///                 column: "customerId".into(),
+///                 filter: SqlValue::Int(customer),
+///                 cmp: SQLComp::EQ,
+///             }]);
+///             q.limit(20).prepare_params().unwrap()
+///         })
+///     })
+///     .collect();
+/// for handler in handlers {
+///     let (sql, params) = handler.join().unwrap();
+/// }
+/// ```
+///
+/// Filters are shared too, which is why `Filter` and `ToSql` types must be
+/// `Send` and `Sync`.
+#[derive(Clone)]
+pub struct SQLable {
+    table: Arc<TableName>,
+    cols: Arc<Option<Vec<String>>>,
+    exprs: SharedVec<Expr>,
+    distinct: bool,
+    distinct_on: SharedVec<String>,
+    filter: SharedVec<Condition>,
+    group_by: SharedVec<String>,
+    having: SharedVec<Arc<dyn Filter>>,
+    windows: SharedVec<(String, Window)>,
+    order_by: SharedVec<(Expr, Order, Option<Nulls>)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    archive: Option<TableName>,
+    include_archive: bool,
+    partitions: SharedVec<TableName>,
+    joins: SharedVec<Join>,
+    dialect: Dialect,
+    naming: NamingStrategy,
+    quoting: Quoting,
+    empty_list: EmptyList,
+    cast: CastSyntax,
+    server: Option<Server>,
+    reuse_params: bool,
+    max_statement_bytes: Option<usize>,
+    long_names: LongNames,
+    floats: FloatFormat,
+    uuids: UuidFormat,
+    auto_alias: bool,
+    explicit_columns: Option<Arc<Schema>>,
+}
+
+impl SQLable {
+    /// Creates a query reading from `tbl`, which may be schema-qualified
+    /// and aliased: `"analytics.events e"`.
+    pub fn new(tbl: &str) -> Self {
+        SQLable::from_table(TableName::parse(tbl))
+    }
+
+    /// Like `new`, for a table named from outside the code, see
+    /// `TableName::checked`.
+    pub fn try_new(tbl: &str) -> Result<Self, SQLError> {
+        Ok(SQLable::from_table(TableName::checked(tbl)?))
+    }
+
+    /// Creates a query reading from `sql` written as is, for tables
+    /// computed by trusted code, see `TableName::raw`:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::unchecked_raw_table("generate_series(1, 3)");
+    /// q.alias("n");
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM generate_series(1, 3) AS n\n");
+    /// ```
+    pub fn unchecked_raw_table(sql: &str) -> Self {
+        SQLable::from_table(TableName::raw(sql))
+    }
+
+    /// Creates a query reading from `table` as given, without parsing.
+    pub fn from_table(table: TableName) -> Self {
+        SQLable {
+            table: Arc::new(table),
+            cols: Arc::new(None),
+            exprs: SharedVec::default(),
+            distinct: false,
+            distinct_on: SharedVec::default(),
+            filter: SharedVec::default(),
+            group_by: SharedVec::default(),
+            having: SharedVec::default(),
+            windows: SharedVec::default(),
+            order_by: SharedVec::default(),
+            limit: None,
+            offset: None,
+            archive: None,
+            include_archive: false,
+            partitions: SharedVec::default(),
+            joins: SharedVec::default(),
+            dialect: Dialect::default(),
+            naming: NamingStrategy::default(),
+            quoting: Quoting::default(),
+            empty_list: EmptyList::default(),
+            cast: CastSyntax::default(),
+            server: None,
+            reuse_params: false,
+            max_statement_bytes: None,
+            long_names: LongNames::default(),
+            floats: FloatFormat::default(),
+            uuids: UuidFormat::default(),
+            auto_alias: false,
+            explicit_columns: None,
+        }
+    }
+
+    /// Ends a chain of builder calls with an owned query, so that a query
+    /// can be built in a single expression:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let q = SQLable::new("users")
+    ///     .select(vec!["id".to_owned()])
+    ///     .order_by("id", Order::Asc)
+    ///     .build();
+    /// assert_eq!(q.prepare(), "SELECT\n  id\nFROM users\nORDER BY\n  id ASC\n");
+    /// ```
+    ///
+    /// The query is copied, which shares its parts rather than cloning
+    /// them, see `SQLable`.
+    pub fn build(&self) -> SQLable {
+        self.clone()
+    }
+
+    pub fn get_table(&self) -> &TableName {
+        &self.table
+    }
+
+    /// Qualifies `column` with the table alias (or name), for use in the
+    /// select list and in filters: `q.col("userId")` gives `e.userId`.
+    pub fn col(&self, column: &str) -> String {
+        format!("{}.{}", self.table.qualifier(), column)
+    }
+
+    pub fn get_cols(&self) -> &Option<Vec<String>> {
+        &self.cols
+    }
+
+    pub fn get_snake_cols(&self) -> Option<Vec<String>> {
+        self.cols
+            .as_deref()
+            .map(|v| v.iter().map(|s| snake_case(s)).collect::<Vec<String>>())
+    }
+
+    /// Sets the selected columns, replacing any selected before; see
+    /// `add_columns` to build the list in steps.
+    pub fn select(&mut self, cols: Vec<String>) -> &mut Self {
+        self.cols = Arc::new(Some(cols));
+        self
+    }
+
+    /// Appends a column to the select list. A query that selected `*`
+    /// then selects this column only.
+    pub fn add_column(&mut self, col: &str) -> &mut Self {
+        self.add_columns(vec![col.to_owned()])
+    }
+
+    /// Appends columns to the select list, as `add_column`:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// # let with_email = true;
+    /// let mut q = SQLable::new("users");
+    /// q.add_column("id");
+    /// if with_email {
+    ///     q.add_columns(vec!["email".to_owned(), "verifiedAt".to_owned()]);
+    /// }
+    /// assert_eq!(q.prepare(), "SELECT\n  id,email,verified_at\nFROM users\n");
+    /// ```
+    pub fn add_columns(&mut self, cols: Vec<String>) -> &mut Self {
+        Arc::make_mut(&mut self.cols)
+            .get_or_insert_with(Vec::new)
+            .extend(cols);
+        self
+    }
+
+    /// Empties the select list, expressions included, so that the query
+    /// selects `*` again.
+    pub fn clear_columns(&mut self) -> &mut Self {
+        self.cols = Arc::new(None);
+        self.exprs = SharedVec::default();
+        self
+    }
+
+    /// Sets the filters, replacing any set before; see `add_filters` to
+    /// add them in steps.
+    pub fn filter(&mut self, cols: Vec<Box<dyn Filter>>) -> &mut Self {
+        self.filter = cols.into_iter().map(Condition::from).collect();
+        self
+    }
+
+    /// Adds a filter to those the rows must all meet.
+    pub fn add_filter(&mut self, filter: Box<dyn Filter>) -> &mut Self {
+        self.add_filters(vec![filter])
+    }
+
+    /// Adds filters to those the rows must all meet:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// # let country: Option<&str> = Some("fr");
+    /// let mut q = SQLable::new("users");
+    /// q.add_filter(Box::new(SQLFilter { column: "active".into(), filter: 1, cmp: SQLComp::EQ }));
+    /// if let Some(country) = country {
+    ///     q.add_filters(vec![Box::new(SQLFilter { column: "country".into(), filter: country, cmp: SQLComp::EQ })]);
+    /// }
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM users\nWHERE\n  (active = 1)\n  AND (country = 'fr')\n"
+    /// );
+    /// ```
+    pub fn add_filters(&mut self, filters: Vec<Box<dyn Filter>>) -> &mut Self {
+        self.filter
+            .to_mut()
+            .extend(filters.into_iter().map(Condition::from));
+        self
+    }
+
+    /// Removes every filter.
+    pub fn clear_filters(&mut self) -> &mut Self {
+        self.filter = SharedVec::default();
+        self
+    }
+
+    /// Adds comparisons with values of any type, known at runtime, to the
+    /// filters. They are stored as they are, without a box per filter:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// q.filter_values(vec![
+    ///     SQLFilter {
+    ///         column: "age".into(),
+    ///         filter: SqlValue::Int(18),
+    ///         cmp: SQLComp::GEQ,
+    ///     },
+    ///     SQLFilter {
+    ///         column: "country".into(),
+    ///         filter: "fr".into(),
+    ///         cmp: SQLComp::EQ,
+    ///     },
+    /// ]);
+    /// ```
+    pub fn filter_values(&mut self, filters: Vec<SQLFilter<SqlValue>>) -> &mut Self {
+        self.filter
+            .to_mut()
+            .extend(filters.into_iter().map(Condition::Value));
+        self
+    }
+
+    /// Adds comparisons with values of different types to the filters.
+    /// Values that are data are stored inline as with `filter_values`,
+    /// others, such as subqueries, behind a pointer shared by the query's
+    /// copies:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut active = SQLable::new("sessions");
+    /// active.select(vec!["userId".to_owned()]);
+    ///
+    /// let mut q = SQLable::new("users");
+    /// q.filter_dyn(vec![
+    ///     SQLFilter { column: "age".into(), filter: Box::new(18), cmp: SQLComp::GEQ },
+    ///     SQLFilter { column: "country".into(), filter: Box::new(vec!["fr", "de"]), cmp: SQLComp::EQ },
+    ///     SQLFilter { column: "id".into(), filter: Any(active), cmp: SQLComp::EQ }.boxed(),
+    /// ]);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM users\nWHERE\n  (age >= 18)\n  AND (country IN ('fr','de'))\n  AND (id = ANY (SELECT\n  user_id\nFROM sessions))\n"
+    /// );
+    /// ```
+    pub fn filter_dyn(&mut self, filters: Vec<DynFilter>) -> &mut Self {
+        self.filter
+            .to_mut()
+            .extend(filters.into_iter().map(|f| match f.filter.to_value() {
+                // a value its type renders wrapped, e.g. in a cast, stays typed
+                Some(value) if !f.filter.wraps_value() => Condition::Value(SQLFilter {
+                    column: f.column,
+                    filter: value,
+                    cmp: f.cmp,
+                }),
+                _ => Condition::Shared(Arc::new(f)),
+            }));
+        self
+    }
+
+    /// Returns each distinct row once: `SELECT DISTINCT`.
+    pub fn distinct(&mut self) -> &mut Self {
+        self.distinct = true;
+        self
+    }
+
+    /// Returns the first row of each group of rows equal on `cols`, in the
+    /// query's order: `SELECT DISTINCT ON (cols)`. Postgres only.
+    pub fn distinct_on(&mut self, cols: Vec<String>) -> &mut Self {
+        self.distinct_on = cols.into();
+        self
+    }
+
+    /// Adds an expression to the select list, after the selected columns.
+    /// A query with only expressions does not select `*`.
+    pub fn select_expr(&mut self, expr: Expr) -> &mut Self {
+        self.exprs.push(expr);
+        self
+    }
+
+    /// Names the expressions selected without an alias, each after what it
+    /// computes, see `Expr::default_alias`, numbered if the name is taken
+    /// by a column or another expression. Results then have the same
+    /// column names in every dialect, and the query can be used where its
+    /// columns must be named, such as a derived table:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("orders");
+    /// q.select(vec!["customerId".to_owned()])
+    ///     .select_expr(count_star())
+    ///     .select_expr(sum("total"))
+    ///     .select_expr(sum("total").alias("sum_total_2"))
+    ///     .select_expr(sum("total"))
+    ///     .group_by(vec!["customerId".to_owned()])
+    ///     .auto_alias(true);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  customer_id,COUNT(*) AS count,SUM(total) AS sum_total,SUM(total) AS sum_total_2,SUM(total) AS sum_total_3\nFROM orders\nGROUP BY\n  customer_id\n"
+    /// );
+    /// ```
+    pub fn auto_alias(&mut self, on: bool) -> &mut Self {
+        self.auto_alias = on;
+        self
+    }
+
+    /// Sets the `GROUP BY` columns.
+    pub fn group_by(&mut self, cols: Vec<String>) -> &mut Self {
+        self.group_by = cols.into();
+        self
+    }
+
+    /// Sets the `HAVING` conditions, typically `ExprFilter`s on aggregates.
+    pub fn having(&mut self, filters: Vec<Box<dyn Filter>>) -> &mut Self {
+        self.having = filters.into_iter().map(Arc::from).collect();
+        self
+    }
+
+    /// Appends an `ORDER BY` key, a column name or any expression.
+    pub fn order_by(&mut self, key: impl Into<Expr>, order: Order) -> &mut Self {
+        self.order_by.push((key.into(), order, None));
+        self
+    }
+
+    /// Appends an `ORDER BY` key with its NULLs first or last, whatever the
+    /// database's default. MySQL and SQL Server have no `NULLS FIRST` /
+    /// `NULLS LAST`; where their default differs, the key is preceded by
+    /// one sorting on whether it is NULL:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// q.order_by_nulls("lastLogin", Order::Asc, Nulls::Last);
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM users\nORDER BY\n  last_login ASC NULLS LAST\n");
+    ///
+    /// q.dialect(Dialect::MySql);
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM users\nORDER BY\n  last_login IS NULL ASC,last_login ASC\n");
+    /// ```
+    ///
+    /// SQLite before 3.30 is sorted the same way when rendered for its
+    /// `Server`.
+    pub fn order_by_nulls(
+        &mut self,
+        key: impl Into<Expr>,
+        order: Order,
+        nulls: Nulls,
+    ) -> &mut Self {
+        self.order_by.push((key.into(), order, Some(nulls)));
+        self
+    }
+
+    /// Removes every `ORDER BY` key.
+    pub fn clear_order_by(&mut self) -> &mut Self {
+        self.order_by = SharedVec::default();
+        self
+    }
+
+    /// Returns at most `n` rows: `LIMIT n`, `FETCH NEXT n ROWS ONLY` on
+    /// SQL Server.
+    pub fn limit(&mut self, n: u64) -> &mut Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Skips the first `n` rows, in the query's order.
+    pub fn offset(&mut self, n: u64) -> &mut Self {
+        self.offset = Some(n);
+        self
+    }
+
+    /// Sets the schema of the table.
+    pub fn schema(&mut self, schema: &str) -> &mut Self {
+        Arc::make_mut(&mut self.table).schema = Some(schema.to_owned());
+        self
+    }
+
+    /// Sets the alias of the table, rendered as `FROM table AS alias`.
+    pub fn alias(&mut self, alias: &str) -> &mut Self {
+        Arc::make_mut(&mut self.table).alias = Some(alias.to_owned());
+        self
+    }
+
+    /// Sets the table holding archived rows of the same shape as the
+    /// query's table; see `include_archive`.
+    pub fn archive(&mut self, table: &str) -> &mut Self {
+        self.archive = Some(TableName::parse(table));
+        self
+    }
+
+    /// Reads the archive table too: the query then reads from the
+    /// `UNION ALL` of both tables, each filtered by the query's filters so
+    /// that partitions can be pruned on either side. With joins, only the
+    /// filters on columns qualified with the table's qualifier are; the
+    /// others stay in `WHERE`.
+    pub fn include_archive(&mut self, include: bool) -> &mut Self {
+        self.include_archive = include;
+        self
+    }
+
+    /// Reads from these tables, each holding part of the query's table,
+    /// instead of from the table itself: from the `UNION ALL` of them, each
+    /// filtered by the query's filters, under the table's qualifier. The
+    /// archive table is then not read. See `Partitioning`.
+    pub fn partitions(&mut self, tables: Vec<TableName>) -> &mut Self {
+        self.partitions = tables.into();
+        self
+    }
+
+    /// Sets the dialect the query is rendered for.
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Sets how column names map to database names, for the select list
+    /// and filters alike. Defaults to `NamingStrategy::SnakeCase`.
+    pub fn naming(&mut self, naming: NamingStrategy) -> &mut Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Sets when table and column names are quoted.
+    pub fn quoting(&mut self, quoting: Quoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Sets how filters against empty collections are rendered.
+    pub fn empty_list(&mut self, policy: EmptyList) -> &mut Self {
+        self.empty_list = policy;
+        self
+    }
+
+    /// Sets what becomes of names longer than the dialect takes, such as
+    /// aliases generated from long column names:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("events");
+    /// q.dialect(Dialect::Postgres)
+    ///     .select_expr(count_star().alias(&format!("events_{}", "per_customer_segment_".repeat(3))));
+    /// q.long_names(LongNames::Error);
+    /// assert!(matches!(q.try_prepare(), Err(SQLError::InvalidName(_))));
+    ///
+    /// q.long_names(LongNames::Truncate);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  COUNT(*) AS events_per_customer_segment_per_customer_segment_per_c_70bd7e72\nFROM events\n"
+    /// );
+    /// ```
+    pub fn long_names(&mut self, policy: LongNames) -> &mut Self {
+        self.long_names = policy;
+        self
+    }
+
+    /// Sets how floats are written inline, and what becomes of NaN and
+    /// infinities, which SQL has no literal for:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("readings");
+    /// q.filter(vec![Box::new(SQLFilter { column: "value".into(), filter: f64::NAN, cmp: SQLComp::LT })]);
+    /// assert!(matches!(q.try_prepare(), Err(SQLError::NonFinite(_))));
+    ///
+    /// q.float_format(FloatFormat { non_finite: NonFinite::Null, ..Default::default() });
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM readings\nWHERE\n  (value < NULL)\n");
+    ///
+    /// q.filter(vec![Box::new(SQLFilter { column: "value".into(), filter: 2.0f64 / 3.0, cmp: SQLComp::LT })]);
+    /// q.float_format(FloatFormat { precision: Some(4), ..Default::default() });
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM readings\nWHERE\n  (value < 0.6667)\n");
+    ///
+    /// q.filter(vec![Box::new(SQLFilter { column: "value".into(), filter: 1.5e300, cmp: SQLComp::LT })]);
+    /// q.float_format(FloatFormat { scientific: true, ..Default::default() });
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM readings\nWHERE\n  (value < 1.5e300)\n");
+    /// ```
+    pub fn float_format(&mut self, format: FloatFormat) -> &mut Self {
+        self.floats = format;
+        self
+    }
+
+    /// Sets how `Uuid` values are written, e.g. as bytes for MySQL tables
+    /// that keep them in `BINARY(16)` columns.
+    pub fn uuid_format(&mut self, format: UuidFormat) -> &mut Self {
+        self.uuids = format;
+        self
+    }
+
+    /// Sets how `CAST` expressions are written.
+    pub fn cast_syntax(&mut self, syntax: CastSyntax) -> &mut Self {
+        self.cast = syntax;
+        self
+    }
+
+    /// Has `prepare_params` bind each distinct value once, referencing its
+    /// placeholder wherever the value recurs, on Postgres and SQL Server.
+    /// Dialects with positional `?` placeholders bind every occurrence.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// q.dialect(Dialect::Postgres).reuse_params(true).filter(vec![
+    ///     Box::new(SQLFilter { column: "status".into(), filter: vec!["active", "invited"], cmp: SQLComp::EQ }),
+    ///     Box::new(SQLFilter { column: "previousStatus".into(), filter: "active", cmp: SQLComp::NEQ }),
+    /// ]);
+    /// let (sql, params) = q.prepare_params().unwrap();
+    /// assert_eq!(sql, "SELECT\n  *\nFROM users\nWHERE\n  (status IN ($1,$2))\n  AND (previous_status <> $1)\n");
+    /// assert_eq!(params, vec![SqlValue::from("active"), SqlValue::from("invited")]);
+    ///
+    /// // values are compared when rendering, after any change to the query
+    /// q.add_filter(Box::new(SQLFilter { column: "name".into(), filter: "invited", cmp: SQLComp::EQ }));
+    /// let (sql, params) = q.prepare_params().unwrap();
+    /// assert!(sql.ends_with("AND (name = $2)\n"));
+    /// assert_eq!(params.len(), 2);
+    /// ```
+    ///
+    /// `fingerprint` is unaffected, so that queries differing only in
+    /// their values keep sharing it.
+    pub fn reuse_params(&mut self, reuse: bool) -> &mut Self {
+        self.reuse_params = reuse;
+        self
+    }
+
+    /// Caps the size of the rendered statement, with its bound values, in
+    /// place of the server's limit: by default MySQL's `max_allowed_packet`
+    /// of the version, 64 MiB without a `Server`, 256 MiB on SQL Server,
+    /// 1 GiB on Postgres and SQLite, and none for `Dialect::Generic`. A
+    /// statement over the limit fails to render with
+    /// `SQLError::StatementTooLarge`, naming its largest clause:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let ids: Vec<i64> = (0..10_000).collect();
+    /// let mut q = SQLable::new("users");
+    /// q.dialect(Dialect::MySql)
+    ///     .max_statement_bytes(4096)
+    ///     .filter(vec![Box::new(SQLFilter { column: "id".into(), filter: ids, cmp: SQLComp::EQ })]);
+    /// match q.prepare_params() {
+    ///     Err(SQLError::StatementTooLarge { clause, .. }) => assert_eq!(clause, "WHERE"),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// Subqueries are measured on their own as well as within the query.
+    pub fn max_statement_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_statement_bytes = Some(bytes);
+        self
+    }
+
+    /// Renders for `server`, in its dialect: constructs that its version
+    /// lacks fail to render, naming the version that has them.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("events");
+    /// q.select_expr(row_number().over(Window::new().order_by("id", Order::Asc)))
+    ///     .server(Server::parse("5.7.44").unwrap());
+    /// assert!(q.try_prepare().is_err());
+    ///
+    /// q.server(Server::parse("8.0.36").unwrap());
+    /// assert!(q.try_prepare().is_ok());
+    /// ```
+    pub fn server(&mut self, server: Server) -> &mut Self {
+        self.dialect = server.dialect;
+        self.server = Some(server);
+        self
+    }
+
+    /// Renders the query. If it cannot be rendered, see `try_prepare`, the
+    /// SQL is the error between angle brackets, which the database refuses
+    /// to run.
+    pub fn prepare(&self) -> String {
+        match self.try_prepare() {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e) + "\n",
+        }
+    }
+
+    /// Renders the query, failing if a filter cannot be expressed under the
+    /// configured options (e.g. an empty list with `EmptyList::Error`).
+    /// `Display` writes the same SQL, or the error in its place.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// q.empty_list(EmptyList::Error).filter_values(vec![SQLFilter {
+    ///     column: "id".into(),
+    ///     filter: SqlValue::List(vec![]),
+    ///     cmp: SQLComp::EQ,
+    /// }]);
+    /// assert!(q.try_prepare().is_err());
+    /// assert_eq!(q.to_string(), "<empty list used as filter for column id>\n");
+    /// ```
+    pub fn try_prepare(&self) -> Result<String, SQLError> {
+        self.render_query(&mut self.renderer())
+    }
+
+    /// Renders the query with placeholders in place of the filter values,
+    /// returning the values to bind in placeholder order.
+    pub fn prepare_params(&self) -> Result<(String, Vec<SqlValue>), SQLError> {
+        self.prepare_params_with(&BTreeMap::new())
+    }
+
+    /// Like `prepare_params`, taking the values of the query's `param`s
+    /// from `values`.
+    pub fn prepare_params_with(
+        &self,
+        values: &BTreeMap<String, SqlValue>,
+    ) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        r.values = values.clone();
+        let sql = self.render_query(&mut r)?;
+        Ok((sql, r.into_params()))
+    }
+
+    /// A stable hash of the query's shape: the parameterized SQL, so queries
+    /// differing only in their filter values share a fingerprint.
+    pub fn fingerprint(&self) -> Result<u64, SQLError> {
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        r.reuse_params = false;
+        Ok(fingerprint(&self.render_query(&mut r)?))
+    }
+
+    fn renderer(&self) -> Renderer {
+        Renderer {
+            dialect: self.dialect,
+            naming: self.naming,
+            quoting: self.quoting,
+            empty_list: self.empty_list,
+            cast: self.cast,
+            server: self.server,
+            reuse_params: self.reuse_params,
+            values: BTreeMap::new(),
+            max_statement_bytes: self.max_statement_bytes,
+            long_names: self.long_names,
+            floats: self.floats,
+            uuids: self.uuids,
+            params: None,
+            marks: false,
+            slots: None,
+        }
+    }
+
+    fn render_query(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.render_select(&self.ast(), r)
+    }
+
+    /// Renders `ast`, the tree of this query or a rewrite of it, after the
+    /// checks every way of rendering the query makes.
+    pub(crate) fn render_select(
+        &self,
+        ast: &Select<'_>,
+        r: &mut Renderer,
+    ) -> Result<String, SQLError> {
+        if let Some(schema) = &self.explicit_columns {
+            schema.get(&self.table)?;
+        }
+        if r.long_names == LongNames::Error {
+            self.check_names_for(r.dialect)?;
+        }
+        ast.render(r)
+    }
+}
+
+/// Renders `ORDER BY` keys, for queries and window definitions alike.
+pub(crate) fn order_keys(
+    keys: &[(Expr, Order, Option<Nulls>)],
+    r: &mut Renderer,
+) -> Result<Vec<String>, SQLError> {
+    let mut result = Vec::with_capacity(keys.len());
+    for (key, order, nulls) in keys {
+        let dir = match order {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC",
+        };
+        let key = key.render(r)?;
+        let native = match r.dialect {
+            Dialect::MySql | Dialect::MsSql => false,
+            _ => r.supports(Feature::NullsOrdering),
+        };
+        match nulls {
+            None => result.push(format!("{} {}", key, dir)),
+            Some(Nulls::First) if native => result.push(format!("{} {} NULLS FIRST", key, dir)),
+            Some(Nulls::Last) if native => result.push(format!("{} {} NULLS LAST", key, dir)),
+            Some(nulls) => {
+                // NULLs sort as the smallest value on MySQL, SQL Server
+                // and SQLite
+                let default = match order {
+                    Order::Asc => Nulls::First,
+                    Order::Desc => Nulls::Last,
+                };
+                if *nulls != default {
+                    let null_dir = match nulls {
+                        Nulls::First => "DESC",
+                        Nulls::Last => "ASC",
+                    };
+                    let is_null = match r.dialect {
+                        Dialect::MsSql => format!("CASE WHEN {} IS NULL THEN 1 ELSE 0 END", key),
+                        _ => format!("{} IS NULL", key),
+                    };
+                    result.push(format!("{} {}", is_null, null_dir));
+                }
+                result.push(format!("{} {}", key, dir));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// FNV-1a of the rendered SQL, stable across builds and platforms unlike
+/// `DefaultHasher`.
+pub(crate) fn fingerprint(sql: &str) -> u64 {
+    sql.bytes().fold(0xcbf29ce484222325, |h: u64, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Renders `keyword` followed by the AND-ed conditions, or nothing.
+fn conditions(keyword: &str, conds: &[String]) -> String {
+    let len: usize = conds.iter().map(|c| c.len() + 9).sum();
+    let mut result = String::with_capacity(keyword.len() + 1 + len);
+    if !conds.is_empty() {
+        result.push_str(keyword);
+        result.push('\n');
+        for (idx, val) in conds.iter().enumerate() {
+            result.push_str("  ");
+
+            if idx > 0 {
+                result.push_str("AND ");
+            }
+
+            result.push('(');
+            result.push_str(val);
+            result.push(')');
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+/// Filters show as the SQL they render to.
+impl std::fmt::Debug for SQLable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let having: Vec<FilterSql> = self.having.iter().map(|h| FilterSql(h.as_ref())).collect();
+        f.debug_struct("SQLable")
+            .field("table", &self.table)
+            .field("cols", &self.cols)
+            .field("exprs", &&self.exprs[..])
+            .field("distinct", &self.distinct)
+            .field("distinct_on", &&self.distinct_on[..])
+            .field("filter", &&self.filter[..])
+            .field("group_by", &&self.group_by[..])
+            .field("having", &having)
+            .field("windows", &&self.windows[..])
+            .field("order_by", &&self.order_by[..])
+            .field("limit", &self.limit)
+            .field("offset", &self.offset)
+            .field("archive", &self.archive)
+            .field("include_archive", &self.include_archive)
+            .field("partitions", &&self.partitions[..])
+            .field("joins", &&self.joins[..])
+            .field("dialect", &self.dialect)
+            .field("naming", &self.naming)
+            .field("quoting", &self.quoting)
+            .field("empty_list", &self.empty_list)
+            .field("cast", &self.cast)
+            .field("server", &self.server)
+            .field("reuse_params", &self.reuse_params)
+            .field("max_statement_bytes", &self.max_statement_bytes)
+            .field("long_names", &self.long_names)
+            .field("floats", &self.floats)
+            .field("uuids", &self.uuids)
+            .field("auto_alias", &self.auto_alias)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The SQL of `prepare`, e.g. `format!("{}", q)`.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("users");
+/// q.select(vec!["id".to_owned()]);
+/// assert_eq!(q.to_string(), q.prepare());
+/// ```
+///
+/// Where the query cannot be rendered, the error is written between angle
+/// brackets, which no database runs; `try_prepare` returns it.
+impl std::fmt::Display for SQLable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_prepare() {
+            Ok(sql) => f.write_str(&sql),
+            Err(e) => writeln!(f, "{}", unrendered(&e)),
+        }
+    }
+}
+
+/// A query used as a value renders as a parenthesised subquery, sharing the
+/// outer query's dialect and parameter numbering.
+impl ToSql for SQLable {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "({})", self.prepare().trim_end())
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(format!("({})", self.render_query(r)?.trim_end()))
+    }
+
+    fn is_subquery(&self) -> bool {
+        true
+    }
+}
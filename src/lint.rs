@@ -0,0 +1,81 @@
+use crate::{Condition, Filter, SQLComp, SQLFilter, SQLable, SqlValue, ToSql};
+
+/// A warning about a query that renders fine but is probably not what was
+/// meant, or is likely to be slow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    /// No columns or expressions selected, so the query returns `*`.
+    SelectStar,
+    /// The query has no filters and reads the whole table.
+    NoFilter,
+    /// The column is compared against an empty list, so the condition is a
+    /// constant.
+    EmptyList(String),
+    /// The column is compared against NULL with an operator for which the
+    /// result is always NULL, e.g. `col > NULL`.
+    NullComparison(String),
+    /// The query with this fingerprint ran this many times in one context,
+    /// probably once per row of an earlier result.
+    RepeatedQuery(u64, usize),
+    /// A lint of a filter labelled with its origin, see `Labeled`.
+    Origin(String, Box<Lint>),
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lint::SelectStar => write!(f, "selects * instead of explicit columns"),
+            Lint::NoFilter => write!(f, "no filter, the whole table is read"),
+            Lint::EmptyList(col) => {
+                write!(f, "{} is compared against an empty list", col)
+            }
+            Lint::NullComparison(col) => {
+                write!(f, "{} is compared against NULL and never matches", col)
+            }
+            Lint::RepeatedQuery(fingerprint, n) => write!(
+                f,
+                "query {:016x} ran {} times, consider an IN list or a join",
+                fingerprint, n
+            ),
+            Lint::Origin(origin, lint) => write!(f, "{} (from {})", lint, origin),
+        }
+    }
+}
+
+impl<T: ToSql> SQLFilter<T> {
+    pub(crate) fn lints(&self) -> Vec<Lint> {
+        if self.filter.is_empty_list() && !self.cmp.is_array() {
+            return vec![Lint::EmptyList(self.column.to_string())];
+        }
+
+        let ordered = matches!(
+            self.cmp,
+            SQLComp::GT | SQLComp::LT | SQLComp::GEQ | SQLComp::LEQ
+        );
+        if ordered && self.filter.to_value() == Some(SqlValue::Null) {
+            return vec![Lint::NullComparison(self.column.to_string())];
+        }
+
+        vec![]
+    }
+}
+
+impl SQLable {
+    /// Checks the query for common mistakes.
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut result = Vec::new();
+        if self.cols.as_deref().is_none_or(|c| c.is_empty()) && self.exprs.is_empty() {
+            result.push(Lint::SelectStar);
+        }
+
+        let filter: &[Condition] = &self.filter;
+        if filter.is_empty() {
+            result.push(Lint::NoFilter);
+        }
+        for f in filter {
+            result.extend(f.lint());
+        }
+
+        result
+    }
+}
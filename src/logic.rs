@@ -0,0 +1,159 @@
+#[cfg(feature = "serde")]
+use crate::FilterDef;
+use crate::{unrendered, Filter, Lint, Predicate, Renderer, SQLError};
+
+/// Matches when every filter matches: `(a) AND (b)`. With no filters it
+/// matches everything.
+pub struct And(pub Vec<Box<dyn Filter>>);
+
+/// Matches when any filter matches: `(a) OR (b)`. With no filters it
+/// matches nothing.
+pub struct Or(pub Vec<Box<dyn Filter>>);
+
+/// Matches when the filter does not: `NOT (a)`.
+pub struct Not(pub Box<dyn Filter>);
+
+/// The filter, labelled with where it comes from: a rule name, a request
+/// id, a code location. It renders as the filter does; the label follows
+/// it into lints, `Debug` output and `QueryDef`s, to tell which rule
+/// added a predicate:
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("orders");
+/// q.filter(vec![Box::new(Labeled(
+///     "rule:hide_cancelled".to_owned(),
+///     Box::new(SQLFilter { column: "status".into(), filter: Vec::<String>::new(), cmp: SQLComp::NEQ }),
+/// ))]);
+/// assert_eq!(q.prepare(), "SELECT\n  *\nFROM orders\nWHERE\n  (1=1)\n");
+/// assert_eq!(
+///     q.lint()[1].to_string(),
+///     "status is compared against an empty list (from rule:hide_cancelled)"
+/// );
+/// ```
+pub struct Labeled(pub String, pub Box<dyn Filter>);
+
+fn join(
+    filters: &[Box<dyn Filter>],
+    op: &str,
+    empty: &str,
+    r: &mut Renderer,
+) -> Result<String, SQLError> {
+    let conds = filters
+        .iter()
+        .map(|f| f.try_apply_filter(r))
+        .collect::<Result<Vec<String>, SQLError>>()?;
+    Ok(match conds.len() {
+        0 => empty.to_owned(),
+        1 => conds[0].clone(),
+        _ => format!("({})", conds.join(&format!(") {} (", op))),
+    })
+}
+
+fn lints(filters: &[Box<dyn Filter>]) -> Vec<Lint> {
+    filters.iter().flat_map(|f| f.lint()).collect()
+}
+
+fn predicates(filters: &[Box<dyn Filter>]) -> Option<Vec<Predicate>> {
+    filters.iter().map(|f| f.predicate()).collect()
+}
+
+impl Filter for And {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        join(&self.0, "AND", "1=1", r)
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        lints(&self.0)
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        Some(Predicate::and(predicates(&self.0)?))
+    }
+}
+
+impl Filter for Or {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        join(&self.0, "OR", "1=0", r)
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        lints(&self.0)
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        Some(Predicate::or(predicates(&self.0)?))
+    }
+}
+
+impl Filter for Not {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        Ok(format!("NOT ({})", self.0.try_apply_filter(r)?))
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        self.0.lint()
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        Some(!self.0.predicate()?)
+    }
+}
+
+impl Filter for Labeled {
+    fn apply_filter(&self) -> String {
+        self.1.apply_filter()
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.1.try_apply_filter(r)
+    }
+
+    /// The filter's lints, labelled unless a filter within already is.
+    fn lint(&self) -> Vec<Lint> {
+        self.1
+            .lint()
+            .into_iter()
+            .map(|lint| match lint {
+                Lint::Origin(..) => lint,
+                lint => Lint::Origin(self.0.clone(), Box::new(lint)),
+            })
+            .collect()
+    }
+
+    fn predicate(&self) -> Option<Predicate> {
+        self.1.predicate()
+    }
+
+    fn origin(&self) -> Option<&str> {
+        Some(&self.0)
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_def(&self) -> Option<FilterDef> {
+        let mut def = self.1.to_def()?;
+        def.origin = Some(self.0.clone());
+        Some(def)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Dialect, Renderer, SQLError, TableDef, TableName};
+
+/// A maintenance task run over tables, rendered with the dialect's own
+/// statement:
+///
+/// | task            | Postgres         | MySQL            | SQLite              | SQL Server                       |
+/// |-----------------|------------------|------------------|---------------------|----------------------------------|
+/// | `Reindex`       | `REINDEX TABLE`  | `OPTIMIZE TABLE` | `REINDEX`           | `ALTER INDEX ALL ON ... REBUILD` |
+/// | `Vacuum`        | `VACUUM`         | `OPTIMIZE TABLE` | `VACUUM`            | -                                |
+/// | `Analyze`       | `ANALYZE`        | `ANALYZE TABLE`  | `ANALYZE`           | `UPDATE STATISTICS`              |
+/// | `VacuumAnalyze` | `VACUUM ANALYZE` | `OPTIMIZE TABLE` | `VACUUM`, `ANALYZE` | -                                |
+///
+/// MySQL's `OPTIMIZE TABLE` rebuilds the table and its indexes and
+/// refreshes its statistics. SQLite vacuums the whole database, once
+/// whatever the tables.
+///
+/// ```
+/// # use traits::*;
+/// let tables = [TableName::new("orders"), TableName::parse("billing.invoices")];
+/// let sql = Maintenance::VacuumAnalyze.statements(&tables, Dialect::Postgres).unwrap();
+/// assert_eq!(sql, vec!["VACUUM ANALYZE orders\n", "VACUUM ANALYZE billing.invoices\n"]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Maintenance {
+    /// Rebuilds the tables' indexes.
+    Reindex,
+    /// Reclaims the space left by deleted and updated rows.
+    Vacuum,
+    /// Refreshes the statistics the planner uses.
+    Analyze,
+    /// `Vacuum` then `Analyze`.
+    VacuumAnalyze,
+}
+
+impl Maintenance {
+    /// The statements running the task over `tables`, one per table. Fails
+    /// for the generic dialect, and for vacuuming on SQL Server which has
+    /// no equivalent.
+    pub fn statements(
+        &self,
+        tables: &[TableName],
+        dialect: Dialect,
+    ) -> Result<Vec<String>, SQLError> {
+        let r = Renderer {
+            dialect,
+            ..Default::default()
+        };
+        self.render(tables, &r)
+    }
+
+    pub(crate) fn render(
+        &self,
+        tables: &[TableName],
+        r: &Renderer,
+    ) -> Result<Vec<String>, SQLError> {
+        // maintenance statements take the table itself, never an alias
+        let name = |table: &TableName| {
+            TableName {
+                alias: None,
+                ..table.clone()
+            }
+            .render(r)
+        };
+        let per_table = |task: &str| {
+            tables
+                .iter()
+                .map(|t| format!("{} {}\n", task, name(t)))
+                .collect::<Vec<String>>()
+        };
+
+        let result = match (self, r.dialect) {
+            (_, Dialect::Generic)
+            | (Maintenance::Vacuum | Maintenance::VacuumAnalyze, Dialect::MsSql) => {
+                return Err(SQLError::Unsupported(format!(
+                    "{:?} in {:?}",
+                    self, r.dialect
+                )))
+            }
+            (Maintenance::Reindex, Dialect::Postgres) => per_table("REINDEX TABLE"),
+            (Maintenance::Reindex, Dialect::Sqlite) => per_table("REINDEX"),
+            (Maintenance::Reindex, Dialect::MsSql) => tables
+                .iter()
+                .map(|t| format!("ALTER INDEX ALL ON {} REBUILD\n", name(t)))
+                .collect(),
+            (Maintenance::Analyze, Dialect::MySql) => per_table("ANALYZE TABLE"),
+            (Maintenance::Analyze, Dialect::MsSql) => per_table("UPDATE STATISTICS"),
+            (_, Dialect::MySql) => per_table("OPTIMIZE TABLE"),
+            (Maintenance::Analyze, _) => per_table("ANALYZE"),
+            (Maintenance::Vacuum, Dialect::Sqlite) => vec!["VACUUM\n".to_owned()],
+            (Maintenance::VacuumAnalyze, Dialect::Sqlite) => {
+                let mut result = vec!["VACUUM\n".to_owned()];
+                result.extend(per_table("ANALYZE"));
+                result
+            }
+            (Maintenance::Vacuum, _) => per_table("VACUUM"),
+            (Maintenance::VacuumAnalyze, _) => per_table("VACUUM ANALYZE"),
+        };
+        Ok(result)
+    }
+}
+
+impl TableDef {
+    /// The statements running `task` over the table, see `Maintenance`.
+    pub fn maintenance(
+        &self,
+        task: Maintenance,
+        dialect: Dialect,
+    ) -> Result<Vec<String>, SQLError> {
+        task.render(std::slice::from_ref(&self.table), &self.renderer(dialect))
+    }
+}
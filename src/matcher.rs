@@ -0,0 +1,421 @@
+use std::cmp::Ordering;
+
+use crate::exec::NamedRow;
+use crate::{
+    EmptyList, Filter, NamingStrategy, Record, Renderer, Row, SQLComp, SQLError, SQLable, SqlValue,
+    ToRow,
+};
+
+/// A filter as a condition evaluated in process, following SQL: comparing
+/// with `NULL` is unknown, and a row only matches when the condition is
+/// true. Built by `Filter::predicate`; see `SQLable::matcher`.
+#[derive(Clone, Debug)]
+pub struct Predicate(Node);
+
+#[derive(Clone, Debug)]
+enum Node {
+    Compare {
+        column: String,
+        cmp: SQLComp,
+        value: SqlValue,
+    },
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// `column <cmp> value`, rendered as `SQLFilter` renders it: `=` with
+    /// `NULL` is `IS NULL`, with a list `IN`.
+    pub fn compare(column: &str, cmp: SQLComp, value: SqlValue) -> Self {
+        Predicate(Node::Compare {
+            column: column.to_owned(),
+            cmp,
+            value,
+        })
+    }
+
+    /// True when all are; with none, always true.
+    pub fn and(predicates: Vec<Predicate>) -> Self {
+        Predicate(Node::And(predicates))
+    }
+
+    /// True when any is; with none, never true.
+    pub fn or(predicates: Vec<Predicate>) -> Self {
+        Predicate(Node::Or(predicates))
+    }
+
+    /// Evaluates the predicate on `row`, looking columns up through
+    /// `naming`: `None` when the result is unknown, as when comparing with
+    /// `NULL`. A column missing from the row reads as unknown.
+    pub fn eval(&self, row: &dyn Row, naming: NamingStrategy) -> Option<bool> {
+        match &self.0 {
+            Node::Compare { column, cmp, value } => {
+                let lhs = read(&NamedRow { row, naming }, column)?;
+                compare(&lhs, cmp, value)
+            }
+            Node::And(predicates) => {
+                let mut result = Some(true);
+                for p in predicates {
+                    match p.eval(row, naming) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => {}
+                    }
+                }
+                result
+            }
+            Node::Or(predicates) => {
+                let mut result = Some(false);
+                for p in predicates {
+                    match p.eval(row, naming) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => {}
+                    }
+                }
+                result
+            }
+            Node::Not(p) => p.eval(row, naming).map(|b| !b),
+        }
+    }
+
+    /// Evaluates the predicate on `value`, see `Filter::matches`.
+    pub(crate) fn matches(&self, value: &dyn ToRow) -> Result<bool, SQLError> {
+        self.check(EmptyList::default())?;
+        let naming = NamingStrategy::default();
+        let record = value.to_row()?;
+        let row = Record {
+            columns: record.columns.iter().map(|c| naming.apply(c)).collect(),
+            values: record.values,
+        };
+        Ok(self.eval(&row, naming) == Some(true))
+    }
+
+    /// The comparisons the predicate is made of, in order.
+    pub(crate) fn comparisons(&self) -> Vec<(&str, SQLComp, &SqlValue)> {
+        match &self.0 {
+            Node::Compare { column, cmp, value } => vec![(column.as_str(), *cmp, value)],
+            Node::And(predicates) | Node::Or(predicates) => {
+                predicates.iter().flat_map(Predicate::comparisons).collect()
+            }
+            Node::Not(p) => p.comparisons(),
+        }
+    }
+
+    /// The predicate in a canonical form: columns renamed by `column`,
+    /// negations pushed down to the comparisons, nested `AND`s and `OR`s
+    /// flattened, and operands and list values sorted and deduplicated, so
+    /// that predicates differing only in form have the same `key`.
+    pub(crate) fn normalize(
+        &self,
+        column: &dyn Fn(&str) -> Result<String, SQLError>,
+    ) -> Result<Predicate, SQLError> {
+        self.normal(false, column)
+    }
+
+    fn normal(
+        &self,
+        negate: bool,
+        column: &dyn Fn(&str) -> Result<String, SQLError>,
+    ) -> Result<Predicate, SQLError> {
+        match &self.0 {
+            Node::Compare {
+                column: c,
+                cmp,
+                value,
+            } => {
+                let value = match value {
+                    SqlValue::List(values) => {
+                        let mut values = values.clone();
+                        values.sort_by_cached_key(|v| format!("{:?}", v));
+                        values.dedup();
+                        match values.as_slice() {
+                            // `IN (x)` is `= x`, except that `= NULL` is `IS NULL`
+                            // and `= (list)` is `IN (list)`
+                            [v] if !matches!(v, SqlValue::Null | SqlValue::List(_))
+                                && matches!(cmp, SQLComp::EQ | SQLComp::NEQ) =>
+                            {
+                                v.clone()
+                            }
+                            _ => SqlValue::List(values),
+                        }
+                    }
+                    v => v.clone(),
+                };
+                let compare = Predicate::compare(&column(c)?, *cmp, value);
+                match negate {
+                    false => Ok(compare),
+                    true => Ok(compare.negated()),
+                }
+            }
+            Node::Not(p) => p.normal(!negate, column),
+            Node::And(predicates) | Node::Or(predicates) => {
+                // De Morgan's laws hold with unknowns too
+                let and = matches!(self.0, Node::And(_)) != negate;
+                let mut operands = Vec::with_capacity(predicates.len());
+                for p in predicates {
+                    match p.normal(negate, column)?.0 {
+                        Node::And(inner) if and => operands.extend(inner),
+                        Node::Or(inner) if !and => operands.extend(inner),
+                        node => operands.push(Predicate(node)),
+                    }
+                }
+                operands.sort_by_cached_key(Predicate::key);
+                operands.dedup_by(|a, b| a.key() == b.key());
+                Ok(match operands.len() {
+                    1 => operands.remove(0),
+                    _ if and => Predicate::and(operands),
+                    _ => Predicate::or(operands),
+                })
+            }
+        }
+    }
+
+    /// The opposite comparison, true when this one is false and unknown
+    /// when it is. An ordering against an empty list stays negated, as it
+    /// is false either way.
+    fn negated(self) -> Predicate {
+        let cmp = match &self.0 {
+            Node::Compare {
+                cmp,
+                value: SqlValue::List(values),
+                ..
+            } if values.is_empty() && !matches!(cmp, SQLComp::EQ | SQLComp::NEQ) => None,
+            Node::Compare { cmp, .. } => Some(match cmp {
+                SQLComp::EQ => SQLComp::NEQ,
+                SQLComp::NEQ => SQLComp::EQ,
+                SQLComp::GT => SQLComp::LEQ,
+                SQLComp::LEQ => SQLComp::GT,
+                SQLComp::LT => SQLComp::GEQ,
+                SQLComp::GEQ => SQLComp::LT,
+                SQLComp::DISTINCT => SQLComp::NOTDISTINCT,
+                SQLComp::NOTDISTINCT => SQLComp::DISTINCT,
+                SQLComp::OVERLAPS | SQLComp::CONTAINS | SQLComp::CONTAINEDBY => {
+                    return !self;
+                }
+            }),
+            _ => None,
+        };
+        match (self.0, cmp) {
+            (Node::Compare { column, value, .. }, Some(cmp)) => {
+                Predicate(Node::Compare { column, cmp, value })
+            }
+            (node, _) => !Predicate(node),
+        }
+    }
+
+    /// Text telling predicates apart, the same for equal predicates.
+    pub(crate) fn key(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// The operands of an `AND`, or the predicate alone.
+    pub(crate) fn conjuncts(self) -> Vec<Predicate> {
+        match self.0 {
+            Node::And(predicates) => predicates,
+            node => vec![Predicate(node)],
+        }
+    }
+
+    /// The comparison the predicate is, if it is one.
+    pub(crate) fn as_compare(&self) -> Option<(&str, SQLComp, &SqlValue)> {
+        match &self.0 {
+            Node::Compare { column, cmp, value } => Some((column, *cmp, value)),
+            _ => None,
+        }
+    }
+
+    /// Fails on what the database would refuse, or the query's renderer.
+    fn check(&self, empty_list: EmptyList) -> Result<(), SQLError> {
+        match &self.0 {
+            Node::Compare {
+                column,
+                cmp,
+                value: SqlValue::List(values),
+            } => {
+                if values.is_empty() && empty_list == EmptyList::Error {
+                    return Err(SQLError::EmptyList(column.clone()));
+                }
+                if !values.is_empty() && !matches!(cmp, SQLComp::EQ | SQLComp::NEQ) {
+                    return Err(SQLError::Unsupported(format!(
+                        "{} with a list outside the database",
+                        cmp.symbol()
+                    )));
+                }
+                Ok(())
+            }
+            Node::Compare { .. } => Ok(()),
+            Node::And(predicates) | Node::Or(predicates) => {
+                predicates.iter().try_for_each(|p| p.check(empty_list))
+            }
+            Node::Not(p) => p.check(empty_list),
+        }
+    }
+}
+
+/// True when the predicate is false, unknown when it is.
+impl std::ops::Not for Predicate {
+    type Output = Predicate;
+
+    fn not(self) -> Predicate {
+        Predicate(Node::Not(Box::new(self)))
+    }
+}
+
+/// The error for a filter without a predicate.
+pub(crate) fn unsupported(f: &(impl Filter + ?Sized), r: &mut Renderer) -> SQLError {
+    let sql = f.try_apply_filter(r);
+    SQLError::Unsupported(format!(
+        "{} outside the database",
+        sql.as_deref().unwrap_or("a filter")
+    ))
+}
+
+/// The value of `column` in `row`, unqualified; `None` if missing.
+fn read(row: &NamedRow, column: &str) -> Option<SqlValue> {
+    let name = column.rsplit_once('.').map_or(column, |(_, c)| c);
+    let idx = row.index_of(name)?;
+    row.value(idx).ok()
+}
+
+fn compare(lhs: &SqlValue, cmp: &SQLComp, rhs: &SqlValue) -> Option<bool> {
+    let is_null = matches!(lhs, SqlValue::Null);
+    if cmp.is_array() {
+        return array_compare(lhs, cmp, rhs);
+    }
+    match (cmp, rhs) {
+        (SQLComp::EQ, SqlValue::Null) => Some(is_null),
+        (SQLComp::NEQ, SqlValue::Null) => Some(!is_null),
+        // see `EmptyList::Constant`
+        (SQLComp::NEQ | SQLComp::DISTINCT, SqlValue::List(values)) if values.is_empty() => {
+            Some(true)
+        }
+        (_, SqlValue::List(values)) if values.is_empty() => Some(false),
+        (SQLComp::EQ, SqlValue::List(values)) => is_in(lhs, values),
+        (SQLComp::NEQ, SqlValue::List(values)) => is_in(lhs, values).map(|b| !b),
+        (SQLComp::DISTINCT, _) => distinct(lhs, rhs),
+        (SQLComp::NOTDISTINCT, _) => distinct(lhs, rhs).map(|b| !b),
+        (SQLComp::EQ, _) => order(lhs, rhs).map(Ordering::is_eq),
+        (SQLComp::NEQ, _) => order(lhs, rhs).map(Ordering::is_ne),
+        (SQLComp::GT, _) => order(lhs, rhs).map(Ordering::is_gt),
+        (SQLComp::LT, _) => order(lhs, rhs).map(Ordering::is_lt),
+        (SQLComp::GEQ, _) => order(lhs, rhs).map(Ordering::is_ge),
+        (SQLComp::LEQ, _) => order(lhs, rhs).map(Ordering::is_le),
+        (SQLComp::OVERLAPS | SQLComp::CONTAINS | SQLComp::CONTAINEDBY, _) => None,
+    }
+}
+
+/// The array operators over lists. Like Postgres, NULL elements equal
+/// nothing, not even each other; a NULL array makes the result unknown.
+fn array_compare(lhs: &SqlValue, cmp: &SQLComp, rhs: &SqlValue) -> Option<bool> {
+    let (SqlValue::List(lhs), SqlValue::List(rhs)) = (lhs, rhs) else {
+        return None;
+    };
+    let found = |x: &SqlValue, among: &[SqlValue]| is_in(x, among) == Some(true);
+    match cmp {
+        SQLComp::OVERLAPS => Some(lhs.iter().any(|x| found(x, rhs))),
+        SQLComp::CONTAINS => Some(rhs.iter().all(|x| found(x, lhs))),
+        _ => Some(lhs.iter().all(|x| found(x, rhs))),
+    }
+}
+
+/// `lhs IN (values)`: true on a match, unknown without one if a value is
+/// unknown to compare.
+fn is_in(lhs: &SqlValue, values: &[SqlValue]) -> Option<bool> {
+    let mut result = Some(false);
+    for value in values {
+        match order(lhs, value) {
+            Some(Ordering::Equal) => return Some(true),
+            None => result = None,
+            Some(_) => {}
+        }
+    }
+    result
+}
+
+/// `IS DISTINCT FROM`: `NULL`s are equal to each other only.
+fn distinct(lhs: &SqlValue, rhs: &SqlValue) -> Option<bool> {
+    match (lhs, rhs) {
+        (SqlValue::Null, SqlValue::Null) => Some(false),
+        (SqlValue::Null, _) | (_, SqlValue::Null) => Some(true),
+        _ => order(lhs, rhs).map(Ordering::is_ne),
+    }
+}
+
+/// How two values compare: numbers by value whatever their type, text
+/// byte by byte. `None` for `NULL` and values of different kinds, which
+/// databases either reject or convert in their own way.
+fn order(lhs: &SqlValue, rhs: &SqlValue) -> Option<Ordering> {
+    use SqlValue::*;
+    match (lhs, rhs) {
+        (Int(a), Int(b)) => Some(a.cmp(b)),
+        (UInt(a), UInt(b)) => Some(a.cmp(b)),
+        (Int(a), UInt(b)) => Some((*a as i128).cmp(&(*b as i128))),
+        (UInt(a), Int(b)) => Some((*a as i128).cmp(&(*b as i128))),
+        (Float(a), Float(b)) => a.partial_cmp(b),
+        (Float(a), Int(b)) => a.partial_cmp(&(*b as f64)),
+        (Float(a), UInt(b)) => a.partial_cmp(&(*b as f64)),
+        (Int(a), Float(b)) => (*a as f64).partial_cmp(b),
+        (UInt(a), Float(b)) => (*a as f64).partial_cmp(b),
+        (Text(a), Text(b)) => Some(a.cmp(b)),
+        // arrays, element by element, then the shorter first
+        (List(a), List(b)) => {
+            for (x, y) in a.iter().zip(b) {
+                match order(x, y)? {
+                    Ordering::Equal => {}
+                    o => return Some(o),
+                }
+            }
+            Some(a.len().cmp(&b.len()))
+        }
+        _ => None,
+    }
+}
+
+impl SQLable {
+    /// Compiles the query's filters into a function telling whether a row
+    /// matches them, e.g. to route change-data-capture events with the same
+    /// definitions as the queries:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("orders");
+    /// q.filter(vec![
+    ///     Box::new(SQLFilter { column: "status".into(), filter: vec!["paid", "shipped"], cmp: SQLComp::EQ }),
+    ///     Box::new(SQLFilter { column: "totalCents".into(), filter: 10_000, cmp: SQLComp::GEQ }),
+    /// ]);
+    /// let matches = q.matcher().unwrap();
+    ///
+    /// let event = Record {
+    ///     columns: vec!["status".to_owned(), "total_cents".to_owned()],
+    ///     values: vec!["paid".into(), SqlValue::Int(25_000)],
+    /// };
+    /// assert!(matches(&event));
+    /// ```
+    ///
+    /// Columns are looked up through the query's naming strategy, then as
+    /// given, without their qualifier. Text is compared byte by byte,
+    /// which may differ from the database's collation. Fails if a filter
+    /// can only be evaluated by the database, such as `Raw` SQL, a
+    /// subquery or an expression, or if the query groups rows.
+    pub fn matcher(&self) -> Result<impl Fn(&dyn Row) -> bool + Send + Sync, SQLError> {
+        if !self.group_by.is_empty() || !self.having.is_empty() {
+            return Err(SQLError::Unsupported(
+                "grouped rows outside the database".to_owned(),
+            ));
+        }
+        let mut predicates = Vec::with_capacity(self.filter.len());
+        for f in self.filter.iter() {
+            let predicate = f
+                .predicate()
+                .ok_or_else(|| unsupported(f, &mut self.renderer()))?;
+            predicate.check(self.empty_list)?;
+            predicates.push(predicate);
+        }
+
+        let predicate = Predicate::and(predicates);
+        let naming = self.naming;
+        Ok(move |row: &dyn Row| predicate.eval(row, naming) == Some(true))
+    }
+}
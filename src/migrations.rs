@@ -0,0 +1,333 @@
+use crate::cast::Timestamp;
+use crate::{
+    quote, ColumnDef, Dialect, ExecError, Execute, Insert, SQLError, SchemaDiff, Script, SqlValue,
+    TableDef, TableName,
+};
+
+/// A versioned change to the schema, built from the same definitions as
+/// queries so that it renders for the dialect of the `Migrations` running
+/// it:
+///
+/// ```
+/// # use traits::*;
+/// let users = TableDef::new("users")
+///     .column(ColumnDef::of::<i64>("id").not_null())
+///     .column(ColumnDef::of::<String>("email").not_null())
+///     .primary_key(&["id"]);
+/// let mut admin = Insert::new("users");
+/// admin
+///     .columns(vec!["id".to_owned(), "email".to_owned()])
+///     .row(vec![1.into(), "admin@example.com".into()]);
+///
+/// let create_users = Migration::new(1, "create users")
+///     .create_table(users)
+///     .insert(admin)
+///     .up("CREATE INDEX users_email ON users (email)")
+///     .down("DROP INDEX users_email");
+/// ```
+///
+/// The down steps undo the migration in reverse order: the last step added
+/// is undone first. `create_table` and `diff` add their own down steps;
+/// inserts have none.
+#[derive(Clone, Debug)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    up: Vec<Step>,
+    down: Vec<Step>,
+}
+
+#[derive(Clone, Debug)]
+enum Step {
+    CreateTable(TableDef),
+    DropTable(TableDef),
+    Diff(SchemaDiff),
+    Insert(Insert),
+    Sql(String),
+}
+
+/// A rendered step: an insert keeps its values to bind.
+enum Statement {
+    Sql(String),
+    Insert(Box<Insert>),
+}
+
+impl Migration {
+    pub fn new(version: u64, name: &str) -> Self {
+        Migration {
+            version,
+            name: name.to_owned(),
+            up: Vec::new(),
+            down: Vec::new(),
+        }
+    }
+
+    /// Creates the table with `TableDef::create_statements`, and drops it
+    /// on the way down.
+    pub fn create_table(mut self, def: TableDef) -> Self {
+        self.up.push(Step::CreateTable(def.clone()));
+        self.down.push(Step::DropTable(def));
+        self
+    }
+
+    /// Applies the changes of a diff, and its `rollback` on the way down.
+    /// The diff is rendered for its own dialect.
+    pub fn diff(mut self, diff: SchemaDiff) -> Self {
+        self.down.push(Step::Diff(diff.rollback()));
+        self.up.push(Step::Diff(diff));
+        self
+    }
+
+    /// Inserts rows, e.g. reference data, rendered for the migrations'
+    /// dialect.
+    pub fn insert(mut self, insert: Insert) -> Self {
+        self.up.push(Step::Insert(insert));
+        self
+    }
+
+    /// Runs a statement as written on the way up.
+    pub fn up(mut self, sql: impl Into<String>) -> Self {
+        self.up.push(Step::Sql(sql.into()));
+        self
+    }
+
+    /// Runs a statement as written on the way down.
+    pub fn down(mut self, sql: impl Into<String>) -> Self {
+        self.down.push(Step::Sql(sql.into()));
+        self
+    }
+}
+
+impl Step {
+    fn statements(&self, dialect: Dialect) -> Result<Vec<Statement>, SQLError> {
+        let result = match self {
+            Step::CreateTable(def) => def
+                .create_statements(dialect)?
+                .into_iter()
+                .map(Statement::Sql)
+                .collect(),
+            Step::DropTable(def) => {
+                let table = def.table.render(&def.renderer(dialect));
+                vec![Statement::Sql(format!("DROP TABLE {}\n", table))]
+            }
+            Step::Diff(diff) => diff.statements()?.into_iter().map(Statement::Sql).collect(),
+            Step::Insert(insert) => {
+                let mut insert = insert.clone();
+                insert.dialect(dialect);
+                vec![Statement::Insert(Box::new(insert))]
+            }
+            Step::Sql(sql) => vec![Statement::Sql(sql.clone())],
+        };
+        Ok(result)
+    }
+}
+
+/// The migrations of a database, applied in order of version and recorded
+/// in a tracking table, `schema_migrations` unless renamed:
+///
+/// ```no_run
+/// # use traits::*;
+/// # fn run<C: Execute>(conn: &C) -> Result<(), ExecError<C::Error>> {
+/// let migrations = Migrations::new(Dialect::Postgres)
+///     .migration(Migration::new(1, "create users").up("CREATE TABLE users (id BIGINT)"))
+///     .migration(Migration::new(2, "add email").up("ALTER TABLE users ADD email TEXT"));
+///
+/// let applied = migrations.migrate_up(conn)?;
+/// let reverted = migrations.migrate_down(conn, 1)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Each migration runs in its own transaction together with its row in the
+/// tracking table, see `Script`, so a failed migration leaves the ones
+/// before it applied. MySQL commits DDL statements as they run, so a
+/// failure there may leave part of a migration applied.
+#[derive(Clone, Debug)]
+pub struct Migrations {
+    dialect: Dialect,
+    table: TableName,
+    migrations: Vec<Migration>,
+}
+
+impl Migrations {
+    pub fn new(dialect: Dialect) -> Self {
+        Migrations {
+            dialect,
+            table: TableName::parse("schema_migrations"),
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Sets the tracking table, which may be schema-qualified.
+    pub fn table(mut self, tbl: &str) -> Self {
+        self.table = TableName::parse(tbl);
+        self
+    }
+
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// The tracking table: the version and name of each applied migration
+    /// and when it was applied.
+    pub fn tracking_table(&self) -> TableDef {
+        TableDef::from_table(self.table.clone())
+            .column(ColumnDef::of::<i64>("version").not_null())
+            .column(ColumnDef::of::<String>("name").not_null())
+            .column(
+                ColumnDef::of::<Timestamp>("applied_at")
+                    .not_null()
+                    .default("CURRENT_TIMESTAMP"),
+            )
+            .primary_key(&["version"])
+    }
+
+    /// The versions of the applied migrations, oldest first. Creates the
+    /// tracking table if it is missing.
+    pub fn applied<C: Execute + ?Sized>(&self, conn: &C) -> Result<Vec<u64>, ExecError<C::Error>> {
+        conn.execute(&self.create_tracking_table()?, &[])?;
+
+        let def = self.tracking_table();
+        let r = def.renderer(self.dialect);
+        let version = r.column("version");
+        let sql = format!(
+            "SELECT {}\nFROM {}\nORDER BY\n  {}\n",
+            version,
+            self.table.render(&r),
+            version
+        );
+        let mut result = Vec::new();
+        conn.query(&sql, &[], &mut |row| {
+            result.push(row.get_at::<i64>(0)? as u64);
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Applies the migrations that are not yet, oldest first, returning
+    /// their versions. Fails before applying any if two migrations have the
+    /// same version or an applied one is not defined.
+    pub fn migrate_up<C: Execute + ?Sized>(
+        &self,
+        conn: &C,
+    ) -> Result<Vec<u64>, ExecError<C::Error>> {
+        let applied = self.applied(conn)?;
+        let pending: Vec<&Migration> = self
+            .sorted(&applied)?
+            .into_iter()
+            .filter(|m| !applied.contains(&m.version))
+            .collect();
+
+        let mut result = Vec::with_capacity(pending.len());
+        for migration in pending {
+            let mut statements = Vec::new();
+            for step in &migration.up {
+                statements.extend(step.statements(self.dialect)?);
+            }
+            statements.push(Statement::Insert(Box::new(self.record(migration))));
+            self.run(&statements, conn)?;
+            result.push(migration.version);
+        }
+        Ok(result)
+    }
+
+    /// Reverts the applied migrations newer than `version`, newest first,
+    /// returning their versions. `migrate_down(conn, 0)` reverts them all.
+    pub fn migrate_down<C: Execute + ?Sized>(
+        &self,
+        conn: &C,
+        version: u64,
+    ) -> Result<Vec<u64>, ExecError<C::Error>> {
+        let applied = self.applied(conn)?;
+        let reverting: Vec<&Migration> = self
+            .sorted(&applied)?
+            .into_iter()
+            .rev()
+            .filter(|m| m.version > version && applied.contains(&m.version))
+            .collect();
+
+        let mut result = Vec::with_capacity(reverting.len());
+        for migration in reverting {
+            let mut statements = Vec::new();
+            for step in migration.down.iter().rev() {
+                statements.extend(step.statements(self.dialect)?);
+            }
+            statements.push(Statement::Sql(self.forget(migration)));
+            self.run(&statements, conn)?;
+            result.push(migration.version);
+        }
+        Ok(result)
+    }
+
+    /// The migrations by version, checking them against the `applied`
+    /// versions.
+    fn sorted(&self, applied: &[u64]) -> Result<Vec<&Migration>, SQLError> {
+        let mut result: Vec<&Migration> = self.migrations.iter().collect();
+        result.sort_by_key(|m| m.version);
+        if let Some(pair) = result.windows(2).find(|p| p[0].version == p[1].version) {
+            return Err(SQLError::DuplicateMigration(pair[0].version));
+        }
+        if let Some(version) = applied
+            .iter()
+            .find(|v| !result.iter().any(|m| m.version == **v))
+        {
+            return Err(SQLError::UnknownMigration(*version));
+        }
+        Ok(result)
+    }
+
+    fn run<C: Execute + ?Sized>(
+        &self,
+        statements: &[Statement],
+        conn: &C,
+    ) -> Result<u64, ExecError<C::Error>> {
+        let mut script = Script::new(self.dialect);
+        for statement in statements {
+            match statement {
+                Statement::Sql(sql) => script.raw(sql.as_str()),
+                Statement::Insert(insert) => script.insert(insert),
+            };
+        }
+        script.execute(conn)
+    }
+
+    /// `CREATE TABLE` for the tracking table, doing nothing if it exists.
+    fn create_tracking_table(&self) -> Result<String, SQLError> {
+        let create = self.tracking_table().create_table(self.dialect)?;
+        let result = match self.dialect {
+            Dialect::MsSql => {
+                let name = match &self.table.schema {
+                    Some(schema) => format!("{}.{}", schema, self.table.name),
+                    None => self.table.name.clone(),
+                };
+                format!("IF OBJECT_ID(N{}, N'U') IS NULL\n{}", quote(&name), create)
+            }
+            _ => create.replacen("CREATE TABLE", "CREATE TABLE IF NOT EXISTS", 1),
+        };
+        Ok(result)
+    }
+
+    /// The row of the tracking table recording `migration`.
+    fn record(&self, migration: &Migration) -> Insert {
+        let mut insert = Insert::from_table(self.table.clone());
+        insert
+            .columns(vec!["version".to_owned(), "name".to_owned()])
+            .row(vec![
+                SqlValue::Int(migration.version as i64).into(),
+                SqlValue::Text(migration.name.clone()).into(),
+            ])
+            .dialect(self.dialect);
+        insert
+    }
+
+    fn forget(&self, migration: &Migration) -> String {
+        let r = self.tracking_table().renderer(self.dialect);
+        format!(
+            "DELETE FROM {}\nWHERE {} = {}\n",
+            self.table.render(&r),
+            r.column("version"),
+            migration.version
+        )
+    }
+}
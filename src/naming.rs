@@ -0,0 +1,46 @@
+use crate::inflection::{camel_case, is_snake_case, snake_case};
+
+/// How column names given to the builder map to database column names.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum NamingStrategy {
+    /// `userId` becomes `user_id`.
+    #[default]
+    SnakeCase,
+    /// `user_id` becomes `userId`.
+    CamelCase,
+    /// Names are used as given.
+    AsIs,
+    Custom(fn(&str) -> String),
+}
+
+impl NamingStrategy {
+    /// Converts a column name. For a qualified name (`e.userId`) only the
+    /// column part is converted, the qualifier must match the table alias.
+    pub fn apply(&self, name: &str) -> String {
+        match name.rsplit_once('.') {
+            Some((qualifier, column)) => {
+                let mut result = String::with_capacity(name.len() + 4);
+                result.push_str(qualifier);
+                result.push('.');
+                self.push_converted(&mut result, column);
+                result
+            }
+            None => {
+                let mut result = String::with_capacity(name.len() + 4);
+                self.push_converted(&mut result, name);
+                result
+            }
+        }
+    }
+
+    fn push_converted(&self, out: &mut String, name: &str) {
+        match self {
+            _ if name == "*" => out.push_str(name),
+            NamingStrategy::SnakeCase if is_snake_case(name) => out.push_str(name),
+            NamingStrategy::SnakeCase => out.push_str(&snake_case(name)),
+            NamingStrategy::CamelCase => out.push_str(&camel_case(name)),
+            NamingStrategy::AsIs => out.push_str(name),
+            NamingStrategy::Custom(f) => out.push_str(&f(name)),
+        }
+    }
+}
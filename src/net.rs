@@ -0,0 +1,37 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{Dialect, Renderer, SQLError, SqlValue, ToSql};
+
+// Postgres compares addresses as `inet`; other dialects keep them as text.
+macro_rules! ip_addr {
+    ($($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                    write!(out, "'{}'", self)
+                }
+
+                fn to_value(&self) -> Option<SqlValue> {
+                    Some(SqlValue::Text(self.to_string()))
+                }
+
+                fn wraps_value(&self) -> bool {
+                    true
+                }
+
+                fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+                    match (r.dialect, r.is_parameterized()) {
+                        (Dialect::Postgres, true) => {
+                            Ok(format!("{}::inet", r.bind(SqlValue::Text(self.to_string()))))
+                        }
+                        (Dialect::Postgres, false) => Ok(format!("inet '{}'", self)),
+                        (_, true) => Ok(r.bind(SqlValue::Text(self.to_string()))),
+                        (_, false) => Ok(self.to_sql()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+ip_addr!(IpAddr, Ipv4Addr, Ipv6Addr);
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use crate::{Lint, SqlValue, Touched};
+
+/// Receives the statements rendered in a context such as a `QueryBudget`,
+/// and the warnings the context raises about them.
+pub trait Observer {
+    /// Called for every statement, before it is handed back for execution.
+    fn statement(&mut self, _sql: &str, _params: &[SqlValue]) {}
+
+    /// Called for every warning, e.g. `Lint::RepeatedQuery`.
+    fn warning(&mut self, _lint: &Lint) {}
+
+    /// Called for every write, after `statement`, with the rows it may
+    /// change.
+    fn touched(&mut self, _touched: &Touched) {}
+}
+
+/// Counts how often each query shape runs and raises `Lint::RepeatedQuery`
+/// when one runs more than `threshold` times: the usual sign of a loop
+/// issuing one query per row where an IN list or a join would do.
+#[derive(Clone, Debug)]
+pub(crate) struct RepeatDetector {
+    threshold: usize,
+    seen: HashMap<u64, usize>,
+}
+
+impl RepeatDetector {
+    pub(crate) fn new(threshold: usize) -> Self {
+        RepeatDetector {
+            threshold,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Records one run of `fingerprint`, returning the warning the first time
+    /// it goes over the threshold.
+    pub(crate) fn record(&mut self, fingerprint: u64) -> Option<Lint> {
+        let count = self.seen.entry(fingerprint).or_insert(0);
+        *count += 1;
+        (*count == self.threshold + 1).then_some(Lint::RepeatedQuery(fingerprint, *count))
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.seen.clear();
+    }
+}
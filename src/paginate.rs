@@ -0,0 +1,342 @@
+use std::borrow::Cow;
+
+use crate::{
+    fingerprint, And, Expr, Filter, Or, Order, Row, SQLComp, SQLError, SQLFilter, SQLable, SqlValue,
+};
+
+/// Keyset pagination with opaque cursors, for web APIs handing out links
+/// to the next and previous pages. A cursor holds the sort keys of the
+/// row a page starts after, and the ordering it belongs to, so a cursor
+/// from another query or listing is refused.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("orders");
+/// q.dialect(Dialect::Postgres)
+///     .order_by("placedAt", Order::Desc)
+///     .order_by("id", Order::Desc);
+/// let pages = q.paginate(2);
+///
+/// // the first page, and one row to tell whether there is a next
+/// assert_eq!(pages.query(None).unwrap().prepare(), "SELECT\n  *\nFROM orders\nORDER BY\n  placed_at DESC,id DESC\nLIMIT 3\n");
+/// let mut rows = vec![
+///     Record { columns: vec!["id".into(), "placed_at".into()], values: vec![9.into(), "2024-05-02".into()] },
+///     Record { columns: vec!["id".into(), "placed_at".into()], values: vec![8.into(), "2024-05-01".into()] },
+///     Record { columns: vec!["id".into(), "placed_at".into()], values: vec![5.into(), "2024-05-01".into()] },
+/// ];
+/// let links = pages.links(None, &mut rows).unwrap();
+/// assert_eq!((rows.len(), links.prev), (2, None));
+///
+/// let next = pages.query(links.next.as_deref()).unwrap();
+/// assert_eq!(
+///     next.prepare_params().unwrap().0,
+///     "SELECT\n  *\nFROM orders\nWHERE\n  ((placed_at < $1) OR ((placed_at = $2) AND (id < $3)))\nORDER BY\n  placed_at DESC,id DESC\nLIMIT 3\n"
+/// );
+/// assert!(matches!(pages.query(Some("forged")), Err(SQLError::InvalidCursor(_))));
+/// ```
+///
+/// The sort keys must be columns, selected by the query, the last of them
+/// unique so that rows are never tied. Cursors are only encoded: to keep
+/// clients from editing them, sign them with `signed`.
+pub struct Paginator<'a> {
+    query: &'a SQLable,
+    page_size: u64,
+    signer: Option<&'a dyn CursorSigner>,
+}
+
+/// Signs cursors, e.g. with an HMAC keyed by a server secret; a cursor is
+/// accepted only if `sign` gives back its signature.
+pub trait CursorSigner: Send + Sync {
+    fn sign(&self, payload: &[u8]) -> Vec<u8>;
+}
+
+/// The cursors of the pages around a page, `None` where there is none.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Links {
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+/// A decoded cursor: the sort keys of the row to start from, and whether
+/// the page comes before it.
+struct Cursor {
+    before: bool,
+    values: Vec<SqlValue>,
+}
+
+impl SQLable {
+    /// Pages of this query of `page_size` rows, see `Paginator`.
+    pub fn paginate(&self, page_size: u64) -> Paginator<'_> {
+        Paginator {
+            query: self,
+            page_size: page_size.max(1),
+            signer: None,
+        }
+    }
+}
+
+impl<'a> Paginator<'a> {
+    /// Signs the cursors handed out, and refuses those without a valid
+    /// signature.
+    pub fn signed(mut self, signer: &'a dyn CursorSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// The query reading the page `cursor` points to, the first page
+    /// without one. It reads one row more than a page, which `links` drops.
+    pub fn query(&self, cursor: Option<&str>) -> Result<SQLable, SQLError> {
+        let keys = self.keys()?;
+        let mut q = self.query.clone();
+        if let Some(cursor) = cursor {
+            let cursor = self.decode(cursor, &keys)?;
+            q.add_filter(seek(&keys, &cursor));
+            if cursor.before {
+                for (_, order, _) in q.order_by.to_mut() {
+                    *order = match order {
+                        Order::Asc => Order::Desc,
+                        Order::Desc => Order::Asc,
+                    };
+                }
+            }
+        }
+        q.limit(self.page_size + 1);
+        Ok(q)
+    }
+
+    /// The cursors of the pages around the rows read with
+    /// `query(cursor)`, which are cut to the page and put in the query's
+    /// order.
+    pub fn links<R: Row>(
+        &self,
+        cursor: Option<&str>,
+        rows: &mut Vec<R>,
+    ) -> Result<Links, SQLError> {
+        let keys = self.keys()?;
+        let before = match cursor {
+            Some(cursor) => Some(self.decode(cursor, &keys)?.before),
+            None => None,
+        };
+        let more = rows.len() as u64 > self.page_size;
+        rows.truncate(self.page_size as usize);
+        if before == Some(true) {
+            rows.reverse();
+        }
+
+        let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+            return Ok(Links::default());
+        };
+        let link = |row: &R, before| -> Result<String, SQLError> {
+            let values = keys
+                .iter()
+                .map(|(column, _)| self.key_value(row, column))
+                .collect::<Result<Vec<SqlValue>, SQLError>>()?;
+            Ok(self.encode(&keys, &Cursor { before, values }))
+        };
+        Ok(Links {
+            next: match before {
+                Some(true) => Some(link(last, false)?),
+                _ => more.then(|| link(last, false)).transpose()?,
+            },
+            prev: match before {
+                Some(true) => more.then(|| link(first, true)).transpose()?,
+                Some(false) => Some(link(first, true)?),
+                None => None,
+            },
+        })
+    }
+
+    /// The sort keys of the query, which must be columns.
+    fn keys(&self) -> Result<Vec<(&'a str, Order)>, SQLError> {
+        let keys = self
+            .query
+            .order_by
+            .iter()
+            .map(|(key, order, nulls)| match (key, nulls) {
+                (Expr::Column(c), None) => Ok((c.as_str(), *order)),
+                _ => Err(SQLError::Unsupported(
+                    "pagination ordered by an expression or with NULLs placed".to_owned(),
+                )),
+            })
+            .collect::<Result<Vec<_>, SQLError>>()?;
+        match keys.is_empty() {
+            true => Err(SQLError::Unsupported(
+                "pagination of an unordered query".to_owned(),
+            )),
+            false => Ok(keys),
+        }
+    }
+
+    /// The value of the sort key `column` in `row`, which names its
+    /// columns as the database does.
+    fn key_value<R: Row>(&self, row: &R, column: &str) -> Result<SqlValue, SQLError> {
+        let name = column.rsplit('.').next().unwrap_or(column);
+        let idx = row
+            .index_of(&self.query.naming.apply(name))
+            .or_else(|| row.index_of(name))
+            .ok_or_else(|| SQLError::Decode(format!("no column {}", name)))?;
+        match row.value(idx)? {
+            SqlValue::Null | SqlValue::List(_) => Err(SQLError::Unsupported(format!(
+                "pagination on {}, which is not a single value",
+                name
+            ))),
+            value => Ok(value),
+        }
+    }
+
+    fn encode(&self, keys: &[(&str, Order)], cursor: &Cursor) -> String {
+        let mut payload = format!(
+            "{}{:016x}",
+            if cursor.before { 'b' } else { 'a' },
+            ordering(keys)
+        );
+        for value in &cursor.values {
+            match value {
+                SqlValue::Int(v) => payload.push_str(&format!("i{};", v)),
+                SqlValue::UInt(v) => payload.push_str(&format!("u{};", v)),
+                SqlValue::Float(v) => payload.push_str(&format!("f{:x};", v.to_bits())),
+                SqlValue::Text(v) => payload.push_str(&format!("t{}:{}", v.len(), v)),
+                // refused by key_value
+                SqlValue::Null | SqlValue::List(_) => {}
+            }
+        }
+        let mut token = base64(payload.as_bytes());
+        if let Some(signer) = self.signer {
+            token.push('.');
+            token.push_str(&base64(&signer.sign(payload.as_bytes())));
+        }
+        token
+    }
+
+    fn decode(&self, token: &str, keys: &[(&str, Order)]) -> Result<Cursor, SQLError> {
+        let invalid = |what: &str| SQLError::InvalidCursor(what.to_owned());
+        let (payload, signature) = match token.split_once('.') {
+            Some((payload, signature)) => (payload, Some(signature)),
+            None => (token, None),
+        };
+        let payload = unbase64(payload).ok_or_else(|| invalid("not a cursor"))?;
+        if let Some(signer) = self.signer {
+            let signature = signature
+                .and_then(unbase64)
+                .ok_or_else(|| invalid("not signed"))?;
+            if !same(&signer.sign(&payload), &signature) {
+                return Err(invalid("bad signature"));
+            }
+        }
+        let payload = String::from_utf8(payload).map_err(|_| invalid("not a cursor"))?;
+
+        let before = match payload.get(..1) {
+            Some("a") => false,
+            Some("b") => true,
+            _ => return Err(invalid("not a cursor")),
+        };
+        if payload.get(1..17) != Some(&format!("{:016x}", ordering(keys))) {
+            return Err(invalid("from another ordering"));
+        }
+        let mut rest = &payload[17..];
+        let mut values = Vec::with_capacity(keys.len());
+        while !rest.is_empty() {
+            let (value, tail) = read_value(rest).ok_or_else(|| invalid("not a cursor"))?;
+            values.push(value);
+            rest = tail;
+        }
+        match values.len() == keys.len() {
+            true => Ok(Cursor { before, values }),
+            false => Err(invalid("from another ordering")),
+        }
+    }
+}
+
+/// Tells orderings apart, so that a cursor only applies to its own.
+fn ordering(keys: &[(&str, Order)]) -> u64 {
+    let keys: Vec<String> = keys.iter().map(|(c, o)| format!("{} {:?}", c, o)).collect();
+    fingerprint(&keys.join(","))
+}
+
+/// The rows past the cursor in the query's order, or before it:
+/// `(a > x) OR (a = x AND b > y) ...`.
+fn seek(keys: &[(&str, Order)], cursor: &Cursor) -> Box<dyn Filter> {
+    let compare = |idx: usize, cmp| -> Box<dyn Filter> {
+        Box::new(SQLFilter {
+            column: Cow::Owned(keys[idx].0.to_owned()),
+            filter: cursor.values[idx].clone(),
+            cmp,
+        })
+    };
+    let branches = (0..keys.len())
+        .map(|idx| {
+            let cmp = match (keys[idx].1, cursor.before) {
+                (Order::Asc, false) | (Order::Desc, true) => SQLComp::GT,
+                (Order::Desc, false) | (Order::Asc, true) => SQLComp::LT,
+            };
+            let mut and: Vec<Box<dyn Filter>> = (0..idx).map(|j| compare(j, SQLComp::EQ)).collect();
+            and.push(compare(idx, cmp));
+            match and.len() {
+                1 => and.remove(0),
+                _ => Box::new(And(and)) as Box<dyn Filter>,
+            }
+        })
+        .collect();
+    Box::new(Or(branches))
+}
+
+/// The value at the start of a cursor's keys, and what follows it.
+fn read_value(s: &str) -> Option<(SqlValue, &str)> {
+    let (tag, s) = (s.get(..1)?, &s[1..]);
+    if tag == "t" {
+        let (len, rest) = s.split_once(':')?;
+        let len: usize = len.parse().ok()?;
+        return Some((
+            SqlValue::Text(rest.get(..len)?.to_owned()),
+            rest.get(len..)?,
+        ));
+    }
+    let (v, rest) = s.split_once(';')?;
+    let value = match tag {
+        "i" => SqlValue::Int(v.parse().ok()?),
+        "u" => SqlValue::UInt(v.parse().ok()?),
+        "f" => SqlValue::Float(f64::from_bits(u64::from_str_radix(v, 16).ok()?)),
+        _ => return None,
+    };
+    Some((value, rest))
+}
+
+/// Compares signatures in a time independent of where they differ.
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// URL-safe base64, without padding.
+fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (idx, b)| n | (*b as u32) << (16 - 8 * idx));
+        for idx in 0..=chunk.len() {
+            out.push(BASE64[(n >> (18 - 6 * idx) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+fn unbase64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() < 2 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (idx, c) in chunk.iter().enumerate() {
+            let digit = BASE64.iter().position(|b| b == c)? as u32;
+            n |= digit << (18 - 6 * idx);
+        }
+        for idx in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * idx)) as u8);
+        }
+    }
+    Some(out)
+}
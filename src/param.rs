@@ -0,0 +1,91 @@
+use crate::{Renderer, SQLComp, SQLError, SqlValue, ToSql};
+
+/// A filter value supplied when the query is rendered, so that a query is
+/// built once and run with different values:
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use traits::*;
+/// let mut q = SQLable::new("users");
+/// q.dialect(Dialect::Postgres).filter(vec![
+///     Box::new(SQLFilter { column: "age".into(), filter: param("min_age"), cmp: SQLComp::GEQ }),
+///     Box::new(SQLFilter { column: "country".into(), filter: param("country"), cmp: SQLComp::EQ }),
+/// ]);
+///
+/// let mut values = BTreeMap::new();
+/// values.insert("min_age".to_owned(), SqlValue::Int(18));
+/// values.insert("country".to_owned(), SqlValue::from("fr"));
+/// let (sql, params) = q.prepare_params_with(&values).unwrap();
+/// assert_eq!(sql, "SELECT\n  *\nFROM users\nWHERE\n  (age >= $1)\n  AND (country = $2)\n");
+/// assert_eq!(params, vec![SqlValue::Int(18), SqlValue::from("fr")]);
+///
+/// values.insert("min_age".to_owned(), SqlValue::Int(21));
+/// assert_eq!(q.prepare_params_with(&values).unwrap().1[0], SqlValue::Int(21));
+/// ```
+///
+/// The comparison follows the value as for a `SQLFilter<SqlValue>`: NULL
+/// renders `IS NULL` and a list `IN (...)`, so the SQL, and a driver's
+/// cached statement, stays the same as long as the values keep their
+/// shape. A parameter without a value, or bound to an empty list, fails to
+/// render, as does any parameter with `try_prepare`. `to_sql`, which has
+/// no values, writes the parameter as `:name`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    name: String,
+}
+
+/// The parameter `name`, see `Param`.
+pub fn param(name: &str) -> Param {
+    Param {
+        name: name.to_owned(),
+    }
+}
+
+impl Param {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value<'r>(&self, r: &'r Renderer) -> Result<&'r SqlValue, SQLError> {
+        r.values
+            .get(&self.name)
+            .ok_or_else(|| SQLError::MissingParam(self.name.clone()))
+    }
+
+    /// Runs `render`, telling a `StatementCache` that what it binds comes
+    /// from this parameter.
+    fn bound(
+        &self,
+        r: &mut Renderer,
+        render: impl FnOnce(&mut Renderer) -> Result<String, SQLError>,
+    ) -> Result<String, SQLError> {
+        if let Some(slots) = r.slots.as_mut() {
+            slots.enter(&self.name);
+        }
+        let result = render(r);
+        if let Some(slots) = r.slots.as_mut() {
+            slots.leave();
+        }
+        result
+    }
+}
+
+impl ToSql for Param {
+    /// `:name`, having no values to render.
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, ":{}", self.name)
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let value = self.value(r)?.clone();
+        self.bound(r, |r| value.render(r))
+    }
+
+    fn render_compare(&self, cmp: &SQLComp, r: &mut Renderer) -> Result<String, SQLError> {
+        let value = self.value(r)?.clone();
+        if value.is_empty_list() && !cmp.is_array() {
+            return Err(SQLError::EmptyList(self.name.clone()));
+        }
+        self.bound(r, |r| value.render_compare(cmp, r))
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+use crate::{Condition, Filter, SQLComp, SQLError, SQLFilter, SQLable, SqlValue, TableName};
+
+/// The span of time held by one partition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bucket {
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+    Year,
+}
+
+impl Bucket {
+    /// The first day of the bucket holding `date`.
+    fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Bucket::Day => date,
+            Bucket::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Bucket::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+            Bucket::Year => NaiveDate::from_ymd(date.year(), 1, 1),
+        }
+    }
+
+    /// The first day of the next bucket, from the first day of a bucket.
+    fn next(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Bucket::Day => start + Duration::days(1),
+            Bucket::Week => start + Duration::days(7),
+            Bucket::Month if start.month() == 12 => NaiveDate::from_ymd(start.year() + 1, 1, 1),
+            Bucket::Month => NaiveDate::from_ymd(start.year(), start.month() + 1, 1),
+            Bucket::Year => NaiveDate::from_ymd(start.year() + 1, 1, 1),
+        }
+    }
+}
+
+/// A table split by hand into one table per day, week, month or year of a
+/// timestamp column, named after the bucket's first day with a `strftime`
+/// pattern. Reading a time range then only scans the partitions that hold
+/// it:
+///
+/// ```
+/// # use traits::*;
+/// use chrono::NaiveDate;
+///
+/// let events = Partitioning::new("createdAt", "events_%Y_%m", Bucket::Month);
+/// let from = NaiveDate::from_ymd(2024, 1, 15).and_hms(0, 0, 0);
+/// let to = NaiveDate::from_ymd(2024, 3, 1).and_hms(0, 0, 0);
+///
+/// let names: Vec<String> = events
+///     .partitions(from, to)
+///     .unwrap()
+///     .iter()
+///     .map(|t| t.name.clone())
+///     .collect();
+/// assert_eq!(names, vec!["events_2024_01", "events_2024_02"]);
+///
+/// let mut q = SQLable::new("events e");
+/// events.apply(&mut q, from, to).unwrap();
+/// ```
+///
+/// The pattern may be schema-qualified: `"archive.events_%Y%m%d"`. Weekly
+/// partitions are usually named with `%G` and `%V`, the ISO year and week.
+#[derive(Clone, Debug)]
+pub struct Partitioning {
+    column: String,
+    pattern: String,
+    bucket: Bucket,
+}
+
+impl Partitioning {
+    pub fn new(column: &str, pattern: &str, bucket: Bucket) -> Self {
+        Partitioning {
+            column: column.to_owned(),
+            pattern: pattern.to_owned(),
+            bucket,
+        }
+    }
+
+    /// The partitions holding the rows from `from`, included, to `to`,
+    /// excluded, oldest first. Fails on an empty range or an invalid
+    /// pattern.
+    pub fn partitions(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<TableName>, SQLError> {
+        if from >= to {
+            return Err(SQLError::InvalidFilter(format!(
+                "empty time range from {} to {}",
+                from, to
+            )));
+        }
+        if StrftimeItems::new(&self.pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(SQLError::InvalidFilter(format!(
+                "invalid partition pattern {}",
+                self.pattern
+            )));
+        }
+
+        let mut result = Vec::new();
+        let mut start = self.bucket.start(from.date());
+        while start.and_hms(0, 0, 0) < to {
+            result.push(TableName::parse(&start.format(&self.pattern).to_string()));
+            start = self.bucket.next(start);
+        }
+        Ok(result)
+    }
+
+    /// The filter on the range: `column >= from AND column < to`. The
+    /// partitions hold whole buckets, so it is needed even when reading
+    /// only the partitions.
+    pub fn filters(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<Box<dyn Filter>> {
+        let bound = |at: NaiveDateTime, cmp| -> Box<dyn Filter> {
+            Box::new(SQLFilter {
+                column: self.column.clone().into(),
+                filter: SqlValue::Text(at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                cmp,
+            })
+        };
+        vec![bound(from, SQLComp::GEQ), bound(to, SQLComp::LT)]
+    }
+
+    /// Makes `query` read the range from its partitions: adds `filters` to
+    /// the query's and sets its `partitions`.
+    pub fn apply(
+        &self,
+        query: &mut SQLable,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<(), SQLError> {
+        let partitions = self.partitions(from, to)?;
+        query
+            .filter
+            .to_mut()
+            .extend(self.filters(from, to).into_iter().map(Condition::from));
+        query.partitions(partitions);
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{SQLError, SQLable, SqlValue};
+
+/// Stands for a bound value while rendering parts; never valid SQL.
+pub(crate) const MARK: &str = "\u{0}";
+
+/// A piece of a rendered query: SQL text, or a value to bind in its place.
+///
+/// Drivers that number placeholders themselves take a query as parts, e.g.
+/// sqlx's `QueryBuilder`:
+///
+/// ```ignore
+/// let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new("");
+/// for part in q.prepare_parts()? {
+///     match part {
+///         SqlPart::Sql(sql) => builder.push(sql),
+///         SqlPart::Bind(SqlValue::Int(v)) => builder.push_bind(v),
+///         SqlPart::Bind(SqlValue::Text(v)) => builder.push_bind(v),
+///         // ...
+///     };
+/// }
+/// let rows = builder.build().fetch_all(&pool).await?;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqlPart {
+    Sql(String),
+    Bind(SqlValue),
+}
+
+impl SQLable {
+    /// Renders the query as `prepare_params` does, split at the bound
+    /// values instead of numbering placeholders, for the query's dialect.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("users");
+    /// q.filter(vec![Box::new(SQLFilter {
+    ///     column: "age".into(),
+    ///     filter: 18,
+    ///     cmp: SQLComp::GEQ,
+    /// })]);
+    /// let parts = q.prepare_parts().unwrap();
+    /// assert_eq!(parts[1], SqlPart::Bind(SqlValue::Int(18)));
+    /// ```
+    pub fn prepare_parts(&self) -> Result<Vec<SqlPart>, SQLError> {
+        let mut r = self.renderer();
+        r.params = Some(Vec::new());
+        r.marks = true;
+        let sql = self.render_query(&mut r)?;
+        Ok(split(&sql, r.into_params()))
+    }
+}
+
+fn split(sql: &str, params: Vec<SqlValue>) -> Vec<SqlPart> {
+    let mut result = Vec::with_capacity(params.len() * 2 + 1);
+    let mut values = params.into_iter();
+    for (idx, sql) in sql.split(MARK).enumerate() {
+        if idx > 0 {
+            // the renderer bound exactly one value per mark
+            result.push(SqlPart::Bind(values.next().unwrap_or(SqlValue::Null)));
+        }
+        if !sql.is_empty() {
+            result.push(SqlPart::Sql(sql.to_owned()));
+        }
+    }
+    result
+}
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+
+use crate::{Dialect, ExecError, Execute, SQLError, SQLable, SqlValue};
+
+impl SQLable {
+    /// The statement printing the query's plan, and its parameters:
+    /// `EXPLAIN (COSTS OFF)` on Postgres, `EXPLAIN FORMAT=TREE` on MySQL,
+    /// `EXPLAIN QUERY PLAN` on SQLite. SQL Server only shows plans for a
+    /// whole batch, with `SET SHOWPLAN_TEXT ON`, so it is unsupported.
+    pub fn explain(&self) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let prefix = match self.dialect {
+            Dialect::Postgres => "EXPLAIN (COSTS OFF)\n",
+            Dialect::MySql => "EXPLAIN FORMAT=TREE\n",
+            Dialect::Sqlite => "EXPLAIN QUERY PLAN\n",
+            Dialect::Generic => "EXPLAIN\n",
+            Dialect::MsSql => return Err(SQLError::Unsupported("EXPLAIN in MsSql".to_owned())),
+        };
+        let (sql, params) = self.prepare_params()?;
+        Ok((format!("{}{}", prefix, sql), params))
+    }
+}
+
+/// The plans of a set of named queries, captured from a reference database
+/// and kept, e.g. as JSON with the `serde` feature, to be compared with the
+/// plans of a later run:
+///
+/// ```
+/// # use traits::*;
+/// let mut before = QueryPlans::new();
+/// before.insert("active_users", "Seq Scan on users\n  Filter: (status = $1)");
+/// let mut after = QueryPlans::new();
+/// after.insert(
+///     "active_users",
+///     "Index Scan using users_status on users\n  Index Cond: (status = $1)",
+/// );
+///
+/// let changes = before.diff(&after);
+/// assert_eq!(changes.len(), 1);
+/// print!("{}", changes[0]);
+/// ```
+///
+/// Plans are normalized when inserted or read: estimated costs, row counts
+/// and timings are dropped so that only the plan's shape is compared.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")
+)]
+pub struct QueryPlans {
+    plans: BTreeMap<String, String>,
+}
+
+impl QueryPlans {
+    pub fn new() -> Self {
+        QueryPlans::default()
+    }
+
+    /// Runs `EXPLAIN` for `query` on `conn` and keeps the plan as `name`.
+    /// The plan is read from the last column of each row, one line per row.
+    pub fn capture<C: Execute + ?Sized>(
+        &mut self,
+        name: &str,
+        query: &SQLable,
+        conn: &C,
+    ) -> Result<(), ExecError<C::Error>> {
+        let (sql, params) = query.explain()?;
+        let mut lines = Vec::new();
+        conn.query(&sql, &params, &mut |row| {
+            if row.is_empty() {
+                return Ok(());
+            }
+            lines.push(row.get_at::<String>(row.len() - 1)?);
+            Ok(())
+        })?;
+        self.insert(name, &lines.join("\n"));
+        Ok(())
+    }
+
+    /// Keeps `plan` as the plan of `name`, replacing any previous one.
+    pub fn insert(&mut self, name: &str, plan: &str) {
+        self.plans.insert(name.to_owned(), normalize(plan));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.plans.get(name).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.plans
+            .iter()
+            .map(|(name, plan)| (name.as_str(), plan.as_str()))
+    }
+
+    /// How the plans of `current` differ from these, by query name.
+    pub fn diff(&self, current: &QueryPlans) -> Vec<PlanChange> {
+        let mut changes = Vec::new();
+        for (name, before) in &self.plans {
+            match current.plans.get(name) {
+                None => changes.push(PlanChange::Removed(name.clone())),
+                Some(after) if after != before => changes.push(PlanChange::Changed {
+                    name: name.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for name in current.plans.keys() {
+            if !self.plans.contains_key(name) {
+                changes.push(PlanChange::Added(name.clone()));
+            }
+        }
+        changes
+    }
+}
+
+/// Plans by query name, normalized.
+impl From<BTreeMap<String, String>> for QueryPlans {
+    fn from(plans: BTreeMap<String, String>) -> Self {
+        let mut result = QueryPlans::new();
+        for (name, plan) in plans {
+            result.insert(&name, &plan);
+        }
+        result
+    }
+}
+
+impl From<QueryPlans> for BTreeMap<String, String> {
+    fn from(plans: QueryPlans) -> Self {
+        plans.plans
+    }
+}
+
+/// A difference between two sets of plans, shown as a line diff of the
+/// plans.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlanChange {
+    /// A query only in the current plans.
+    Added(String),
+    /// A query missing from the current plans.
+    Removed(String),
+    Changed {
+        name: String,
+        before: String,
+        after: String,
+    },
+}
+
+impl std::fmt::Display for PlanChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanChange::Added(name) => writeln!(f, "+ plan {}", name),
+            PlanChange::Removed(name) => writeln!(f, "- plan {}", name),
+            PlanChange::Changed {
+                name,
+                before,
+                after,
+            } => {
+                writeln!(f, "~ plan {}", name)?;
+                let before: Vec<&str> = before.lines().collect();
+                let after: Vec<&str> = after.lines().collect();
+                for (sign, line) in line_diff(&before, &after) {
+                    writeln!(f, "  {} {}", sign, line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The lines of `a` and `b` in order, marked `-` when only in `a`, `+` when
+/// only in `b` and ` ` when in both, after their longest common subsequence.
+fn line_diff<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(char, &'a str)> {
+    let mut common = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            common[i][j] = if a[i] == b[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            result.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            result.push(('-', a[i]));
+            i += 1;
+        } else {
+            result.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    result.extend(a[i..].iter().map(|line| ('-', *line)));
+    result.extend(b[j..].iter().map(|line| ('+', *line)));
+    result
+}
+
+/// Drops what varies between runs of the same plan: parenthesized cost,
+/// row and timing estimates (`(cost=0.00..1.10 rows=10 width=4)`,
+/// `(actual time=...)`), trailing spaces and blank lines.
+fn normalize(plan: &str) -> String {
+    plan.lines()
+        .map(|line| strip_estimates(line).trim_end().to_owned())
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn strip_estimates(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('(') {
+        let inner = &rest[start + 1..];
+        let is_estimate = ["cost=", "actual ", "rows="]
+            .iter()
+            .any(|prefix| inner.starts_with(prefix));
+        match inner.find(')') {
+            Some(end) if is_estimate => {
+                result.push_str(rest[..start].trim_end());
+                rest = &inner[end + 1..];
+            }
+            _ => {
+                result.push_str(&rest[..=start]);
+                rest = inner;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
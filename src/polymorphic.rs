@@ -0,0 +1,144 @@
+use crate::{And, Filter, Or, SQLComp, SQLError, SQLFilter, SQLable, SqlValue, TableName};
+
+/// A polymorphic association: rows that belong to a row of one of several
+/// tables, named by a type column and keyed by an id column, e.g. comments
+/// on posts or photos. Filtering on the id without the type matches rows
+/// of another table that happen to share the id, so the helpers keep the
+/// two paired:
+///
+/// ```
+/// # use traits::*;
+/// let commentable = Polymorphic::new("commentable").table("Post", "posts");
+///
+/// let mut comments = SQLable::new("comments");
+/// comments.filter(vec![Box::new(commentable.any_of(vec![("Post", 1), ("Photo", 7), ("Post", 2)]))]);
+/// assert_eq!(
+///     comments.prepare(),
+///     "SELECT\n  *\nFROM comments\nWHERE\n  (((commentable_type = 'Post') AND (commentable_id IN (1,2))) OR ((commentable_type = 'Photo') AND (commentable_id = 7)))\n"
+/// );
+///
+/// let parents = commentable.parent_queries(vec![("Post", 1), ("Photo", 7)]).unwrap();
+/// assert_eq!(parents[0].0, "Post");
+/// assert_eq!(parents[0].1.prepare(), "SELECT\n  *\nFROM posts\nWHERE\n  (id = 1)\n");
+/// assert_eq!(parents[1].1.prepare(), "SELECT\n  *\nFROM Photo\nWHERE\n  (id = 7)\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Polymorphic {
+    pub type_column: String,
+    pub id_column: String,
+    /// The key the id column refers to in each table.
+    pub key: String,
+    /// The table of each type; other types name their table.
+    pub tables: Vec<(String, String)>,
+}
+
+impl Polymorphic {
+    /// The association of the `{name}_type` and `{name}_id` columns.
+    pub fn new(name: &str) -> Self {
+        Polymorphic::columns(&format!("{}_type", name), &format!("{}_id", name))
+    }
+
+    pub fn columns(type_column: &str, id_column: &str) -> Self {
+        Polymorphic {
+            type_column: type_column.to_owned(),
+            id_column: id_column.to_owned(),
+            key: "id".to_owned(),
+            tables: Vec::new(),
+        }
+    }
+
+    /// Sets the table of rows of type `ty`.
+    pub fn table(mut self, ty: &str, table: &str) -> Self {
+        self.tables.push((ty.to_owned(), table.to_owned()));
+        self
+    }
+
+    /// Sets the key the id column refers to, `id` unless set.
+    pub fn key(mut self, column: &str) -> Self {
+        self.key = column.to_owned();
+        self
+    }
+
+    /// Rows that belong to the row `id` of type `ty`.
+    pub fn of(&self, ty: &str, id: impl Into<SqlValue>) -> And {
+        self.of_any(ty, vec![id.into()])
+    }
+
+    /// Rows that belong to any of the rows `ids` of type `ty`.
+    pub fn of_any(&self, ty: &str, ids: Vec<SqlValue>) -> And {
+        And(vec![
+            Box::new(SQLFilter {
+                column: self.type_column.clone().into(),
+                filter: ty.to_owned(),
+                cmp: SQLComp::EQ,
+            }),
+            Box::new(SQLFilter {
+                column: self.id_column.clone().into(),
+                filter: SqlValue::List(ids),
+                cmp: SQLComp::EQ,
+            }),
+        ])
+    }
+
+    /// Rows that belong to any of the `(type, id)` parents, one condition
+    /// per type.
+    pub fn any_of<S: AsRef<str>, I: Into<SqlValue>>(
+        &self,
+        parents: impl IntoIterator<Item = (S, I)>,
+    ) -> Or {
+        let filters = by_type(parents)
+            .into_iter()
+            .map(|(ty, ids)| Box::new(self.of_any(&ty, ids)) as Box<dyn Filter>)
+            .collect();
+        Or(filters)
+    }
+
+    /// `children` narrowed to the rows that belong to the rows `ids` of
+    /// type `ty`.
+    pub fn children(&self, children: &SQLable, ty: &str, ids: Vec<SqlValue>) -> SQLable {
+        let mut query = children.clone();
+        query.add_filter(Box::new(self.of_any(ty, ids)));
+        query
+    }
+
+    /// A query for the parents of each type among `parents`, with the type:
+    /// the rows of its table whose key is among the ids. Fails with
+    /// `SQLError::InvalidName` on a type without a table that is no valid
+    /// table name, since types are usually read from the database.
+    pub fn parent_queries<S: AsRef<str>, I: Into<SqlValue>>(
+        &self,
+        parents: impl IntoIterator<Item = (S, I)>,
+    ) -> Result<Vec<(String, SQLable)>, SQLError> {
+        let mut result = Vec::new();
+        for (ty, ids) in by_type(parents) {
+            let table = match self.tables.iter().find(|(t, _)| *t == ty) {
+                Some((_, table)) => TableName::parse(table),
+                None => TableName::checked(&ty)?,
+            };
+            let mut query = SQLable::from_table(table);
+            query.filter(vec![Box::new(SQLFilter {
+                column: self.key.clone().into(),
+                filter: SqlValue::List(ids),
+                cmp: SQLComp::EQ,
+            })]);
+            result.push((ty, query));
+        }
+        Ok(result)
+    }
+}
+
+/// The ids of each type, types in the order they first appear.
+fn by_type<S: AsRef<str>, I: Into<SqlValue>>(
+    parents: impl IntoIterator<Item = (S, I)>,
+) -> Vec<(String, Vec<SqlValue>)> {
+    let mut result: Vec<(String, Vec<SqlValue>)> = Vec::new();
+    for (ty, id) in parents {
+        let id = id.into();
+        match result.iter_mut().find(|(t, _)| t == ty.as_ref()) {
+            Some((_, ids)) if ids.contains(&id) => {}
+            Some((_, ids)) => ids.push(id),
+            None => result.push((ty.as_ref().to_owned(), vec![id])),
+        }
+    }
+    result
+}
@@ -0,0 +1,181 @@
+use crate::{Condition, Filter, Order, SQLComp, SQLError, SQLFilter, SQLable, SqlValue, ValueType};
+
+/// Translates REST-style query strings into filters, ordering and
+/// pagination, allowing only the listed columns:
+///
+/// ```
+/// # use traits::*;
+/// let params = QueryParams::new(&[
+///     ("age", ValueType::Integer),
+///     ("status", ValueType::Text),
+///     ("createdAt", ValueType::Text),
+/// ]);
+/// let mut q = SQLable::new("users");
+/// params
+///     .apply(&mut q, "age__gte=18&status__in=a,b&sort=-createdAt&limit=20&page=2")
+///     .unwrap();
+/// ```
+///
+/// A filter is `column=value` or `column__op=value`, with `op` one of `eq`,
+/// `ne`, `gt`, `gte`, `lt`, `lte`, `in` and `nin` (comma-separated lists),
+/// or `isnull` (`true` or `false`). `sort` takes comma-separated columns,
+/// descending when prefixed with `-`. `limit` (capped by `max_limit`) and
+/// `offset` or `page` (1-based, pages of `limit` rows) paginate. Anything
+/// else is rejected with `SQLError::InvalidFilter`.
+#[derive(Clone, Debug)]
+pub struct QueryParams {
+    columns: Vec<(String, ValueType)>,
+    max_limit: u64,
+}
+
+impl QueryParams {
+    pub fn new(columns: &[(&str, ValueType)]) -> Self {
+        QueryParams {
+            columns: columns
+                .iter()
+                .map(|(name, ty)| (name.to_string(), *ty))
+                .collect(),
+            max_limit: 100,
+        }
+    }
+
+    /// Caps `limit`, and is the page size when `page` is given without a
+    /// limit. Defaults to 100.
+    pub fn max_limit(mut self, n: u64) -> Self {
+        self.max_limit = n.max(1);
+        self
+    }
+
+    /// Adds the filters, ordering and pagination of `query_string`, with or
+    /// without its leading `?`, to `query`. Nothing is added on error.
+    pub fn apply(&self, query: &mut SQLable, query_string: &str) -> Result<(), SQLError> {
+        let mut filters: Vec<Box<dyn Filter>> = Vec::new();
+        let mut order_by = Vec::new();
+        let mut limit = None;
+        let mut offset = None;
+        let mut page = None;
+
+        let pairs = query_string
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|pair| !pair.is_empty());
+        for pair in pairs {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let (key, value) = (decode(key)?, decode(value)?);
+            match key.as_str() {
+                "sort" => {
+                    for col in value.split(',').filter(|c| !c.is_empty()) {
+                        let (col, order) = match col.strip_prefix('-') {
+                            Some(col) => (col, Order::Desc),
+                            None => (col, Order::Asc),
+                        };
+                        self.column(col)?;
+                        order_by.push((col.to_owned(), order));
+                    }
+                }
+                "limit" => limit = Some(number(&key, &value)?),
+                "offset" => offset = Some(number(&key, &value)?),
+                "page" => page = Some(number(&key, &value)?.max(1)),
+                _ => filters.push(self.filter(&key, &value)?),
+            }
+        }
+
+        if !filters.is_empty() {
+            query
+                .filter
+                .to_mut()
+                .extend(filters.into_iter().map(Condition::from));
+        }
+        for (col, order) in order_by {
+            query.order_by(col.as_str(), order);
+        }
+        let limit = limit.map(|n| n.min(self.max_limit));
+        if let Some(page) = page {
+            let size = limit.unwrap_or(self.max_limit);
+            query.limit(size).offset((page - 1).saturating_mul(size));
+        } else {
+            if let Some(limit) = limit {
+                query.limit(limit);
+            }
+            if let Some(offset) = offset {
+                query.offset(offset);
+            }
+        }
+        Ok(())
+    }
+
+    fn column(&self, name: &str) -> Result<ValueType, SQLError> {
+        self.columns
+            .iter()
+            .find(|(col, _)| col == name)
+            .map(|(_, ty)| *ty)
+            .ok_or_else(|| SQLError::InvalidFilter(format!("unknown column {}", name)))
+    }
+
+    fn filter(&self, key: &str, value: &str) -> Result<Box<dyn Filter>, SQLError> {
+        let (col, op) = key.rsplit_once("__").unwrap_or((key, "eq"));
+        let ty = self.column(col)?;
+        let invalid = |what: String| SQLError::InvalidFilter(format!("{}: {}", key, what));
+        let scalar = |v: &str| {
+            ty.parse(v)
+                .ok_or_else(|| invalid(format!("expected {:?}, got {}", ty, v)))
+        };
+        let list = || {
+            value
+                .split(',')
+                .map(scalar)
+                .collect::<Result<Vec<SqlValue>, SQLError>>()
+                .map(SqlValue::List)
+        };
+
+        let (filter, cmp) = match op {
+            "eq" => (scalar(value)?, SQLComp::EQ),
+            "ne" => (scalar(value)?, SQLComp::NEQ),
+            "gt" => (scalar(value)?, SQLComp::GT),
+            "gte" => (scalar(value)?, SQLComp::GEQ),
+            "lt" => (scalar(value)?, SQLComp::LT),
+            "lte" => (scalar(value)?, SQLComp::LEQ),
+            "in" => (list()?, SQLComp::EQ),
+            "nin" => (list()?, SQLComp::NEQ),
+            "isnull" => match value {
+                "true" => (SqlValue::Null, SQLComp::EQ),
+                "false" => (SqlValue::Null, SQLComp::NEQ),
+                _ => return Err(invalid("expected true or false".to_owned())),
+            },
+            _ => return Err(invalid(format!("unknown operator {}", op))),
+        };
+        Ok(Box::new(SQLFilter {
+            column: col.to_owned().into(),
+            filter,
+            cmp,
+        }))
+    }
+}
+
+fn number(key: &str, value: &str) -> Result<u64, SQLError> {
+    value
+        .parse()
+        .map_err(|_| SQLError::InvalidFilter(format!("{}: {} is not a count", key, value)))
+}
+
+/// Percent-decodes a query-string component, `+` standing for a space.
+fn decode(s: &str) -> Result<String, SQLError> {
+    let invalid = || SQLError::InvalidFilter(format!("malformed query string {}", s));
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [
+                    iter.next().ok_or_else(invalid)?,
+                    iter.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
@@ -0,0 +1,102 @@
+use crate::{unrendered, Filter, Renderer, SQLError, SqlValue, ToSql};
+
+/// A SQL fragment emitted verbatim, for clauses the builder cannot express.
+///
+/// It works both as a filter, `Raw::new("date_trunc('day', created_at) >= now()")`,
+/// and as a filter value, e.g. `created_at > now() - interval '1 day'` with
+/// `Raw::new("now() - interval '1 day'")`. Nothing is escaped or checked:
+/// never build the fragment from user input, pass values with `raw` instead.
+/// With the `macros` feature, `raw_sql!` checks the fragment's syntax at
+/// compile time.
+pub struct Raw {
+    sql: String,
+    params: Option<Vec<SqlValue>>,
+}
+
+/// A fragment whose `?` placeholders take `params`, in order:
+/// `raw("date_trunc('day', created_at) >= ?", vec!["2024-01-01".into()])`.
+///
+/// The values are bound like any other filter value, so the placeholders are
+/// renumbered to fit the rest of the query (`$3` on Postgres if two values
+/// were bound before). A `?` inside a quoted string or identifier is not a
+/// placeholder.
+pub fn raw(sql: impl Into<String>, params: Vec<SqlValue>) -> Raw {
+    Raw {
+        sql: sql.into(),
+        params: Some(params),
+    }
+}
+
+impl Raw {
+    /// A fragment without parameters, `?` included as written.
+    pub fn new(sql: impl Into<String>) -> Self {
+        Raw {
+            sql: sql.into(),
+            params: None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.sql
+    }
+
+    fn render_raw(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let params = match &self.params {
+            Some(params) => params,
+            None => return Ok(self.sql.clone()),
+        };
+
+        let mut result = String::with_capacity(self.sql.len());
+        let mut values = params.iter();
+        let mut placeholders = 0;
+        let mut quote: Option<char> = None;
+        for ch in self.sql.chars() {
+            match quote {
+                // a doubled quote closes and reopens, which comes out right
+                Some(q) if ch == q => quote = None,
+                Some(_) => {}
+                None if matches!(ch, '\'' | '"' | '`') => quote = Some(ch),
+                None if ch == '?' => {
+                    placeholders += 1;
+                    if let Some(v) = values.next() {
+                        result.push_str(&v.render(r)?);
+                    }
+                    continue;
+                }
+                None => {}
+            }
+            result.push(ch);
+        }
+
+        if placeholders != params.len() {
+            return Err(SQLError::ParamCount {
+                placeholders,
+                params: params.len(),
+            });
+        }
+        Ok(result)
+    }
+}
+
+impl Filter for Raw {
+    fn apply_filter(&self) -> String {
+        self.to_sql()
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.render_raw(r)
+    }
+}
+
+impl ToSql for Raw {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        match self.render_raw(&mut Renderer::default()) {
+            Ok(sql) => out.write_str(&sql),
+            Err(e) => out.write_str(&unrendered(&e)),
+        }
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        self.render_raw(r)
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use crate::exec::NamedRow;
+use crate::{
+    ExecError, Execute, FromRow, Row, SQLComp, SQLError, SQLFilter, SQLable, Schema, SqlValue,
+    TableName, ToSql,
+};
+
+/// A one-to-many relation: the rows of a child table whose `foreign_key`
+/// equals the `key` of a parent row, loaded for many parents at once with
+/// `SQLable::fetch_related`.
+#[derive(Clone, Debug)]
+pub struct Relation {
+    children: SQLable,
+    foreign_key: String,
+    key: String,
+}
+
+impl Relation {
+    /// The rows of `children` whose `foreign_key` column equals the parent's
+    /// `key` column. The query may sort or filter the children further.
+    pub fn new(children: SQLable, foreign_key: &str, key: &str) -> Self {
+        Relation {
+            children,
+            foreign_key: foreign_key.to_owned(),
+            key: key.to_owned(),
+        }
+    }
+
+    /// The relation the single-column foreign key of `child` to `parent`
+    /// in `schema` declares, with `ColumnDef::references` or
+    /// `#[sql(references = "...")]`, or with `TableDef::foreign_key`.
+    ///
+    /// Fails if either table is missing from `schema`, with
+    /// `SQLError::NoJoinPath` if `child` has no foreign key to `parent`, and
+    /// with `SQLError::Ambiguous` if it has several.
+    pub fn of(schema: &Schema, parent: &str, child: &str) -> Result<Self, SQLError> {
+        let parent_def = schema.get(&TableName::parse(parent))?;
+        let child_def = schema.get(&TableName::parse(child))?;
+        let mut keys = child_def.all_foreign_keys().into_iter().filter(|fk| {
+            schema
+                .get(&fk.table)
+                .is_ok_and(|def| std::ptr::eq(def, parent_def))
+        });
+        let fk = match (keys.next(), keys.next()) {
+            (Some(fk), None) => fk,
+            (Some(_), Some(_)) => {
+                return Err(SQLError::Ambiguous(format!(
+                    "relation, {} has several foreign keys to {}",
+                    child, parent
+                )))
+            }
+            (None, _) => {
+                return Err(SQLError::NoJoinPath {
+                    from: parent.to_owned(),
+                    to: child.to_owned(),
+                })
+            }
+        };
+        match (&fk.columns[..], &fk.references[..]) {
+            ([column], [key]) => Ok(Relation::new(SQLable::new(child), column, key)),
+            _ => Err(SQLError::Unsupported(format!(
+                "relation over the foreign key {} of several columns",
+                fk.name
+            ))),
+        }
+    }
+
+    /// The query for the children of the parents with `keys`:
+    /// `WHERE foreign_key IN (keys)` added to the relation's query.
+    pub fn query(&self, keys: Vec<SqlValue>) -> SQLable {
+        let mut query = self.children.clone();
+        query.add_filter(Box::new(SQLFilter {
+            column: self.foreign_key.clone().into(),
+            filter: SqlValue::List(keys),
+            cmp: SQLComp::EQ,
+        }));
+        query
+    }
+}
+
+/// A parent row with its children, see `SQLable::fetch_related`.
+pub type WithChildren<P, C> = (P, Vec<C>);
+
+/// The value of `column` of `row`, a key of the relation.
+fn key_value(row: &dyn Row, column: &str) -> Result<SqlValue, SQLError> {
+    let idx = row
+        .index_of(column)
+        .ok_or_else(|| SQLError::Decode(format!("no column {}", column)))?;
+    row.value(idx)
+}
+
+impl SQLable {
+    /// Runs the query on `conn`, then a single query for the children of
+    /// all the rows through `relation`, and pairs each row with its
+    /// children, in the order of both queries. The parent rows must hold
+    /// the relation's key, and the children its foreign key; a key on
+    /// several parent rows gets its children on the first.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// # struct Db;
+    /// # impl Execute for Db {
+    /// #     type Error = ();
+    /// #     fn query(
+    /// #         &self,
+    /// #         sql: &str,
+    /// #         _: &[SqlValue],
+    /// #         each: &mut dyn FnMut(&dyn Row) -> Result<(), SQLError>,
+    /// #     ) -> Result<(), ExecError<()>> {
+    /// #         let (columns, rows) = match sql.contains("FROM customers") {
+    /// #             true => (["id", "name"], vec![[1.into(), "ann".into()], [2.into(), "bob".into()]]),
+    /// #             false => (["id", "customer_id"], vec![[10.into(), 1.into()], [11.into(), 1.into()]]),
+    /// #         };
+    /// #         for values in rows {
+    /// #             let columns = columns.iter().map(|c| c.to_string()).collect();
+    /// #             each(&Record { columns, values: values.to_vec() })?;
+    /// #         }
+    /// #         Ok(())
+    /// #     }
+    /// #     fn execute(&self, _: &str, _: &[SqlValue]) -> Result<u64, ExecError<()>> {
+    /// #         Ok(0)
+    /// #     }
+    /// # }
+    /// let schema = Schema::new()
+    ///     .table(TableDef::new("customers").column(ColumnDef::of::<i64>("id")))
+    ///     .table(
+    ///         TableDef::new("orders")
+    ///             .column(ColumnDef::of::<i64>("id"))
+    ///             .column(ColumnDef::of::<i64>("customerId").references("customers", "id")),
+    ///     );
+    /// let orders = Relation::of(&schema, "customers", "orders").unwrap();
+    /// assert_eq!(
+    ///     orders.query(vec![1.into(), 2.into()]).prepare(),
+    ///     "SELECT\n  *\nFROM orders\nWHERE\n  (customer_id IN (1,2))\n"
+    /// );
+    ///
+    /// let customers: Vec<((i64, String), Vec<(i64, i64)>)> =
+    ///     SQLable::new("customers").fetch_related(&Db, &orders).unwrap();
+    /// assert_eq!(
+    ///     customers,
+    ///     vec![((1, "ann".to_owned()), vec![(10, 1), (11, 1)]), ((2, "bob".to_owned()), vec![])]
+    /// );
+    /// ```
+    pub fn fetch_related<P: FromRow, C: FromRow, E: Execute + ?Sized>(
+        &self,
+        conn: &E,
+        relation: &Relation,
+    ) -> Result<Vec<WithChildren<P, C>>, ExecError<E::Error>> {
+        let (sql, params) = self.prepare_params()?;
+        let naming = self.naming;
+        let mut parents: Vec<WithChildren<P, C>> = Vec::new();
+        let mut keys: Vec<SqlValue> = Vec::new();
+        // the first parent of each key, by its literal
+        let mut by_key: BTreeMap<String, usize> = BTreeMap::new();
+        conn.query(&sql, &params, &mut |row| {
+            let row = NamedRow { row, naming };
+            let key = key_value(&row, &relation.key)?;
+            if let Entry::Vacant(entry) = by_key.entry(key.to_sql()) {
+                entry.insert(parents.len());
+                keys.push(key);
+            }
+            parents.push((P::from_row(&row)?, Vec::new()));
+            Ok(())
+        })?;
+        if keys.is_empty() {
+            return Ok(parents);
+        }
+
+        let children = relation.query(keys);
+        let (sql, params) = children.prepare_params()?;
+        let naming = children.naming;
+        conn.query(&sql, &params, &mut |row| {
+            let row = NamedRow { row, naming };
+            let key = key_value(&row, &relation.foreign_key)?.to_sql();
+            if let Some(&idx) = by_key.get(&key) {
+                parents[idx].1.push(C::from_row(&row)?);
+            }
+            Ok(())
+        })?;
+        Ok(parents)
+    }
+}
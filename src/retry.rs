@@ -0,0 +1,208 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{DbErrorKind, ExecError, Execute, FromRow, Insert, SQLError, SQLable, SqlValue};
+
+/// Re-runs a transaction that failed with a serialization failure or a
+/// deadlock, waiting a jittered, exponentially growing delay in between.
+///
+/// Only transactions made entirely of `SQLable` statements are retried:
+/// they are rendered once up front, so every attempt sends exactly the same
+/// statements and parameters.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one.
+    pub max_attempts: u32,
+    /// The delay cap before the second attempt, doubled after each failure.
+    pub base_delay: Duration,
+    /// The largest delay cap.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why `RetryPolicy::run` gave up.
+#[derive(Debug)]
+pub enum RetryError<E> {
+    /// A statement could not be rendered; nothing was executed.
+    Render(SQLError),
+    /// The last attempt failed, with an error that is not retried or after
+    /// `attempts` attempts.
+    Execute { error: E, attempts: u32 },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryError::Render(e) => write!(f, "{}", e),
+            RetryError::Execute { error, attempts: 1 } => write!(f, "{}", error),
+            RetryError::Execute { error, attempts } => {
+                write!(f, "{} (after {} attempts)", error, attempts)
+            }
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for RetryError<E> {}
+
+impl RetryPolicy {
+    /// Whether a transaction that failed with `kind` on its `attempt`-th
+    /// (1-based) attempt is run again.
+    pub fn should_retry(&self, kind: DbErrorKind, attempt: u32) -> bool {
+        attempt < self.max_attempts
+            && matches!(
+                kind,
+                DbErrorKind::SerializationFailure | DbErrorKind::Deadlock
+            )
+    }
+
+    /// The delay after the `attempt`-th failure: uniformly random up to
+    /// `base_delay * 2^(attempt - 1)`, capped at `max_delay`, so concurrent
+    /// transactions that collided do not collide again.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        let cap = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        cap.mul_f64(jitter(attempt))
+    }
+
+    /// Renders `statements` and passes them to `execute`, which runs them
+    /// in one transaction. `classify` tells the policy what went wrong.
+    pub fn run<T, E, F, C>(
+        &self,
+        statements: &[&SQLable],
+        mut execute: F,
+        classify: C,
+    ) -> Result<T, RetryError<E>>
+    where
+        F: FnMut(&[(String, Vec<SqlValue>)]) -> Result<T, E>,
+        C: Fn(&E) -> DbErrorKind,
+    {
+        let prepared = statements
+            .iter()
+            .map(|q| q.prepare_params())
+            .collect::<Result<Vec<_>, SQLError>>()
+            .map_err(RetryError::Render)?;
+
+        let mut attempt = 1;
+        loop {
+            match execute(&prepared) {
+                Ok(result) => return Ok(result),
+                Err(error) if self.should_retry(classify(&error), attempt) => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(error) => {
+                    return Err(RetryError::Execute {
+                        error,
+                        attempts: attempt,
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Runs `query` on a connection from `connect` as `SQLable::fetch`
+    /// does, taking a new connection for each attempt so that a pool can
+    /// replace a broken one. Besides the failures `should_retry` accepts,
+    /// a read is retried after a connection failure.
+    ///
+    /// `connect` is typically `pool.get()` wrapped in the type implementing
+    /// `Execute`. `ExecError::Sql` errors, a query that cannot be rendered
+    /// or rows that cannot be read, are never retried.
+    pub fn fetch<T, C, F, K>(
+        &self,
+        query: &SQLable,
+        mut connect: F,
+        classify: K,
+    ) -> Result<Vec<T>, RetryError<ExecError<C::Error>>>
+    where
+        T: FromRow,
+        C: Execute,
+        F: FnMut() -> Result<C, C::Error>,
+        K: Fn(&C::Error) -> DbErrorKind,
+    {
+        self.attempts(
+            || query.fetch(&connect().map_err(ExecError::Database)?),
+            |kind, attempt| {
+                self.should_retry(kind, attempt)
+                    || (kind == DbErrorKind::ConnectionFailure && attempt < self.max_attempts)
+            },
+            classify,
+        )
+    }
+
+    /// Runs `insert` on a connection from `connect` as `Insert::execute`
+    /// does, taking a new connection for each attempt. Only the failures
+    /// `should_retry` accepts are retried: after a connection failure the
+    /// rows may have been inserted.
+    pub fn execute<C, F, K>(
+        &self,
+        insert: &Insert,
+        mut connect: F,
+        classify: K,
+    ) -> Result<u64, RetryError<ExecError<C::Error>>>
+    where
+        C: Execute,
+        F: FnMut() -> Result<C, C::Error>,
+        K: Fn(&C::Error) -> DbErrorKind,
+    {
+        self.attempts(
+            || insert.execute(&connect().map_err(ExecError::Database)?),
+            |kind, attempt| self.should_retry(kind, attempt),
+            classify,
+        )
+    }
+
+    fn attempts<T, E, F, R, K>(
+        &self,
+        mut run: F,
+        retry: R,
+        classify: K,
+    ) -> Result<T, RetryError<ExecError<E>>>
+    where
+        F: FnMut() -> Result<T, ExecError<E>>,
+        R: Fn(DbErrorKind, u32) -> bool,
+        K: Fn(&E) -> DbErrorKind,
+    {
+        let mut attempt = 1;
+        loop {
+            match run() {
+                Ok(result) => return Ok(result),
+                Err(ExecError::Database(e)) if retry(classify(&e), attempt) => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(error) => {
+                    return Err(RetryError::Execute {
+                        error,
+                        attempts: attempt,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// A number in `[0, 1)`, good enough to spread out retries without pulling
+/// in a random number generator.
+fn jitter(attempt: u32) -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos() as u64);
+    // xorshift64*
+    let mut x = (nanos ^ ((attempt as u64) << 32)) | 1;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    let x = x.wrapping_mul(0x2545f4914f6cdd1d);
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Filter, FilterSpec, Labeled, SQLError, ValueType};
+
+/// Business rules over named fields, kept as data in a config file or a
+/// table and compiled to filters, e.g. from JSON:
+///
+/// ```
+/// # use traits::*;
+/// let rules: RuleSet = serde_json::from_str(r#"{
+///     "fields": [
+///         {"name": "customer_age", "column": "age", "type": "integer"},
+///         {"name": "region", "column": "countryCode", "type": "text"}
+///     ],
+///     "rules": [
+///         {"name": "adults_only", "when": {"col": "customer_age", "op": ">=", "value": 18}},
+///         {"name": "eu_launch", "flag": "eu", "when": {"not": {"col": "region", "op": "in", "value": ["fr", "de"]}}}
+///     ]
+/// }"#).unwrap();
+///
+/// let mut q = SQLable::new("customers");
+/// q.filter(rules.compile(|flag| flag == "eu").unwrap());
+/// assert_eq!(
+///     q.prepare(),
+///     "SELECT\n  *\nFROM customers\nWHERE\n  (age >= 18)\n  AND (NOT (country_code IN ('fr','de')))\n"
+/// );
+/// ```
+///
+/// Conditions are `FilterSpec`s whose `col` names a field: rules only
+/// reach the columns their fields map to, compared with values of the
+/// field's type, so rules edited outside the code stay safe to run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub fields: Vec<Field>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// A field rules may refer to: the column it is stored in, and the type
+/// of the values it is compared with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    pub column: String,
+    #[serde(rename = "type")]
+    pub ty: ValueType,
+}
+
+/// A named condition, applied unless it has a `flag` that is off.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<String>,
+    pub when: FilterSpec,
+}
+
+impl RuleSet {
+    /// The filters of the rules whose flag `enabled` accepts, in order,
+    /// each `Labeled` `rule:<name>`. Fails on the first rule that refers
+    /// to an unknown field or compares one with a value of another type.
+    pub fn compile(
+        &self,
+        enabled: impl Fn(&str) -> bool,
+    ) -> Result<Vec<Box<dyn Filter>>, SQLError> {
+        let columns: Vec<(&str, ValueType)> = self
+            .fields
+            .iter()
+            .map(|f| (f.column.as_str(), f.ty))
+            .collect();
+
+        let mut filters: Vec<Box<dyn Filter>> = Vec::new();
+        for rule in &self.rules {
+            if !rule.flag.as_deref().is_none_or(&enabled) {
+                continue;
+            }
+            let in_rule = |e| match e {
+                SQLError::InvalidFilter(what) => {
+                    SQLError::InvalidFilter(format!("rule {}: {}", rule.name, what))
+                }
+                e => e,
+            };
+            let filter = self
+                .columns_of(&rule.when)
+                .and_then(|spec| spec.to_filter(&columns))
+                .map_err(in_rule)?;
+            filters.push(Box::new(Labeled(format!("rule:{}", rule.name), filter)));
+        }
+        Ok(filters)
+    }
+
+    /// `spec` with its fields replaced by their columns.
+    fn columns_of(&self, spec: &FilterSpec) -> Result<FilterSpec, SQLError> {
+        let all = |specs: &[FilterSpec]| {
+            specs
+                .iter()
+                .map(|s| self.columns_of(s))
+                .collect::<Result<Vec<FilterSpec>, SQLError>>()
+        };
+        Ok(match spec {
+            FilterSpec::And { and } => FilterSpec::And { and: all(and)? },
+            FilterSpec::Or { or } => FilterSpec::Or { or: all(or)? },
+            FilterSpec::Not { not } => FilterSpec::Not {
+                not: Box::new(self.columns_of(not)?),
+            },
+            FilterSpec::Compare { col, op, value } => {
+                let field = self
+                    .fields
+                    .iter()
+                    .find(|f| f.name == *col)
+                    .ok_or_else(|| SQLError::InvalidFilter(format!("unknown field {}", col)))?;
+                FilterSpec::Compare {
+                    col: field.column.clone(),
+                    op: op.clone(),
+                    value: value.clone(),
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,712 @@
+use crate::{
+    quote, CastType, Dialect, Insert, NamingStrategy, Quoting, Renderer, SQLError, SqlType,
+    TableName,
+};
+
+/// A Rust enum stored as a database enum, usually through
+/// `#[derive(SqlEnum)]` with the `macros` feature:
+///
+/// ```
+/// # #[cfg(feature = "macros")] {
+/// # use traits::*;
+/// #[derive(SqlEnum)]
+/// #[sql(name = "order_status")]
+/// enum OrderStatus {
+///     #[sql(rename = "pending")]
+///     Pending,
+///     #[sql(rename = "shipped")]
+///     Shipped,
+/// }
+///
+/// assert_eq!(OrderStatus::VARIANTS, &["pending", "shipped"]);
+/// assert_eq!(OrderStatus::Shipped.to_sql(), "'shipped'");
+/// # }
+/// ```
+///
+/// The derive also makes the enum a filter and insert value, written as
+/// its label.
+pub trait SqlEnum {
+    /// The name of the type, created with `TableDef::create_types` on Postgres.
+    const TYPE_NAME: &'static str;
+    /// The labels, in declaration order.
+    const VARIANTS: &'static [&'static str];
+
+    fn as_str(&self) -> &'static str;
+}
+
+/// A struct mapped to a table, usually through `#[derive(Table)]` with the
+/// `macros` feature. Doc comments on the struct and its fields become the
+/// table and column comments:
+///
+/// ```
+/// # #[cfg(feature = "macros")] {
+/// # use traits::*;
+/// /// Registered accounts.
+/// #[derive(Table)]
+/// #[sql(name = "accounts")]
+/// struct Account {
+///     id: i64,
+///     /// Shown to other users.
+///     display_name: Option<String>,
+/// }
+///
+/// let ddl = Account::table_def().create_statements(Dialect::Postgres).unwrap();
+/// assert_eq!(ddl[0], "CREATE TABLE accounts (\n  id BIGINT NOT NULL,\n  display_name TEXT\n)\n");
+/// assert_eq!(ddl[1], "COMMENT ON TABLE accounts IS 'Registered accounts.'\n");
+/// # }
+/// ```
+///
+/// Columns are named after the fields unless renamed with
+/// `#[sql(rename = "...")]`, and are `NOT NULL` unless the field is an
+/// `Option`. The table is named after the struct in snake case unless
+/// named with `#[sql(name = "...")]`.
+///
+/// Constraints are declared on the fields:
+///
+/// ```
+/// # #[cfg(feature = "macros")] {
+/// # use traits::*;
+/// #[derive(Table)]
+/// struct Order {
+///     #[sql(primary_key)]
+///     id: i64,
+///     #[sql(references = "accounts(id)")]
+///     account_id: i64,
+///     #[sql(unique)]
+///     reference: String,
+///     #[sql(default = "CURRENT_TIMESTAMP")]
+///     created_at: String,
+/// }
+///
+/// let def = Order::table_def();
+/// assert_eq!(def.primary_key, vec!["id".to_owned()]);
+/// let sql = def.create_table(Dialect::Postgres).unwrap();
+/// assert!(sql.contains("FOREIGN KEY (account_id) REFERENCES accounts (id)"));
+/// # }
+/// ```
+///
+/// Several `primary_key` fields make a composite key, in field order.
+pub trait Table {
+    fn table_def() -> TableDef;
+}
+
+/// The enum type of a column: `CREATE TYPE` on Postgres, an inline `ENUM`
+/// on MySQL, and text with a `CHECK` on the label elsewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnumType {
+    pub name: &'static str,
+    pub variants: &'static [&'static str],
+}
+
+impl EnumType {
+    pub fn of<E: SqlEnum>() -> Self {
+        EnumType {
+            name: E::TYPE_NAME,
+            variants: E::VARIANTS,
+        }
+    }
+
+    fn labels(&self) -> String {
+        self.variants
+            .iter()
+            .map(|v| quote(v))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    fn create_type(&self, r: &Renderer) -> String {
+        format!(
+            "CREATE TYPE {} AS ENUM ({})\n",
+            r.ident(self.name),
+            self.labels()
+        )
+    }
+}
+
+/// How a generated column's value is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Storage {
+    /// Computed on write and stored: `STORED`, `PERSISTED` on SQL Server.
+    Stored,
+    /// Computed on read.
+    Virtual,
+}
+
+/// A column of a `TableDef`.
+#[derive(Clone, Debug)]
+pub struct ColumnDef {
+    pub name: String,
+    pub ty: CastType,
+    pub nullable: bool,
+    /// The SQL expression computing the column, written as given.
+    pub generated: Option<(String, Storage)>,
+    pub enumeration: Option<EnumType>,
+    /// The SQL expression of the default value, written as given.
+    pub default: Option<String>,
+    pub unique: bool,
+    /// The table and column the column refers to, the table as written.
+    pub references: Option<(String, String)>,
+    /// `CHECK` constraints on the column, written as given.
+    pub checks: Vec<String>,
+    pub comment: Option<String>,
+}
+
+impl ColumnDef {
+    /// A nullable column of the type written as given.
+    pub fn new(name: &str, ty: &'static str) -> Self {
+        ColumnDef {
+            name: name.to_owned(),
+            ty: CastType::Named(ty),
+            nullable: true,
+            generated: None,
+            enumeration: None,
+            default: None,
+            unique: false,
+            references: None,
+            checks: Vec::new(),
+            comment: None,
+        }
+    }
+
+    /// A nullable column of the SQL type of `T` in the rendered dialect.
+    pub fn of<T: SqlType>(name: &str) -> Self {
+        ColumnDef {
+            ty: CastType::Of(T::column_type),
+            ..ColumnDef::new(name, "")
+        }
+    }
+
+    /// A nullable column holding the labels of `E`, see `EnumType`.
+    pub fn enumeration<E: SqlEnum>(name: &str) -> Self {
+        ColumnDef {
+            enumeration: Some(EnumType::of::<E>()),
+            ..ColumnDef::of::<String>(name)
+        }
+    }
+
+    /// Adds `CHECK (expr)`, e.g. `ColumnDef::of::<i32>("qty").check("qty > 0")`.
+    pub fn check(mut self, expr: &str) -> Self {
+        self.checks.push(expr.to_owned());
+        self
+    }
+
+    pub fn not_null(mut self) -> Self {
+        self.nullable = false;
+        self
+    }
+
+    /// Sets `DEFAULT expr`, e.g. `.default("0")` or `.default("'draft'")`.
+    /// MySQL needs expressions other than literals in parentheses.
+    pub fn default(mut self, expr: &str) -> Self {
+        self.default = Some(expr.to_owned());
+        self
+    }
+
+    /// Adds `UNIQUE`. MySQL and SQL Server cannot index unbounded text,
+    /// so a unique string column there needs a type such as `VARCHAR(255)`.
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    /// Makes the column a foreign key to `column` of `table`, see
+    /// `TableDef::foreign_key` for keys over several columns.
+    pub fn references(mut self, table: &str, column: &str) -> Self {
+        self.references = Some((table.to_owned(), column.to_owned()));
+        self
+    }
+
+    /// Makes the column generated from `expr`, e.g. `first || ' ' || last`.
+    /// Generated columns are left out of inserts.
+    pub fn generated(mut self, expr: &str, storage: Storage) -> Self {
+        self.generated = Some((expr.to_owned(), storage));
+        self
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.generated.is_some()
+    }
+
+    /// Documents the column in the database, see `TableDef::comments`.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_owned());
+        self
+    }
+
+    /// The type as the database reports it, without quoting, for comparing
+    /// against an introspected column.
+    pub(crate) fn type_name(&self, dialect: Dialect) -> String {
+        match &self.enumeration {
+            Some(e) if dialect == Dialect::Postgres => e.name.to_owned(),
+            Some(e) if dialect == Dialect::MySql => format!("ENUM({})", e.labels()),
+            _ => self.ty.name(dialect).to_owned(),
+        }
+    }
+
+    pub(crate) fn render(&self, r: &Renderer) -> Result<String, SQLError> {
+        let name = r.column(&self.name);
+        let mut checks = self.checks.clone();
+        let ty = match &self.enumeration {
+            Some(e) if r.dialect == Dialect::Postgres => r.ident(e.name),
+            Some(e) if r.dialect == Dialect::MySql => format!("ENUM({})", e.labels()),
+            Some(e) => {
+                checks.push(format!("{} IN ({})", name, e.labels()));
+                self.ty.name(r.dialect).to_owned()
+            }
+            None => self.ty.name(r.dialect).to_owned(),
+        };
+
+        let mut sql = match &self.generated {
+            // SQL Server infers the type of a computed column
+            Some((expr, storage)) if r.dialect == Dialect::MsSql => {
+                let persisted = match storage {
+                    Storage::Stored => " PERSISTED",
+                    Storage::Virtual => "",
+                };
+                format!("{} AS ({}){}", name, expr, persisted)
+            }
+            Some((expr, storage)) => {
+                let storage = match storage {
+                    Storage::Stored => "STORED",
+                    Storage::Virtual => "VIRTUAL",
+                };
+                format!("{} {} GENERATED ALWAYS AS ({}) {}", name, ty, expr, storage)
+            }
+            None => format!("{} {}", name, ty),
+        };
+        if !self.nullable {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        if self.unique {
+            sql.push_str(" UNIQUE");
+        }
+        for check in checks {
+            sql.push_str(&format!(" CHECK ({})", check));
+        }
+        // MySQL keeps comments in the definition, see `TableDef::comments`
+        if let (Some(comment), Dialect::MySql) = (&self.comment, r.dialect) {
+            sql.push_str(&format!(" COMMENT {}", r.literal(comment)));
+        }
+        Ok(sql)
+    }
+}
+
+/// What a foreign key does to the referring rows when the referred row is
+/// deleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnDelete {
+    NoAction,
+    /// Not on SQL Server, where `NoAction` behaves the same.
+    Restrict,
+    Cascade,
+    SetNull,
+}
+
+/// A named foreign key over one or more columns of a `TableDef`.
+#[derive(Clone, Debug)]
+pub struct ForeignKey {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub table: TableName,
+    pub references: Vec<String>,
+    pub on_delete: Option<OnDelete>,
+}
+
+impl ForeignKey {
+    /// `columns` referring to `references` of `table`, in order.
+    pub fn new(name: &str, columns: &[&str], table: &str, references: &[&str]) -> Self {
+        ForeignKey {
+            name: name.to_owned(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            table: TableName::parse(table),
+            references: references.iter().map(|c| c.to_string()).collect(),
+            on_delete: None,
+        }
+    }
+
+    pub fn on_delete(mut self, action: OnDelete) -> Self {
+        self.on_delete = Some(action);
+        self
+    }
+}
+
+/// The definition of a table, rendered as `CREATE TABLE`.
+///
+/// ```
+/// # use traits::*;
+/// let users = TableDef::new("users")
+///     .column(ColumnDef::of::<i64>("id").not_null())
+///     .column(ColumnDef::of::<String>("email").not_null().unique())
+///     .column(ColumnDef::of::<String>("firstName"))
+///     .column(ColumnDef::of::<String>("lastName"))
+///     .column(ColumnDef::of::<String>("fullName").generated("first_name || ' ' || last_name", Storage::Stored))
+///     .column(ColumnDef::of::<bool>("active").not_null().default("TRUE"))
+///     .primary_key(&["id"]);
+/// let ddl = users.create_table(Dialect::Postgres).unwrap();
+///
+/// let members = TableDef::new("members")
+///     .column(ColumnDef::of::<i64>("userId").not_null().references("users", "id"))
+///     .column(ColumnDef::of::<i64>("teamId").not_null())
+///     .primary_key(&["userId", "teamId"])
+///     .foreign_key(
+///         ForeignKey::new("members_team", &["teamId"], "teams", &["id"]).on_delete(OnDelete::Cascade),
+///     );
+/// let ddl = members.create_table(Dialect::MySql).unwrap();
+/// ```
+///
+/// Constraints follow the columns: the primary key, unique constraints,
+/// foreign keys, then checks. Column references are written as table
+/// constraints too, since MySQL ignores them inline.
+#[derive(Clone, Debug)]
+pub struct TableDef {
+    pub table: TableName,
+    pub columns: Vec<ColumnDef>,
+    pub primary_key: Vec<String>,
+    /// Named `UNIQUE` constraints over several columns.
+    pub uniques: Vec<(String, Vec<String>)>,
+    pub foreign_keys: Vec<ForeignKey>,
+    /// Named table `CHECK` constraints, written as given.
+    pub checks: Vec<(String, String)>,
+    /// Named indexes, each over its columns in order.
+    pub indexes: Vec<(String, Vec<String>)>,
+    pub comment: Option<String>,
+    naming: NamingStrategy,
+    quoting: Quoting,
+}
+
+impl TableDef {
+    pub fn new(tbl: &str) -> Self {
+        TableDef::from_table(TableName::parse(tbl))
+    }
+
+    /// Defines `table` as given, without parsing.
+    pub fn from_table(table: TableName) -> Self {
+        TableDef {
+            table,
+            columns: Vec::new(),
+            primary_key: Vec::new(),
+            uniques: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+            indexes: Vec::new(),
+            comment: None,
+            naming: NamingStrategy::default(),
+            quoting: Quoting::default(),
+        }
+    }
+
+    pub fn column(mut self, column: ColumnDef) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Sets the columns of the primary key, in order.
+    pub fn primary_key(mut self, columns: &[&str]) -> Self {
+        self.primary_key = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Adds `CONSTRAINT name UNIQUE (...)`, for uniqueness over several
+    /// columns; see `ColumnDef::unique` for one.
+    pub fn unique(mut self, name: &str, columns: &[&str]) -> Self {
+        let columns = columns.iter().map(|c| c.to_string()).collect();
+        self.uniques.push((name.to_owned(), columns));
+        self
+    }
+
+    /// Adds an index over `columns`, in order, see `create_indexes`.
+    pub fn index(mut self, name: &str, columns: &[&str]) -> Self {
+        let columns = columns.iter().map(|c| c.to_string()).collect();
+        self.indexes.push((name.to_owned(), columns));
+        self
+    }
+
+    pub fn foreign_key(mut self, key: ForeignKey) -> Self {
+        self.foreign_keys.push(key);
+        self
+    }
+
+    /// Adds `CONSTRAINT name CHECK (expr)`, for checks over several
+    /// columns: `.check("valid_period", "starts_at < ends_at")`.
+    pub fn check(mut self, name: &str, expr: &str) -> Self {
+        self.checks.push((name.to_owned(), expr.to_owned()));
+        self
+    }
+
+    /// Documents the table in the database, see `comments`.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_owned());
+        self
+    }
+
+    /// Sets how column names map to database names, as on `SQLable`.
+    pub fn naming(mut self, naming: NamingStrategy) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    pub fn quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn get_column(&self, name: &str) -> Option<&ColumnDef> {
+        let name = self.naming.apply(name);
+        self.columns
+            .iter()
+            .find(|c| self.naming.apply(&c.name) == name)
+    }
+
+    /// Whether `name` is a generated column, which cannot be written to.
+    pub fn is_generated(&self, name: &str) -> bool {
+        self.get_column(name).is_some_and(ColumnDef::is_generated)
+    }
+
+    /// The columns an insert may give values for, in table order.
+    pub fn insertable_columns(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|c| !c.is_generated())
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    /// An insert into the table, over its insertable columns.
+    pub fn insert(&self) -> Insert {
+        let mut insert = Insert::from_table(self.table.clone());
+        insert
+            .columns(self.insertable_columns())
+            .naming(self.naming)
+            .quoting(self.quoting)
+            .table_def(self);
+        insert
+    }
+
+    /// The `CREATE TYPE` statements the table's enum columns need, to run
+    /// before `create_table`; there are none outside Postgres.
+    pub fn create_types(&self, dialect: Dialect) -> Vec<String> {
+        if dialect != Dialect::Postgres {
+            return Vec::new();
+        }
+        let r = self.renderer(dialect);
+        let mut types: Vec<EnumType> = Vec::new();
+        for e in self.columns.iter().filter_map(|c| c.enumeration) {
+            if !types.iter().any(|t| t.name == e.name) {
+                types.push(e);
+            }
+        }
+        types.iter().map(|t| t.create_type(&r)).collect()
+    }
+
+    pub fn create_table(&self, dialect: Dialect) -> Result<String, SQLError> {
+        let r = self.renderer(dialect);
+        let mut cols = self
+            .columns
+            .iter()
+            .map(|c| c.render(&r))
+            .collect::<Result<Vec<String>, SQLError>>()?;
+        let list = |columns: &[String]| {
+            columns
+                .iter()
+                .map(|c| r.column(c))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        if !self.primary_key.is_empty() {
+            cols.push(format!("PRIMARY KEY ({})", list(&self.primary_key)));
+        }
+        for (name, columns) in &self.uniques {
+            cols.push(format!(
+                "CONSTRAINT {} UNIQUE ({})",
+                r.ident(name),
+                list(columns)
+            ));
+        }
+        for column in &self.columns {
+            if let Some((table, referred)) = &column.references {
+                cols.push(format!(
+                    "FOREIGN KEY ({}) REFERENCES {} ({})",
+                    r.column(&column.name),
+                    TableName::parse(table).render(&r),
+                    r.column(referred)
+                ));
+            }
+        }
+        for key in &self.foreign_keys {
+            let on_delete = match (key.on_delete, dialect) {
+                (None, _) => "",
+                (Some(OnDelete::NoAction), _) => " ON DELETE NO ACTION",
+                (Some(OnDelete::Restrict), Dialect::MsSql) => {
+                    return Err(SQLError::Unsupported(format!(
+                        "ON DELETE RESTRICT in {:?}",
+                        dialect
+                    )))
+                }
+                (Some(OnDelete::Restrict), _) => " ON DELETE RESTRICT",
+                (Some(OnDelete::Cascade), _) => " ON DELETE CASCADE",
+                (Some(OnDelete::SetNull), _) => " ON DELETE SET NULL",
+            };
+            cols.push(format!(
+                "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}){}",
+                r.ident(&key.name),
+                list(&key.columns),
+                key.table.render(&r),
+                list(&key.references),
+                on_delete
+            ));
+        }
+        for (name, expr) in &self.checks {
+            cols.push(format!("CONSTRAINT {} CHECK ({})", r.ident(name), expr));
+        }
+
+        let comment = match (&self.comment, dialect) {
+            (Some(comment), Dialect::MySql) => format!(" COMMENT = {}", r.literal(comment)),
+            _ => String::new(),
+        };
+
+        Ok(format!(
+            "CREATE TABLE {} (\n  {}\n){}\n",
+            self.table.render(&r),
+            cols.join(",\n  "),
+            comment
+        ))
+    }
+
+    /// The statements documenting the table and its columns, to run after
+    /// `create_table`: `COMMENT ON` on Postgres, extended properties on SQL
+    /// Server. MySQL comments are part of `create_table`, and SQLite keeps
+    /// none.
+    pub fn comments(&self, dialect: Dialect) -> Vec<String> {
+        let r = self.renderer(dialect);
+        let table = self.table.render(&r);
+        let columns = self
+            .columns
+            .iter()
+            .filter_map(|c| Some((c, c.comment.as_deref()?)));
+
+        let mut result = Vec::new();
+        match dialect {
+            Dialect::MySql | Dialect::Sqlite => {}
+            Dialect::MsSql => {
+                let schema = self.table.schema.as_deref().unwrap_or("dbo");
+                let target = format!(
+                    "@level0type = N'SCHEMA', @level0name = N{}, @level1type = N'TABLE', @level1name = N{}",
+                    quote(schema),
+                    quote(&self.table.name)
+                );
+                if let Some(comment) = &self.comment {
+                    result.push(ms_description(comment, &target));
+                }
+                for (column, comment) in columns {
+                    let target = format!(
+                        "{}, @level2type = N'COLUMN', @level2name = N{}",
+                        target,
+                        quote(&self.naming.apply(&column.name))
+                    );
+                    result.push(ms_description(comment, &target));
+                }
+            }
+            Dialect::Generic | Dialect::Postgres => {
+                if let Some(comment) = &self.comment {
+                    result.push(format!(
+                        "COMMENT ON TABLE {} IS {}\n",
+                        table,
+                        quote(comment)
+                    ));
+                }
+                for (column, comment) in columns {
+                    result.push(format!(
+                        "COMMENT ON COLUMN {}.{} IS {}\n",
+                        table,
+                        r.column(&column.name),
+                        quote(comment)
+                    ));
+                }
+            }
+        }
+        result
+    }
+
+    /// The `CREATE INDEX` statements of the table's indexes, to run after
+    /// `create_table`:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let orders = TableDef::new("orders")
+    ///     .column(ColumnDef::of::<i64>("customerId"))
+    ///     .column(ColumnDef::of::<String>("placedAt"))
+    ///     .index("orders_customer", &["customerId", "placedAt"]);
+    /// assert_eq!(
+    ///     orders.create_indexes(Dialect::Postgres),
+    ///     vec!["CREATE INDEX orders_customer ON orders (customer_id,placed_at)\n"]
+    /// );
+    /// ```
+    pub fn create_indexes(&self, dialect: Dialect) -> Vec<String> {
+        let r = self.renderer(dialect);
+        let table = self.table.render(&r);
+        self.indexes
+            .iter()
+            .map(|(name, columns)| {
+                let columns: Vec<String> = columns.iter().map(|c| r.column(c)).collect();
+                format!(
+                    "CREATE INDEX {} ON {} ({})\n",
+                    r.ident(name),
+                    table,
+                    columns.join(",")
+                )
+            })
+            .collect()
+    }
+
+    /// Everything creating the table, in order: `create_types`,
+    /// `create_table`, `create_indexes` then `comments`.
+    pub fn create_statements(&self, dialect: Dialect) -> Result<Vec<String>, SQLError> {
+        let mut result = self.create_types(dialect);
+        result.push(self.create_table(dialect)?);
+        result.extend(self.create_indexes(dialect));
+        result.extend(self.comments(dialect));
+        Ok(result)
+    }
+
+    /// Every foreign key of the table: those of single columns, declared
+    /// with `ColumnDef::references`, then `foreign_keys`.
+    pub(crate) fn all_foreign_keys(&self) -> Vec<ForeignKey> {
+        let mut keys: Vec<ForeignKey> = self
+            .columns
+            .iter()
+            .filter_map(|c| {
+                let (table, referred) = c.references.as_ref()?;
+                let name = format!("{}_{}_fkey", self.table.name, c.name);
+                Some(ForeignKey::new(&name, &[&c.name], table, &[referred]))
+            })
+            .collect();
+        keys.extend(self.foreign_keys.iter().cloned());
+        keys
+    }
+
+    /// The name of a column in the database, after the naming strategy.
+    pub(crate) fn column_name(&self, column: &ColumnDef) -> String {
+        self.naming.apply(&column.name)
+    }
+
+    pub(crate) fn renderer(&self, dialect: Dialect) -> Renderer {
+        Renderer {
+            dialect,
+            naming: self.naming,
+            quoting: self.quoting,
+            ..Default::default()
+        }
+    }
+}
+
+fn ms_description(comment: &str, target: &str) -> String {
+    format!(
+        "EXEC sp_addextendedproperty @name = N'MS_Description', @value = N{}, {}\n",
+        quote(comment),
+        target
+    )
+}
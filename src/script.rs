@@ -0,0 +1,184 @@
+use crate::{Dialect, ExecError, Execute, Insert, Quoting, SQLError, SQLable, SqlValue};
+
+/// Statements run in one transaction, e.g. a data fixup that must apply
+/// entirely or not at all:
+///
+/// ```
+/// # use traits::*;
+/// let mut insert = Insert::new("plans");
+/// insert.columns(vec!["name".to_owned()]).row(vec!["pro".into()]);
+/// let mut check = SQLable::new("plans");
+/// check.dialect(Dialect::Postgres);
+///
+/// let mut script = Script::new(Dialect::Postgres);
+/// script
+///     .insert(&insert)
+///     .savepoint("before_cleanup")
+///     .raw("DELETE FROM plans WHERE name = 'legacy'")
+///     .query(&check);
+/// print!("{}", script.render().unwrap());
+/// ```
+///
+/// Queries and inserts are rendered with their own dialect and options;
+/// the script's dialect only decides how transactions and savepoints are
+/// written.
+pub struct Script<'a> {
+    dialect: Dialect,
+    steps: Vec<Step<'a>>,
+}
+
+enum Step<'a> {
+    Query(&'a SQLable),
+    Insert(&'a Insert),
+    Raw(String),
+    Savepoint(String),
+    Release(String),
+    RollbackTo(String),
+}
+
+impl<'a> Script<'a> {
+    pub fn new(dialect: Dialect) -> Self {
+        Script {
+            dialect,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a query; `execute` reads and discards its rows.
+    pub fn query(&mut self, query: &'a SQLable) -> &mut Self {
+        self.steps.push(Step::Query(query));
+        self
+    }
+
+    pub fn insert(&mut self, insert: &'a Insert) -> &mut Self {
+        self.steps.push(Step::Insert(insert));
+        self
+    }
+
+    /// Adds a statement as written, e.g. one rendered by `Grant` or
+    /// `TableDef`. A trailing `;` is dropped.
+    pub fn raw(&mut self, sql: impl Into<String>) -> &mut Self {
+        self.steps.push(Step::Raw(sql.into()));
+        self
+    }
+
+    /// Marks a point the transaction can be rolled back to: `SAVEPOINT`,
+    /// `SAVE TRANSACTION` on SQL Server.
+    pub fn savepoint(&mut self, name: &str) -> &mut Self {
+        self.steps.push(Step::Savepoint(name.to_owned()));
+        self
+    }
+
+    /// Forgets a savepoint: `RELEASE SAVEPOINT`, nothing on SQL Server
+    /// which has no such statement.
+    pub fn release(&mut self, name: &str) -> &mut Self {
+        self.steps.push(Step::Release(name.to_owned()));
+        self
+    }
+
+    /// Undoes what ran since the savepoint, keeping the transaction open.
+    pub fn rollback_to(&mut self, name: &str) -> &mut Self {
+        self.steps.push(Step::RollbackTo(name.to_owned()));
+        self
+    }
+
+    /// The whole script with values inlined, each statement ending with
+    /// `;`, between `BEGIN` and `COMMIT`.
+    pub fn render(&self) -> Result<String, SQLError> {
+        let mut result = format!("{};\n", self.begin());
+        for step in &self.steps {
+            if let Some(sql) = self.inline(step)? {
+                result.push_str(&format!("{};\n", sql));
+            }
+        }
+        result.push_str(&format!("{};\n", self.commit()));
+        Ok(result)
+    }
+
+    /// The statements between `BEGIN` and `COMMIT`, with placeholders, and
+    /// the values to bind to each.
+    pub fn statements(&self) -> Result<Vec<(String, Vec<SqlValue>)>, SQLError> {
+        let mut result = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let prepared = match step {
+                Step::Query(q) => q.prepare_params()?,
+                Step::Insert(i) => i.prepare_params()?,
+                _ => match self.inline(step)? {
+                    Some(sql) => (sql, Vec::new()),
+                    None => continue,
+                },
+            };
+            result.push(prepared);
+        }
+        Ok(result)
+    }
+
+    /// Runs the script on `conn` in a transaction, returning the number of
+    /// rows changed. Every statement is rendered before the transaction
+    /// starts; on the first failure the transaction is rolled back and the
+    /// error returned.
+    pub fn execute<C: Execute + ?Sized>(&self, conn: &C) -> Result<u64, ExecError<C::Error>> {
+        let statements = self.statements()?;
+        conn.execute(self.begin(), &[])?;
+
+        let mut changed = 0;
+        let steps = self.steps.iter().filter(|s| !self.is_noop(s));
+        for (step, (sql, params)) in steps.zip(&statements) {
+            let result = match step {
+                Step::Query(_) => conn.query(sql, params, &mut |_| Ok(())).map(|_| 0),
+                _ => conn.execute(sql, params),
+            };
+            match result {
+                Ok(n) => changed += n,
+                Err(e) => {
+                    // the original error matters more than a failed rollback
+                    let _ = conn.execute("ROLLBACK", &[]);
+                    return Err(e);
+                }
+            }
+        }
+
+        conn.execute(self.commit(), &[])?;
+        Ok(changed)
+    }
+
+    fn begin(&self) -> &'static str {
+        match self.dialect {
+            Dialect::MySql => "START TRANSACTION",
+            Dialect::MsSql => "BEGIN TRANSACTION",
+            _ => "BEGIN",
+        }
+    }
+
+    fn commit(&self) -> &'static str {
+        match self.dialect {
+            Dialect::MsSql => "COMMIT TRANSACTION",
+            _ => "COMMIT",
+        }
+    }
+
+    /// Whether the step renders to nothing in the script's dialect.
+    fn is_noop(&self, step: &Step) -> bool {
+        matches!(step, Step::Release(_)) && self.dialect == Dialect::MsSql
+    }
+
+    /// The statement of a step with values inlined, without its trailing
+    /// newline or `;`.
+    fn inline(&self, step: &Step) -> Result<Option<String>, SQLError> {
+        let ident = |name: &str| self.dialect.ident(name, Quoting::Auto);
+        let sql = match (step, self.dialect) {
+            (Step::Query(q), _) => q.try_prepare()?,
+            (Step::Insert(i), _) => i.try_prepare()?,
+            (Step::Raw(sql), _) => sql.clone(),
+            (Step::Savepoint(name), Dialect::MsSql) => format!("SAVE TRANSACTION {}", ident(name)),
+            (Step::Savepoint(name), _) => format!("SAVEPOINT {}", ident(name)),
+            (Step::Release(_), Dialect::MsSql) => return Ok(None),
+            (Step::Release(name), _) => format!("RELEASE SAVEPOINT {}", ident(name)),
+            (Step::RollbackTo(name), Dialect::MsSql) => {
+                format!("ROLLBACK TRANSACTION {}", ident(name))
+            }
+            (Step::RollbackTo(name), _) => format!("ROLLBACK TO SAVEPOINT {}", ident(name)),
+        };
+        Ok(Some(sql.trim_end().trim_end_matches(';').to_owned()))
+    }
+}
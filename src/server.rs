@@ -0,0 +1,238 @@
+use crate::{Dialect, ExecError, Execute, SQLError};
+
+/// The database server a connection talks to, detected with `detect` so
+/// that the dialect need not be configured per environment:
+///
+/// ```no_run
+/// # use traits::*;
+/// # fn run<C: Execute>(conn: &C) -> Result<(), ExecError<C::Error>> {
+/// let server = Server::detect(conn)?;
+/// let mut q = SQLable::new("users");
+/// q.dialect(server.dialect);
+/// if server.supports(Feature::Returning) {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Server {
+    pub dialect: Dialect,
+    /// Major, minor and patch version.
+    pub version: (u32, u32, u32),
+    /// MariaDB rather than MySQL, both rendered as `Dialect::MySql`.
+    pub mariadb: bool,
+}
+
+/// A feature that only some servers, or some versions, have. Queries
+/// rendered for a `Server` fail on the features it lacks, see
+/// `SQLable::server`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// `INSERT ... RETURNING`: Postgres, SQLite 3.35 and MariaDB 10.5.
+    Returning,
+    /// `OVER (...)`: MySQL 8.0, MariaDB 10.2 and SQLite 3.25.
+    WindowFunctions,
+    /// The `WINDOW` clause: MySQL 8.0, SQLite 3.28 and SQL Server 2022,
+    /// not MariaDB.
+    NamedWindows,
+    /// `IS DISTINCT FROM`: SQL Server 2022, and everywhere else with the
+    /// operators of the dialect.
+    IsDistinctFrom,
+    /// `GENERATED ALWAYS AS`: Postgres 12, MySQL 5.7, MariaDB 10.2 and
+    /// SQLite 3.31.
+    GeneratedColumns,
+    /// `ALTER TABLE ... DROP COLUMN`: SQLite 3.35.
+    DropColumn,
+    /// `NULLS FIRST` / `NULLS LAST`: Postgres and SQLite 3.30, not MySQL or
+    /// SQL Server. Emulated where missing, see `SQLable::order_by_nulls`.
+    NullsOrdering,
+}
+
+impl Feature {
+    /// Every feature, for tools that list them.
+    pub const ALL: [Feature; 7] = [
+        Feature::Returning,
+        Feature::WindowFunctions,
+        Feature::NamedWindows,
+        Feature::IsDistinctFrom,
+        Feature::GeneratedColumns,
+        Feature::DropColumn,
+        Feature::NullsOrdering,
+    ];
+
+    /// The first version of the server that has the feature, `None` if no
+    /// version has it; `(0, 0, 0)` if every version has it.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// assert_eq!(Feature::Returning.since(Dialect::Sqlite, false), Some((3, 35, 0)));
+    /// assert_eq!(Feature::Returning.since(Dialect::MySql, false), None);
+    /// assert_eq!(Feature::Returning.describe(), "RETURNING");
+    /// ```
+    pub fn since(self, dialect: Dialect, mariadb: bool) -> Option<(u32, u32, u32)> {
+        const ALWAYS: Option<(u32, u32, u32)> = Some((0, 0, 0));
+        match (self, dialect) {
+            (Feature::Returning, Dialect::Postgres) => ALWAYS,
+            (Feature::Returning, Dialect::Sqlite) => Some((3, 35, 0)),
+            (Feature::Returning, Dialect::MySql) if mariadb => Some((10, 5, 0)),
+            (Feature::Returning, _) => None,
+            (Feature::WindowFunctions, Dialect::Sqlite) => Some((3, 25, 0)),
+            (Feature::WindowFunctions, Dialect::MySql) if mariadb => Some((10, 2, 0)),
+            (Feature::WindowFunctions, Dialect::MySql) => Some((8, 0, 0)),
+            (Feature::NamedWindows, Dialect::Sqlite) => Some((3, 28, 0)),
+            (Feature::NamedWindows, Dialect::MySql) if mariadb => None,
+            (Feature::NamedWindows, Dialect::MySql) => Some((8, 0, 0)),
+            (Feature::NamedWindows | Feature::IsDistinctFrom, Dialect::MsSql) => Some((16, 0, 0)),
+            (Feature::GeneratedColumns, Dialect::Postgres) => Some((12, 0, 0)),
+            (Feature::GeneratedColumns, Dialect::Sqlite) => Some((3, 31, 0)),
+            (Feature::GeneratedColumns, Dialect::MySql) if mariadb => Some((10, 2, 0)),
+            (Feature::GeneratedColumns, Dialect::MySql) => Some((5, 7, 0)),
+            (Feature::DropColumn, Dialect::Sqlite) => Some((3, 35, 0)),
+            (Feature::NullsOrdering, Dialect::Sqlite) => Some((3, 30, 0)),
+            (Feature::NullsOrdering, Dialect::MySql | Dialect::MsSql) => None,
+            _ => ALWAYS,
+        }
+    }
+
+    /// The feature as errors name it, e.g. `the WINDOW clause`.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Feature::Returning => "RETURNING",
+            Feature::WindowFunctions => "window functions",
+            Feature::NamedWindows => "the WINDOW clause",
+            Feature::IsDistinctFrom => "IS DISTINCT FROM",
+            Feature::GeneratedColumns => "generated columns",
+            Feature::DropColumn => "DROP COLUMN",
+            Feature::NullsOrdering => "NULLS FIRST / LAST",
+        }
+    }
+}
+
+impl Server {
+    /// Asks the server for its version: `SELECT version()`, understood by
+    /// Postgres, MySQL and MariaDB, then `SELECT @@VERSION` for SQL Server
+    /// and `SELECT sqlite_version()` for SQLite. Fails with the error of
+    /// the last attempt, or `SQLError::Unsupported` if the version is not
+    /// recognized.
+    pub fn detect<C: Execute + ?Sized>(conn: &C) -> Result<Server, ExecError<C::Error>> {
+        let mut error = None;
+        for (sql, dialect) in [
+            ("SELECT version()\n", None),
+            ("SELECT @@VERSION\n", None),
+            ("SELECT sqlite_version()\n", Some(Dialect::Sqlite)),
+        ] {
+            let mut text = String::new();
+            let attempt = conn.query(sql, &[], &mut |row| {
+                text = row.get_at(0)?;
+                Ok(())
+            });
+            if let Err(e) = attempt {
+                error = Some(e);
+                continue;
+            }
+
+            let server = match dialect {
+                Some(dialect) => parse_version(&text).map(|version| Server {
+                    dialect,
+                    version,
+                    mariadb: false,
+                }),
+                None => Server::parse(&text),
+            };
+            return server
+                .ok_or_else(|| SQLError::Unsupported(format!("server version {}", text)).into());
+        }
+        Err(error.expect("a failed attempt"))
+    }
+
+    /// Recognizes the version string of Postgres, MySQL, MariaDB or SQL
+    /// Server, as returned by `SELECT version()` or `SELECT @@VERSION`:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let server = Server::parse("10.6.12-MariaDB-0ubuntu0.22.04.1").unwrap();
+    /// assert_eq!(server.dialect, Dialect::MySql);
+    /// assert!(server.supports(Feature::Returning));
+    ///
+    /// let server = Server::parse("PostgreSQL 15.3 on x86_64-pc-linux-gnu").unwrap();
+    /// assert_eq!(server.version, (15, 3, 0));
+    /// ```
+    pub fn parse(text: &str) -> Option<Server> {
+        let server = |dialect, version| Server {
+            dialect,
+            version,
+            mariadb: false,
+        };
+        if let Some(rest) = text.split("PostgreSQL ").nth(1) {
+            return parse_version(rest).map(|v| server(Dialect::Postgres, v));
+        }
+        if text.starts_with("Microsoft SQL Server") {
+            // "Microsoft SQL Server 2019 (RTM) - 15.0.2000.5 (X64) ..."
+            let rest = text.split(" - ").nth(1)?;
+            return parse_version(rest).map(|v| server(Dialect::MsSql, v));
+        }
+        if text.contains("MariaDB") {
+            // replication used to require a 5.5.5- prefix
+            let rest = text.strip_prefix("5.5.5-").unwrap_or(text);
+            return parse_version(rest).map(|version| Server {
+                mariadb: true,
+                ..server(Dialect::MySql, version)
+            });
+        }
+        parse_version(text).map(|v| server(Dialect::MySql, v))
+    }
+
+    /// Whether the server has `feature`.
+    pub fn supports(&self, feature: Feature) -> bool {
+        feature
+            .since(self.dialect, self.mariadb)
+            .is_some_and(|since| self.version >= since)
+    }
+
+    /// Fails unless the server has `feature`, naming the version that
+    /// introduced it.
+    pub(crate) fn require(&self, feature: Feature) -> Result<(), SQLError> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+        let needs = match feature.since(self.dialect, self.mariadb) {
+            Some((major, minor, patch)) => format!("needs {}.{}.{}", major, minor, patch),
+            None => "has none".to_owned(),
+        };
+        Err(SQLError::Unsupported(format!(
+            "{} in {}, which {}",
+            feature.describe(),
+            self,
+            needs
+        )))
+    }
+}
+
+/// The product and version, e.g. `MariaDB 10.4.0`.
+impl std::fmt::Display for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let product = match self.dialect {
+            Dialect::MySql if self.mariadb => "MariaDB",
+            Dialect::MySql => "MySQL",
+            Dialect::Postgres => "PostgreSQL",
+            Dialect::Sqlite => "SQLite",
+            Dialect::MsSql => "SQL Server",
+            Dialect::Generic => "SQL",
+        };
+        let (major, minor, patch) = self.version;
+        write!(f, "{} {}.{}.{}", product, major, minor, patch)
+    }
+}
+
+/// The leading `major.minor.patch` of `text`, missing parts as 0.
+fn parse_version(text: &str) -> Option<(u32, u32, u32)> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let mut parts = text[..end].split('.').map(|p| p.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
@@ -0,0 +1,59 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A list shared by the copies of a query until one of them changes it,
+/// when that copy gets its own. Empty lists allocate nothing.
+#[derive(Debug)]
+pub(crate) struct SharedVec<T>(Option<Arc<Vec<T>>>);
+
+impl<T: Clone> SharedVec<T> {
+    /// The list to change, copied first if other queries share it.
+    pub(crate) fn to_mut(&mut self) -> &mut Vec<T> {
+        Arc::make_mut(self.0.get_or_insert_with(Default::default))
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.to_mut().push(item);
+    }
+}
+
+impl<T> Default for SharedVec<T> {
+    fn default() -> Self {
+        SharedVec(None)
+    }
+}
+
+impl<T> Clone for SharedVec<T> {
+    fn clone(&self) -> Self {
+        SharedVec(self.0.clone())
+    }
+}
+
+impl<T> Deref for SharedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.0.as_deref().map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<T> From<Vec<T>> for SharedVec<T> {
+    fn from(items: Vec<T>) -> Self {
+        SharedVec((!items.is_empty()).then(|| Arc::new(items)))
+    }
+}
+
+impl<T> FromIterator<T> for SharedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<T>>().into()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SharedVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
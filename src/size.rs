@@ -0,0 +1,82 @@
+use crate::budget::estimated_size;
+use crate::{Dialect, Renderer, SQLError};
+
+/// Measures a statement clause by clause as it is rendered, so that one
+/// over the server's limit is refused naming the clause that weighs most.
+/// A clause weighs its SQL and the values it bound.
+pub(crate) struct StatementSize {
+    params: usize,
+    bytes: usize,
+    largest: (&'static str, usize),
+}
+
+impl StatementSize {
+    pub(crate) fn new(r: &Renderer) -> Self {
+        StatementSize {
+            params: bound(r),
+            bytes: 0,
+            largest: ("", 0),
+        }
+    }
+
+    /// Adds the clause just rendered as `sql`.
+    pub(crate) fn clause(&mut self, name: &'static str, sql: &str, r: &Renderer) {
+        let params = r.params.as_deref().unwrap_or_default();
+        let bytes = sql.len()
+            + params[self.params.min(params.len())..]
+                .iter()
+                .map(estimated_size)
+                .sum::<usize>();
+        self.params = params.len();
+        self.bytes += bytes;
+        if bytes > self.largest.1 {
+            self.largest = (name, bytes);
+        }
+    }
+
+    /// Fails if the statement exceeds the limit of `r`.
+    pub(crate) fn check(&self, r: &Renderer) -> Result<(), SQLError> {
+        match r.statement_limit() {
+            Some(limit) if self.bytes > limit => Err(SQLError::StatementTooLarge {
+                clause: self.largest.0.to_owned(),
+                bytes: self.bytes,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn bound(r: &Renderer) -> usize {
+    r.params.as_ref().map_or(0, Vec::len)
+}
+
+impl Renderer {
+    /// The largest statement the server takes, in bytes: `max_statement_bytes`
+    /// if set, otherwise the default of the server or the dialect. `None`
+    /// for `Dialect::Generic`.
+    pub(crate) fn statement_limit(&self) -> Option<usize> {
+        const MIB: usize = 1 << 20;
+        if self.max_statement_bytes.is_some() {
+            return self.max_statement_bytes;
+        }
+        let server = self.server.filter(|s| s.dialect == self.dialect);
+        match self.dialect {
+            // max_allowed_packet, raised from 4 MiB in MySQL 8.0 and
+            // MariaDB 10.2.4
+            Dialect::MySql => match server {
+                Some(s) if s.mariadb && s.version >= (10, 2, 4) => Some(16 * MIB),
+                Some(s) if !s.mariadb && s.version >= (8, 0, 0) => Some(64 * MIB),
+                Some(_) => Some(4 * MIB),
+                None => Some(64 * MIB),
+            },
+            // 65,536 network packets of 4 KiB
+            Dialect::MsSql => Some(256 * MIB),
+            // MaxAllocSize
+            Dialect::Postgres => Some(1024 * MIB - 1),
+            // SQLITE_MAX_SQL_LENGTH
+            Dialect::Sqlite => Some(1_000_000_000),
+            Dialect::Generic => None,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{ast::filters, conditions, order_keys, Dialect, Renderer, SQLError, SQLable, SqlValue};
+
+/// Stable pagination over a query whose rows may change between pages.
+///
+/// `create()` copies the keys of every matching row, numbered in the query's
+/// order, into a temporary table. `page(n)` then joins that table back to
+/// the source, so every page is cut from the same set of rows in the same
+/// order no matter what was inserted or updated in between; rows deleted in
+/// the meantime are left out of their page.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("orders o");
+/// q.select(vec!["id".to_owned(), "total".to_owned()])
+///     .order_by("createdAt", Order::Desc);
+/// let snap = q.snapshot("orders_snap", "id", 50);
+/// let (create, _) = snap.create().unwrap();
+/// let (page, params) = snap.page(2).unwrap();
+/// let drop = snap.drop_table();
+/// ```
+pub struct Snapshot<'a> {
+    query: &'a SQLable,
+    table: String,
+    key: String,
+    page_size: usize,
+}
+
+impl SQLable {
+    /// A snapshot of this query's rows in the temporary table `table`,
+    /// identified by the unique column `key` and read `page_size` at a time.
+    pub fn snapshot(&self, table: &str, key: &str, page_size: usize) -> Snapshot<'_> {
+        Snapshot {
+            query: self,
+            table: table.to_owned(),
+            key: key.to_owned(),
+            page_size: page_size.max(1),
+        }
+    }
+}
+
+impl Snapshot<'_> {
+    /// The statement filling the temporary table, and its parameters.
+    pub fn create(&self) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let q = self.query;
+        if !q.group_by.is_empty() || !q.having.is_empty() {
+            return Err(SQLError::Unsupported(
+                "snapshot of a grouped query".to_owned(),
+            ));
+        }
+
+        let mut r = self.renderer();
+        let key = r.column(&q.col(&self.key));
+        // the key breaks ties, so the numbering does not depend on the plan
+        let mut order = order_keys(&q.order_by, &mut r)?;
+        order.push(key.clone());
+        let select = format!(
+            "SELECT\n  ROW_NUMBER() OVER (ORDER BY {}) AS snapshot_pos,{} AS snapshot_key\n",
+            order.join(","),
+            key
+        );
+        let from = format!("FROM {}\n", q.table.render(&r));
+        let whr = conditions("WHERE", &filters(&q.ast().filter, &mut r)?);
+
+        let sql = match r.dialect {
+            Dialect::MsSql => format!("{}INTO {}\n{}{}", select, self.table_name(&r), from, whr),
+            _ => format!(
+                "CREATE TEMPORARY TABLE {} AS\n{}{}{}",
+                self.table_name(&r),
+                select,
+                from,
+                whr
+            ),
+        };
+        Ok((sql, r.into_params()))
+    }
+
+    /// The statement reading the `n`-th page (from 0), and its parameters.
+    pub fn page(&self, n: usize) -> Result<(String, Vec<SqlValue>), SQLError> {
+        let q = self.query;
+        let mut r = self.renderer();
+        let qualifier = q.table.qualifier();
+
+        let mut select = q.ast().render_projection(&mut r)?;
+        if select == "*" {
+            select = format!("{}.*", r.ident(qualifier));
+        }
+        let snap = self.table_name(&r);
+        let first = r.bind(SqlValue::UInt((n * self.page_size) as u64));
+        let last = r.bind(SqlValue::UInt(((n + 1) * self.page_size) as u64));
+
+        let sql = format!(
+            "SELECT\n  {}\nFROM {}\nJOIN {} ON {}.snapshot_key = {}\nWHERE\n  ({}.snapshot_pos > {})\n  AND ({}.snapshot_pos <= {})\nORDER BY\n  {}.snapshot_pos\n",
+            select,
+            q.table.render(&r),
+            snap,
+            snap,
+            r.column(&q.col(&self.key)),
+            snap,
+            first,
+            snap,
+            last,
+            snap
+        );
+        Ok((sql, r.into_params()))
+    }
+
+    /// The statement dropping the temporary table once done.
+    pub fn drop_table(&self) -> String {
+        let r = self.renderer();
+        match r.dialect {
+            Dialect::MySql => format!("DROP TEMPORARY TABLE {}", self.table_name(&r)),
+            _ => format!("DROP TABLE {}", self.table_name(&r)),
+        }
+    }
+
+    fn renderer(&self) -> Renderer {
+        let mut r = self.query.renderer();
+        r.params = Some(Vec::new());
+        r
+    }
+
+    /// SQL Server keeps temporary tables in `#name`.
+    fn table_name(&self, r: &Renderer) -> String {
+        match r.dialect {
+            Dialect::MsSql => r.dialect.ident_part(&format!("#{}", self.table), r.quoting),
+            _ => r.ident(&self.table),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{And, Filter, Not, Or, SQLComp, SQLError, SQLFilter, SqlValue, ValueType};
+
+/// A tree of filters as data, typically user-defined filters received by
+/// an API:
+///
+/// ```
+/// # use traits::*;
+/// let spec: FilterSpec = serde_json::from_str(r#"{"and": [
+///     {"col": "age", "op": ">=", "value": 18},
+///     {"col": "status", "op": "in", "value": ["a", "b"]}
+/// ]}"#).unwrap();
+/// let filter = spec
+///     .to_filter(&[("age", ValueType::Integer), ("status", ValueType::Text)])
+///     .unwrap();
+/// let mut q = SQLable::new("users");
+/// q.filter(vec![filter]);
+/// ```
+///
+/// `op` is anything `SQLComp` parses, or `in` / `not in` which take a
+/// list. Nothing is trusted: see `to_filter`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterSpec {
+    And {
+        and: Vec<FilterSpec>,
+    },
+    Or {
+        or: Vec<FilterSpec>,
+    },
+    Not {
+        not: Box<FilterSpec>,
+    },
+    Compare {
+        col: String,
+        op: String,
+        #[serde(default = "null")]
+        value: SqlValue,
+    },
+}
+
+fn null() -> SqlValue {
+    SqlValue::Null
+}
+
+impl FilterSpec {
+    /// Builds the filter, allowing only the listed columns, each compared
+    /// with values of its type. `in` / `not in` take a list of such values,
+    /// the other operators a single one; NULL is only accepted by `=`, `<>`
+    /// and the `DISTINCT` comparisons.
+    pub fn to_filter(&self, columns: &[(&str, ValueType)]) -> Result<Box<dyn Filter>, SQLError> {
+        let all = |specs: &[FilterSpec]| {
+            specs
+                .iter()
+                .map(|s| s.to_filter(columns))
+                .collect::<Result<Vec<Box<dyn Filter>>, SQLError>>()
+        };
+
+        match self {
+            FilterSpec::And { and } => Ok(Box::new(And(all(and)?))),
+            FilterSpec::Or { or } => Ok(Box::new(Or(all(or)?))),
+            FilterSpec::Not { not } => Ok(Box::new(Not(not.to_filter(columns)?))),
+            FilterSpec::Compare { col, op, value } => compare(col, op, value, columns),
+        }
+    }
+}
+
+fn compare(
+    col: &str,
+    op: &str,
+    value: &SqlValue,
+    columns: &[(&str, ValueType)],
+) -> Result<Box<dyn Filter>, SQLError> {
+    let invalid = |what: String| Err(SQLError::InvalidFilter(format!("{}: {}", col, what)));
+    let ty = match columns.iter().find(|(name, _)| *name == col) {
+        Some((_, ty)) => *ty,
+        None => return Err(SQLError::InvalidFilter(format!("unknown column {}", col))),
+    };
+
+    let (cmp, list) = match op.trim().to_lowercase().as_str() {
+        "in" => (SQLComp::EQ, true),
+        "not in" => (SQLComp::NEQ, true),
+        _ => {
+            let cmp = op.parse::<SQLComp>()?;
+            (cmp, cmp.is_array())
+        }
+    };
+
+    match value {
+        SqlValue::List(items) if list => {
+            if let Some(item) = items.iter().find(|v| !ty.accepts(v)) {
+                return invalid(format!("expected {:?}, got {:?}", ty, item));
+            }
+        }
+        _ if list => return invalid(format!("{} takes a list", op)),
+        SqlValue::Null
+            if !matches!(
+                cmp,
+                SQLComp::EQ | SQLComp::NEQ | SQLComp::DISTINCT | SQLComp::NOTDISTINCT
+            ) =>
+        {
+            return invalid(format!("cannot compare with NULL using {}", op));
+        }
+        SqlValue::Null => {}
+        v if !ty.accepts(v) => return invalid(format!("expected {:?}, got {:?}", ty, v)),
+        _ => {}
+    }
+
+    Ok(Box::new(SQLFilter {
+        column: col.to_owned().into(),
+        filter: value.clone(),
+        cmp,
+    }))
+}
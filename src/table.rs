@@ -0,0 +1,141 @@
+use crate::{Renderer, SQLError};
+
+/// The table a query reads from: an optional schema, the table name and an
+/// optional alias, each quoted on its own when rendered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableName {
+    pub schema: Option<String>,
+    pub name: String,
+    pub alias: Option<String>,
+    /// The name is SQL written as is, see `TableName::raw`.
+    pub raw: bool,
+}
+
+impl TableName {
+    pub fn new(name: &str) -> Self {
+        TableName {
+            schema: None,
+            name: name.to_owned(),
+            alias: None,
+            raw: false,
+        }
+    }
+
+    /// A table written as is, not quoted: a table function or a subquery
+    /// computed by trusted code, e.g. `generate_series(1, 10)`. Never
+    /// from user input, which `checked` is for. A subquery, in
+    /// parentheses, is aliased `subquery`, as most dialects require a
+    /// derived table to be named.
+    pub fn raw(sql: &str) -> Self {
+        TableName {
+            alias: sql
+                .trim_start()
+                .starts_with('(')
+                .then(|| "subquery".to_owned()),
+            raw: true,
+            ..TableName::new(sql)
+        }
+    }
+
+    /// Like `parse`, but fails unless `s` is `[schema.]table [[AS] alias]`
+    /// with no empty part or control character, for names from outside
+    /// the code:
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let table = TableName::checked("reports.Order o").unwrap();
+    /// let mut q = SQLable::from_table(table);
+    /// q.dialect(Dialect::Postgres);
+    /// assert_eq!(q.prepare(), "SELECT\n  *\nFROM reports.\"Order\" AS o\n");
+    ///
+    /// assert!(TableName::checked("users; DROP TABLE users").is_err());
+    /// assert!(TableName::checked("users.").is_err());
+    /// ```
+    ///
+    /// Each part is quoted as the query's `Quoting` requires, so any other
+    /// character is safe, except with `Quoting::Never`, which writes names
+    /// as given.
+    pub fn checked(s: &str) -> Result<Self, SQLError> {
+        let invalid = || SQLError::InvalidName(s.to_owned());
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (qualified, alias) = match words.as_slice() {
+            [name] => (*name, None),
+            [name, alias] => (*name, Some(*alias)),
+            [name, kw, alias] if kw.eq_ignore_ascii_case("as") => (*name, Some(*alias)),
+            _ => return Err(invalid()),
+        };
+        let (schema, name) = match qualified.rsplit_once('.') {
+            Some((schema, name)) => (Some(schema), name),
+            None => (None, qualified),
+        };
+
+        let parts = schema.into_iter().flat_map(|s| s.split('.'));
+        let valid = |part: &str| !part.is_empty() && !part.chars().any(char::is_control);
+        if !parts.chain([name]).chain(alias).all(valid) {
+            return Err(invalid());
+        }
+        Ok(TableName {
+            schema: schema.map(str::to_owned),
+            name: name.to_owned(),
+            alias: alias.map(str::to_owned),
+            raw: false,
+        })
+    }
+
+    /// Splits `schema.table alias` (or `schema.table AS alias`) into its
+    /// parts. Anything else is kept as the table name.
+    pub fn parse(s: &str) -> Self {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (qualified, alias) = match words.as_slice() {
+            [name] => (*name, None),
+            [name, alias] => (*name, Some(*alias)),
+            [name, kw, alias] if kw.eq_ignore_ascii_case("as") => (*name, Some(*alias)),
+            _ => return TableName::new(s),
+        };
+
+        let (schema, name) = match qualified.rsplit_once('.') {
+            Some((schema, name)) if !schema.is_empty() && !name.is_empty() => {
+                (Some(schema.to_owned()), name)
+            }
+            _ => (None, qualified),
+        };
+
+        TableName {
+            schema,
+            name: name.to_owned(),
+            alias: alias.map(str::to_owned),
+            raw: false,
+        }
+    }
+
+    /// The name columns are qualified with: the alias if there is one,
+    /// otherwise the table name.
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    pub(crate) fn render(&self, r: &Renderer) -> String {
+        let mut result = String::new();
+        if let Some(schema) = &self.schema {
+            // a schema may itself be qualified, e.g. `db.dbo` on SQL Server
+            result.push_str(&r.ident(schema));
+            result.push('.');
+        }
+        match self.raw {
+            true => result.push_str(&self.name),
+            false => result.push_str(&r.dialect.ident_part(&r.fit_part(&self.name), r.quoting)),
+        }
+        if let Some(alias) = &self.alias {
+            result.push_str(" AS ");
+            result.push_str(&r.dialect.ident_part(&r.fit_part(alias), r.quoting));
+        }
+
+        result
+    }
+}
+
+impl From<&str> for TableName {
+    fn from(s: &str) -> Self {
+        TableName::parse(s)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{quote, unrendered, Dialect, Expr, Filter, Renderer, SQLError, SqlValue, ToSql};
+
+impl Expr {
+    /// A filter keeping rows whose text matches the words of `query`, with
+    /// the full-text search of the dialect:
+    /// `to_tsvector(body) @@ plainto_tsquery('...')` on Postgres,
+    /// `MATCH(body) AGAINST ('...')` on MySQL, which needs a `FULLTEXT`
+    /// index on the column, `body MATCH '...'` on SQLite, for a column of
+    /// an FTS table, and `FREETEXT(body, '...')` on SQL Server.
+    ///
+    /// ```
+    /// # use traits::*;
+    /// let mut q = SQLable::new("posts");
+    /// q.dialect(Dialect::Postgres)
+    ///     .filter(vec![Box::new(col("body").matches("rust sql").config("english"))]);
+    /// assert_eq!(
+    ///     q.prepare_params().unwrap().0,
+    ///     "SELECT\n  *\nFROM posts\nWHERE\n  (to_tsvector('english', body) @@ plainto_tsquery('english', $1))\n"
+    /// );
+    ///
+    /// q.dialect(Dialect::MySql);
+    /// assert_eq!(
+    ///     q.prepare(),
+    ///     "SELECT\n  *\nFROM posts\nWHERE\n  (MATCH(body) AGAINST ('rust sql'))\n"
+    /// );
+    /// ```
+    pub fn matches(self, query: &str) -> TextMatch {
+        TextMatch {
+            expr: self,
+            query: query.to_owned(),
+            config: None,
+        }
+    }
+}
+
+/// A full-text search condition, see `Expr::matches`.
+#[derive(Clone, Debug)]
+pub struct TextMatch {
+    pub expr: Expr,
+    pub query: String,
+    /// The Postgres text search configuration, e.g. `english`; the
+    /// server's default if `None`. Ignored elsewhere.
+    pub config: Option<String>,
+}
+
+impl TextMatch {
+    pub fn config(mut self, config: &str) -> Self {
+        self.config = Some(config.to_owned());
+        self
+    }
+}
+
+impl Filter for TextMatch {
+    fn apply_filter(&self) -> String {
+        match self.try_apply_filter(&mut Renderer::default()) {
+            Ok(sql) => sql,
+            Err(e) => unrendered(&e),
+        }
+    }
+
+    fn try_apply_filter(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        if r.dialect == Dialect::MySql && !matches!(self.expr, Expr::Column(_)) {
+            return Err(SQLError::Unsupported(
+                "MATCH ... AGAINST over an expression, MySQL matches columns".to_owned(),
+            ));
+        }
+        let expr = self.expr.render(r)?;
+        let query = SqlValue::Text(self.query.clone()).render(r)?;
+        match r.dialect {
+            Dialect::Postgres => {
+                let config = match &self.config {
+                    Some(config) => format!("{}, ", quote(config)),
+                    None => String::new(),
+                };
+                Ok(format!(
+                    "to_tsvector({}{}) @@ plainto_tsquery({}{})",
+                    config, expr, config, query
+                ))
+            }
+            Dialect::MySql => Ok(format!("MATCH({}) AGAINST ({})", expr, query)),
+            Dialect::Sqlite => Ok(format!("{} MATCH {}", expr, query)),
+            Dialect::MsSql => Ok(format!("FREETEXT({}, {})", expr, query)),
+            Dialect::Generic => Err(SQLError::Unsupported(
+                "full-text search in Generic".to_owned(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{quote, Dialect, FromValue, Renderer, SQLError, SqlValue, ToSql};
+
+/// A UUID, written `'67e55044-10b1-426f-9247-bb680e5fe0c8'`, or as its 16
+/// bytes on MySQL under `UuidFormat::Binary`:
+///
+/// ```
+/// # use traits::*;
+/// let id: Uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap();
+/// let mut q = SQLable::new("sessions");
+/// q.filter(vec![Box::new(SQLFilter { column: "id".into(), filter: id, cmp: SQLComp::EQ })]);
+/// q.dialect(Dialect::MySql).uuid_format(UuidFormat::Binary);
+/// assert_eq!(
+///     q.prepare(),
+///     "SELECT\n  *\nFROM sessions\nWHERE\n  (id = UNHEX('67e5504410b1426f9247bb680e5fe0c8'))\n"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uuid(pub [u8; 16]);
+
+/// How UUIDs are written, see `SQLable::uuid_format`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UuidFormat {
+    /// The hyphenated text form, for `UUID`, `uniqueidentifier` and text
+    /// columns.
+    #[default]
+    Text,
+    /// `UNHEX('...')` on MySQL, for `BINARY(16)` columns; text elsewhere.
+    Binary,
+}
+
+impl Uuid {
+    /// The 32 hex digits, without hyphens.
+    pub fn hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl std::fmt::Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = self.hex();
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+impl std::str::FromStr for Uuid {
+    type Err = SQLError;
+
+    /// Parses the hyphenated form or the bare 32 hex digits, in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SQLError::Decode(format!("invalid UUID {}", quote(s)));
+        let hex: Vec<u8> = match s.len() {
+            32 => s.bytes().collect(),
+            36 if [8, 13, 18, 23].iter().all(|&i| s.as_bytes()[i] == b'-') => {
+                s.bytes().filter(|b| *b != b'-').collect()
+            }
+            _ => return Err(invalid()),
+        };
+        // from_str_radix alone would take a sign, or a hyphen out of place
+        if hex.len() != 32 || !hex.iter().all(u8::is_ascii_hexdigit) {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 16];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Uuid(bytes))
+    }
+}
+
+impl FromValue for Uuid {
+    fn from_value(value: SqlValue) -> Result<Self, SQLError> {
+        match value {
+            SqlValue::Text(s) => s.parse(),
+            v => Err(SQLError::Decode(format!("cannot read {:?} as Uuid", v))),
+        }
+    }
+}
+
+impl ToSql for Uuid {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "'{}'", self)
+    }
+
+    fn to_value(&self) -> Option<SqlValue> {
+        Some(SqlValue::Text(self.to_string()))
+    }
+
+    fn wraps_value(&self) -> bool {
+        true
+    }
+
+    fn render(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let binary = r.uuids == UuidFormat::Binary && r.dialect == Dialect::MySql;
+        match (binary, r.is_parameterized()) {
+            (true, true) => Ok(format!("UNHEX({})", r.bind(SqlValue::Text(self.hex())))),
+            (true, false) => Ok(format!("UNHEX('{}')", self.hex())),
+            (false, true) => Ok(r.bind(SqlValue::Text(self.to_string()))),
+            (false, false) => Ok(self.to_sql()),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use std::sync::Arc;
+
+use crate::{
+    ColumnDef, Dialect, Expr, Filter, FunctionDef, NamingStrategy, SQLError, SQLable, SqlValue,
+    Table, TableDef, TableName, ValueType,
+};
+
+/// The tables a database is known to have, to check queries against
+/// before they reach it, see `SQLable::validate`. Tables are added as
+/// definitions, written by hand or derived with `#[derive(Table)]`:
+///
+/// ```
+/// # use traits::*;
+/// let schema = Schema::new().table(
+///     TableDef::new("users")
+///         .column(ColumnDef::of::<i64>("id").not_null())
+///         .column(ColumnDef::of::<String>("email")),
+/// );
+///
+/// let mut q = SQLable::new("users");
+/// q.select(vec!["id".to_owned(), "email".to_owned()]);
+/// q.filter(vec![Box::new(SQLFilter { column: "id".into(), filter: 7, cmp: SQLComp::EQ })]);
+/// assert!(q.validate(&schema).is_ok());
+///
+/// q.filter(vec![Box::new(SQLFilter { column: "id".into(), filter: "7", cmp: SQLComp::EQ })]);
+/// assert!(matches!(q.validate(&schema), Err(SQLError::InvalidFilter(_))));
+///
+/// q.select(vec!["name".to_owned()]);
+/// assert!(matches!(q.validate(&schema), Err(SQLError::UnknownColumn(_))));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    tables: Vec<TableDef>,
+    functions: Vec<FunctionDef>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    pub fn table(mut self, def: TableDef) -> Self {
+        self.tables.push(def);
+        self
+    }
+
+    /// Adds the table of a struct, see `Table`.
+    pub fn of<T: Table>(self) -> Self {
+        self.table(T::table_def())
+    }
+
+    pub fn tables(&self) -> &[TableDef] {
+        &self.tables
+    }
+
+    /// Adds a function the database has, see `FunctionDef`. A function
+    /// added again replaces the earlier one.
+    pub fn function(mut self, def: FunctionDef) -> Self {
+        self.functions
+            .retain(|f| !f.name.eq_ignore_ascii_case(&def.name));
+        self.functions.push(def);
+        self
+    }
+
+    pub fn functions(&self) -> &[FunctionDef] {
+        &self.functions
+    }
+
+    /// The function named `name`, in any case.
+    pub(crate) fn function_def(&self, name: &str) -> Option<&FunctionDef> {
+        self.functions
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The definition of `table`. Without a schema, the name must be
+    /// defined once across schemas.
+    pub fn get(&self, table: &TableName) -> Result<&TableDef, SQLError> {
+        let mut found = self.tables.iter().filter(|def| {
+            def.table.name == table.name
+                && (table.schema.is_none()
+                    || def.table.schema.is_none()
+                    || def.table.schema == table.schema)
+        });
+        match (found.next(), found.next()) {
+            (Some(def), None) => Ok(def),
+            (Some(_), Some(_)) => Err(SQLError::Ambiguous(format!(
+                "table {}, defined in several schemas",
+                table.name
+            ))),
+            (None, _) => Err(SQLError::UnknownTable(name(table))),
+        }
+    }
+}
+
+fn name(table: &TableName) -> String {
+    match &table.schema {
+        Some(schema) => format!("{}.{}", schema, table.name),
+        None => table.name.clone(),
+    }
+}
+
+impl SQLable {
+    /// Checks the query against `schema`: the table must be defined, the
+    /// columns it selects, filters, groups and sorts on must exist, and
+    /// filters must compare them with values of their type. A qualified
+    /// column must be qualified with the query's table or alias.
+    ///
+    /// Column types are told apart broadly: integer, number and text
+    /// columns take values of that kind, other types any value. Calls to
+    /// the functions of `schema` must match their `FunctionDef`. Raw SQL,
+    /// expression filters, `HAVING` and `CASE` conditions are left to the
+    /// database.
+    pub fn validate(&self, schema: &Schema) -> Result<(), SQLError> {
+        let scope = Scope::new(self, schema)?;
+
+        let mut exprs: Vec<&Expr> = self.exprs.iter().collect();
+        exprs.extend(self.windows.iter().flat_map(|(_, w)| w.exprs()));
+        let aliases: Vec<&str> = self
+            .exprs
+            .iter()
+            .filter_map(|e| match e {
+                Expr::Alias(_, alias) => Some(alias.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let mut columns: Vec<&str> = Vec::new();
+        columns.extend(self.cols.iter().flatten().map(String::as_str));
+        columns.extend(self.distinct_on.iter().map(String::as_str));
+        exprs.iter().for_each(|e| expr_columns(e, &mut columns));
+        for column in columns {
+            scope.check(column)?;
+        }
+
+        let mut keys: Vec<&str> = self.group_by.iter().map(String::as_str).collect();
+        for (key, _, _) in self.order_by.iter() {
+            expr_columns(key, &mut keys);
+        }
+        for key in keys {
+            if !aliases.contains(&key) {
+                scope.check(key)?;
+            }
+        }
+
+        exprs.extend(self.order_by.iter().map(|(key, _, _)| key));
+        for expr in exprs {
+            self.check_calls(&scope, schema, expr)?;
+        }
+
+        for predicate in self.filter.iter().filter_map(|f| f.predicate()) {
+            for (column, _, value) in predicate.comparisons() {
+                let ty = value_type(scope.column(column)?);
+                let mut values = match value {
+                    SqlValue::List(values) => values.iter().collect(),
+                    v => vec![v],
+                };
+                values.retain(|v| !matches!(v, SqlValue::Null));
+                if let Some(v) = values.into_iter().find(|v| !ty.accepts(v)) {
+                    return Err(SQLError::InvalidFilter(format!(
+                        "{}: expected {:?}, got {:?}",
+                        column, ty, v
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SQLable {
+    /// Fails with `SQLError::InvalidName` on the first name the query
+    /// renders that its dialect refuses, see `Dialect::check_name`: the
+    /// table, columns and aliases, as the naming strategy writes them. Raw
+    /// SQL and expression filters are left to the database. Rendering
+    /// checks the names under `LongNames::Error`.
+    pub fn check_names(&self) -> Result<(), SQLError> {
+        self.check_names_for(self.dialect)
+    }
+
+    pub(crate) fn check_names_for(&self, dialect: Dialect) -> Result<(), SQLError> {
+        let mut names: Vec<String> = Vec::new();
+        if !self.table.raw {
+            names.extend(
+                self.table
+                    .schema
+                    .iter()
+                    .flat_map(|s| s.split('.'))
+                    .map(str::to_owned),
+            );
+            names.push(self.table.name.clone());
+            names.extend(self.table.alias.clone());
+        }
+
+        let mut exprs: Vec<&Expr> = self.exprs.iter().collect();
+        exprs.extend(self.windows.iter().flat_map(|(_, w)| w.exprs()));
+        exprs.extend(self.order_by.iter().map(|(key, _, _)| key));
+        for e in &exprs {
+            if let Expr::Alias(_, alias) = e {
+                names.push(alias.clone());
+            }
+        }
+        names.extend(self.windows.iter().map(|(name, _)| name.clone()));
+
+        let mut columns: Vec<&str> = Vec::new();
+        columns.extend(self.cols.iter().flatten().map(String::as_str));
+        columns.extend(self.distinct_on.iter().map(String::as_str));
+        columns.extend(self.group_by.iter().map(String::as_str));
+        exprs.iter().for_each(|e| expr_columns(e, &mut columns));
+        let mut columns: Vec<String> = columns.into_iter().map(str::to_owned).collect();
+        for predicate in self.filter.iter().filter_map(|f| f.predicate()) {
+            columns.extend(
+                predicate
+                    .comparisons()
+                    .into_iter()
+                    .map(|(c, _, _)| c.to_owned()),
+            );
+        }
+        for column in columns {
+            let column = self.naming.apply(&column);
+            names.extend(column.split('.').filter(|p| *p != "*").map(str::to_owned));
+        }
+
+        names.iter().try_for_each(|name| dialect.check_name(name))
+    }
+
+    /// Replaces `*` in the select list with the columns of the query's
+    /// table in `schema`, in order, and `alias.*` with them qualified. A
+    /// query selecting nothing selects them all. The columns are named as
+    /// in the database, which the query's naming strategy should keep.
+    pub fn expand_star(&mut self, schema: &Schema) -> Result<&mut Self, SQLError> {
+        if let Some(cols) = self.expanded(schema.get(&self.table)?) {
+            *Arc::make_mut(&mut self.cols) = Some(cols);
+        }
+        Ok(self)
+    }
+
+    /// Has `prepare` and the other renderings write the columns of the
+    /// query's table in `schema` in place of `*`, as `expand_star` does,
+    /// leaving the query as built. Rendering fails if `schema` does not
+    /// define the table.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use traits::*;
+    /// let schema = Arc::new(Schema::new().table(
+    ///     TableDef::new("users")
+    ///         .column(ColumnDef::of::<i64>("id"))
+    ///         .column(ColumnDef::of::<String>("email")),
+    /// ));
+    /// let mut q = SQLable::new("users");
+    /// q.explicit_columns(schema);
+    /// assert_eq!(q.prepare(), "SELECT\n  id,email\nFROM users\n");
+    /// ```
+    pub fn explicit_columns(&mut self, schema: Arc<Schema>) -> &mut Self {
+        self.explicit_columns = Some(schema);
+        self
+    }
+
+    /// The select list with `*` expanded from `def`, `None` if it has no
+    /// `*`.
+    pub(crate) fn expanded(&self, def: &TableDef) -> Option<Vec<String>> {
+        let qualifier = self.table.qualifier();
+        let star = format!("{}.*", qualifier);
+        let selected = match self.cols.as_deref() {
+            Some(cols) if cols.iter().any(|c| *c == "*" || *c == star) => cols,
+            None if self.exprs.is_empty() => &["*".to_owned()][..],
+            _ => return None,
+        };
+
+        let mut cols = Vec::with_capacity(selected.len() + def.columns.len());
+        for col in selected {
+            match col {
+                c if *c == "*" => cols.extend(def.columns.iter().map(|c| c.name.clone())),
+                c if *c == star => cols.extend(
+                    def.columns
+                        .iter()
+                        .map(|c| format!("{}.{}", qualifier, c.name)),
+                ),
+                c => cols.push(c.clone()),
+            }
+        }
+        Some(cols)
+    }
+}
+
+/// What the query's columns resolve against.
+pub(crate) struct Scope<'a> {
+    pub(crate) def: &'a TableDef,
+    qualifier: &'a str,
+    naming: NamingStrategy,
+    def_naming: NamingStrategy,
+}
+
+impl<'a> Scope<'a> {
+    pub(crate) fn new(query: &'a SQLable, schema: &'a Schema) -> Result<Self, SQLError> {
+        let def = schema.get(&query.table)?;
+        Ok(Scope {
+            def,
+            qualifier: query.table.qualifier(),
+            naming: query.naming,
+            def_naming: def.renderer(Dialect::Generic).naming,
+        })
+    }
+
+    /// The database name of `column`, or `*`.
+    pub(crate) fn name(&self, column: &str) -> Result<String, SQLError> {
+        match self.unqualified(column)? {
+            "*" => Ok("*".to_owned()),
+            _ => Ok(self.db_name(self.column(column)?)),
+        }
+    }
+
+    pub(crate) fn db_name(&self, column: &ColumnDef) -> String {
+        self.def_naming.apply(&column.name)
+    }
+
+    /// The column sets no two rows share: the primary key and the unique
+    /// constraints, by database name.
+    pub(crate) fn unique_keys(&self) -> Vec<Vec<String>> {
+        let names = |cols: &[String]| cols.iter().map(|c| self.def_naming.apply(c)).collect();
+        let mut keys: Vec<Vec<String>> = Vec::new();
+        if !self.def.primary_key.is_empty() {
+            keys.push(names(&self.def.primary_key));
+        }
+        keys.extend(self.def.uniques.iter().map(|(_, cols)| names(cols)));
+        keys.extend(
+            self.def
+                .columns
+                .iter()
+                .filter(|c| c.unique)
+                .map(|c| vec![self.db_name(c)]),
+        );
+        keys
+    }
+
+    /// The column lists the database keeps sorted, by database name: the
+    /// unique keys and the indexes, each with what names it.
+    pub(crate) fn indexes(&self) -> Vec<(String, Vec<String>)> {
+        let names = |cols: &[String]| cols.iter().map(|c| self.def_naming.apply(c)).collect();
+        let mut indexes: Vec<(String, Vec<String>)> = Vec::new();
+        if !self.def.primary_key.is_empty() {
+            indexes.push(("PRIMARY KEY".to_owned(), names(&self.def.primary_key)));
+        }
+        for (name, cols) in self.def.uniques.iter().chain(&self.def.indexes) {
+            indexes.push((name.clone(), names(cols)));
+        }
+        for c in self.def.columns.iter().filter(|c| c.unique) {
+            indexes.push((format!("{} UNIQUE", self.db_name(c)), vec![self.db_name(c)]));
+        }
+        indexes
+    }
+
+    /// Fails unless `column` is one of the table's, or `*`.
+    fn check(&self, column: &str) -> Result<(), SQLError> {
+        match self.unqualified(column)? {
+            "*" => Ok(()),
+            _ => self.column(column).map(|_| ()),
+        }
+    }
+
+    /// The definition of `column`, named as in the query.
+    pub(crate) fn column(&self, column: &str) -> Result<&'a ColumnDef, SQLError> {
+        let name = self.naming.apply(self.unqualified(column)?);
+        self.def
+            .columns
+            .iter()
+            .find(|c| self.db_name(c) == name)
+            .ok_or_else(|| SQLError::UnknownColumn(column.to_owned()))
+    }
+
+    /// `column` without its qualifier, which must be the table's.
+    fn unqualified<'c>(&self, column: &'c str) -> Result<&'c str, SQLError> {
+        match column.rsplit_once('.') {
+            Some((qualifier, name)) if qualifier == self.qualifier => Ok(name),
+            Some(_) => Err(SQLError::UnknownColumn(column.to_owned())),
+            None => Ok(column),
+        }
+    }
+}
+
+/// The columns `expr` reads.
+pub(crate) fn expr_columns<'a>(expr: &'a Expr, out: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Column(c) => out.push(c),
+        Expr::Value(_) | Expr::Case(_) => {}
+        Expr::Function { args, .. } | Expr::Concat(args) => {
+            args.iter().for_each(|e| expr_columns(e, out))
+        }
+        Expr::Cast(e, _) | Expr::Neg(e) | Expr::Alias(e, _) => expr_columns(e, out),
+        Expr::Json { expr, .. } => expr_columns(expr, out),
+        Expr::Over(e, window) => {
+            expr_columns(e, out);
+            window.exprs().for_each(|e| expr_columns(e, out));
+        }
+        Expr::Binary(lhs, _, rhs) => {
+            expr_columns(lhs, out);
+            expr_columns(rhs, out);
+        }
+    }
+}
+
+/// The kind of values a column holds, from its type.
+pub(crate) fn value_type(column: &ColumnDef) -> ValueType {
+    if column.enumeration.is_some() {
+        return ValueType::Text;
+    }
+    type_named(column.ty.name(Dialect::Generic))
+}
+
+/// The kind of values of the SQL type `name`.
+pub(crate) fn type_named(name: &str) -> ValueType {
+    let ty = name.to_uppercase();
+    let has = |words: &[&str]| words.iter().any(|w| ty.contains(w));
+    if has(&["INT"]) && !has(&["INTERVAL", "POINT"]) {
+        ValueType::Integer
+    } else if has(&["REAL", "FLOAT", "DOUBLE", "NUMERIC", "DECIMAL"]) {
+        ValueType::Number
+    } else if has(&["CHAR", "TEXT", "CLOB"]) {
+        ValueType::Text
+    } else {
+        ValueType::Any
+    }
+}
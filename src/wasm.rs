@@ -0,0 +1,177 @@
+//! JavaScript bindings, so that browser-side tools can preview the SQL their
+//! filters will generate.
+//!
+//! ```js
+//! const q = Query.fromObject({
+//!   table: "tbl",
+//!   dialect: "postgres",
+//!   columns: ["userId", "name"],
+//!   filters: [{ column: "age", op: ">=", value: 18 }],
+//! });
+//! const { sql, params } = q.prepare();
+//! ```
+//!
+//! The module is built with `cargo rustc --lib --crate-type cdylib --target
+//! wasm32-unknown-unknown --features wasm --profile wasm-release`; the crate
+//! itself is an rlib only, so that dependents do not build the module.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Dialect, SQLComp, SQLFilter, SQLable, SqlValue};
+
+#[wasm_bindgen]
+pub struct Query {
+    inner: SQLable,
+}
+
+#[wasm_bindgen]
+impl Query {
+    /// A query reading from `table`, `[schema.]table [[AS] alias]`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(table: &str) -> Result<Query, JsError> {
+        let inner = SQLable::try_new(table).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Query { inner })
+    }
+
+    /// Builds a query from `{ table, dialect?, columns?, filters? }`, where
+    /// each filter is `{ column, op, value }`.
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(obj: JsValue) -> Result<Query, JsError> {
+        let table = get(&obj, "table")?
+            .as_string()
+            .ok_or_else(|| JsError::new("`table` must be a string"))?;
+        let mut query = Query::new(&table)?;
+
+        let dialect = get(&obj, "dialect")?;
+        if !dialect.is_undefined() {
+            let dialect = dialect
+                .as_string()
+                .ok_or_else(|| JsError::new("`dialect` must be a string"))?;
+            query.dialect(&dialect)?;
+        }
+
+        let columns = get(&obj, "columns")?;
+        if !columns.is_undefined() {
+            let columns = array(&columns, "columns")?
+                .iter()
+                .map(|c| {
+                    c.as_string()
+                        .ok_or_else(|| JsError::new("`columns` must contain strings"))
+                })
+                .collect::<Result<Vec<String>, JsError>>()?;
+            query.select(columns);
+        }
+
+        let filters = get(&obj, "filters")?;
+        if !filters.is_undefined() {
+            for f in array(&filters, "filters")?.iter() {
+                let column = get(&f, "column")?
+                    .as_string()
+                    .ok_or_else(|| JsError::new("filter `column` must be a string"))?;
+                let op = get(&f, "op")?
+                    .as_string()
+                    .ok_or_else(|| JsError::new("filter `op` must be a string"))?;
+                query.filter(&column, &op, get(&f, "value")?)?;
+            }
+        }
+
+        Ok(query)
+    }
+
+    /// Sets the dialect by name, e.g. `"postgres"`, `"mysql"` or `"sqlite"`.
+    pub fn dialect(&mut self, name: &str) -> Result<(), JsError> {
+        let dialect: Dialect = name.parse()?;
+        self.inner.dialect(dialect);
+        Ok(())
+    }
+
+    pub fn select(&mut self, columns: Vec<String>) {
+        self.inner.select(columns);
+    }
+
+    /// Adds a filter; `value` may be a number, a string, `null` or an array
+    /// of those.
+    pub fn filter(&mut self, column: &str, op: &str, value: JsValue) -> Result<(), JsError> {
+        let cmp: SQLComp = op.parse()?;
+        let filter = SQLFilter {
+            column: column.to_owned().into(),
+            filter: to_value(&value)?,
+            cmp,
+        };
+        self.inner.filter_values(vec![filter]);
+        Ok(())
+    }
+
+    /// The SQL with values inlined as literals.
+    pub fn sql(&self) -> Result<String, JsError> {
+        Ok(self.inner.try_prepare()?)
+    }
+
+    /// Returns `{ sql, params }` with the dialect's placeholders in `sql`.
+    pub fn prepare(&self) -> Result<JsValue, JsError> {
+        let (sql, params) = self.inner.prepare_params()?;
+        let out = js_sys::Object::new();
+        js_sys::Reflect::set(&out, &"sql".into(), &sql.into()).map_err(js_error)?;
+        js_sys::Reflect::set(&out, &"params".into(), &from_values(&params)).map_err(js_error)?;
+        Ok(out.into())
+    }
+}
+
+fn get(obj: &JsValue, key: &str) -> Result<JsValue, JsError> {
+    js_sys::Reflect::get(obj, &key.into()).map_err(js_error)
+}
+
+fn array(value: &JsValue, name: &str) -> Result<js_sys::Array, JsError> {
+    if !js_sys::Array::is_array(value) {
+        return Err(JsError::new(&format!("`{}` must be an array", name)));
+    }
+    Ok(js_sys::Array::from(value))
+}
+
+fn js_error(e: JsValue) -> JsError {
+    JsError::new(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+}
+
+fn to_value(value: &JsValue) -> Result<SqlValue, JsError> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(SqlValue::Null);
+    }
+    if let Some(n) = value.as_f64() {
+        // JS only has doubles; keep integral values as integers so that the
+        // rendered SQL reads `1` rather than `1.0`
+        if n.fract() == 0.0 && n.abs() <= 9_007_199_254_740_991.0 {
+            return Ok(SqlValue::Int(n as i64));
+        }
+        return Ok(SqlValue::Float(n));
+    }
+    if let Some(s) = value.as_string() {
+        return Ok(SqlValue::Text(s));
+    }
+    if js_sys::Array::is_array(value) {
+        return js_sys::Array::from(value)
+            .iter()
+            .map(|v| to_value(&v))
+            .collect::<Result<Vec<SqlValue>, JsError>>()
+            .map(SqlValue::List);
+    }
+
+    Err(JsError::new(&format!(
+        "unsupported filter value {:?}",
+        value
+    )))
+}
+
+fn from_values(values: &[SqlValue]) -> JsValue {
+    values
+        .iter()
+        .map(|v| match v {
+            SqlValue::Null => JsValue::NULL,
+            SqlValue::Int(n) => JsValue::from_f64(*n as f64),
+            SqlValue::UInt(n) => JsValue::from_f64(*n as f64),
+            SqlValue::Float(n) => JsValue::from_f64(*n),
+            SqlValue::Text(s) => JsValue::from_str(s),
+            SqlValue::List(v) => from_values(v),
+        })
+        .collect::<js_sys::Array>()
+        .into()
+}
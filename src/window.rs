@@ -0,0 +1,162 @@
+use crate::{
+    func, lit, order_keys, Expr, Feature, Nulls, Order, Renderer, SQLError, SQLable, ToSql,
+};
+
+/// The window of a window function: `OVER (PARTITION BY ... ORDER BY ...)`.
+///
+/// ```
+/// # use traits::*;
+/// let mut q = SQLable::new("payments");
+/// q.select(vec!["userId".to_owned(), "amount".to_owned()])
+///     .select_expr(row_number().over(Window::new().partition_by("userId").order_by("paidAt", Order::Asc)))
+///     .select_expr(sum("amount").over(Window::named("w").running()).alias("balance"))
+///     .window("w", Window::new().partition_by("userId").order_by("paidAt", Order::Asc));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Window {
+    name: Option<String>,
+    partition_by: Vec<Expr>,
+    order_by: Vec<(Expr, Order, Option<Nulls>)>,
+    frame: Option<String>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Window::default()
+    }
+
+    /// Refers to a window defined with `SQLable::window`; it may be refined
+    /// with a frame, or an `ORDER BY` if the named window has none.
+    pub fn named(name: &str) -> Self {
+        Window {
+            name: Some(name.to_owned()),
+            ..Window::default()
+        }
+    }
+
+    /// The partition and sort keys.
+    pub(crate) fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.partition_by
+            .iter()
+            .chain(self.order_by.iter().map(|(key, _, _)| key))
+    }
+
+    /// Adds a `PARTITION BY` key.
+    pub fn partition_by(mut self, key: impl Into<Expr>) -> Self {
+        self.partition_by.push(key.into());
+        self
+    }
+
+    /// Adds an `ORDER BY` key.
+    pub fn order_by(mut self, key: impl Into<Expr>, order: Order) -> Self {
+        self.order_by.push((key.into(), order, None));
+        self
+    }
+
+    /// Sets the frame clause as written, e.g. `ROWS BETWEEN 6 PRECEDING AND CURRENT ROW`.
+    pub fn frame(mut self, frame: &str) -> Self {
+        self.frame = Some(frame.to_owned());
+        self
+    }
+
+    /// Frames every row from the start of the partition to the current one,
+    /// so an aggregate becomes a running total. Unlike the default frame,
+    /// rows that tie in the ordering are not counted together.
+    pub fn running(self) -> Self {
+        self.frame("ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW")
+    }
+
+    /// The definition between the parentheses.
+    fn render_spec(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        let mut parts = Vec::new();
+        if let Some(name) = &self.name {
+            parts.push(r.ident(name));
+        }
+        if !self.partition_by.is_empty() {
+            let keys = self
+                .partition_by
+                .iter()
+                .map(|k| k.render(r))
+                .collect::<Result<Vec<String>, SQLError>>()?;
+            parts.push(format!("PARTITION BY {}", keys.join(",")));
+        }
+        if !self.order_by.is_empty() {
+            parts.push(format!(
+                "ORDER BY {}",
+                order_keys(&self.order_by, r)?.join(",")
+            ));
+        }
+        if let Some(frame) = &self.frame {
+            parts.push(frame.clone());
+        }
+        Ok(parts.join(" "))
+    }
+
+    /// `OVER name` for a bare reference, `OVER (...)` otherwise.
+    pub(crate) fn render_over(&self, r: &mut Renderer) -> Result<String, SQLError> {
+        r.require(Feature::WindowFunctions)?;
+        let bare = self.partition_by.is_empty() && self.order_by.is_empty() && self.frame.is_none();
+        match &self.name {
+            Some(name) if bare => Ok(format!("OVER {}", r.ident(name))),
+            _ => Ok(format!("OVER ({})", self.render_spec(r)?)),
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates the function over `window`: `row_number().over(...)`, or
+    /// a running aggregate such as `sum("amount").over(...)`.
+    pub fn over(self, window: Window) -> Expr {
+        Expr::Over(Box::new(self), window)
+    }
+}
+
+impl SQLable {
+    /// Defines a named window, rendered in a `WINDOW` clause and used
+    /// with `Window::named`.
+    pub fn window(&mut self, name: &str, window: Window) -> &mut Self {
+        self.windows.push((name.to_owned(), window));
+        self
+    }
+}
+
+/// Renders the `WINDOW` clause defining `windows`, or nothing.
+pub(crate) fn window_clause(
+    windows: &[(String, Window)],
+    r: &mut Renderer,
+) -> Result<String, SQLError> {
+    if windows.is_empty() {
+        return Ok(String::new());
+    }
+    r.require(Feature::NamedWindows)?;
+    let mut defs = Vec::with_capacity(windows.len());
+    for (name, window) in windows {
+        defs.push(format!("{} AS ({})", r.ident(name), window.render_spec(r)?));
+    }
+    Ok(format!("WINDOW\n  {}\n", defs.join(",")))
+}
+
+/// `ROW_NUMBER()`
+pub fn row_number() -> Expr {
+    func("ROW_NUMBER", [])
+}
+
+/// `RANK()`, with gaps after ties.
+pub fn rank() -> Expr {
+    func("RANK", [])
+}
+
+/// `DENSE_RANK()`, without gaps after ties.
+pub fn dense_rank() -> Expr {
+    func("DENSE_RANK", [])
+}
+
+/// `LAG(expr, offset)`, the value `offset` rows before the current one.
+pub fn lag(expr: impl Into<Expr>, offset: i64) -> Expr {
+    func("LAG", [expr.into(), lit(offset)])
+}
+
+/// `LEAD(expr, offset)`, the value `offset` rows after the current one.
+pub fn lead(expr: impl Into<Expr>, offset: i64) -> Expr {
+    func("LEAD", [expr.into(), lit(offset)])
+}
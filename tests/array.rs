@@ -0,0 +1,45 @@
+use traits::*;
+
+fn post(tags: Vec<SqlValue>) -> Record {
+    Record {
+        columns: vec!["tags".to_owned()],
+        values: vec![SqlValue::List(tags)],
+    }
+}
+
+#[test]
+fn arrays_compare_whole_with_equals() {
+    let f = SQLFilter {
+        column: "tags".into(),
+        filter: Array(vec!["rust", "sql"]),
+        cmp: SQLComp::EQ,
+    };
+    assert!(f.matches(&post(vec!["rust".into(), "sql".into()])).unwrap());
+    assert!(!f.matches(&post(vec!["rust".into()])).unwrap());
+    assert!(!f.matches(&post(vec!["sql".into(), "rust".into()])).unwrap());
+
+    let f = SQLFilter {
+        column: "tags".into(),
+        filter: Array(vec!["rust"]),
+        cmp: SQLComp::NEQ,
+    };
+    assert!(!f.matches(&post(vec!["rust".into()])).unwrap());
+    assert!(f.matches(&post(vec!["rust".into(), "sql".into()])).unwrap());
+}
+
+#[test]
+fn boxed_arrays_stay_arrays() {
+    let mut q = SQLable::new("posts");
+    q.dialect(Dialect::Postgres).filter_dyn(vec![SQLFilter {
+        column: "tags".into(),
+        filter: Box::new(Array(vec!["rust", "sql"])),
+        cmp: SQLComp::EQ,
+    }]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM posts\nWHERE\n  (tags = ARRAY['rust','sql'])\n"
+    );
+    let matches = q.matcher().unwrap();
+    assert!(matches(&post(vec!["rust".into(), "sql".into()])));
+    assert!(!matches(&post(vec!["rust".into()])));
+}
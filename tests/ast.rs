@@ -0,0 +1,110 @@
+use traits::*;
+
+struct Tenant(i64);
+
+impl QueryVisitor for Tenant {
+    fn visit_select(&mut self, select: &mut Select<'_>) {
+        select.filter.push(Box::new(SQLFilter {
+            column: "tenantId".into(),
+            filter: self.0,
+            cmp: SQLComp::EQ,
+        }));
+    }
+
+    fn visit_table(&mut self, table: &mut TableName) {
+        table.name = format!("{}_{}", table.name, self.0 % 4);
+    }
+}
+
+struct Nothing;
+
+impl QueryVisitor for Nothing {}
+
+#[test]
+fn visitor_rewrites_the_tree() {
+    let mut q = SQLable::new("orders");
+    q.dialect(Dialect::Postgres);
+    let (sql, params) = q.prepare_with(&mut Tenant(42)).unwrap();
+    assert_eq!(
+        sql,
+        "SELECT\n  *\nFROM orders_2\nWHERE\n  (tenant_id = $1)\n"
+    );
+    assert_eq!(params, vec![SqlValue::Int(42)]);
+}
+
+#[test]
+fn tree_renders_as_prepare() {
+    let mut q = SQLable::new("users");
+    q.select(vec!["id".to_owned()]).limit(5);
+    assert_eq!(
+        q.ast().render(&mut Renderer::default()).unwrap(),
+        q.prepare()
+    );
+    assert_eq!(
+        q.prepare_with(&mut Nothing).unwrap(),
+        q.prepare_params().unwrap()
+    );
+}
+
+#[test]
+fn prepare_with_checks_as_prepare_params() {
+    let long = "x".repeat(80);
+    let mut q = SQLable::new(&long);
+    q.dialect(Dialect::Postgres).long_names(LongNames::Error);
+    assert!(matches!(q.prepare_params(), Err(SQLError::InvalidName(_))));
+    assert!(matches!(
+        q.prepare_with(&mut Nothing),
+        Err(SQLError::InvalidName(_))
+    ));
+}
+
+struct Archive;
+
+impl QueryVisitor for Archive {
+    fn visit_join(&mut self, join: &mut Join) {
+        join.on
+            .push(("orders.archived".to_owned(), "items.archived".to_owned()));
+    }
+
+    fn visit_table(&mut self, table: &mut TableName) {
+        table.schema = Some("archive".to_owned());
+    }
+}
+
+#[test]
+fn visitor_walks_joins() {
+    let mut q = SQLable::new("orders");
+    q.join("items", &[("orders.id", "items.orderId")]);
+    let (sql, _) = q.prepare_with(&mut Archive).unwrap();
+    assert_eq!(
+        sql,
+        "SELECT\n  *\nFROM archive.orders\nJOIN archive.items ON orders.id = items.order_id AND orders.archived = items.archived\n"
+    );
+}
+
+#[test]
+fn union_all_keeps_filters_on_joined_tables_outside() {
+    let mut q = SQLable::new("events e");
+    q.dialect(Dialect::Postgres)
+        .archive("events_archive")
+        .include_archive(true)
+        .join("users u", &[("e.userId", "u.id")])
+        .filter(vec![
+            Box::new(SQLFilter {
+                column: "e.kind".into(),
+                filter: "click",
+                cmp: SQLComp::EQ,
+            }),
+            Box::new(SQLFilter {
+                column: "u.country".into(),
+                filter: "FR",
+                cmp: SQLComp::EQ,
+            }),
+        ]);
+    let (sql, params) = q.prepare_params().unwrap();
+    assert_eq!(
+        sql,
+        "SELECT\n  *\nFROM (\n  SELECT * FROM events AS e\n  WHERE (e.kind = $1)\n  UNION ALL\n  SELECT * FROM events_archive AS e\n  WHERE (e.kind = $2)\n) AS e\nJOIN users AS u ON e.user_id = u.id\nWHERE\n  (u.country = $3)\n"
+    );
+    assert_eq!(params.len(), 3);
+}
@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+
+use traits::*;
+
+/// Records the statements it runs.
+#[derive(Default)]
+struct Log(RefCell<Vec<String>>);
+
+impl Execute for Log {
+    type Error = ();
+
+    fn query(
+        &self,
+        _: &str,
+        _: &[SqlValue],
+        _: &mut dyn FnMut(&dyn Row) -> Result<(), SQLError>,
+    ) -> Result<(), ExecError<()>> {
+        Ok(())
+    }
+
+    fn execute(&self, sql: &str, params: &[SqlValue]) -> Result<u64, ExecError<()>> {
+        assert!(params.is_empty());
+        self.0.borrow_mut().push(sql.to_owned());
+        Ok(0)
+    }
+}
+
+#[test]
+fn postgres_batches_are_do_blocks() {
+    let mut insert = Insert::new("audit");
+    insert
+        .columns(vec!["note".to_owned()])
+        .row(vec!["it's $$ done".into()]);
+    let mut next = Call::function("next_invoice");
+    next.dialect(Dialect::Postgres);
+
+    let mut block = Block::new(Dialect::Postgres);
+    block.insert(&insert).batch().batch().call(&next);
+    assert_eq!(
+        block.batches().unwrap(),
+        vec![
+            "DO $do$\nBEGIN\n  INSERT INTO audit (note)\n  VALUES\n    ('it''s $$ done');\nEND\n$do$\n"
+                .to_owned(),
+            "DO $$\nBEGIN\n  PERFORM next_invoice();\nEND\n$$\n".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn execute_runs_every_batch() {
+    let mut block = Block::new(Dialect::MsSql);
+    block.raw("CREATE SCHEMA audit;").batch().raw("SELECT 1");
+    let log = Log::default();
+    block.execute(&log).unwrap();
+    assert_eq!(
+        log.0.into_inner(),
+        vec![
+            "CREATE SCHEMA audit;\n".to_owned(),
+            "SELECT 1;\n".to_owned()
+        ]
+    );
+}
+
+#[test]
+fn unsupported_blocks_fail() {
+    let block = Block::new(Dialect::MySql);
+    assert!(matches!(block.render(), Err(SQLError::Unsupported(_))));
+
+    let mut transfer = Call::procedure("transfer");
+    transfer.dialect(Dialect::Postgres).out::<i64>("balance");
+    let mut block = Block::new(Dialect::Postgres);
+    block.call(&transfer);
+    assert!(matches!(block.render(), Err(SQLError::Unsupported(_))));
+    assert!(matches!(
+        block.execute(&Log::default()),
+        Err(ExecError::Sql(SQLError::Unsupported(_)))
+    ));
+}
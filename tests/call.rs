@@ -0,0 +1,29 @@
+use traits::*;
+
+#[test]
+fn functions_are_selected() {
+    let mut call = Call::function("billing.next_invoice");
+    call.arg("it's").dialect(Dialect::Postgres);
+    assert_eq!(call.prepare(), "SELECT billing.next_invoice('it''s')\n");
+    assert_eq!(call.outputs(), vec!["next_invoice".to_owned()]);
+    assert_eq!(
+        call.statements().unwrap(),
+        vec![(
+            "SELECT billing.next_invoice($1)\n".to_owned(),
+            vec!["it's".into()]
+        )]
+    );
+}
+
+#[test]
+fn unrenderable_calls_do_not_panic() {
+    let mut call = Call::procedure("transfer");
+    call.out::<i64>("new balance").dialect(Dialect::MySql);
+    assert!(matches!(call.try_prepare(), Err(SQLError::InvalidName(_))));
+    assert_eq!(call.prepare(), "<invalid name  new balance >\n");
+
+    let mut call = Call::procedure("transfer");
+    call.arg(1).dialect(Dialect::Sqlite);
+    assert!(call.try_prepare().is_err());
+    assert!(call.prepare().starts_with('<'));
+}
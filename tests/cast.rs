@@ -0,0 +1,57 @@
+use traits::*;
+
+fn render(e: &Expr, dialect: Dialect, syntax: CastSyntax) -> String {
+    let mut r = Renderer::default();
+    r.dialect = dialect;
+    r.cast = syntax;
+    e.render(&mut r).unwrap()
+}
+
+#[test]
+fn casts_name_the_type_of_the_dialect() {
+    let e = lit("42").cast::<i64>();
+    assert_eq!(
+        render(&e, Dialect::Postgres, CastSyntax::Standard),
+        "CAST('42' AS BIGINT)"
+    );
+    assert_eq!(
+        render(&e, Dialect::MySql, CastSyntax::Standard),
+        "CAST('42' AS SIGNED)"
+    );
+    assert_eq!(
+        render(&e, Dialect::Sqlite, CastSyntax::Standard),
+        "CAST('42' AS INTEGER)"
+    );
+
+    let e = col("name").cast::<String>();
+    assert_eq!(
+        render(&e, Dialect::MsSql, CastSyntax::Standard),
+        "CAST(name AS NVARCHAR(MAX))"
+    );
+    assert_eq!(
+        <u32 as SqlType>::column_type(Dialect::MySql),
+        "BIGINT UNSIGNED"
+    );
+}
+
+#[test]
+fn shorthand_is_postgres_only() {
+    let e = cast("createdAt", "DATE");
+    assert_eq!(
+        render(&e, Dialect::Postgres, CastSyntax::Shorthand),
+        "created_at::date"
+    );
+    assert_eq!(
+        render(&e, Dialect::MySql, CastSyntax::Shorthand),
+        "CAST(created_at AS DATE)"
+    );
+
+    let mut q = SQLable::new("events");
+    q.dialect(Dialect::Postgres)
+        .cast_syntax(CastSyntax::Shorthand)
+        .select_expr((col("a") + col("b")).cast::<f64>());
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  (a + b)::double precision\nFROM events\n"
+    );
+}
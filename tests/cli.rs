@@ -0,0 +1,246 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const DEFS: &str = r#"
+active_users:
+  table: users
+  columns: [userId, name]
+  filters:
+    - { column: status, op: "=", value: active }
+    - { column: country, op: "=", param: country }
+all_orders:
+  table: orders
+"#;
+
+/// Writes `contents` to a file of the test's own, so tests can run in
+/// parallel.
+fn file(name: &str, contents: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dyn-trait"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the command with `input` on its standard input.
+fn run_with(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dyn-trait"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(out: &Output) -> String {
+    String::from_utf8(out.stdout.clone()).unwrap()
+}
+
+#[test]
+fn render_prints_each_query() {
+    let defs = file("render.yaml", DEFS);
+    let out = run(&[
+        "render",
+        defs.to_str().unwrap(),
+        "-d",
+        "postgres",
+        "-q",
+        "active_users",
+        "-p",
+        "country=fr",
+    ]);
+    assert!(out.status.success());
+    assert_eq!(
+        stdout(&out),
+        "-- active_users\nSELECT\n  user_id,name\nFROM users\nWHERE\n  (status = 'active')\n  AND (country = 'fr')\n\n"
+    );
+
+    let out = run(&[
+        "render",
+        defs.to_str().unwrap(),
+        "--bind",
+        "-p",
+        "country=fr",
+    ]);
+    assert!(out.status.success());
+    assert!(stdout(&out).contains("-- params: [Text(\"active\"), Text(\"fr\")]"));
+    assert!(stdout(&out).contains("-- all_orders\nSELECT\n  *\nFROM orders\n"));
+}
+
+#[test]
+fn errors_exit_with_status_2() {
+    let defs = file("errors.yaml", DEFS);
+    let out = run(&["render", defs.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "dyn-trait: active_users: no value for parameter country\n"
+    );
+
+    let out = run(&["render", defs.to_str().unwrap(), "-q", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn lint_exits_with_status_1_on_warnings() {
+    let defs = file("lint.yaml", DEFS);
+    let out = run(&["lint", defs.to_str().unwrap(), "-q", "all_orders"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stdout(&out).contains("all_orders: warning: no filter, the whole table is read\n"));
+
+    let out = run(&[
+        "lint",
+        defs.to_str().unwrap(),
+        "-q",
+        "active_users",
+        "-p",
+        "country=fr",
+    ]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out), "");
+}
+
+#[test]
+fn fingerprints_ignore_values() {
+    let defs = file(
+        "fingerprint.json",
+        r#"{"q": {"table": "users", "filters": [{"column": "id", "op": "=", "param": "id"}]}}"#,
+    );
+    let fp = |id: &str| {
+        let out = run(&["fingerprint", defs.to_str().unwrap(), "-p", id]);
+        assert!(out.status.success());
+        stdout(&out)
+    };
+    assert_eq!(fp("id=1"), fp("id=2"));
+    assert!(fp("id=1").ends_with("  q\n"));
+}
+
+#[test]
+fn repl_renders_after_each_change() {
+    let out = run_with(
+        &["repl", "-t", "users"],
+        "filter age >= 18\nselect id name\ndialect postgres\nparams\ndrop 5\nquit\nselect x\n",
+    );
+    assert!(out.status.success());
+    let out = stdout(&out);
+    assert!(out
+        .starts_with("SELECT\n  *\nFROM users\n\nSELECT\n  *\nFROM users\nWHERE\n  (age >= 18)\n"));
+    assert!(out
+        .ends_with("WHERE\n  (age >= $1)\n-- params: [Int(18)]\n\nerror: there is no filter 5\n"));
+}
+
+#[test]
+fn repl_starts_from_a_definition() {
+    let defs = file("repl.yaml", DEFS);
+    let out = run_with(
+        &["repl", defs.to_str().unwrap(), "-q", "all_orders"],
+        "show\n",
+    );
+    assert!(out.status.success());
+    assert!(stdout(&out).starts_with("SELECT\n  *\nFROM orders\n\ntable: orders\n"));
+
+    let out = run_with(&["repl", defs.to_str().unwrap()], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+/// A project of two definition files, in a directory of its own.
+fn project(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::create_dir_all(dir.join("queries")).unwrap();
+    std::fs::write(dir.join("queries/users.yaml"), DEFS).unwrap();
+    std::fs::write(
+        dir.join("queries/reports.json"),
+        r#"{"daily_totals": {"table": "totals"}}"#,
+    )
+    .unwrap();
+    let manifest = dir.join("dyn-trait.yaml");
+    std::fs::write(
+        &manifest,
+        "dialect: postgres\ncatalogs:\n  - queries/users.yaml\n  - queries/reports.json\n",
+    )
+    .unwrap();
+    manifest
+}
+
+#[test]
+fn catalog_lists_and_renders_the_manifest_queries() {
+    let manifest = project("catalog");
+    let manifest = manifest.to_str().unwrap();
+    let out = run(&["catalog", "-m", manifest]);
+    assert!(out.status.success());
+    let lines: Vec<String> = stdout(&out)
+        .lines()
+        .map(|l| l.split('\t').take(2).collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "active_users (country) users",
+            "all_orders orders",
+            "daily_totals totals"
+        ]
+    );
+
+    let out = run(&["catalog", "-m", manifest, "render", "daily_totals"]);
+    assert!(out.status.success());
+    assert_eq!(
+        stdout(&out),
+        "-- daily_totals\nSELECT\n  *\nFROM totals\n\n"
+    );
+
+    let out = run(&["catalog", "-m", manifest, "render", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn completions_know_the_catalog_queries() {
+    let manifest = project("completions");
+    let out = run(&["completions", "bash", "-m", manifest.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert!(stdout(&out).contains("daily_totals"));
+}
+
+#[test]
+fn plan_diff_exits_with_status_1_on_changes() {
+    let before = file(
+        "before.yaml",
+        "by_email: |\n  Index Scan using users_email on users\nrecent: Seq Scan on orders\n",
+    );
+    let after = file(
+        "after.yaml",
+        "by_email: |\n  Seq Scan on users\ntotals: Seq Scan on totals\n",
+    );
+    let out = run(&[
+        "plan-diff",
+        before.to_str().unwrap(),
+        after.to_str().unwrap(),
+    ]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        stdout(&out),
+        "~ plan by_email\n  - Index Scan using users_email on users\n  + Seq Scan on users\n- plan recent\n+ plan totals\n"
+    );
+
+    let out = run(&[
+        "plan-diff",
+        before.to_str().unwrap(),
+        before.to_str().unwrap(),
+    ]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out), "");
+}
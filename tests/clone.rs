@@ -0,0 +1,49 @@
+//! Copies of a query share their parts until one of them changes.
+
+use traits::*;
+
+fn active() -> Box<SQLFilter<&'static str>> {
+    Box::new(SQLFilter {
+        column: "status".into(),
+        filter: "active",
+        cmp: SQLComp::EQ,
+    })
+}
+
+#[test]
+fn changing_a_copy_leaves_the_original() {
+    let mut base = SQLable::new("users");
+    base.select(vec!["id".to_owned()]).filter(vec![active()]);
+    let before = base.prepare();
+
+    let mut copy = base.clone();
+    copy.add_filter(Box::new(SQLFilter {
+        column: "age".into(),
+        filter: 18,
+        cmp: SQLComp::GEQ,
+    }))
+    .add_column("name")
+    .order_by(col("id"), Order::Desc);
+    assert_eq!(base.prepare(), before);
+    assert_eq!(
+        copy.prepare(),
+        "SELECT\n  id,name\nFROM users\nWHERE\n  (status = 'active')\n  AND (age >= 18)\nORDER BY\n  id DESC\n"
+    );
+
+    base.clear_filters();
+    assert_eq!(base.prepare(), "SELECT\n  id\nFROM users\n");
+    assert!(copy.prepare().contains("(status = 'active')"));
+}
+
+#[test]
+fn copies_render_alike_across_threads() {
+    let mut base = SQLable::new("users");
+    base.filter(vec![active()]);
+    let copies: Vec<SQLable> = (0..4).map(|_| base.clone()).collect();
+    let rendered: Vec<String> = copies
+        .into_iter()
+        .map(|q| std::thread::spawn(move || q.prepare()))
+        .map(|h| h.join().unwrap())
+        .collect();
+    assert!(rendered.iter().all(|sql| *sql == base.prepare()));
+}
@@ -0,0 +1,70 @@
+use traits::*;
+
+#[test]
+fn sqlstates_are_classified() {
+    assert_eq!(
+        DbErrorKind::from_sqlstate("23505"),
+        DbErrorKind::UniqueViolation
+    );
+    assert_eq!(DbErrorKind::from_sqlstate("40P01"), DbErrorKind::Deadlock);
+    assert_eq!(
+        DbErrorKind::from_sqlstate("42S02"),
+        DbErrorKind::UndefinedTable
+    );
+    // classes without a kind of their own
+    assert_eq!(
+        DbErrorKind::from_sqlstate("23000"),
+        DbErrorKind::IntegrityViolation
+    );
+    assert_eq!(
+        DbErrorKind::from_sqlstate("08006"),
+        DbErrorKind::ConnectionFailure
+    );
+    assert_eq!(DbErrorKind::from_sqlstate("XX000"), DbErrorKind::Other);
+}
+
+#[test]
+fn vendor_codes_are_classified_per_dialect() {
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::MySql, 1062),
+        DbErrorKind::UniqueViolation
+    );
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::MsSql, 2627),
+        DbErrorKind::UniqueViolation
+    );
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::Sqlite, 2067),
+        DbErrorKind::UniqueViolation
+    );
+    // SQLITE_CONSTRAINT_TRIGGER and SQLITE_LOCKED_SHAREDCACHE
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::Sqlite, 1811),
+        DbErrorKind::IntegrityViolation
+    );
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::Sqlite, 262),
+        DbErrorKind::LockTimeout
+    );
+    // the same number means different things per vendor
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::MySql, 1205),
+        DbErrorKind::LockTimeout
+    );
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::MsSql, 1205),
+        DbErrorKind::Deadlock
+    );
+    assert_eq!(
+        DbErrorKind::from_code(Dialect::Postgres, 1062),
+        DbErrorKind::Other
+    );
+}
+
+#[test]
+fn transient_kinds() {
+    assert!(DbErrorKind::SerializationFailure.is_transient());
+    assert!(DbErrorKind::ConnectionFailure.is_transient());
+    assert!(!DbErrorKind::UniqueViolation.is_transient());
+    assert_eq!(DbErrorKind::Deadlock.to_string(), "deadlock detected");
+}
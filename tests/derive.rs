@@ -0,0 +1,197 @@
+//! The derives of the `macros` feature, run on real types.
+#![cfg(feature = "macros")]
+
+use traits::*;
+
+#[derive(Debug, PartialEq, SqlEnum)]
+#[sql(name = "order_status")]
+enum OrderStatus {
+    #[sql(rename = "pending")]
+    Pending,
+    Shipped,
+}
+
+/// Registered accounts.
+#[derive(Table)]
+#[sql(name = "accounts")]
+pub struct Account {
+    #[sql(primary_key)]
+    pub id: i64,
+    /// Shown to other users.
+    pub display_name: Option<String>,
+}
+
+#[derive(Table)]
+pub struct OrderLine {
+    #[sql(primary_key)]
+    pub order_id: i64,
+    #[sql(primary_key, rename = "line")]
+    pub line_no: i64,
+    #[sql(references = "accounts(id)")]
+    pub account_id: i64,
+    #[sql(unique)]
+    pub reference: String,
+    #[sql(default = "CURRENT_TIMESTAMP")]
+    pub created_at: String,
+}
+
+#[derive(Debug, PartialEq, FromRow)]
+struct User {
+    id: i64,
+    #[sql(rename = "full_name")]
+    name: String,
+    nickname: Option<String>,
+}
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Pair(i64, String);
+
+fn record(columns: &[&str], values: Vec<SqlValue>) -> Record {
+    Record {
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        values,
+    }
+}
+
+#[test]
+fn sql_enum_writes_labels() {
+    assert_eq!(OrderStatus::TYPE_NAME, "order_status");
+    assert_eq!(OrderStatus::VARIANTS, &["pending", "Shipped"]);
+    assert_eq!(OrderStatus::Pending.as_str(), "pending");
+    assert_eq!(OrderStatus::Shipped.to_sql(), "'Shipped'");
+    assert_eq!(
+        SqlValue::from(OrderStatus::Pending),
+        SqlValue::from("pending")
+    );
+
+    let f = SQLFilter {
+        column: "status".into(),
+        filter: OrderStatus::Pending,
+        cmp: SQLComp::EQ,
+    };
+    assert_eq!(f.apply_filter(), "status = 'pending'");
+}
+
+#[test]
+fn table_builds_the_definition() {
+    let def = Account::table_def();
+    assert_eq!(def.table.name, "accounts");
+    assert_eq!(def.primary_key, vec!["id".to_owned()]);
+    assert_eq!(def.create_statements(Dialect::Postgres).unwrap(), vec![
+            "CREATE TABLE accounts (\n  id BIGINT NOT NULL,\n  display_name TEXT,\n  PRIMARY KEY (id)\n)\n",
+            "COMMENT ON TABLE accounts IS 'Registered accounts.'\n",
+            "COMMENT ON COLUMN accounts.display_name IS 'Shown to other users.'\n",
+        ]);
+}
+
+#[test]
+fn table_constraints() {
+    let def = OrderLine::table_def();
+    assert_eq!(def.table.name, "order_line");
+    assert_eq!(
+        def.primary_key,
+        vec!["order_id".to_owned(), "line".to_owned()]
+    );
+    assert_eq!(
+        def.create_table(Dialect::Postgres).unwrap(),
+        "CREATE TABLE order_line (\n  order_id BIGINT NOT NULL,\n  line BIGINT NOT NULL,\n  account_id BIGINT NOT NULL,\n  reference TEXT NOT NULL UNIQUE,\n  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,\n  PRIMARY KEY (order_id,line),\n  FOREIGN KEY (account_id) REFERENCES accounts (id)\n)\n"
+    );
+}
+
+#[test]
+fn from_row_reads_named_columns() {
+    let row = record(
+        &["id", "full_name", "nickname"],
+        vec![1.into(), "Ann".into(), SqlValue::Null],
+    );
+    assert_eq!(
+        User::from_row(&row).unwrap(),
+        User {
+            id: 1,
+            name: "Ann".to_owned(),
+            nickname: None
+        }
+    );
+
+    let missing = record(&["id"], vec![1.into()]);
+    assert!(User::from_row(&missing).is_err());
+}
+
+#[test]
+fn from_row_reads_tuple_structs_in_order() {
+    let row = record(&["a", "b"], vec![7.into(), "x".into()]);
+    assert_eq!(Pair::from_row(&row).unwrap(), Pair(7, "x".to_owned()));
+}
+
+#[derive(ToRow)]
+struct Person {
+    #[sql(rename = "age")]
+    years: i64,
+    name: String,
+    status: OrderStatus,
+    nickname: Option<String>,
+}
+
+#[derive(ToRow)]
+struct Cooldown {
+    wait: Interval,
+}
+
+#[derive(ToRow)]
+struct Computed {
+    created: Raw,
+}
+
+#[test]
+fn to_row_names_columns_as_from_row() {
+    let person = Person {
+        years: 30,
+        name: "Ann".to_owned(),
+        status: OrderStatus::Shipped,
+        nickname: None,
+    };
+    let row = person.to_row().unwrap();
+    assert_eq!(row.columns, vec!["age", "name", "status", "nickname"]);
+    assert_eq!(
+        row.values,
+        vec![30.into(), "Ann".into(), "Shipped".into(), SqlValue::Null]
+    );
+
+    let adults = SQLFilter {
+        column: "age".into(),
+        filter: 18,
+        cmp: SQLComp::GEQ,
+    };
+    assert!(adults.matches(&person).unwrap());
+    let nameless = SQLFilter {
+        column: "nickname".into(),
+        filter: SqlValue::Null,
+        cmp: SQLComp::EQ,
+    };
+    assert!(nameless.matches(&person).unwrap());
+}
+
+#[test]
+fn to_row_fails_on_fields_without_a_value() {
+    let f = SQLFilter {
+        column: "wait".into(),
+        filter: 1,
+        cmp: SQLComp::GT,
+    };
+    let cooldown = Cooldown {
+        wait: Interval::from_micros(1_000_000),
+    };
+    assert!(matches!(cooldown.to_row(), Err(SQLError::Unsupported(_))));
+    assert!(f.matches(&cooldown).is_err());
+
+    let computed = Computed {
+        created: Raw::new("now()"),
+    };
+    assert!(matches!(computed.to_row(), Err(SQLError::Unsupported(_))));
+    let f = SQLFilter {
+        column: "created".into(),
+        filter: SqlValue::Null,
+        cmp: SQLComp::EQ,
+    };
+    assert!(f.matches(&computed).is_err());
+}
@@ -0,0 +1,52 @@
+//! NULL-safe comparisons per dialect.
+
+use traits::*;
+
+fn query(cmp: SQLComp, dialect: Dialect) -> String {
+    let mut q = SQLable::new("users");
+    q.dialect(dialect).filter(vec![Box::new(SQLFilter {
+        column: "managerId".into(),
+        filter: Some(7),
+        cmp,
+    })]);
+    q.prepare()
+}
+
+fn where_clause(cmp: SQLComp, dialect: Dialect) -> String {
+    query(cmp, dialect)
+        .split_once("WHERE\n  ")
+        .unwrap()
+        .1
+        .trim_end()
+        .to_owned()
+}
+
+#[test]
+fn null_safe_comparisons_follow_the_dialect() {
+    assert_eq!(
+        where_clause(SQLComp::DISTINCT, Dialect::Postgres),
+        "(manager_id IS DISTINCT FROM 7)"
+    );
+    assert_eq!(
+        where_clause(SQLComp::NOTDISTINCT, Dialect::Generic),
+        "(manager_id IS NOT DISTINCT FROM 7)"
+    );
+    assert_eq!(
+        where_clause(SQLComp::DISTINCT, Dialect::Sqlite),
+        "(manager_id IS NOT 7)"
+    );
+    assert_eq!(
+        where_clause(SQLComp::NOTDISTINCT, Dialect::MySql),
+        "(manager_id <=> 7)"
+    );
+    assert_eq!(
+        where_clause(SQLComp::DISTINCT, Dialect::MySql),
+        "(NOT (manager_id <=> 7))"
+    );
+}
+
+#[test]
+fn operators_parse() {
+    assert!(matches!("<=>".parse(), Ok(SQLComp::NOTDISTINCT)));
+    assert!(matches!("is distinct from".parse(), Ok(SQLComp::DISTINCT)));
+}
@@ -0,0 +1,82 @@
+use traits::*;
+
+#[test]
+fn renders_expressions() {
+    let e = func("lower", [col("userEmail")]);
+    assert_eq!(e.to_sql(), "lower(user_email)");
+    assert_eq!(
+        case()
+            .when(
+                SQLFilter {
+                    column: "age".into(),
+                    filter: 18,
+                    cmp: SQLComp::LT
+                },
+                "minor"
+            )
+            .otherwise("adult")
+            .end()
+            .to_sql(),
+        "CASE WHEN age < 18 THEN 'minor' ELSE 'adult' END"
+    );
+}
+
+#[test]
+fn expr_filter_binds_its_value() {
+    let f = ExprFilter {
+        expr: func("lower", [col("email")]),
+        filter: "a@b.c",
+        cmp: SQLComp::EQ,
+    };
+    assert_eq!(f.apply_filter(), "lower(email) = 'a@b.c'");
+    let mut r = Renderer::parameterized();
+    r.dialect = Dialect::Postgres;
+    assert_eq!(f.try_apply_filter(&mut r).unwrap(), "lower(email) = $1");
+}
+
+#[test]
+fn unrenderable_expressions_do_not_panic() {
+    let empty = case().end();
+    assert!(matches!(
+        empty.render(&mut Renderer::default()),
+        Err(SQLError::Unsupported(_))
+    ));
+    assert_eq!(empty.to_sql(), "<unsupported: CASE without WHEN>");
+
+    let f = ExprFilter {
+        expr: empty,
+        filter: 1,
+        cmp: SQLComp::EQ,
+    };
+    assert!(f.try_apply_filter(&mut Renderer::default()).is_err());
+    assert_eq!(f.apply_filter(), "<unsupported: CASE without WHEN>");
+}
+
+#[test]
+fn empty_lists_bind_nothing_of_the_expression() {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres).filter(vec![
+        Box::new(ExprFilter {
+            expr: func("concat", [col("code"), lit("X")]),
+            filter: Vec::<String>::new(),
+            cmp: SQLComp::EQ,
+        }),
+        Box::new(SQLFilter {
+            column: "a".into(),
+            filter: 5,
+            cmp: SQLComp::EQ,
+        }),
+    ]);
+    let (sql, params) = q.prepare_params().unwrap();
+    assert_eq!(
+        sql,
+        "SELECT\n  *\nFROM users\nWHERE\n  (1=0)\n  AND (a = $1)\n"
+    );
+    assert_eq!(params, vec![SqlValue::Int(5)]);
+
+    q.empty_list(EmptyList::Error);
+    assert!(matches!(
+        q.prepare_params(),
+        Err(SQLError::EmptyList(lhs)) if lhs == "concat(code, 'X')"
+    ));
+}
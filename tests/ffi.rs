@@ -0,0 +1,177 @@
+//! The C ABI, called as a C host would.
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr, CString};
+
+use traits::ffi::*;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn last_error() -> String {
+    let e = dt_last_error();
+    assert!(!e.is_null());
+    unsafe { CStr::from_ptr(e) }.to_str().unwrap().to_owned()
+}
+
+unsafe fn render(q: *const DtQuery) -> String {
+    let sql = dt_query_render(q);
+    assert!(!sql.is_null(), "{}", last_error());
+    let out = CStr::from_ptr(sql).to_str().unwrap().to_owned();
+    dt_string_free(sql);
+    out
+}
+
+unsafe fn text(p: *const c_char) -> String {
+    assert!(!p.is_null(), "{}", last_error());
+    CStr::from_ptr(p).to_str().unwrap().to_owned()
+}
+
+#[test]
+fn renders_a_filtered_query() {
+    unsafe {
+        let q = dt_query_new(c("users").as_ptr());
+        assert_eq!(dt_query_dialect(q, c("postgres").as_ptr()), 0);
+        assert_eq!(dt_query_select(q, c("id").as_ptr()), 0);
+        assert_eq!(
+            dt_query_filter_int(q, c("age").as_ptr(), c(">=").as_ptr(), 18),
+            0
+        );
+        assert_eq!(
+            dt_query_filter_null(q, c("deleted_at").as_ptr(), c("=").as_ptr()),
+            0
+        );
+        let ids = [1, 2, 3];
+        assert_eq!(
+            dt_query_filter_int_list(
+                q,
+                c("team").as_ptr(),
+                c("=").as_ptr(),
+                ids.as_ptr(),
+                ids.len()
+            ),
+            0
+        );
+        assert_eq!(
+            render(q),
+            "SELECT\n  id\nFROM users\nWHERE\n  (age >= 18)\n  AND (deleted_at IS NULL)\n  AND (team IN (1,2,3))\n"
+        );
+        dt_query_free(q);
+    }
+}
+
+#[test]
+fn inline_text_is_escaped() {
+    unsafe {
+        let q = dt_query_new(c("users").as_ptr());
+        dt_query_filter_text(
+            q,
+            c("name").as_ptr(),
+            c("=").as_ptr(),
+            c("O'Brien' OR '1'='1").as_ptr(),
+        );
+        assert_eq!(
+            render(q),
+            "SELECT\n  *\nFROM users\nWHERE\n  (name = 'O''Brien'' OR ''1''=''1')\n"
+        );
+
+        dt_query_dialect(q, c("mysql").as_ptr());
+        dt_query_filter_text(
+            q,
+            c("bio").as_ptr(),
+            c("=").as_ptr(),
+            c("\\' OR 1=1 -- ").as_ptr(),
+        );
+        assert!(render(q).ends_with("AND (bio = '\\\\'' OR 1=1 -- ')\n"));
+        dt_query_free(q);
+    }
+}
+
+#[test]
+fn prepares_with_placeholders() {
+    unsafe {
+        let q = dt_query_new(c("users").as_ptr());
+        dt_query_dialect(q, c("postgres").as_ptr());
+        dt_query_filter_text(
+            q,
+            c("name").as_ptr(),
+            c("=").as_ptr(),
+            c("O'Brien").as_ptr(),
+        );
+        dt_query_filter_int(q, c("age").as_ptr(), c(">").as_ptr(), -1);
+        dt_query_filter_float(q, c("score").as_ptr(), c("<").as_ptr(), 0.5);
+
+        let s = dt_query_prepare(q);
+        assert!(!s.is_null(), "{}", last_error());
+        assert_eq!(
+            text(dt_statement_sql(s)),
+            "SELECT\n  *\nFROM users\nWHERE\n  (name = $1)\n  AND (age > $2)\n  AND (score < $3)\n"
+        );
+        assert_eq!(dt_statement_param_count(s), 3);
+
+        assert_eq!(dt_statement_param_type(s, 0), DT_TEXT);
+        assert_eq!(text(dt_statement_param_text(s, 0)), "O'Brien");
+
+        let mut int = 0;
+        assert_eq!(dt_statement_param_type(s, 1), DT_INT);
+        assert_eq!(dt_statement_param_int(s, 1, &mut int), 0);
+        assert_eq!(int, -1);
+
+        let mut float = 0.0;
+        assert_eq!(dt_statement_param_type(s, 2), DT_FLOAT);
+        assert_eq!(dt_statement_param_float(s, 2, &mut float), 0);
+        assert_eq!(float, 0.5);
+
+        dt_statement_free(s);
+        dt_query_free(q);
+    }
+}
+
+#[test]
+fn reading_the_wrong_parameter_fails() {
+    unsafe {
+        let q = dt_query_new(c("users").as_ptr());
+        dt_query_filter_text(q, c("name").as_ptr(), c("=").as_ptr(), c("bob").as_ptr());
+        let s = dt_query_prepare(q);
+
+        let mut int = 7;
+        assert_eq!(dt_statement_param_int(s, 0, &mut int), -1);
+        assert_eq!(int, 7);
+        assert_eq!(last_error(), "parameter 0 is not an integer");
+
+        assert_eq!(dt_statement_param_type(s, 1), -1);
+        assert!(dt_statement_param_text(s, 1).is_null());
+        assert_eq!(last_error(), "no parameter 1, the statement has 1");
+
+        dt_statement_free(s);
+        dt_query_free(q);
+    }
+}
+
+#[test]
+fn invalid_input_sets_the_last_error() {
+    unsafe {
+        assert!(dt_query_new(std::ptr::null()).is_null());
+        assert_eq!(last_error(), "table is NULL");
+
+        let q = dt_query_new(c("users").as_ptr());
+        assert_eq!(dt_query_dialect(q, c("oracle").as_ptr()), -1);
+        assert!(last_error().contains("oracle"));
+        assert_eq!(
+            dt_query_filter_int(q, c("age").as_ptr(), c("~~").as_ptr(), 1),
+            -1
+        );
+        assert!(last_error().contains("~~"));
+        assert_eq!(
+            dt_query_filter_int_list(q, c("id").as_ptr(), c("=").as_ptr(), std::ptr::null(), 2),
+            -1
+        );
+        assert_eq!(last_error(), "values is NULL");
+
+        assert!(dt_query_prepare(std::ptr::null()).is_null());
+        assert!(dt_query_render(std::ptr::null()).is_null());
+        assert_eq!(last_error(), "query is NULL");
+        dt_query_free(q);
+    }
+}
@@ -0,0 +1,70 @@
+//! Non-finite floats, which SQL has no literal for.
+
+use traits::*;
+
+fn score(v: f64) -> SQLFilter<f64> {
+    SQLFilter {
+        column: "score".into(),
+        filter: v,
+        cmp: SQLComp::EQ,
+    }
+}
+
+fn query(v: f64) -> SQLable {
+    let mut q = SQLable::new("results");
+    q.filter(vec![Box::new(score(v))]);
+    q
+}
+
+#[test]
+fn non_finite_values_fail_by_default() {
+    assert!(matches!(query(f64::NAN).try_prepare(), Err(SQLError::NonFinite(v)) if v == "NaN"));
+    assert!(matches!(
+        query(f64::INFINITY).try_prepare(),
+        Err(SQLError::NonFinite(_))
+    ));
+}
+
+#[test]
+fn infallible_renderers_agree_with_try_prepare() {
+    assert_eq!(f64::NAN.to_sql(), "<NaN has no SQL literal>");
+    assert_eq!(
+        SqlValue::Float(f64::NAN).to_sql(),
+        "<NaN has no SQL literal>"
+    );
+    assert_eq!(
+        score(f64::NAN).apply_filter(),
+        "score = <NaN has no SQL literal>"
+    );
+    assert_eq!(query(f64::NAN).prepare(), "<NaN has no SQL literal>\n");
+    assert_eq!(query(f64::NAN).to_string(), "<NaN has no SQL literal>\n");
+    assert_eq!(1.5f64.to_sql(), "1.5");
+}
+
+#[test]
+fn non_finite_values_follow_the_float_format() {
+    let mut q = query(f64::NEG_INFINITY);
+    q.float_format(FloatFormat {
+        non_finite: NonFinite::Quoted,
+        ..Default::default()
+    });
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM results\nWHERE\n  (score = '-Infinity')\n"
+    );
+    q.float_format(FloatFormat {
+        non_finite: NonFinite::Null,
+        ..Default::default()
+    });
+    assert_eq!(
+        q.try_prepare().unwrap(),
+        "SELECT\n  *\nFROM results\nWHERE\n  (score = NULL)\n"
+    );
+}
+
+#[test]
+fn bound_values_are_passed_as_they_are() {
+    let (sql, params) = query(f64::NAN).prepare_params().unwrap();
+    assert_eq!(sql, "SELECT\n  *\nFROM results\nWHERE\n  (score = ?)\n");
+    assert!(matches!(params[..], [SqlValue::Float(v)] if v.is_nan()));
+}
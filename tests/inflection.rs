@@ -0,0 +1,51 @@
+use traits::inflection::{camel_case, pascal_case, snake_case, words};
+use traits::{NamingStrategy, SQLable};
+
+#[test]
+fn snake_case_splits_case_boundaries() {
+    assert_eq!(snake_case("userId"), "user_id");
+    assert_eq!(snake_case("HTTPServerID"), "http_server_id");
+    assert_eq!(snake_case("v2Beta"), "v2_beta");
+    assert_eq!(snake_case("address2"), "address2");
+    assert_eq!(snake_case("ÉtatCivil"), "état_civil");
+}
+
+#[test]
+fn snake_case_keeps_everything_else() {
+    assert_eq!(snake_case("type_"), "type_");
+    assert_eq!(snake_case("a__b"), "a__b");
+    assert_eq!(snake_case("amount$"), "amount$");
+    assert_eq!(snake_case("_rowid"), "_rowid");
+    assert_eq!(snake_case("__Version__"), "__version__");
+    assert_eq!(snake_case("user_Id"), "user_id");
+    assert_eq!(snake_case("first-Name"), "first-name");
+    assert_eq!(snake_case("Total Amount"), "total amount");
+    assert_eq!(snake_case(""), "");
+}
+
+#[test]
+fn default_naming_keeps_separators() {
+    let mut q = SQLable::new("t");
+    q.select(vec![
+        "type_".to_owned(),
+        "a__b".to_owned(),
+        "amount$".to_owned(),
+        "userId".to_owned(),
+    ]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  type_,a__b,amount$,user_id\nFROM t\n"
+    );
+    assert_eq!(
+        NamingStrategy::SnakeCase.apply("e.createdAt_"),
+        "e.created_at_"
+    );
+}
+
+#[test]
+fn other_cases_join_words() {
+    assert_eq!(words("HTTPServer_id"), vec!["HTTP", "Server", "id"]);
+    assert_eq!(camel_case("http_server_id"), "httpServerId");
+    assert_eq!(camel_case("_row_id"), "_rowId");
+    assert_eq!(pascal_case("http_server_id"), "HttpServerId");
+}
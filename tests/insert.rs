@@ -0,0 +1,60 @@
+use traits::*;
+
+fn users() -> Insert {
+    let mut insert = Insert::new("users");
+    insert.columns(vec!["id".to_owned(), "fullName".to_owned()]);
+    insert
+}
+
+#[test]
+fn renders_rows() {
+    let mut insert = users();
+    insert
+        .row(vec![InsertValue::Default, "O'Brien".into()])
+        .row(vec![2.into(), func("upper", [lit("x")]).into()]);
+    assert_eq!(
+        insert.prepare(),
+        "INSERT INTO users (id,full_name)\nVALUES\n  (DEFAULT,'O''Brien'),\n  (2,upper('x'))\n"
+    );
+
+    insert.dialect(Dialect::Postgres);
+    let (sql, params) = insert.prepare_params().unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO users (id,full_name)\nVALUES\n  (DEFAULT,$1),\n  ($2,upper($3))\n"
+    );
+    assert_eq!(params, vec!["O'Brien".into(), 2.into(), "x".into()]);
+}
+
+#[test]
+fn unrenderable_statements_do_not_panic() {
+    let insert = users();
+    assert!(matches!(
+        insert.try_prepare(),
+        Err(SQLError::Unsupported(_))
+    ));
+    assert_eq!(insert.prepare(), "<unsupported: INSERT without rows>\n");
+}
+
+#[test]
+fn rows_of_defaults_only_insert_default_values() {
+    let def = TableDef::new("events")
+        .column(ColumnDef::of::<i64>("id").generated("1", Storage::Stored))
+        .column(ColumnDef::of::<String>("kind").default("'click'"));
+    let mut insert = Insert::new("events");
+    insert
+        .columns(vec!["id".to_owned()])
+        .table_def(&def)
+        .row(vec![1.into()]);
+    assert_eq!(insert.prepare(), "INSERT INTO events DEFAULT VALUES\n");
+    insert.dialect(Dialect::MySql);
+    assert_eq!(insert.prepare(), "INSERT INTO events () VALUES ()\n");
+
+    let mut insert = Insert::new("events");
+    insert
+        .columns(vec!["id".to_owned(), "kind".to_owned()])
+        .table_def(&def)
+        .row(vec![1.into(), InsertValue::Default]);
+    insert.dialect(Dialect::Sqlite);
+    assert_eq!(insert.prepare(), "INSERT INTO events DEFAULT VALUES\n");
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use traits::*;
+
+#[test]
+fn age_filters_follow_the_dialect() {
+    let f = col("createdAt").older_than(Duration::from_secs(90));
+    assert_eq!(
+        f.apply_filter(),
+        "created_at < CURRENT_TIMESTAMP - INTERVAL '90 seconds'"
+    );
+    let mut r = Renderer::default();
+    r.dialect = Dialect::MsSql;
+    assert_eq!(
+        f.try_apply_filter(&mut r).unwrap(),
+        "created_at < DATEADD(second, -90, SYSDATETIME())"
+    );
+}
+
+#[test]
+fn mssql_age_filters_take_any_precision_and_length() {
+    let mut r = Renderer::default();
+    r.dialect = Dialect::MsSql;
+    let f = col("createdAt").newer_than(Duration::from_millis(1500));
+    assert_eq!(
+        f.try_apply_filter(&mut r).unwrap(),
+        "created_at > DATEADD(microsecond, -500000, DATEADD(second, -1, SYSDATETIME()))"
+    );
+    let f = col("createdAt").older_than(Duration::from_secs(6 * 3600));
+    assert_eq!(
+        f.try_apply_filter(&mut r).unwrap(),
+        "created_at < DATEADD(second, -21600, SYSDATETIME())"
+    );
+    let f = col("createdAt").older_than(Duration::from_micros(6 * 3600 * 1_000_000 + 1));
+    assert_eq!(
+        f.try_apply_filter(&mut r).unwrap(),
+        "created_at < DATEADD(microsecond, -1, DATEADD(second, -21600, SYSDATETIME()))"
+    );
+}
+
+#[test]
+fn unrenderable_age_filters_do_not_panic() {
+    let f = case().end().newer_than(Duration::from_secs(60));
+    assert!(f.try_apply_filter(&mut Renderer::default()).is_err());
+    assert_eq!(f.apply_filter(), "<unsupported: CASE without WHEN>");
+
+    let mut q = SQLable::new("events");
+    q.dialect(Dialect::MsSql).filter(vec![Box::new(
+        col("createdAt").newer_than(Interval::from_micros(i64::MAX as i128)),
+    )]);
+    assert!(matches!(q.try_prepare(), Err(SQLError::Unsupported(_))));
+    assert!(q.prepare().starts_with("<unsupported: an interval of"));
+}
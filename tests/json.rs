@@ -0,0 +1,69 @@
+use traits::*;
+
+fn render(f: &dyn Filter, dialect: Dialect) -> String {
+    let mut r = Renderer::default();
+    r.dialect = dialect;
+    f.try_apply_filter(&mut r).unwrap()
+}
+
+#[test]
+fn keys_are_escaped_as_literals() {
+    let f = col("data").json_text("it's").is(SQLComp::EQ, 1);
+    assert_eq!(render(&f, Dialect::Postgres), "data->>'it''s' = 1");
+    assert_eq!(
+        render(&f, Dialect::Sqlite),
+        "JSON_EXTRACT(data, '$.\"it''s\"') = 1"
+    );
+
+    let f = col("data").json("a\"b").is(SQLComp::EQ, 1);
+    assert_eq!(
+        render(&f, Dialect::MySql),
+        "JSON_EXTRACT(data, '$.\"a\\\\\"b\"') = 1"
+    );
+    assert_eq!(
+        render(&f, Dialect::MsSql),
+        "JSON_QUERY(data, '$.\"a\\\"b\"') = 1"
+    );
+}
+
+#[test]
+fn backslashes_in_keys_are_escaped_first() {
+    let f = col("data").json("a\\\"b").is(SQLComp::EQ, 1);
+    assert_eq!(
+        render(&f, Dialect::Sqlite),
+        "JSON_EXTRACT(data, '$.\"a\\\\\\\"b\"') = 1"
+    );
+    assert_eq!(
+        render(&f, Dialect::MySql),
+        "JSON_EXTRACT(data, '$.\"a\\\\\\\\\\\\\"b\"') = 1"
+    );
+}
+
+#[test]
+fn has_key_escapes_the_key() {
+    let f = col("payload").has_key("it's");
+    assert_eq!(render(&f, Dialect::Postgres), "payload ? 'it''s'");
+    assert_eq!(
+        render(&f, Dialect::MySql),
+        "JSON_CONTAINS_PATH(payload, 'one', '$.\"it''s\"')"
+    );
+}
+
+#[test]
+fn unsupported_filters_do_not_panic() {
+    let f = col("payload").has_key("user");
+    assert!(matches!(
+        f.try_apply_filter(&mut Renderer::default()),
+        Err(SQLError::Unsupported(_))
+    ));
+    assert_eq!(
+        f.apply_filter(),
+        "<unsupported: JSON key tests in Generic, use Postgres>"
+    );
+
+    let mut q = SQLable::new("events");
+    q.dialect(Dialect::Sqlite)
+        .filter(vec![Box::new(col("payload").json_contains("{}"))]);
+    assert!(q.try_prepare().is_err());
+    assert_eq!(q.prepare(), "<unsupported: JSON containment in Sqlite>\n");
+}
@@ -0,0 +1,61 @@
+//! Text values inlined into the SQL.
+
+use traits::*;
+
+fn name_filter(name: &str) -> Box<SQLFilter<String>> {
+    Box::new(SQLFilter {
+        column: "name".into(),
+        filter: name.to_owned(),
+        cmp: SQLComp::EQ,
+    })
+}
+
+#[test]
+fn quotes_are_doubled() {
+    let f = name_filter("O'Brien");
+    assert_eq!(f.apply_filter(), "name = 'O''Brien'");
+    assert_eq!("O'Brien".to_sql(), "'O''Brien'");
+    assert_eq!(
+        SqlValue::from("'; DROP TABLE users; --").to_sql(),
+        "'''; DROP TABLE users; --'"
+    );
+}
+
+#[test]
+fn queries_inline_escaped_text() {
+    let mut q = SQLable::new("users");
+    q.filter(vec![name_filter("O'Brien")]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM users\nWHERE\n  (name = 'O''Brien')\n"
+    );
+
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres)
+        .filter(vec![name_filter("a\\'b")]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM users\nWHERE\n  (name = 'a\\''b')\n"
+    );
+}
+
+#[test]
+fn mysql_backslashes_are_doubled() {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::MySql)
+        .filter(vec![name_filter("\\' OR 1=1 -- ")]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM users\nWHERE\n  (name = '\\\\'' OR 1=1 -- ')\n"
+    );
+}
+
+#[test]
+fn bound_text_is_left_as_is() {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres)
+        .filter(vec![name_filter("O'Brien")]);
+    let (sql, params) = q.prepare_params().unwrap();
+    assert_eq!(sql, "SELECT\n  *\nFROM users\nWHERE\n  (name = $1)\n");
+    assert_eq!(params, vec![SqlValue::from("O'Brien")]);
+}
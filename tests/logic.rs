@@ -0,0 +1,32 @@
+use traits::*;
+
+fn age(cmp: SQLComp, n: i64) -> Box<dyn Filter> {
+    Box::new(SQLFilter {
+        column: "age".into(),
+        filter: n,
+        cmp,
+    })
+}
+
+#[test]
+fn combines_filters() {
+    let f = Or(vec![
+        Box::new(And(vec![age(SQLComp::GEQ, 18), age(SQLComp::LT, 65)])),
+        Box::new(Not(age(SQLComp::EQ, 30))),
+    ]);
+    assert_eq!(
+        f.apply_filter(),
+        "((age >= 18) AND (age < 65)) OR (NOT (age = 30))"
+    );
+    assert_eq!(And(vec![]).apply_filter(), "1=1");
+    assert_eq!(Or(vec![]).apply_filter(), "1=0");
+}
+
+#[test]
+fn errors_of_a_part_do_not_panic() {
+    let broken: Box<dyn Filter> = Box::new(raw("a = ?", vec![]));
+    let f = And(vec![age(SQLComp::GEQ, 18), broken]);
+    assert!(f.try_apply_filter(&mut Renderer::default()).is_err());
+    assert!(f.apply_filter().starts_with('<'));
+    assert!(Not(Box::new(f)).apply_filter().starts_with('<'));
+}
@@ -0,0 +1,37 @@
+use traits::*;
+
+fn query(naming: NamingStrategy) -> String {
+    let mut q = SQLable::new("users AS u");
+    q.naming(naming)
+        .select(vec!["u.userId".to_owned(), "created_at".to_owned()])
+        .filter(vec![Box::new(SQLFilter {
+            column: "u.firstName".into(),
+            filter: "Ann",
+            cmp: SQLComp::EQ,
+        })]);
+    q.prepare()
+}
+
+#[test]
+fn strategies_convert_the_column_part() {
+    assert_eq!(NamingStrategy::SnakeCase.apply("e.userId"), "e.user_id");
+    assert_eq!(NamingStrategy::CamelCase.apply("e.user_id"), "e.userId");
+    assert_eq!(NamingStrategy::AsIs.apply("userId"), "userId");
+    assert_eq!(NamingStrategy::SnakeCase.apply("u.*"), "u.*");
+    assert_eq!(
+        NamingStrategy::Custom(|c| c.to_uppercase()).apply("u.id"),
+        "u.ID"
+    );
+}
+
+#[test]
+fn queries_convert_selected_and_filtered_columns() {
+    assert_eq!(
+        query(NamingStrategy::default()),
+        "SELECT\n  u.user_id,created_at\nFROM users AS u\nWHERE\n  (u.first_name = 'Ann')\n"
+    );
+    assert_eq!(
+        query(NamingStrategy::CamelCase),
+        "SELECT\n  u.userId,createdAt\nFROM users AS u\nWHERE\n  (u.firstName = 'Ann')\n"
+    );
+}
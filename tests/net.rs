@@ -0,0 +1,107 @@
+//! IP addresses and UUIDs as filter values.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use traits::*;
+
+fn query<T: ToSql + 'static>(column: &'static str, value: T, dialect: Dialect) -> SQLable {
+    let mut q = SQLable::new("sessions");
+    q.dialect(dialect).filter(vec![Box::new(SQLFilter {
+        column: column.into(),
+        filter: value,
+        cmp: SQLComp::EQ,
+    })]);
+    q
+}
+
+#[test]
+fn addresses_are_inet_on_postgres() {
+    let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let q = query("clientIp", ip, Dialect::Postgres);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM sessions\nWHERE\n  (client_ip = inet '10.0.0.1')\n"
+    );
+    assert_eq!(
+        q.prepare_params().unwrap(),
+        (
+            "SELECT\n  *\nFROM sessions\nWHERE\n  (client_ip = $1::inet)\n".to_owned(),
+            vec![SqlValue::Text("10.0.0.1".to_owned())]
+        )
+    );
+
+    let q = query("clientIp", vec![Ipv6Addr::LOCALHOST], Dialect::MySql);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM sessions\nWHERE\n  (client_ip = '::1')\n"
+    );
+}
+
+#[test]
+fn uuids_parse_and_render() {
+    let id: Uuid = "67E55044-10B1-426F-9247-BB680E5FE0C8".parse().unwrap();
+    assert_eq!(id, "67e5504410b1426f9247bb680e5fe0c8".parse().unwrap());
+    assert_eq!(id.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    assert!("67e55044".parse::<Uuid>().is_err());
+    assert!("67e55044-10b1-426f-9247-bb680e5fe0cg"
+        .parse::<Uuid>()
+        .is_err());
+    assert!("+7e55044-10b1-426f-9247-bb680e5fe0c8"
+        .parse::<Uuid>()
+        .is_err());
+    assert!("+7e5504410b1426f9247bb680e5fe0c8".parse::<Uuid>().is_err());
+    assert!("-67e5504-10b1-426f-9247-bb680e5fe0c8"
+        .parse::<Uuid>()
+        .is_err());
+
+    let mut q = query("id", id, Dialect::MySql);
+    q.uuid_format(UuidFormat::Binary);
+    assert_eq!(
+        q.prepare_params().unwrap().1,
+        vec![SqlValue::Text(id.hex())]
+    );
+    q.dialect(Dialect::Postgres);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM sessions\nWHERE\n  (id = '67e55044-10b1-426f-9247-bb680e5fe0c8')\n"
+    );
+}
+
+#[test]
+fn boxed_addresses_and_uuids_stay_typed() {
+    let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let id: Uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap();
+    let mut q = SQLable::new("sessions");
+    q.dialect(Dialect::Postgres).filter_dyn(vec![
+        SQLFilter {
+            column: "clientIp".into(),
+            filter: Box::new(ip),
+            cmp: SQLComp::EQ,
+        },
+        SQLFilter {
+            column: "peerIp".into(),
+            filter: Box::new(vec![ip, IpAddr::V6(Ipv6Addr::LOCALHOST)]),
+            cmp: SQLComp::EQ,
+        },
+    ]);
+    assert_eq!(
+        q.prepare(),
+        "SELECT\n  *\nFROM sessions\nWHERE\n  (client_ip = inet '10.0.0.1')\n  AND (peer_ip IN (inet '10.0.0.1',inet '::1'))\n"
+    );
+
+    let mut q = SQLable::new("sessions");
+    q.dialect(Dialect::MySql)
+        .uuid_format(UuidFormat::Binary)
+        .filter_dyn(vec![SQLFilter {
+            column: "id".into(),
+            filter: Box::new(id),
+            cmp: SQLComp::EQ,
+        }]);
+    assert_eq!(
+        q.prepare_params().unwrap(),
+        (
+            "SELECT\n  *\nFROM sessions\nWHERE\n  (id = UNHEX(?))\n".to_owned(),
+            vec![SqlValue::Text(id.hex())]
+        )
+    );
+}
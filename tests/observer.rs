@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use traits::*;
+
+/// Keeps what it is told, shared with the test.
+#[derive(Clone, Default)]
+struct Log(Rc<RefCell<Vec<String>>>);
+
+impl Observer for Log {
+    fn statement(&mut self, sql: &str, params: &[SqlValue]) {
+        self.0
+            .borrow_mut()
+            .push(format!("{} {:?}", sql.trim_end(), params));
+    }
+
+    fn warning(&mut self, lint: &Lint) {
+        self.0.borrow_mut().push(format!("warning: {}", lint));
+    }
+
+    fn touched(&mut self, touched: &Touched) {
+        self.0
+            .borrow_mut()
+            .push(format!("touched {} {:?}", touched.table.name, touched.rows));
+    }
+}
+
+#[test]
+fn inserts_report_the_rows_they_touch() {
+    let log = Log::default();
+    let mut budget = QueryBudget::new().observer(log.clone());
+
+    let mut insert = Insert::new("users");
+    insert
+        .columns(vec!["id".to_owned(), "name".to_owned()])
+        .key(vec!["id".to_owned()])
+        .row(vec![7.into(), "ann".into()]);
+    budget.prepare_insert(&insert).unwrap();
+
+    // the database computes the key
+    let mut insert = Insert::new("users");
+    insert
+        .columns(vec!["name".to_owned()])
+        .key(vec!["id".to_owned()])
+        .row(vec!["bob".into()]);
+    budget.prepare_insert(&insert).unwrap();
+
+    assert_eq!(
+        *log.0.borrow(),
+        vec![
+            "INSERT INTO users (id,name)\nVALUES\n  (?,?) [Int(7), Text(\"ann\")]",
+            "touched users Keys { columns: [\"id\"], values: [[Int(7)]] }",
+            "INSERT INTO users (name)\nVALUES\n  (?) [Text(\"bob\")]",
+            "touched users All",
+        ]
+    );
+}
+
+#[test]
+fn reads_touch_nothing() {
+    let log = Log::default();
+    let mut budget = QueryBudget::new().warn_repeats(1).observer(log.clone());
+    let q = SQLable::new("users");
+    budget.prepare(&q).unwrap();
+    budget.prepare(&q).unwrap();
+    let log = log.0.borrow();
+    assert_eq!(log.len(), 3);
+    assert!(log[2].starts_with("warning: query "));
+}
+
+#[test]
+fn key_ranges_hold_their_keys() {
+    let keys = KeyRange::Keys {
+        columns: vec!["id".to_owned()],
+        values: vec![vec![SqlValue::Int(7)]],
+    };
+    assert!(keys.contains(&[SqlValue::Int(7)]));
+    assert!(!keys.contains(&[SqlValue::Int(8)]));
+    assert!(KeyRange::All.contains(&[SqlValue::Int(8)]));
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use traits::*;
+
+fn min_age() -> SQLable {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres)
+        .filter(vec![Box::new(SQLFilter {
+            column: "age".into(),
+            filter: param("min_age"),
+            cmp: SQLComp::GEQ,
+        })]);
+    q
+}
+
+#[test]
+fn values_are_bound_when_rendering() {
+    let mut values = BTreeMap::new();
+    values.insert("min_age".to_owned(), SqlValue::Int(18));
+    let (sql, params) = min_age().prepare_params_with(&values).unwrap();
+    assert_eq!(sql, "SELECT\n  *\nFROM users\nWHERE\n  (age >= $1)\n");
+    assert_eq!(params, vec![SqlValue::Int(18)]);
+}
+
+#[test]
+fn missing_values_fail_to_render() {
+    let q = min_age();
+    assert!(matches!(q.try_prepare(), Err(SQLError::MissingParam(name)) if name == "min_age"));
+    assert!(matches!(q.prepare_params(), Err(SQLError::MissingParam(_))));
+}
+
+#[test]
+fn to_sql_writes_a_named_placeholder() {
+    assert_eq!(param("min_age").to_sql(), ":min_age");
+    let f = SQLFilter {
+        column: "age".into(),
+        filter: param("min_age"),
+        cmp: SQLComp::GEQ,
+    };
+    assert_eq!(f.apply_filter(), "age >= :min_age");
+}
@@ -0,0 +1,29 @@
+use traits::*;
+
+#[test]
+fn build_copies_the_query() {
+    let mut q = SQLable::new("users");
+    let built = q.select(vec!["id".to_owned()]).build();
+    q.limit(1);
+    assert_eq!(built.prepare(), "SELECT\n  id\nFROM users\n");
+
+    let shared = &built;
+    assert_eq!(shared.build().prepare(), built.prepare());
+}
+
+#[test]
+fn display_does_not_fail() {
+    let mut q = SQLable::new("users");
+    q.server(Server::parse("5.7.44").unwrap())
+        .select_expr(row_number().over(Window::new().order_by("id", Order::Asc)));
+    assert!(q.try_prepare().is_err());
+    let shown = format!("{}", q);
+    assert!(
+        shown.starts_with('<') && shown.ends_with(">\n"),
+        "{}",
+        shown
+    );
+
+    q.server(Server::parse("8.0.36").unwrap());
+    assert_eq!(q.to_string(), q.try_prepare().unwrap());
+}
@@ -0,0 +1,52 @@
+use traits::*;
+
+#[test]
+fn fragments_are_verbatim() {
+    let f = Raw::new("created_at > now() - interval '1 day'");
+    assert_eq!(f.apply_filter(), "created_at > now() - interval '1 day'");
+    assert_eq!(f.to_sql(), f.as_str());
+}
+
+#[test]
+fn placeholders_are_renumbered() {
+    let mut q = SQLable::new("events");
+    q.dialect(Dialect::Postgres).filter(vec![
+        Box::new(SQLFilter {
+            column: "kind".into(),
+            filter: "click",
+            cmp: SQLComp::EQ,
+        }),
+        Box::new(raw(
+            "date_trunc('day', at) >= ? AND note <> '?'",
+            vec!["2024-01-01".into()],
+        )),
+    ]);
+    let (sql, params) = q.prepare_params().unwrap();
+    assert_eq!(
+        sql,
+        "SELECT\n  *\nFROM events\nWHERE\n  (kind = $1)\n  AND (date_trunc('day', at) >= $2 AND note <> '?')\n"
+    );
+    assert_eq!(
+        params,
+        vec![SqlValue::from("click"), SqlValue::from("2024-01-01")]
+    );
+
+    assert_eq!(
+        raw("a = ? OR b = ?", vec![1.into(), "x'y".into()]).apply_filter(),
+        "a = 1 OR b = 'x''y'"
+    );
+}
+
+#[test]
+fn placeholder_count_mismatch_does_not_panic() {
+    let f = raw("a = ? AND b = ?", vec![1.into()]);
+    assert!(matches!(
+        f.try_apply_filter(&mut Renderer::default()),
+        Err(SQLError::ParamCount {
+            placeholders: 2,
+            params: 1
+        })
+    ));
+    assert!(f.apply_filter().starts_with('<'));
+    assert_eq!(f.to_sql(), f.apply_filter());
+}
@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use traits::*;
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+    }
+}
+
+fn users() -> SQLable {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres)
+        .filter(vec![Box::new(SQLFilter {
+            column: "id".into(),
+            filter: 7,
+            cmp: SQLComp::EQ,
+        })]);
+    q
+}
+
+#[test]
+fn only_serialization_failures_and_deadlocks_are_retried() {
+    let p = policy();
+    assert!(p.should_retry(DbErrorKind::Deadlock, 1));
+    assert!(p.should_retry(DbErrorKind::SerializationFailure, 2));
+    assert!(!p.should_retry(DbErrorKind::SerializationFailure, 3));
+    assert!(!p.should_retry(DbErrorKind::ConnectionFailure, 1));
+    assert!(!p.should_retry(DbErrorKind::UniqueViolation, 1));
+}
+
+#[test]
+fn backoff_is_capped() {
+    let p = RetryPolicy {
+        max_attempts: 10,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(25),
+    };
+    assert!(p.backoff(1) < Duration::from_millis(10));
+    assert!(p.backoff(2) < Duration::from_millis(20));
+    assert!(p.backoff(8) < Duration::from_millis(25));
+}
+
+#[test]
+fn run_sends_the_same_statements_each_attempt() {
+    let q = users();
+    let mut sent = Vec::new();
+    let result = policy().run(
+        &[&q],
+        |statements| {
+            sent.push(statements.to_vec());
+            match sent.len() {
+                1 => Err("40001"),
+                _ => Ok(sent.len()),
+            }
+        },
+        |state| DbErrorKind::from_sqlstate(state),
+    );
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(sent[0], sent[1]);
+    assert_eq!(
+        sent[0],
+        vec![(
+            "SELECT\n  *\nFROM users\nWHERE\n  (id = $1)\n".to_owned(),
+            vec![SqlValue::Int(7)]
+        )]
+    );
+}
+
+#[test]
+fn run_gives_up() {
+    let q = users();
+    let mut attempts = 0;
+    let result: Result<(), _> = policy().run(
+        &[&q],
+        |_| {
+            attempts += 1;
+            Err("40P01")
+        },
+        |state| DbErrorKind::from_sqlstate(state),
+    );
+    assert!(matches!(
+        result,
+        Err(RetryError::Execute {
+            error: "40P01",
+            attempts: 3
+        })
+    ));
+    assert_eq!(attempts, 3);
+
+    let result: Result<(), RetryError<&str>> = policy().run(
+        &[&q],
+        |_| Err("23505"),
+        |state| DbErrorKind::from_sqlstate(state),
+    );
+    assert_eq!(result.unwrap_err().to_string(), "23505");
+
+    let mut empty = SQLable::new("users");
+    empty
+        .empty_list(EmptyList::Error)
+        .filter(vec![Box::new(SQLFilter {
+            column: "id".into(),
+            filter: Vec::<i64>::new(),
+            cmp: SQLComp::EQ,
+        })]);
+    let result: Result<(), RetryError<&str>> = policy().run(
+        &[&empty],
+        |_| -> Result<(), &str> { panic!("nothing runs") },
+        |state| DbErrorKind::from_sqlstate(state),
+    );
+    assert!(matches!(result, Err(RetryError::Render(_))));
+}
+
+/// A connection that fails with the SQLSTATE it is given, or else returns
+/// one row.
+struct Conn(Option<&'static str>);
+
+impl Execute for Conn {
+    type Error = &'static str;
+
+    fn query(
+        &self,
+        _: &str,
+        _: &[SqlValue],
+        each: &mut dyn FnMut(&dyn Row) -> Result<(), SQLError>,
+    ) -> Result<(), ExecError<&'static str>> {
+        if let Some(state) = self.0 {
+            return Err(ExecError::Database(state));
+        }
+        each(&Record {
+            columns: vec!["id".to_owned()],
+            values: vec![7.into()],
+        })?;
+        Ok(())
+    }
+
+    fn execute(&self, _: &str, _: &[SqlValue]) -> Result<u64, ExecError<&'static str>> {
+        match self.0 {
+            Some(state) => Err(ExecError::Database(state)),
+            None => Ok(1),
+        }
+    }
+}
+
+/// Connects with each of `failures` in turn, then with a working
+/// connection, counting the connections made.
+fn connect<'a>(
+    failures: &'a [&'static str],
+    made: &'a Cell<usize>,
+) -> impl FnMut() -> Result<Conn, &'static str> + 'a {
+    move || {
+        made.set(made.get() + 1);
+        Ok(Conn(failures.get(made.get() - 1).copied()))
+    }
+}
+
+#[test]
+fn fetch_retries_on_fresh_connections() {
+    let made = Cell::new(0);
+    let rows: Vec<(i64,)> = policy()
+        .fetch(&users(), connect(&["08006", "40001"], &made), |s| {
+            DbErrorKind::from_sqlstate(s)
+        })
+        .unwrap();
+    assert_eq!(rows, vec![(7,)]);
+    assert_eq!(made.get(), 3);
+
+    let made = Cell::new(0);
+    let result = policy().fetch::<(i64,), _, _, _>(&users(), connect(&["42P01"], &made), |s| {
+        DbErrorKind::from_sqlstate(s)
+    });
+    assert!(matches!(
+        result,
+        Err(RetryError::Execute {
+            error: ExecError::Database("42P01"),
+            attempts: 1
+        })
+    ));
+}
+
+#[test]
+fn execute_does_not_retry_connection_failures() {
+    let mut insert = Insert::new("users");
+    insert.columns(vec!["id".to_owned()]).row(vec![7.into()]);
+
+    let made = Cell::new(0);
+    let result = policy().execute(&insert, connect(&["40P01"], &made), |s| {
+        DbErrorKind::from_sqlstate(s)
+    });
+    assert_eq!(result.unwrap(), 1);
+
+    let made = Cell::new(0);
+    let result = policy().execute(&insert, connect(&["08006"], &made), |s| {
+        DbErrorKind::from_sqlstate(s)
+    });
+    assert!(matches!(
+        result,
+        Err(RetryError::Execute { attempts: 1, .. })
+    ));
+    assert_eq!(made.get(), 1);
+}
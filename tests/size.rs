@@ -0,0 +1,63 @@
+//! Statements over the server's size limit.
+
+use traits::*;
+
+fn ids(n: i64) -> Box<SQLFilter<Vec<i64>>> {
+    Box::new(SQLFilter {
+        column: "id".into(),
+        filter: (0..n).collect(),
+        cmp: SQLComp::EQ,
+    })
+}
+
+#[test]
+fn the_limit_follows_the_server() {
+    let mut q = SQLable::new("users");
+    q.filter(vec![ids(700_000)]);
+    // 4 MiB before MySQL 8.0
+    q.server(Server::parse("5.7.44").unwrap());
+    match q.try_prepare() {
+        Err(SQLError::StatementTooLarge { clause, limit, .. }) => {
+            assert_eq!(clause, "WHERE");
+            assert_eq!(limit, 4 << 20);
+        }
+        other => panic!("{:?}", other.map(|sql| sql.len())),
+    }
+    q.server(Server::parse("8.0.36").unwrap());
+    assert!(q.try_prepare().is_ok());
+    q.dialect(Dialect::Generic);
+    assert!(q.try_prepare().is_ok());
+}
+
+#[test]
+fn bound_values_count_towards_the_limit() {
+    let mut q = SQLable::new("users");
+    q.dialect(Dialect::Postgres)
+        .max_statement_bytes(1000)
+        .filter(vec![Box::new(SQLFilter {
+            column: "bio".into(),
+            filter: "x".repeat(2000),
+            cmp: SQLComp::EQ,
+        })]);
+    assert!(matches!(
+        q.prepare_params(),
+        Err(SQLError::StatementTooLarge { .. })
+    ));
+}
+
+#[test]
+fn inserts_name_their_values() {
+    let mut insert = Insert::new("users");
+    insert
+        .columns(vec!["id".to_owned()])
+        .max_statement_bytes(100);
+    for id in 0..50 {
+        insert.row(vec![id.into()]);
+    }
+    match insert.prepare_params() {
+        Err(e @ SQLError::StatementTooLarge { .. }) => {
+            assert!(e.to_string().contains("VALUES"), "{}", e)
+        }
+        other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,36 @@
+use traits::*;
+
+fn render(f: &dyn Filter, dialect: Dialect) -> String {
+    let mut r = Renderer::default();
+    r.dialect = dialect;
+    f.try_apply_filter(&mut r).unwrap()
+}
+
+#[test]
+fn matches_follows_the_dialect() {
+    let f = col("body").matches("don't panic").config("english");
+    assert_eq!(
+        render(&f, Dialect::Postgres),
+        "to_tsvector('english', body) @@ plainto_tsquery('english', 'don''t panic')"
+    );
+    assert_eq!(render(&f, Dialect::Sqlite), "body MATCH 'don''t panic'");
+    assert_eq!(render(&f, Dialect::MsSql), "FREETEXT(body, 'don''t panic')");
+}
+
+#[test]
+fn unsupported_searches_do_not_panic() {
+    let f = col("body").matches("rust");
+    assert!(matches!(
+        f.try_apply_filter(&mut Renderer::default()),
+        Err(SQLError::Unsupported(_))
+    ));
+    assert_eq!(
+        f.apply_filter(),
+        "<unsupported: full text search in Generic>"
+    );
+
+    let f = func("lower", [col("body")]).matches("rust");
+    let mut r = Renderer::default();
+    r.dialect = Dialect::MySql;
+    assert!(f.try_apply_filter(&mut r).is_err());
+}
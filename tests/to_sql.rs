@@ -0,0 +1,39 @@
+//! Values written through `ToSql::write_sql`.
+
+use std::time::Duration;
+
+use traits::*;
+
+struct Cents(i64);
+
+impl ToSql for Cents {
+    fn write_sql(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(out, "{}.{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+#[test]
+fn to_sql_is_collected_from_write_sql() {
+    assert_eq!(Cents(1234).to_sql(), "12.34");
+    assert_eq!(
+        Cents(1234).render(&mut Renderer::default()).unwrap(),
+        "12.34"
+    );
+    let f = SQLFilter {
+        column: "price".into(),
+        filter: Cents(5),
+        cmp: SQLComp::LT,
+    };
+    assert_eq!(f.apply_filter(), "price < 0.05");
+}
+
+#[test]
+fn wrappers_write_their_elements() {
+    assert_eq!(Array(vec![1, 2]).to_sql(), "ARRAY[1,2]");
+    assert_eq!(Array::<i32>(vec![]).to_sql(), "'{}'");
+    assert_eq!(Any(vec![1, 2]).to_sql(), "ANY (ARRAY[1,2])");
+    assert_eq!(All(vec!["a"]).to_sql(), "ALL (ARRAY['a'])");
+    assert_eq!(Some(3).to_sql(), "3");
+    assert_eq!(None::<i32>.to_sql(), "NULL");
+    assert_eq!(Duration::from_secs(60).to_sql(), "INTERVAL '60 seconds'");
+}
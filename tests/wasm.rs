@@ -0,0 +1,23 @@
+//! The bindings outside a JS host: only what does not call into JS runs
+//! natively, the rest is covered by the wasm32 build.
+#![cfg(feature = "wasm")]
+
+use traits::wasm::Query;
+
+#[test]
+fn renders_inline_sql() {
+    let mut q = Query::new("users AS u").unwrap();
+    q.dialect("postgres").unwrap();
+    q.select(vec!["id".to_owned(), "name".to_owned()]);
+    assert_eq!(q.sql().unwrap(), "SELECT\n  id,name\nFROM users AS u\n");
+}
+
+#[test]
+fn dialect_changes_quoting() {
+    let mut q = Query::new("order").unwrap();
+    q.select(vec!["select".to_owned()]);
+    q.dialect("mysql").unwrap();
+    let mysql = q.sql().unwrap();
+    q.dialect("postgres").unwrap();
+    assert_ne!(mysql, q.sql().unwrap());
+}